@@ -1,3 +1,18 @@
+use std::path::Path;
+
+/// The SCL grammar `pest_derive` compiles into the parser, relative to the
+/// crate root. The `#[grammar]` attribute in `src/parser/scl.rs` names the
+/// same file relative to `src/`.
+const GRAMMAR: &str = "src/parser/scl.pest";
+
 fn main() {
-    println!("cargo:rerun-if-changed=src/parser/scl.pest");
-}
\ No newline at end of file
+    println!("cargo:rerun-if-changed={}", GRAMMAR);
+    let path = Path::new(&std::env::var("CARGO_MANIFEST_DIR").unwrap()).join(GRAMMAR);
+    if !path.is_file() {
+        panic!(
+            "SCL grammar not found at {}. It must stay at {} next to src/parser/scl.rs.",
+            path.display(),
+            GRAMMAR
+        );
+    }
+}
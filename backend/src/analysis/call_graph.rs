@@ -0,0 +1,233 @@
+//! Direct call graph between the routines of a program.
+//!
+//! Edges come from `Statement::Call` and from function calls used inside
+//! expressions (`x := Scale(HMI_Speed)`). Only direct, by-name calls are
+//! modelled; names are matched case-insensitively as in SCL.
+
+use std::collections::HashMap;
+
+use crate::ast::{Expression, Function, Program, Statement};
+use crate::rules::utils::{self, IdentifierClass};
+use crate::rules::Policy;
+
+/// One call from `caller` to `callee`.
+#[derive(Debug, Clone)]
+pub struct CallSite {
+    pub caller: String,
+    pub callee: String,
+    pub args: Vec<Expression>,
+    pub line: usize,
+}
+
+impl CallSite {
+    /// True if any argument is (or is computed from) a sensitive source
+    /// such as an HMI or recipe variable.
+    pub fn has_sensitive_arg(&self, policy: &Policy) -> bool {
+        self.args.iter().any(|a| arg_is_sensitive(a, policy))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    /// Upper-cased caller name -> outgoing calls, in source order.
+    calls: HashMap<String, Vec<CallSite>>,
+    /// Upper-cased routine names in program order.
+    order: Vec<String>,
+}
+
+impl CallGraph {
+    pub fn build(program: &Program) -> Self {
+        let mut calls: HashMap<String, Vec<CallSite>> = HashMap::new();
+        let mut order = Vec::new();
+        for f in program.code() {
+            let mut sites = Vec::new();
+            collect_calls(&f.name, &f.statements, &mut sites);
+            let key = f.name.to_ascii_uppercase();
+            if !calls.contains_key(&key) {
+                order.push(key.clone());
+            }
+            calls.entry(key).or_default().extend(sites);
+        }
+        Self { calls, order }
+    }
+
+    /// Calls made directly by `caller`.
+    pub fn callees(&self, caller: &str) -> &[CallSite] {
+        self.calls
+            .get(&caller.to_ascii_uppercase())
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Every call site whose target is `callee`.
+    pub fn callers_of(&self, callee: &str) -> Vec<&CallSite> {
+        self.calls
+            .values()
+            .flatten()
+            .filter(|s| s.callee.eq_ignore_ascii_case(callee))
+            .collect()
+    }
+
+    /// Call cycles between routines of the program (direct or mutual
+    /// recursion). Each cycle is the chain of call sites around it, the
+    /// last one being the call that closes it. Routines are explored in
+    /// program order and one cycle is reported per closing call, so every
+    /// recursive routine appears in at least one cycle.
+    pub fn cycles(&self) -> Vec<Vec<&CallSite>> {
+        let mut on_stack: HashMap<&str, bool> = HashMap::new();
+        let mut path = Vec::new();
+        let mut out = Vec::new();
+        for name in &self.order {
+            if !on_stack.contains_key(name.as_str()) {
+                self.find_cycles(name, &mut on_stack, &mut path, &mut out);
+            }
+        }
+        out
+    }
+
+    fn find_cycles<'g>(
+        &'g self,
+        node: &'g str,
+        on_stack: &mut HashMap<&'g str, bool>,
+        path: &mut Vec<&'g CallSite>,
+        out: &mut Vec<Vec<&'g CallSite>>,
+    ) {
+        on_stack.insert(node, true);
+        for site in self.calls.get(node).into_iter().flatten() {
+            // Calls to built-ins and undefined blocks cannot recurse.
+            let Some((callee, _)) = self.calls.get_key_value(&site.callee.to_ascii_uppercase()) else { continue };
+            match on_stack.get(callee.as_str()) {
+                Some(true) => {
+                    let start = path.iter().position(|s| s.caller.eq_ignore_ascii_case(callee)).unwrap_or(path.len());
+                    let mut cycle = path[start..].to_vec();
+                    cycle.push(site);
+                    out.push(cycle);
+                }
+                Some(false) => {}
+                None => {
+                    path.push(site);
+                    self.find_cycles(callee, on_stack, path, out);
+                    path.pop();
+                }
+            }
+        }
+        on_stack.insert(node, false);
+    }
+
+    /// Resolve a call site to the routine it invokes, if it is defined in
+    /// `program` (built-ins like TON are not).
+    pub fn resolve<'p>(&self, program: &'p Program, site: &CallSite) -> Option<&'p Function> {
+        program.functions.iter().find(|f| f.name.eq_ignore_ascii_case(&site.callee))
+    }
+}
+
+/// True if `e` reads a sensitive source anywhere in its tree.
+pub fn arg_is_sensitive(e: &Expression, policy: &Policy) -> bool {
+    match e {
+        Expression::Identifier(_) | Expression::Member { .. } => {
+            e.name().is_some_and(|name| utils::is_identifier_class(&name, IdentifierClass::SensitiveSource, policy))
+        }
+        Expression::UnaryOp { expr, .. } => arg_is_sensitive(expr, policy),
+        Expression::BinaryOp { left, right, .. } => arg_is_sensitive(left, policy) || arg_is_sensitive(right, policy),
+        Expression::Index { base, index, .. } => arg_is_sensitive(base, policy) || arg_is_sensitive(index, policy),
+        Expression::FuncCall { args, .. } => args.iter().any(|a| arg_is_sensitive(a, policy)),
+        _ => false,
+    }
+}
+
+fn collect_calls(caller: &str, stmts: &[Statement], out: &mut Vec<CallSite>) {
+    for st in stmts {
+        match st {
+            Statement::Call { name, args, line } => {
+                for (_, a) in args {
+                    collect_expr_calls(caller, a, *line, out);
+                }
+                out.push(CallSite {
+                    caller: caller.to_string(),
+                    callee: name.clone(),
+                    args: args.iter().map(|(_, a)| a.clone()).collect(),
+                    line: *line,
+                });
+            }
+            Statement::Assign { target, value, line } => {
+                collect_expr_calls(caller, target, *line, out);
+                collect_expr_calls(caller, value, *line, out);
+            }
+            Statement::Expr { expr, line } => collect_expr_calls(caller, expr, *line, out),
+            Statement::IfStmt { condition, then_branch, else_branch, line } => {
+                collect_expr_calls(caller, condition, *line, out);
+                collect_calls(caller, then_branch, out);
+                collect_calls(caller, else_branch, out);
+            }
+            Statement::CaseStmt { expression, cases, else_branch, line } => {
+                collect_expr_calls(caller, expression, *line, out);
+                for (_, body) in cases {
+                    collect_calls(caller, body, out);
+                }
+                collect_calls(caller, else_branch, out);
+            }
+            Statement::WhileStmt { condition, body, line } => {
+                collect_expr_calls(caller, condition, *line, out);
+                collect_calls(caller, body, out);
+            }
+            Statement::ForStmt { start, end, body, line, .. } => {
+                collect_expr_calls(caller, start, *line, out);
+                collect_expr_calls(caller, end, *line, out);
+                collect_calls(caller, body, out);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_expr_calls(caller: &str, e: &Expression, line: usize, out: &mut Vec<CallSite>) {
+    match e {
+        Expression::FuncCall { name, args, .. } => {
+            for a in args {
+                collect_expr_calls(caller, a, line, out);
+            }
+            out.push(CallSite {
+                caller: caller.to_string(),
+                callee: name.clone(),
+                args: args.clone(),
+                line,
+            });
+        }
+        Expression::UnaryOp { expr, .. } => collect_expr_calls(caller, expr, line, out),
+        Expression::BinaryOp { left, right, .. } => {
+            collect_expr_calls(caller, left, line, out);
+            collect_expr_calls(caller, right, line, out);
+        }
+        Expression::Index { base, index, .. } => {
+            collect_expr_calls(caller, base, line, out);
+            collect_expr_calls(caller, index, line, out);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn records_nested_and_expression_calls() {
+        let src = "FUNCTION_BLOCK FB_Motor\nBEGIN\n    Speed := 0;\nEND_FUNCTION_BLOCK\n\
+                   ORGANIZATION_BLOCK OB1\nBEGIN\n    IF Run THEN\n        fb_motor(HMI_Speed);\n    END_IF;\n    \
+                   x := Scale(Raw);\nEND_ORGANIZATION_BLOCK\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let graph = CallGraph::build(&program);
+
+        let sites = graph.callees("ob1");
+        let names: Vec<&str> = sites.iter().map(|s| s.callee.as_str()).collect();
+        assert_eq!(names, vec!["fb_motor", "Scale"]);
+        assert!(sites[0].has_sensitive_arg(&Policy::default()));
+        assert!(!sites[1].has_sensitive_arg(&Policy::default()));
+        assert_eq!(sites[0].line, 8);
+
+        assert_eq!(graph.resolve(&program, &sites[0]).unwrap().name, "FB_Motor");
+        assert!(graph.resolve(&program, &sites[1]).is_none());
+        assert_eq!(graph.callers_of("FB_MOTOR").len(), 1);
+    }
+}
@@ -0,0 +1,90 @@
+//! Canonical form for comparisons between a variable and a constant.
+//!
+//! `0 < X`, `X > 0` and `X > 1 - 1` all normalise to `("X", Gt, Int(0))`,
+//! so rules reasoning about guards only have to handle the variable on the
+//! left.
+
+use crate::ast::{BinOp, Expression};
+use crate::rules::utils::expr_text;
+
+use super::{const_fold, ConstValue};
+
+/// `(variable, operator, constant)` for a comparison with the variable on
+/// the left, flipping `literal op var` into `var flipped_op literal`. The
+/// variable may be a name, a member or an array element such as `Data[i]`,
+/// given as its source text; the constant side may be any expression
+/// [`const_fold`] can evaluate. `None` if `e` is not a comparison between a
+/// variable and a constant.
+pub fn normalize_comparison(e: &Expression) -> Option<(String, BinOp, ConstValue)> {
+    let Expression::BinaryOp { op, left, right, .. } = e else {
+        return None;
+    };
+    if !op.is_comparison() {
+        return None;
+    }
+    if let (Some(var), Some(value)) = (variable(left), const_fold(right)) {
+        return Some((var, *op, value));
+    }
+    let (Some(value), Some(var)) = (const_fold(left), variable(right)) else {
+        return None;
+    };
+    Some((var, op.flipped(), value))
+}
+
+fn variable(e: &Expression) -> Option<String> {
+    match e {
+        Expression::Identifier(_) | Expression::Member { .. } | Expression::Index { .. } => Some(expr_text(e)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+    use crate::ast::Statement;
+
+    fn condition(cond: &str) -> Expression {
+        let src = format!("FUNCTION FC_A\nBEGIN\n    IF {} THEN\n        x := 1;\n    END_IF;\nEND_FUNCTION\n", cond);
+        match parse_scl_from_str(&src).unwrap().functions.remove(0).statements.remove(0) {
+            Statement::IfStmt { condition, .. } => condition,
+            other => panic!("expected IF, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn literal_on_the_left_is_flipped() {
+        let expected = Some(("Level".to_string(), BinOp::Gt, ConstValue::Int(0)));
+        assert_eq!(normalize_comparison(&condition("Level > 0")), expected);
+        assert_eq!(normalize_comparison(&condition("0 < Level")), expected);
+        assert_eq!(normalize_comparison(&condition("2 - 2 < Level")), expected);
+        assert_eq!(
+            normalize_comparison(&condition("10 >= Tank.Level")),
+            Some(("Tank.Level".to_string(), BinOp::Le, ConstValue::Int(10)))
+        );
+        assert_eq!(
+            normalize_comparison(&condition("-5 <> Offset")),
+            Some(("Offset".to_string(), BinOp::Neq, ConstValue::Int(-5)))
+        );
+    }
+
+    #[test]
+    fn array_elements_are_variables() {
+        assert_eq!(
+            normalize_comparison(&condition("100 >= Data[i]")),
+            Some(("Data[i]".to_string(), BinOp::Le, ConstValue::Int(100)))
+        );
+        assert_eq!(
+            normalize_comparison(&condition("Tank.Levels[2] > 0")),
+            Some(("Tank.Levels[2]".to_string(), BinOp::Gt, ConstValue::Int(0)))
+        );
+    }
+
+    #[test]
+    fn non_comparisons_are_rejected() {
+        assert_eq!(normalize_comparison(&condition("Level + 1")), None);
+        assert_eq!(normalize_comparison(&condition("Level > Limit")), None);
+        assert_eq!(normalize_comparison(&condition("1 < 2")), None);
+        assert_eq!(normalize_comparison(&condition("Run")), None);
+    }
+}
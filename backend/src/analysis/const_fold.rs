@@ -0,0 +1,170 @@
+//! Constant folding over literal expressions.
+//!
+//! `const_fold` evaluates an expression when every operand that matters is
+//! a literal, e.g. `0 + 0`, `2 * (3 - 1)` or `10 > 3`. Boolean operators
+//! short-circuit, so `FALSE AND Run` folds to `FALSE` even though `Run` is
+//! unknown. Anything else yields `None`.
+
+use crate::ast::{BinOp, Expression, UnaryOp};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstValue {
+    Int(i64),
+    Real(f64),
+    Bool(bool),
+}
+
+impl ConstValue {
+    /// True for `0`, `0.0` and `FALSE`, the values rules treat as a safe state.
+    pub fn is_zero(&self) -> bool {
+        match *self {
+            ConstValue::Int(n) => n == 0,
+            ConstValue::Real(r) => r == 0.0,
+            ConstValue::Bool(b) => !b,
+        }
+    }
+
+    fn as_real(&self) -> Option<f64> {
+        match *self {
+            ConstValue::Int(n) => Some(n as f64),
+            ConstValue::Real(r) => Some(r),
+            ConstValue::Bool(_) => None,
+        }
+    }
+}
+
+pub fn const_fold(e: &Expression) -> Option<ConstValue> {
+    match e {
+        Expression::NumberLiteral(n, _) => Some(ConstValue::Int(*n)),
+        Expression::BoolLiteral(b, _) => Some(ConstValue::Bool(*b)),
+        Expression::UnaryOp { op: UnaryOp::Not, expr, .. } => match const_fold(expr)? {
+            ConstValue::Bool(b) => Some(ConstValue::Bool(!b)),
+            // NOT on an integer is a bitwise complement in SCL.
+            ConstValue::Int(n) => Some(ConstValue::Int(!n)),
+            ConstValue::Real(_) => None,
+        },
+        Expression::UnaryOp { op: UnaryOp::Neg, expr, .. } => match const_fold(expr)? {
+            ConstValue::Int(n) => n.checked_neg().map(ConstValue::Int),
+            ConstValue::Real(r) => Some(ConstValue::Real(-r)),
+            ConstValue::Bool(_) => None,
+        },
+        Expression::BinaryOp { op: BinOp::And, left, right, .. } => {
+            fold_logic(const_fold(left), const_fold(right), false)
+        }
+        Expression::BinaryOp { op: BinOp::Or, left, right, .. } => {
+            fold_logic(const_fold(left), const_fold(right), true)
+        }
+        Expression::BinaryOp { op, left, right, .. } => {
+            fold_binary(*op, const_fold(left)?, const_fold(right)?)
+        }
+        _ => None,
+    }
+}
+
+/// `AND` (dominant = false) and `OR` (dominant = true): one constant
+/// dominant operand decides the result on its own.
+fn fold_logic(l: Option<ConstValue>, r: Option<ConstValue>, dominant: bool) -> Option<ConstValue> {
+    match (l, r) {
+        (Some(ConstValue::Bool(b)), _) | (_, Some(ConstValue::Bool(b))) if b == dominant => {
+            Some(ConstValue::Bool(dominant))
+        }
+        (Some(ConstValue::Bool(_)), Some(ConstValue::Bool(_))) => Some(ConstValue::Bool(!dominant)),
+        _ => None,
+    }
+}
+
+fn fold_binary(op: BinOp, l: ConstValue, r: ConstValue) -> Option<ConstValue> {
+    use ConstValue::*;
+
+    if let (Int(a), Int(b)) = (l, r) {
+        return match op {
+            BinOp::Add => a.checked_add(b).map(Int),
+            BinOp::Sub => a.checked_sub(b).map(Int),
+            BinOp::Mul => a.checked_mul(b).map(Int),
+            // Integer division truncates; a zero divisor is left unfolded.
+            BinOp::Div => a.checked_div(b).map(Int),
+            _ => compare(op, a.cmp(&b)),
+        };
+    }
+    if let (Bool(a), Bool(b)) = (l, r) {
+        return match op {
+            BinOp::Eq => Some(Bool(a == b)),
+            BinOp::Neq => Some(Bool(a != b)),
+            _ => None,
+        };
+    }
+
+    let (a, b) = (l.as_real()?, r.as_real()?);
+    match op {
+        BinOp::Add => Some(Real(a + b)),
+        BinOp::Sub => Some(Real(a - b)),
+        BinOp::Mul => Some(Real(a * b)),
+        BinOp::Div if b != 0.0 => Some(Real(a / b)),
+        BinOp::Div => None,
+        _ => compare(op, a.partial_cmp(&b)?),
+    }
+}
+
+fn compare(op: BinOp, ord: std::cmp::Ordering) -> Option<ConstValue> {
+    use std::cmp::Ordering::*;
+
+    let result = match op {
+        BinOp::Eq => ord == Equal,
+        BinOp::Neq => ord != Equal,
+        BinOp::Lt => ord == Less,
+        BinOp::Le => ord != Greater,
+        BinOp::Gt => ord == Greater,
+        BinOp::Ge => ord != Less,
+        _ => return None,
+    };
+    Some(ConstValue::Bool(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(n: i64) -> Expression {
+        Expression::NumberLiteral(n, 1)
+    }
+
+    fn boolean(b: bool) -> Expression {
+        Expression::BoolLiteral(b, 1)
+    }
+
+    fn var(name: &str) -> Expression {
+        Expression::Identifier(name.into())
+    }
+
+    fn bin(op: BinOp, left: Expression, right: Expression) -> Expression {
+        Expression::BinaryOp { op, left: Box::new(left), right: Box::new(right), line: 1, end_line: 1 }
+    }
+
+    #[test]
+    fn folds_nested_arithmetic_and_comparisons() {
+        // 2 * (7 - 3) / 3 = 2
+        let e = bin(BinOp::Div, bin(BinOp::Mul, num(2), bin(BinOp::Sub, num(7), num(3))), num(3));
+        assert_eq!(const_fold(&e), Some(ConstValue::Int(2)));
+        assert!(const_fold(&bin(BinOp::Add, num(0), num(0))).unwrap().is_zero());
+        assert_eq!(const_fold(&bin(BinOp::Ge, e.clone(), num(2))), Some(ConstValue::Bool(true)));
+        assert_eq!(const_fold(&bin(BinOp::Div, num(1), num(0))), None);
+        assert_eq!(const_fold(&bin(BinOp::Add, num(1), var("X"))), None);
+    }
+
+    #[test]
+    fn boolean_operators_short_circuit() {
+        assert_eq!(const_fold(&bin(BinOp::And, boolean(false), var("Run"))), Some(ConstValue::Bool(false)));
+        assert_eq!(const_fold(&bin(BinOp::Or, var("Run"), boolean(true))), Some(ConstValue::Bool(true)));
+        assert_eq!(const_fold(&bin(BinOp::And, boolean(true), var("Run"))), None);
+        assert_eq!(const_fold(&bin(BinOp::Or, boolean(false), boolean(false))), Some(ConstValue::Bool(false)));
+    }
+
+    #[test]
+    fn negation_folds_and_keeps_zero_safe() {
+        let neg = |e| Expression::UnaryOp { op: UnaryOp::Neg, expr: Box::new(e), line: 1, end_line: 1 };
+        assert!(const_fold(&neg(bin(BinOp::Sub, num(2), num(2)))).unwrap().is_zero());
+        assert_eq!(const_fold(&neg(bin(BinOp::Add, num(1), num(2)))), Some(ConstValue::Int(-3)));
+        assert_eq!(const_fold(&neg(num(i64::MIN))), None);
+        assert_eq!(const_fold(&neg(var("Speed"))), None);
+    }
+}
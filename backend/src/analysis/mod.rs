@@ -0,0 +1,16 @@
+//! Program-level analyses shared by several rules. Unlike the rules
+//! themselves these produce facts about a [`Program`], not violations.
+//!
+//! [`Program`]: crate::ast::Program
+
+pub mod call_graph;
+pub mod comparison;
+pub mod const_fold;
+pub mod types;
+pub mod writes;
+
+pub use call_graph::CallGraph;
+pub use comparison::normalize_comparison;
+pub use const_fold::{const_fold, ConstValue};
+pub use types::{declared_type, infer_type, ValueType};
+pub use writes::{WriteSite, Writes};
@@ -0,0 +1,148 @@
+//! Coarse value types of expressions, from the block's declarations.
+//!
+//! Only what the declarations and literals say for certain is inferred:
+//! a name declared `INT` is an integer, `a > b` is a BOOL, `INT_TO_REAL(x)`
+//! is a REAL. Anything else (members of structures, globals, calls to
+//! user functions, mixed operands) is `None`, so callers can treat a
+//! known type as reliable.
+
+use crate::ast::{BinOp, DataType, Expression, Function, UnaryOp};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Bool,
+    /// Signed and unsigned integers and bit strings (`BYTE`, `WORD`, ...).
+    Integer,
+    Real,
+    /// `STRING`, `WSTRING`, `CHAR`, `WCHAR`.
+    Text,
+    /// Durations, dates and times of day.
+    Time,
+}
+
+impl ValueType {
+    /// The class of an elementary type name, e.g. `DINT` or `LREAL`.
+    /// `None` for structures, blocks and unknown names.
+    pub fn from_type_name(name: &str) -> Option<ValueType> {
+        let ty = match name.to_ascii_uppercase().as_str() {
+            "BOOL" => ValueType::Bool,
+            "SINT" | "INT" | "DINT" | "LINT" | "USINT" | "UINT" | "UDINT" | "ULINT" | "BYTE" | "WORD" | "DWORD"
+            | "LWORD" => ValueType::Integer,
+            "REAL" | "LREAL" => ValueType::Real,
+            "CHAR" | "WCHAR" | "STRING" | "WSTRING" => ValueType::Text,
+            "TIME" | "LTIME" | "DATE" | "TIME_OF_DAY" | "TOD" | "LTOD" | "DATE_AND_TIME" | "DT" | "LDT" | "DTL" => {
+                ValueType::Time
+            }
+            _ => return None,
+        };
+        Some(ty)
+    }
+
+    pub fn of(data_type: &DataType) -> Option<ValueType> {
+        match data_type {
+            DataType::String(_) => Some(ValueType::Text),
+            DataType::Named(name) => ValueType::from_type_name(name),
+            DataType::Array { .. } => None,
+        }
+    }
+
+    /// The IEC name shown in reports.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ValueType::Bool => "BOOL",
+            ValueType::Integer => "integer",
+            ValueType::Real => "REAL",
+            ValueType::Text => "STRING",
+            ValueType::Time => "TIME",
+        }
+    }
+}
+
+/// The declared type of the variable `e` names in `f`; for `A[i]`, the
+/// element type of `A`.
+pub fn declared_type(f: &Function, e: &Expression) -> Option<ValueType> {
+    match e {
+        Expression::Identifier(name) => f.declaration(name).and_then(|d| ValueType::of(&d.data_type)),
+        Expression::Index { base, .. } => {
+            let name = base.name()?;
+            match &f.declaration(&name)?.data_type {
+                DataType::Array { element, .. } => ValueType::of(element),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The type `e` evaluates to inside `f`, when it is certain.
+pub fn infer_type(f: &Function, e: &Expression) -> Option<ValueType> {
+    match e {
+        Expression::BoolLiteral(..) => Some(ValueType::Bool),
+        Expression::NumberLiteral(..) => Some(ValueType::Integer),
+        Expression::StringLiteral(..) => Some(ValueType::Text),
+        Expression::Identifier(_) | Expression::Index { .. } => declared_type(f, e),
+        Expression::UnaryOp { op: UnaryOp::Neg, expr, .. } => {
+            infer_type(f, expr).filter(|t| matches!(t, ValueType::Integer | ValueType::Real))
+        }
+        Expression::UnaryOp { op: UnaryOp::Not, expr, .. } => {
+            infer_type(f, expr).filter(|t| matches!(t, ValueType::Bool | ValueType::Integer))
+        }
+        Expression::BinaryOp { op, .. } if op.is_comparison() => Some(ValueType::Bool),
+        Expression::BinaryOp { op: BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div, left, right, .. } => {
+            match (infer_type(f, left)?, infer_type(f, right)?) {
+                (ValueType::Integer, ValueType::Integer) => Some(ValueType::Integer),
+                (ValueType::Real, ValueType::Real | ValueType::Integer) | (ValueType::Integer, ValueType::Real) => {
+                    Some(ValueType::Real)
+                }
+                _ => None,
+            }
+        }
+        Expression::BinaryOp { op: BinOp::And | BinOp::Or, left, right, .. } => {
+            let (l, r) = (infer_type(f, left)?, infer_type(f, right)?);
+            Some(l).filter(|l| *l == r && matches!(l, ValueType::Bool | ValueType::Integer))
+        }
+        // `INT_TO_REAL(x)` returns what its name says.
+        Expression::FuncCall { name, .. } => {
+            let upper = name.to_ascii_uppercase();
+            let (_, to) = upper.split_once("_TO_")?;
+            ValueType::from_type_name(to)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Statement;
+    use crate::parser::scl::parse_scl_from_str;
+
+    fn types(body: &str) -> Vec<Option<ValueType>> {
+        let src = format!(
+            "FUNCTION FC_A\nVAR\n    Run : BOOL;\n    Count : INT;\n    Level : REAL;\n    Name : STRING[10];\n    \
+             Table : ARRAY[0..3] OF DINT;\nEND_VAR\nBEGIN\n{}\nEND_FUNCTION\n",
+            body
+        );
+        let f = parse_scl_from_str(&src).unwrap().functions.remove(0);
+        f.statements
+            .iter()
+            .map(|st| match st {
+                Statement::Assign { value, .. } => infer_type(&f, value),
+                other => panic!("expected an assignment, got {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn types_follow_declarations_operators_and_conversions() {
+        let body = "x := Run;\nx := Count * 2;\nx := Level + Count;\nx := Table[1];\nx := Count > 3;\n\
+                    x := NOT Run AND TRUE;\nx := INT_TO_REAL(Count);\nx := Name;";
+        use ValueType::*;
+        assert_eq!(types(body), vec![Some(Bool), Some(Integer), Some(Real), Some(Integer), Some(Bool), Some(Bool), Some(Real), Some(Text)]);
+    }
+
+    #[test]
+    fn unknown_operands_give_no_type() {
+        assert_eq!(types("x := Motor.Speed;\nx := Other + 1;\nx := Run AND Count;\nx := FC_Scale(Count);"), vec![None; 4]);
+    }
+}
@@ -0,0 +1,155 @@
+//! Where each variable is assigned, and on which control-flow path.
+//!
+//! Every reachable `X := ...` in the program is recorded with the branches
+//! (THEN/ELSE of an IF, arms of a CASE) taken to reach it. Two writes in
+//! the same routine that sit in different arms of one branch can never run
+//! in the same scan; see [`WriteSite::excludes`].
+
+use std::collections::HashMap;
+
+use crate::ast::{reachable, Expression, Function, Program, Statement};
+
+/// One arm taken on the way to a write.
+#[derive(Debug, Clone, Copy)]
+struct Branch<'p> {
+    /// Position of the IF/CASE in walk order; unique within the program.
+    id: usize,
+    /// 0 for THEN, 1 for ELSE; CASE arms in order, the CASE ELSE last.
+    arm: usize,
+    /// The IF condition, which holds in arm 0.
+    condition: Option<&'p Expression>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WriteSite<'p> {
+    pub function: &'p Function,
+    pub line: usize,
+    path: Vec<Branch<'p>>,
+}
+
+impl<'p> WriteSite<'p> {
+    /// True if the two writes are on mutually exclusive paths: the same
+    /// routine, in different arms of the same IF or CASE.
+    pub fn excludes(&self, other: &WriteSite) -> bool {
+        std::ptr::eq(self.function, other.function)
+            && self.path.iter().any(|b| other.path.iter().any(|o| o.id == b.id && o.arm != b.arm))
+    }
+
+    /// IF conditions known to hold at the write.
+    pub fn conditions(&self) -> impl Iterator<Item = &'p Expression> + '_ {
+        self.path.iter().filter(|b| b.arm == 0).filter_map(|b| b.condition)
+    }
+}
+
+/// Writes grouped by variable. Names are matched case-insensitively and
+/// reported as first written; groups are in order of first write.
+#[derive(Debug, Default)]
+pub struct Writes<'p> {
+    by_var: Vec<(String, Vec<WriteSite<'p>>)>,
+    /// Position in `by_var` by upper-cased name.
+    index: HashMap<String, usize>,
+}
+
+impl<'p> Writes<'p> {
+    pub fn collect(program: &'p Program) -> Self {
+        let mut c = Collector { writes: Writes::default(), path: Vec::new(), next_id: 0 };
+        for f in program.code() {
+            c.statements(f, &f.statements);
+        }
+        c.writes
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[WriteSite<'p>])> {
+        self.by_var.iter().map(|(name, sites)| (name.as_str(), sites.as_slice()))
+    }
+
+    /// The writes to `name`, compared case-insensitively.
+    pub fn get(&self, name: &str) -> Option<&[WriteSite<'p>]> {
+        self.index.get(&name.to_ascii_uppercase()).map(|&i| self.by_var[i].1.as_slice())
+    }
+
+    fn push(&mut self, name: String, site: WriteSite<'p>) {
+        let next = self.by_var.len();
+        let i = *self.index.entry(name.to_ascii_uppercase()).or_insert(next);
+        if i == next {
+            self.by_var.push((name, Vec::new()));
+        }
+        self.by_var[i].1.push(site);
+    }
+}
+
+struct Collector<'p> {
+    writes: Writes<'p>,
+    path: Vec<Branch<'p>>,
+    next_id: usize,
+}
+
+impl<'p> Collector<'p> {
+    fn statements(&mut self, f: &'p Function, stmts: &'p [Statement]) {
+        for st in reachable(stmts) {
+            self.statement(f, st);
+        }
+    }
+
+    fn arm(&mut self, f: &'p Function, id: usize, arm: usize, condition: Option<&'p Expression>, body: &'p [Statement]) {
+        self.path.push(Branch { id, arm, condition });
+        self.statements(f, body);
+        self.path.pop();
+    }
+
+    fn statement(&mut self, f: &'p Function, st: &'p Statement) {
+        match st {
+            Statement::Assign { target, line, .. } => {
+                if let Some(name) = target.name() {
+                    let site = WriteSite { function: f, line: *line, path: self.path.clone() };
+                    self.writes.push(name, site);
+                }
+            }
+            Statement::IfStmt { condition, then_branch, else_branch, .. } => {
+                let id = self.next_id();
+                self.arm(f, id, 0, Some(condition), then_branch);
+                self.arm(f, id, 1, Some(condition), else_branch);
+            }
+            Statement::CaseStmt { cases, else_branch, .. } => {
+                let id = self.next_id();
+                for (arm, (_, body)) in cases.iter().enumerate() {
+                    self.arm(f, id, arm, None, body);
+                }
+                self.arm(f, id, cases.len(), None, else_branch);
+            }
+            Statement::WhileStmt { body, .. } | Statement::ForStmt { body, .. } => self.statements(f, body),
+            _ => {}
+        }
+    }
+
+    fn next_id(&mut self) -> usize {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn branches_of_one_if_exclude_each_other() {
+        let src = "FUNCTION FC_A\nBEGIN\n    IF Run THEN\n        Motor := TRUE;\n    ELSIF Jog THEN\n        motor := TRUE;\n    ELSE\n        \
+                   CASE Mode OF\n            1: Motor := FALSE;\n            2: Motor := TRUE;\n        END_CASE;\n    END_IF;\n    Motor := FALSE;\nEND_FUNCTION\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let writes = Writes::collect(&program);
+        let (name, sites) = writes.iter().next().unwrap();
+        assert_eq!(name, "Motor");
+        let lines: Vec<usize> = sites.iter().map(|s| s.line).collect();
+        assert_eq!(lines, vec![4, 6, 9, 10, 13]);
+        assert!(sites[0].excludes(&sites[1]));
+        assert!(sites[1].excludes(&sites[2]));
+        assert!(sites[2].excludes(&sites[3]));
+        assert!(!sites[0].excludes(&sites[4]));
+        assert_eq!(sites[0].conditions().count(), 1);
+        assert_eq!(sites[4].conditions().count(), 0);
+        assert_eq!(writes.get("MOTOR").map(|s| s.len()), Some(5));
+        assert!(writes.get("Pump").is_none());
+    }
+}
@@ -0,0 +1,461 @@
+//! Reusable analysis configuration.
+//!
+//! An [`Analyzer`] holds the policy, the rule selection, any severity
+//! overrides and custom rules (see [`Rule`]), so a caller checking many
+//! files sets them up once:
+//!
+//! ```
+//! use plc_secure_checker_lib::analyzer::Analyzer;
+//! use plc_secure_checker_lib::rules::Policy;
+//!
+//! let analyzer = Analyzer::builder().with_policy(Policy::default()).with_disabled_rules(&[1, 16]).build().unwrap();
+//! let report = analyzer.analyze("FUNCTION FC_A\nBEGIN\n    x := 1;\nEND_FUNCTION\n", "a.scl");
+//! assert!(report.results.iter().all(|r| r.rule_no != 1));
+//! ```
+//!
+//! The WASM entry points in the crate root are thin wrappers around it.
+
+use std::sync::Arc;
+
+use crate::ast::Program;
+use crate::parser::{self, MergedProject};
+use crate::report::AnalysisReport;
+use crate::rules::policy::ListMerge;
+use crate::rules::{self, registry, Policy, Rule, RuleResult, RuleStatus, Severity, WasmRuleResult};
+
+/// A configured analysis. Build one with [`Analyzer::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct Analyzer {
+    policy: Arc<Policy>,
+    /// Rules to run; empty for all.
+    enabled: Vec<u8>,
+    disabled: Vec<u8>,
+    severities: Vec<(u8, Severity)>,
+    custom: Vec<Arc<dyn Rule>>,
+    explain: bool,
+    /// Salt for redaction tokens; `None` when names are not redacted.
+    redaction_salt: Option<Vec<u8>>,
+}
+
+/// Builder for [`Analyzer`]. Every setting is optional: the default runs
+/// every built-in rule under the default policy.
+#[derive(Debug, Clone, Default)]
+pub struct AnalyzerBuilder {
+    analyzer: Analyzer,
+    redact_identifiers: bool,
+    redaction_salt: Option<Vec<u8>>,
+}
+
+impl AnalyzerBuilder {
+    /// The policy to check against. Pass an `Arc<Policy>` to share one
+    /// policy between analyzers without copying it.
+    pub fn with_policy(mut self, policy: impl Into<Arc<Policy>>) -> Self {
+        self.analyzer.policy = policy.into();
+        self
+    }
+
+    /// Lay `overlay` over the policy set so far (see [`Policy::merge`]), e.g.
+    /// a project's overrides over an organisation-wide base policy.
+    pub fn with_policy_overlay(mut self, overlay: Policy, lists: ListMerge) -> Self {
+        let base = Arc::unwrap_or_clone(std::mem::take(&mut self.analyzer.policy));
+        self.analyzer.policy = Arc::new(Policy::merge(base, overlay, lists));
+        self
+    }
+
+    /// Run only these rules. An empty list runs every rule. Numbers that
+    /// are neither built-in nor custom rules make [`build`](Self::build) fail.
+    pub fn with_rules(mut self, rules: &[u8]) -> Self {
+        self.analyzer.enabled = rules.to_vec();
+        self
+    }
+
+    /// Never run these rules, even if [`with_rules`](Self::with_rules) lists them.
+    pub fn with_disabled_rules(mut self, rules: &[u8]) -> Self {
+        self.analyzer.disabled.extend_from_slice(rules);
+        self
+    }
+
+    /// Report every finding of `rule_no` with `severity` instead of the
+    /// severity the rule chose.
+    pub fn with_severity(mut self, rule_no: u8, severity: Severity) -> Self {
+        self.analyzer.severities.retain(|(no, _)| *no != rule_no);
+        self.analyzer.severities.push((rule_no, severity));
+        self
+    }
+
+    /// Run `rule` as well as the built-in rules. It is selected, disabled
+    /// and overridden by its [`Rule::number`] like any other rule; a
+    /// number already used by a built-in rule replaces that rule.
+    pub fn with_custom_rule(mut self, rule: Box<dyn Rule>) -> Self {
+        self.analyzer.custom.push(Arc::from(rule));
+        self
+    }
+
+    /// Attach each rule's rationale and documentation anchor to its
+    /// findings (see [`rules::explain`]). Off by default.
+    pub fn with_explanations(mut self, explain: bool) -> Self {
+        self.analyzer.explain = explain;
+        self
+    }
+
+    /// Replace the program's names in findings with hashed tokens (see
+    /// [`rules::redact`]), for reports shared outside the site. Off by
+    /// default. Tokens match within every report of the built analyzer;
+    /// set [`with_redaction_salt`](Self::with_redaction_salt) to make them
+    /// match across analyzers and runs.
+    pub fn with_redact_identifiers(mut self, redact: bool) -> Self {
+        self.redact_identifiers = redact;
+        self
+    }
+
+    /// The secret key redaction tokens are hashed with. Without one a
+    /// random salt is drawn when the analyzer is built.
+    pub fn with_redaction_salt(mut self, salt: &[u8]) -> Self {
+        self.redaction_salt = Some(salt.to_vec());
+        self
+    }
+
+    /// Finish the configuration. Fails if the policy, after any overlays,
+    /// does not pass [`Policy::validate`], e.g. because a name pattern is
+    /// not a valid regular expression, or if [`with_rules`](Self::with_rules)
+    /// names a rule that does not exist.
+    pub fn build(mut self) -> Result<Analyzer, String> {
+        self.analyzer.policy.validate().map_err(|e| format!("Invalid policy: {}", e))?;
+        let unknown: Vec<String> = self
+            .analyzer
+            .enabled
+            .iter()
+            .filter(|no| registry::lookup(**no).is_none() && !self.analyzer.custom.iter().any(|r| r.number() == **no))
+            .map(|no| no.to_string())
+            .collect();
+        if !unknown.is_empty() {
+            return Err(format!("Unknown rule number(s) {}", unknown.join(", ")));
+        }
+        if self.redact_identifiers {
+            self.analyzer.redaction_salt = Some(self.redaction_salt.unwrap_or_else(rules::redact::random_salt));
+        }
+        Ok(self.analyzer)
+    }
+}
+
+impl Analyzer {
+    pub fn builder() -> AnalyzerBuilder {
+        AnalyzerBuilder::default()
+    }
+
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    /// Parse `source` (the frontend is picked from `file_name`'s extension)
+    /// and run the configured rules. Blocks that fail to parse become
+    /// ERROR entries ahead of the rule entries; the rest are still checked.
+    pub fn analyze(&self, source: &str, file_name: &str) -> AnalysisReport {
+        AnalysisReport { results: self.analyze_program(source, file_name).1, effective_policy: None }
+    }
+
+    /// [`analyze`](Self::analyze), also handing back the parsed program
+    /// (if anything could be parsed).
+    pub fn analyze_program(&self, source: &str, file_name: &str) -> (Option<Program>, Vec<WasmRuleResult>) {
+        match parse_input(source, file_name) {
+            Ok((program, mut results)) => {
+                results.append(&mut self.run(&program, source));
+                (Some(program), results)
+            }
+            Err(only) => (None, only),
+        }
+    }
+
+    /// Run the configured rules on an already parsed program, honouring
+    /// any `plc-checker:ignore` pragmas in `source`.
+    pub fn run(&self, program: &Program, source: &str) -> Vec<WasmRuleResult> {
+        self.run_in(program, source, Vec::new())
+    }
+
+    /// [`run`](Self::run) for a project merged by [`parser::merge_project`].
+    /// Lines are those of the merged source; annotation lookups and block
+    /// attribution stay within each file.
+    pub fn run_project(&self, project: &MergedProject) -> Vec<WasmRuleResult> {
+        self.run_in(&project.program, &project.source, project.file_starts())
+    }
+
+    /// [`run`](Self::run) with the results kept per rule, for the report
+    /// writers that take [`RuleResult`]s.
+    pub fn check(&self, program: &Program, source: &str) -> Vec<RuleResult> {
+        self.check_in(program, source, Vec::new())
+    }
+
+    /// `file_name` as this analyzer reports it: its stem is replaced by a
+    /// token when names are redacted.
+    pub fn report_file_name(&self, program: &Program, file_name: &str) -> String {
+        match &self.redaction_salt {
+            Some(salt) => rules::redact::Redaction::from_program(program, salt).file_name(file_name),
+            None => file_name.to_string(),
+        }
+    }
+
+    fn run_in(&self, program: &Program, source: &str, file_starts: Vec<usize>) -> Vec<WasmRuleResult> {
+        rules::to_wasm_results(self.check_in(program, source, file_starts))
+    }
+
+    fn check_in(&self, program: &Program, source: &str, file_starts: Vec<usize>) -> Vec<RuleResult> {
+        let built_in: Vec<u8> = registry::RULES
+            .iter()
+            .map(|m| m.no)
+            .filter(|no| self.selects(*no) && !self.custom.iter().any(|r| r.number() == *no))
+            .collect();
+        let cx = rules::AnalysisContext { file_starts, ..rules::AnalysisContext::with_source(&self.policy, source) };
+        let mut results = if built_in.is_empty() { Vec::new() } else { rules::run_in_context(program, &cx, &built_in) };
+        for rule in self.custom.iter().filter(|r| self.selects(r.number())) {
+            results.push(rules::finish(rule.number(), rule.name(), program, &cx, || {
+                let mut res = rule.check(program, &self.policy);
+                for v in &mut res.violations {
+                    v.rule_no = rule.number();
+                    v.rule_name = rule.name();
+                }
+                res.update_status();
+                res
+            }));
+        }
+        results.sort_by_key(|r| r.rule_no);
+
+        // A rule that failed stays an error whatever its configured severity.
+        for res in results.iter_mut().filter(|r| r.status != RuleStatus::Error) {
+            if let Some(&(_, severity)) = self.severities.iter().find(|(no, _)| *no == res.rule_no) {
+                res.violations.iter_mut().for_each(|v| v.severity = severity);
+            }
+        }
+        rules::attach_snippets(&mut results, source);
+        if self.explain {
+            rules::explain(&mut results);
+        }
+        if let Some(salt) = &self.redaction_salt {
+            rules::redact::Redaction::from_program(program, salt).apply(&mut results);
+        }
+        results
+    }
+
+    fn selects(&self, rule_no: u8) -> bool {
+        (self.enabled.is_empty() || self.enabled.contains(&rule_no)) && !self.disabled.contains(&rule_no)
+    }
+}
+
+/// Parse the PLC program using the appropriate frontend based on file_name.
+/// Blocks that fail to parse become ERROR entries next to the program; the
+/// rest are still checked. `Err` holds the only entries to report when
+/// there is nothing to check at all. Empty or comment-only source is an
+/// empty program, so the presence rules still report what is missing.
+pub(crate) fn parse_input(source_code: &str, file_name: &str) -> Result<(Program, Vec<WasmRuleResult>), Vec<WasmRuleResult>> {
+    let (program, parse_errors) = match parser::parse_file_partial_from_str(source_code, file_name) {
+        Ok(p) => p,
+        // Construct a sentinel error result if the PLC source fails to parse
+        Err(e) => return Err(vec![parse_error_result(0, format!("Parse Error: {}", e))]),
+    };
+    let parse_errors: Vec<WasmRuleResult> = parse_errors
+        .into_iter()
+        .map(|e| parse_error_result(e.line, format!("Parse Error: {}", e.message)))
+        .collect();
+    if program.functions.is_empty() && !parse_errors.is_empty() {
+        return Err(parse_errors);
+    }
+    Ok((program, parse_errors))
+}
+
+pub(crate) fn parse_error_result(line: usize, reason: String) -> WasmRuleResult {
+    let mut result = crate::sentinel(registry::PARSE_ERROR, reason, "Check file type and syntax.");
+    if let Some(v) = &mut result.violation {
+        v.line = line;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Violation;
+
+    const SRC: &str = "FUNCTION FC_A\nBEGIN\n    Avg := Sum / Count;\n    Table[i] := 0;\nEND_FUNCTION\n";
+
+    fn rules_run(a: &Analyzer) -> Vec<u8> {
+        let mut nos: Vec<u8> = a.analyze(SRC, "a.scl").results.iter().map(|r| r.rule_no).collect();
+        nos.dedup();
+        nos
+    }
+
+    #[test]
+    fn empty_source_runs_the_rules() {
+        let s7 = Analyzer::builder().with_policy(Policy { platform: Some("S7".into()), ..Default::default() }).build().unwrap();
+        for src in ["", "   \n", "// TODO\n"] {
+            let results = s7.analyze(src, "a.scl").results;
+            assert!(results.iter().all(|r| r.status != RuleStatus::Error), "{:?}", src);
+            assert!(results.iter().any(|r| r.rule_no == 15 && r.status == RuleStatus::Violated), "{:?}", src);
+        }
+    }
+
+    #[test]
+    fn policy_overlay_applies_over_the_base() {
+        let base = Policy { pairs: Some(vec![["Motor_Fwd".into(), "Motor_Rev".into()]]), ..Default::default() };
+        let overlay = Policy { pairs: Some(vec![["Valve_Open".into(), "Valve_Close".into()]]), ..Default::default() };
+        let src = "FUNCTION FC_A\nBEGIN\n    Motor_Fwd := TRUE;\n    Motor_Rev := TRUE;\n    \
+                   Valve_Open := TRUE;\n    Valve_Close := TRUE;\nEND_FUNCTION\n";
+        let lines = |lists| {
+            let a = Analyzer::builder().with_policy(base.clone()).with_policy_overlay(overlay.clone(), lists).with_rules(&[7]).build().unwrap();
+            a.analyze(src, "a.scl").results.iter().filter_map(|r| r.violation.as_ref()).map(|v| v.line).collect::<Vec<_>>()
+        };
+        assert_eq!(lines(ListMerge::Replace), vec![5]);
+        assert_eq!(lines(ListMerge::Append), vec![3, 5]);
+    }
+
+    #[test]
+    fn unknown_rule_numbers_are_rejected() {
+        let err = Analyzer::builder().with_rules(&[4, 99, 13]).build().unwrap_err();
+        assert_eq!(err, "Unknown rule number(s) 99, 13");
+        assert!(Analyzer::builder().with_rules(&[4, 100]).with_custom_rule(Box::new(NoTestBlocks)).build().is_ok());
+    }
+
+    #[test]
+    fn enabled_and_disabled_rules_combine() {
+        assert_eq!(rules_run(&Analyzer::builder().with_rules(&[9, 4, 1]).with_disabled_rules(&[1]).build().unwrap()), vec![4, 9]);
+        let all_but_one = rules_run(&Analyzer::builder().with_disabled_rules(&[4]).build().unwrap());
+        assert_eq!(all_but_one.len(), registry::RULES.len() - 1);
+        assert!(!all_but_one.contains(&4));
+        assert!(rules_run(&Analyzer::builder().with_rules(&[4]).with_disabled_rules(&[4]).build().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn severity_override_applies_to_every_finding_of_the_rule() {
+        let a = Analyzer::builder().with_rules(&[4, 9]).with_severity(4, Severity::Info).build().unwrap();
+        let found: Vec<(u8, Severity)> = a
+            .analyze(SRC, "a.scl")
+            .results
+            .iter()
+            .filter_map(|r| r.violation.as_ref())
+            .map(|v| (v.rule_no, v.severity))
+            .collect();
+        assert_eq!(found, vec![(4, Severity::Info), (9, Severity::Warning)]);
+    }
+
+    #[test]
+    fn redaction_hides_names_but_keeps_the_findings() {
+        let findings = |redact| {
+            let a = Analyzer::builder().with_rules(&[4, 9]).with_redact_identifiers(redact).build().unwrap();
+            a.analyze(SRC, "a.scl").results.into_iter().filter_map(|r| r.violation).collect::<Vec<_>>()
+        };
+        let (plain, redacted) = (findings(false), findings(true));
+        assert_eq!(plain.len(), redacted.len());
+        for (p, r) in plain.iter().zip(&redacted) {
+            assert_eq!((p.rule_no, p.line, p.severity), (r.rule_no, r.line, r.severity));
+            assert!(p.snippet.as_deref().unwrap().contains("Count") || p.snippet.as_deref().unwrap().contains("Table"));
+            for name in ["Count", "Table", "Avg", "FC_A"] {
+                assert!(!r.reason.contains(name) && !r.snippet.as_deref().unwrap().contains(name), "{:?}", r);
+            }
+        }
+    }
+
+    #[test]
+    fn check_is_run_before_flattening() {
+        let a = Analyzer::builder().with_redaction_salt(b"site").with_redact_identifiers(true).build().unwrap();
+        let program = crate::parser::parse_file_from_str(SRC, "a.scl").unwrap();
+        assert_eq!(format!("{:?}", rules::to_wasm_results(a.check(&program, SRC))), format!("{:?}", a.run(&program, SRC)));
+        let name = a.report_file_name(&program, "FC_A.scl");
+        assert!(name.ends_with(".scl") && !name.contains("FC_A"), "{}", name);
+        assert_eq!(Analyzer::builder().build().unwrap().report_file_name(&program, "FC_A.scl"), "FC_A.scl");
+    }
+
+    #[test]
+    fn build_rejects_a_policy_that_does_not_validate() {
+        let policy = Policy { estop_patterns: Some(vec!["E_Stop(".into()]), ..Default::default() };
+        let err = Analyzer::builder().with_policy(policy).build().unwrap_err();
+        assert!(err.contains("estop_patterns"), "{}", err);
+    }
+
+    #[test]
+    fn analyzer_can_be_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Analyzer>();
+    }
+
+    /// Example custom rule: test blocks must not ship.
+    struct NoTestBlocks;
+
+    impl Rule for NoTestBlocks {
+        fn number(&self) -> u8 {
+            100
+        }
+        fn name(&self) -> &'static str {
+            "Do not ship test blocks"
+        }
+        fn check(&self, program: &Program, _policy: &Policy) -> rules::RuleResult {
+            let violations: Vec<Violation> = program
+                .functions
+                .iter()
+                .filter(|f| f.name.to_ascii_uppercase().starts_with("TEST_"))
+                .map(|f| Violation {
+                    rule_no: 0,
+                    rule_name: "",
+                    line: f.line,
+                    end_line: None,
+                    reason: format!("Block '{}' looks like test code", f.name),
+                    suggestion: "Remove it from the production project.".into(),
+                    severity: Severity::Warning,
+                    snippet: None,
+                    fix: None,
+                    function: None,
+                    file: None,
+                    rationale: None,
+                    doc_anchor: None,
+                })
+                .collect();
+            rules::RuleResult::violations(violations)
+        }
+    }
+
+    #[test]
+    fn custom_rule_runs_next_to_the_built_in_ones() {
+        let src = "FUNCTION Test_Pump\nBEGIN\n    x := 1;\nEND_FUNCTION\n";
+        let a = Analyzer::builder().with_rules(&[4, 100]).with_custom_rule(Box::new(NoTestBlocks)).build().unwrap();
+        let results = a.analyze(src, "a.scl").results;
+        let custom: Vec<_> = results.iter().filter(|r| r.rule_no == 100).collect();
+        assert_eq!(custom.len(), 1);
+        assert_eq!(custom[0].status, RuleStatus::Violated);
+        let v = custom[0].violation.as_ref().unwrap();
+        assert_eq!((v.rule_no, v.rule_name, v.line), (100, "Do not ship test blocks", 1));
+        assert_eq!(v.function.as_deref(), Some("Test_Pump"));
+
+        let clean = a.analyze(SRC, "a.scl").results;
+        assert!(clean.iter().any(|r| r.rule_no == 100 && r.status == RuleStatus::Passed));
+
+        let disabled = Analyzer::builder().with_custom_rule(Box::new(NoTestBlocks)).with_disabled_rules(&[100]).build().unwrap();
+        assert!(disabled.analyze(src, "a.scl").results.iter().all(|r| r.rule_no != 100));
+    }
+
+    struct Panics;
+
+    impl Rule for Panics {
+        fn number(&self) -> u8 {
+            101
+        }
+        fn name(&self) -> &'static str {
+            "Always panics"
+        }
+        fn check(&self, _program: &Program, _policy: &Policy) -> rules::RuleResult {
+            panic!("index out of bounds")
+        }
+    }
+
+    #[test]
+    fn panicking_custom_rule_is_an_error_whatever_its_severity() {
+        let a = Analyzer::builder()
+            .with_rules(&[4, 101])
+            .with_custom_rule(Box::new(Panics))
+            .with_severity(101, Severity::Info)
+            .build().unwrap();
+        let results = a.analyze(SRC, "a.scl").results;
+        let failed = results.iter().find(|r| r.rule_no == 101).unwrap();
+        assert_eq!(failed.status, RuleStatus::Error);
+        let v = failed.violation.as_ref().unwrap();
+        assert_eq!(v.severity, Severity::Error);
+        assert!(v.reason.contains("index out of bounds"), "{}", v.reason);
+        assert!(results.iter().any(|r| r.rule_no == 4 && r.status == RuleStatus::Violated));
+    }
+}
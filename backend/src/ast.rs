@@ -4,26 +4,138 @@
 
 use std::fmt;
 
+use serde::Serialize;
+
+pub mod pprint;
+
 /// A complete PLC program is a collection of functions (FCs), function
 /// blocks (FBs) and organisational blocks (OBs).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(Serialize))]
 pub struct Program {
     pub functions: Vec<Function>,
 }
 
 /// A top-level routine (FC, FB or OB).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(Serialize))]
 pub struct Function {
     pub name: String,
     pub kind: FunctionKind,
     pub statements: Vec<Statement>,
     /// Best-effort source line where this routine was first seen.
     pub line: usize,
+    /// `REGION` pragmas in the body, outermost first. Statements stay in
+    /// `statements`; a region only labels the lines it spans.
+    pub regions: Vec<Region>,
+    /// Variables from the block's `VAR*` sections, in source order. Empty
+    /// for frontends that do not read declarations.
+    pub declarations: Vec<Declaration>,
 }
 
-/// Kind of routine. We include both generic `OB` and specific OB variants
-/// that certain rules care about (OB1, OB100, OB82, OB86, OB121).
+impl Function {
+    /// The declaration of `name` in this block, compared case-insensitively.
+    /// A `#` local prefix on `name` is ignored.
+    pub fn declaration(&self, name: &str) -> Option<&Declaration> {
+        let name = name.strip_prefix('#').unwrap_or(name);
+        self.declarations.iter().find(|d| d.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// One variable from a `VAR ... END_VAR` section.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(Serialize))]
+pub struct Declaration {
+    pub name: String,
+    pub data_type: DataType,
+    pub section: VarSection,
+    pub line: usize,
+}
+
+/// Which `VAR_*` section a declaration comes from.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ast-serde", derive(Serialize))]
+pub enum VarSection {
+    Input,
+    Output,
+    InOut,
+    Temp,
+    Static,
+    /// Plain `VAR`.
+    Var,
+}
+
+impl VarSection {
+    /// From the section keyword, e.g. `VAR_INPUT` (case-insensitive).
+    pub fn from_keyword(kw: &str) -> Self {
+        match kw.to_ascii_uppercase().as_str() {
+            "VAR_INPUT" => VarSection::Input,
+            "VAR_OUTPUT" => VarSection::Output,
+            "VAR_IN_OUT" => VarSection::InOut,
+            "VAR_TEMP" => VarSection::Temp,
+            "VAR_STAT" => VarSection::Static,
+            _ => VarSection::Var,
+        }
+    }
+
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            VarSection::Input => "VAR_INPUT",
+            VarSection::Output => "VAR_OUTPUT",
+            VarSection::InOut => "VAR_IN_OUT",
+            VarSection::Temp => "VAR_TEMP",
+            VarSection::Static => "VAR_STAT",
+            VarSection::Var => "VAR",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(Serialize))]
+pub enum DataType {
+    /// `STRING[n]` or `WSTRING[n]`; `None` when no length is given.
+    String(Option<usize>),
+    /// `ARRAY[lo..hi, ...] OF T`. A dimension whose bounds are named
+    /// constants rather than literals is `None`.
+    Array { dims: Vec<Option<(i64, i64)>>, element: Box<DataType> },
+    /// Any other type, by name (`INT`, `TON`, `"UDT_Motor"`).
+    Named(String),
+}
+
+impl DataType {
+    /// Characters a string of this type can hold. S7 strings without an
+    /// explicit length hold 254.
+    pub fn string_capacity(&self) -> Option<usize> {
+        match self {
+            DataType::String(len) => Some(len.unwrap_or(254)),
+            _ => None,
+        }
+    }
+}
+
+/// `REGION <name> ... END_REGION`, as used to organise large blocks.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(Serialize))]
+pub struct Region {
+    pub name: String,
+    /// Line of the `REGION` keyword.
+    pub line: usize,
+    /// Line of the matching `END_REGION`.
+    pub end_line: usize,
+}
+
+impl Region {
+    pub fn contains(&self, line: usize) -> bool {
+        self.line <= line && line <= self.end_line
+    }
+}
+
+/// Kind of routine. We include both generic `OB` and specific OB variants
+/// that certain rules care about (OB1, OB100, OB82, OB83, OB85, OB86,
+/// OB121). Data blocks hold no logic but are kept so rules can tell them
+/// apart from executable code. Always serializable, unlike the rest of the
+/// AST, because the outline reports it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum FunctionKind {
     FC,
     FB,
@@ -32,18 +144,23 @@ pub enum FunctionKind {
     OB1,
     OB100,
     OB82,
+    OB83,
+    OB85,
     OB86,
     OB121,
+    DataBlock,
 }
 
 /// Variable (symbolic) reference used in assignments.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(Serialize))]
 pub struct Variable {
     pub name: String,
 }
 
 /// Statements form the imperative body of a routine.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(Serialize))]
 pub enum Statement {
     /// `X := <expr>;`
     Assign {
@@ -81,23 +198,66 @@ pub enum Statement {
         else_branch: Vec<Statement>,
         line: usize,
     },
-    /// Internal marker used while rebuilding IFs from a line-oriented scan.
-    /// Safe to keep; rules ignore it.
+    /// `RETURN` (SCL) / `RET` (IL): leave the routine early.
+    Return {
+        line: usize,
+    },
+    /// `WHILE <cond> DO ... END_WHILE`
+    WhileStmt {
+        condition: Expression,
+        body: Vec<Statement>,
+        line: usize,
+    },
+    /// `FOR <var> := <start> TO <end> [BY <step>] DO ... END_FOR`. The
+    /// bounds and step are arbitrary expressions: nothing guarantees they
+    /// are constant, that `start <= end` or that the step is positive.
+    ForStmt {
+        variable: String,
+        start: Expression,
+        end: Expression,
+        step: Option<Expression>,
+        body: Vec<Statement>,
+        line: usize,
+    },
+    /// `EXIT`: leave the innermost loop.
+    Exit {
+        line: usize,
+    },
+    /// `CONTINUE`: skip to the next iteration of the innermost loop.
+    Continue {
+        line: usize,
+    },
+    /// `GOTO <label>`: jump to a [`Statement::Label`] of the same block.
+    Goto {
+        label: String,
+        line: usize,
+    },
+    /// `<name>:`, a jump target for [`Statement::Goto`].
+    Label {
+        name: String,
+        line: usize,
+    },
+    /// Stands in for an `ELSE` written without statements, so the empty
+    /// branch is still visible (see [`is_empty_block`]). Rules ignore it.
     ElseMarker {
         line: usize,
     },
 }
 
-/// Unary operators used in expressions. At the moment only logical NOT is needed
-/// but this enum makes it easy to extend with additional unary ops in the future.
+/// Unary operators used in expressions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ast-serde", derive(Serialize))]
 pub enum UnaryOp {
     /// Logical negation (e.g. NOT flag)
     Not,
+    /// Arithmetic negation (e.g. -Speed). Negated literals are folded into
+    /// [`Expression::NumberLiteral`] by the parser.
+    Neg,
 }
 
 /// Arithmetic / logical binary operators we care about.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ast-serde", derive(Serialize))]
 pub enum BinOp {
     // Arithmetic operators
     Add,
@@ -119,48 +279,171 @@ pub enum BinOp {
 
 /// Expressions are deliberately minimal; we only model what is useful for rules.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(Serialize))]
 pub enum Expression {
     /// numeric literal with best-effort source line
     NumberLiteral(i64, usize),
     /// boolean literal with best-effort source line
     BoolLiteral(bool, usize),
-    /// `Some.Var.Name`
+    /// A plain variable name. Dotted names are parsed into [`Expression::Member`].
     Identifier(String),
+    /// `Base.Field`: struct member or `DB.Member` access.
+    Member {
+        base: Box<Expression>,
+        field: String,
+        line: usize,
+    },
     /// unary operation (e.g. NOT <expr>)
     UnaryOp {
         op: UnaryOp,
         expr: Box<Expression>,
         line: usize,
+        /// Last source line of the operand (see [`Expression::span`]).
+        end_line: usize,
     },
-    /// `<left> <op> <right>`
+    /// `<left> <op> <right>`. `line` is where `left` starts, not the
+    /// operator's line, so a condition split over several lines is
+    /// reported where it begins.
     BinaryOp {
         op: BinOp,
         left: Box<Expression>,
         right: Box<Expression>,
         line: usize,
+        end_line: usize,
     },
     /// `Base[Index]`
     Index {
         base: Box<Expression>,
         index: Box<Expression>,
         line: usize,
+        end_line: usize,
     },
      FuncCall {
         name: String,
         args: Vec<Expression>,
         line: usize,
+        end_line: usize,
     },
     StringLiteral(String, usize),
 }
 
+impl Program {
+    /// The executable blocks, i.e. everything but data blocks, whose
+    /// `BEGIN` section only holds start values. Statement-level rules and
+    /// analyses walk these.
+    pub fn code(&self) -> impl Iterator<Item = &Function> {
+        self.functions.iter().filter(|f| f.kind != FunctionKind::DataBlock)
+    }
+
+    /// Pretty-printed JSON dump of the AST, for inspecting how a source was
+    /// interpreted (e.g. when chasing a false positive).
+    #[cfg(feature = "ast-serde")]
+    pub fn to_debug_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".into())
+    }
+
+    /// Add `by` to every known (non-zero) source line, e.g. to place a
+    /// file's blocks after the files before it in a merged project.
+    pub fn shift_lines(&mut self, by: usize) {
+        for f in &mut self.functions {
+            shift(&mut f.line, by);
+            for r in &mut f.regions {
+                shift(&mut r.line, by);
+                shift(&mut r.end_line, by);
+            }
+            for d in &mut f.declarations {
+                shift(&mut d.line, by);
+            }
+            for st in &mut f.statements {
+                st.shift_lines(by);
+            }
+        }
+    }
+}
+
+fn shift(line: &mut usize, by: usize) {
+    if *line > 0 {
+        *line += by;
+    }
+}
+
 impl fmt::Display for Variable {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(&self.name)
     }
 }
 
+impl BinOp {
+    /// SCL spelling of the operator.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinOp::Add => "+", BinOp::Sub => "-", BinOp::Mul => "*", BinOp::Div => "/",
+            BinOp::Eq => "=", BinOp::Neq => "<>", BinOp::Lt => "<", BinOp::Le => "<=",
+            BinOp::Gt => ">", BinOp::Ge => ">=", BinOp::And => "AND", BinOp::Or => "OR",
+            BinOp::Assign => ":=",
+        }
+    }
+
+    /// `=`, `<>`, `<`, `<=`, `>` or `>=`.
+    pub fn is_comparison(&self) -> bool {
+        matches!(self, BinOp::Eq | BinOp::Neq | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge)
+    }
+
+    /// The operator that gives the same result with the operands swapped
+    /// (`a < b` is `b > a`). Non-comparisons are returned unchanged.
+    pub fn flipped(&self) -> BinOp {
+        match self {
+            BinOp::Lt => BinOp::Gt,
+            BinOp::Le => BinOp::Ge,
+            BinOp::Gt => BinOp::Lt,
+            BinOp::Ge => BinOp::Le,
+            other => *other,
+        }
+    }
+
+    /// The comparison that holds exactly when this one does not
+    /// (`a < b` becomes `a >= b`). Non-comparisons are returned unchanged.
+    pub fn negated(&self) -> BinOp {
+        match self {
+            BinOp::Eq => BinOp::Neq,
+            BinOp::Neq => BinOp::Eq,
+            BinOp::Lt => BinOp::Ge,
+            BinOp::Le => BinOp::Gt,
+            BinOp::Gt => BinOp::Le,
+            BinOp::Ge => BinOp::Lt,
+            other => *other,
+        }
+    }
+
+    /// Binding strength, mirroring the SCL parser's Pratt table (higher binds tighter).
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinOp::Assign => 0,
+            BinOp::Or => 1,
+            BinOp::And => 2,
+            BinOp::Eq | BinOp::Neq | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => 3,
+            BinOp::Add | BinOp::Sub => 4,
+            BinOp::Mul | BinOp::Div => 5,
+        }
+    }
+}
+
 impl Expression {
-    /// Helper to get the line number from any expression variant.
+    /// First and last source line of the expression. Both are [`line`]
+    /// for single-token expressions; `(0, 0)` when the line is unknown.
+    ///
+    /// [`line`]: Expression::line
+    pub fn span(&self) -> (usize, usize) {
+        match self {
+            Expression::UnaryOp { line, end_line, .. }
+            | Expression::BinaryOp { line, end_line, .. }
+            | Expression::Index { line, end_line, .. }
+            | Expression::FuncCall { line, end_line, .. } => (*line, (*end_line).max(*line)),
+            _ => (self.line(), self.line()),
+        }
+    }
+
+    /// Helper to get the (first) line number from any expression variant.
     pub fn line(&self) -> usize {
         match self {
             Expression::NumberLiteral(_, line) => *line,
@@ -170,7 +453,218 @@ impl Expression {
             Expression::Index { line, .. } => *line,
             Expression::FuncCall { line, .. } => *line,
             Expression::StringLiteral(_, line) => *line,
+            Expression::Member { line, .. } => *line,
             Expression::Identifier(_) => 0,
         }
     }
+
+    /// Build `a.b.c` as nested [`Expression::Member`]s over the identifier `a`.
+    /// Absolute addresses such as `%I0.0` stay a single identifier. Dots
+    /// inside a quoted symbol (`"DB.Recipe".Value`) do not split it.
+    pub fn path(text: &str, line: usize) -> Expression {
+        if text.starts_with('%') {
+            return Expression::Identifier(text.to_string());
+        }
+        let mut in_quotes = false;
+        let mut parts = text.split(|c| {
+            in_quotes ^= c == '"';
+            c == '.' && !in_quotes
+        });
+        let mut e = Expression::Identifier(parts.next().unwrap_or_default().to_string());
+        for field in parts {
+            e = Expression::Member { base: Box::new(e), field: field.to_string(), line };
+        }
+        e
+    }
+
+    /// The variable this expression names, with member accesses joined by
+    /// dots (`Motor.Speed`). `None` for anything that is not a plain name or
+    /// member chain. Rules that only match on names use this so dotted
+    /// access keeps behaving like the old single identifier.
+    pub fn name(&self) -> Option<String> {
+        match self {
+            Expression::Identifier(s) => Some(s.clone()),
+            Expression::Member { base, field, .. } => base.name().map(|b| format!("{}.{}", b, field)),
+            _ => None,
+        }
+    }
+
+    fn shift_lines(&mut self, by: usize) {
+        match self {
+            Expression::NumberLiteral(_, line) | Expression::BoolLiteral(_, line) | Expression::StringLiteral(_, line) => {
+                shift(line, by)
+            }
+            Expression::Identifier(_) => {}
+            Expression::Member { base, line, .. } => {
+                base.shift_lines(by);
+                shift(line, by);
+            }
+            Expression::UnaryOp { expr, line, end_line, .. } => {
+                expr.shift_lines(by);
+                shift(line, by);
+                shift(end_line, by);
+            }
+            Expression::BinaryOp { left, right, line, end_line, .. } => {
+                left.shift_lines(by);
+                right.shift_lines(by);
+                shift(line, by);
+                shift(end_line, by);
+            }
+            Expression::Index { base, index, line, end_line } => {
+                base.shift_lines(by);
+                index.shift_lines(by);
+                shift(line, by);
+                shift(end_line, by);
+            }
+            Expression::FuncCall { args, line, end_line, .. } => {
+                args.iter_mut().for_each(|a| a.shift_lines(by));
+                shift(line, by);
+                shift(end_line, by);
+            }
+        }
+    }
+}
+
+impl Statement {
+    /// Source line the statement starts on.
+    pub fn line(&self) -> usize {
+        match self {
+            Statement::Assign { line, .. }
+            | Statement::Call { line, .. }
+            | Statement::IfStmt { line, .. }
+            | Statement::Expr { line, .. }
+            | Statement::Comment { line, .. }
+            | Statement::CaseStmt { line, .. }
+            | Statement::Return { line }
+            | Statement::WhileStmt { line, .. }
+            | Statement::ForStmt { line, .. }
+            | Statement::Exit { line }
+            | Statement::Continue { line }
+            | Statement::Goto { line, .. }
+            | Statement::Label { line, .. }
+            | Statement::ElseMarker { line } => *line,
+        }
+    }
+
+    fn shift_lines(&mut self, by: usize) {
+        match self {
+            Statement::Assign { target, value, line } => {
+                target.shift_lines(by);
+                value.shift_lines(by);
+                shift(line, by);
+            }
+            Statement::Call { args, line, .. } => {
+                args.iter_mut().for_each(|(_, a)| a.shift_lines(by));
+                shift(line, by);
+            }
+            Statement::IfStmt { condition, then_branch, else_branch, line } => {
+                condition.shift_lines(by);
+                then_branch.iter_mut().chain(else_branch.iter_mut()).for_each(|s| s.shift_lines(by));
+                shift(line, by);
+            }
+            Statement::Expr { expr, line } => {
+                expr.shift_lines(by);
+                shift(line, by);
+            }
+            Statement::CaseStmt { expression, cases, else_branch, line } => {
+                expression.shift_lines(by);
+                for (labels, body) in cases {
+                    labels.iter_mut().for_each(|l| l.shift_lines(by));
+                    body.iter_mut().for_each(|s| s.shift_lines(by));
+                }
+                else_branch.iter_mut().for_each(|s| s.shift_lines(by));
+                shift(line, by);
+            }
+            Statement::WhileStmt { condition, body, line } => {
+                condition.shift_lines(by);
+                body.iter_mut().for_each(|s| s.shift_lines(by));
+                shift(line, by);
+            }
+            Statement::ForStmt { start, end, step, body, line, .. } => {
+                start.shift_lines(by);
+                end.shift_lines(by);
+                step.iter_mut().for_each(|s| s.shift_lines(by));
+                body.iter_mut().for_each(|s| s.shift_lines(by));
+                shift(line, by);
+            }
+            Statement::Comment { line, .. }
+            | Statement::Return { line }
+            | Statement::Exit { line }
+            | Statement::Continue { line }
+            | Statement::Goto { line, .. }
+            | Statement::Label { line, .. }
+            | Statement::ElseMarker { line } => shift(line, by),
+        }
+    }
+
+    /// True if control never falls through this statement to the next one
+    /// in the same block: `RETURN`, `EXIT`, `CONTINUE`, `GOTO`, or an
+    /// IF/CASE whose every branch (including a non-empty ELSE) diverges.
+    /// Loops never diverge themselves, since their body may not run.
+    pub fn diverges(&self) -> bool {
+        self.ends_flow(true)
+    }
+
+    /// [`diverges`](Self::diverges), with `GOTO` counted only if `goto`.
+    fn ends_flow(&self, goto: bool) -> bool {
+        let block = |stmts: &[Statement]| stmts.iter().any(|s| s.ends_flow(goto));
+        match self {
+            Statement::Return { .. } | Statement::Exit { .. } | Statement::Continue { .. } => true,
+            Statement::Goto { .. } => goto,
+            Statement::IfStmt { then_branch, else_branch, .. } => block(then_branch) && block(else_branch),
+            Statement::CaseStmt { cases, else_branch, .. } => {
+                cases.iter().all(|(_, body)| block(body)) && block(else_branch)
+            }
+            _ => false,
+        }
+    }
+
+    /// For `IF cond THEN ... RETURN; END_IF` (or `EXIT`/`CONTINUE` inside a
+    /// loop) without ELSE, the condition under which control leaves early.
+    /// Statements after it in the same block only run when `cond` was
+    /// false, so walkers treat it as a guard. A `GOTO` does not count: it
+    /// may land further down the same block.
+    pub fn early_exit_condition(&self) -> Option<&Expression> {
+        match self {
+            Statement::IfStmt { condition, then_branch, else_branch, .. }
+                if is_empty_block(else_branch) && then_branch.iter().any(|s| s.ends_flow(false)) =>
+            {
+                Some(condition)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// True if a block executes nothing: no statements, or only comments and
+/// the [`Statement::ElseMarker`] of an empty `ELSE`.
+pub fn is_empty_block(stmts: &[Statement]) -> bool {
+    stmts.iter().all(|s| matches!(s, Statement::Comment { .. } | Statement::ElseMarker { .. }))
+}
+
+/// Statements in `stmts` and every nested body, without comments and
+/// [`Statement::ElseMarker`]s.
+pub fn statement_count(stmts: &[Statement]) -> usize {
+    stmts
+        .iter()
+        .map(|st| match st {
+            Statement::Comment { .. } | Statement::ElseMarker { .. } => 0,
+            Statement::IfStmt { then_branch, else_branch, .. } => 1 + statement_count(then_branch) + statement_count(else_branch),
+            Statement::CaseStmt { cases, else_branch, .. } => {
+                1 + cases.iter().map(|(_, body)| statement_count(body)).sum::<usize>() + statement_count(else_branch)
+            }
+            Statement::WhileStmt { body, .. } | Statement::ForStmt { body, .. } => 1 + statement_count(body),
+            _ => 1,
+        })
+        .sum()
+}
+
+/// The statements of a block that can execute: everything up to and
+/// including the first statement that diverges. If a label follows it, a
+/// `GOTO` may land there, and the whole block is kept.
+pub fn reachable(stmts: &[Statement]) -> &[Statement] {
+    match stmts.iter().position(Statement::diverges) {
+        Some(i) if !stmts[i + 1..].iter().any(|s| matches!(s, Statement::Label { .. })) => &stmts[..=i],
+        _ => stmts,
+    }
 }
@@ -0,0 +1,310 @@
+//! Render the AST back into SCL source.
+//!
+//! This is the inverse of the SCL parser: `parse(to_scl(parse(src)))`
+//! yields the same AST, line numbers included, as long as the original
+//! put one statement per line. Statements are emitted at their recorded
+//! line where the layout allows it (blank lines are inserted to catch up),
+//! so findings computed on the printed text point at the same lines.
+//!
+//! Unlike `rules::utils::expr_text`, the form used in messages, string
+//! literals keep their quotes here.
+
+use super::{DataType, Expression, Function, FunctionKind, Program, Statement, UnaryOp};
+
+const INDENT: &str = "    ";
+
+/// Render a whole program as SCL.
+pub fn to_scl(program: &Program) -> String {
+    let mut p = Printer { keep_lines: true, ..Default::default() };
+    for f in &program.functions {
+        p.function(f);
+    }
+    p.out
+}
+
+/// Render one statement (and its nested blocks) as unindented SCL lines,
+/// without a trailing newline. Used to build quick-fix replacements.
+pub fn statement_to_scl(st: &Statement) -> String {
+    let mut p = Printer::default();
+    p.statement(st, 0);
+    p.out.truncate(p.out.trim_end().len());
+    p.out
+}
+
+/// Render a single expression as SCL.
+pub fn expr_to_scl(e: &Expression) -> String {
+    match e {
+        Expression::NumberLiteral(n, _) => n.to_string(),
+        Expression::BoolLiteral(b, _) => if *b { "TRUE".into() } else { "FALSE".into() },
+        Expression::Identifier(s) => s.clone(),
+        Expression::StringLiteral(s, _) => format!("'{}'", s),
+        Expression::UnaryOp { op, expr, .. } => {
+            let op = match op { UnaryOp::Not => "NOT ", UnaryOp::Neg => "-" };
+            match **expr {
+                Expression::BinaryOp { .. } => format!("{}({})", op, expr_to_scl(expr)),
+                _ => format!("{}{}", op, expr_to_scl(expr)),
+            }
+        }
+        Expression::BinaryOp { op, left, right, .. } => {
+            // Operators are left-associative, so a right operand of equal
+            // precedence needs parentheses while a left one does not.
+            let l = operand(left, |p| p < op.precedence());
+            let r = operand(right, |p| p <= op.precedence());
+            format!("{} {} {}", l, op.symbol(), r)
+        }
+        Expression::Index { base, index, .. } => format!("{}[{}]", expr_to_scl(base), expr_to_scl(index)),
+        Expression::Member { base, field, .. } => format!("{}.{}", expr_to_scl(base), field),
+        Expression::FuncCall { name, args, .. } => {
+            let args: Vec<String> = args.iter().map(expr_to_scl).collect();
+            format!("{}({})", name, args.join(", "))
+        }
+    }
+}
+
+fn operand(e: &Expression, needs_parens: impl Fn(u8) -> bool) -> String {
+    match e {
+        Expression::BinaryOp { op, .. } if needs_parens(op.precedence()) => format!("({})", expr_to_scl(e)),
+        _ => expr_to_scl(e),
+    }
+}
+
+fn type_to_scl(t: &DataType) -> String {
+    match t {
+        DataType::String(Some(len)) => format!("STRING[{}]", len),
+        DataType::String(None) => "STRING".into(),
+        DataType::Array { dims, element } => {
+            let dims: Vec<String> = dims
+                .iter()
+                .map(|d| d.map_or_else(|| "*".to_string(), |(lo, hi)| format!("{}..{}", lo, hi)))
+                .collect();
+            format!("ARRAY[{}] OF {}", dims.join(", "), type_to_scl(element))
+        }
+        DataType::Named(name) if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => name.clone(),
+        DataType::Named(name) => format!("\"{}\"", name),
+    }
+}
+
+fn block_keywords(kind: FunctionKind) -> (&'static str, &'static str) {
+    match kind {
+        FunctionKind::FC => ("FUNCTION", "END_FUNCTION"),
+        FunctionKind::FB => ("FUNCTION_BLOCK", "END_FUNCTION_BLOCK"),
+        FunctionKind::Program => ("PROGRAM", "END_PROGRAM"),
+        FunctionKind::OB
+        | FunctionKind::OB1
+        | FunctionKind::OB100
+        | FunctionKind::OB82
+        | FunctionKind::OB83
+        | FunctionKind::OB85
+        | FunctionKind::OB86
+        | FunctionKind::OB121 => ("ORGANIZATION_BLOCK", "END_ORGANIZATION_BLOCK"),
+        FunctionKind::DataBlock => ("DATA_BLOCK", "END_DATA_BLOCK"),
+    }
+}
+
+#[derive(Default)]
+struct Printer {
+    out: String,
+    /// Number of lines written so far.
+    lines: usize,
+    /// Pad with blank lines so statements land on their recorded line.
+    keep_lines: bool,
+}
+
+impl Printer {
+    /// Write one line, first padding with blank lines up to `at` (if known).
+    fn line(&mut self, depth: usize, at: usize, text: &str) {
+        while self.keep_lines && self.lines + 1 < at {
+            self.out.push('\n');
+            self.lines += 1;
+        }
+        for _ in 0..depth {
+            self.out.push_str(INDENT);
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+        self.lines += 1;
+    }
+
+    fn function(&mut self, f: &Function) {
+        let (open, close) = block_keywords(f.kind);
+        self.line(0, f.line, &format!("{} {}", open, f.name));
+        for section in f.declarations.chunk_by(|a, b| a.section == b.section) {
+            self.line(0, 0, section[0].section.keyword());
+            for d in section {
+                self.line(1, d.line, &format!("{} : {};", d.name, type_to_scl(&d.data_type)));
+            }
+            self.line(0, 0, "END_VAR");
+        }
+        self.line(0, 0, "BEGIN");
+        self.block(&f.statements, 1);
+        self.line(0, 0, close);
+        self.line(0, 0, "");
+    }
+
+    fn block(&mut self, stmts: &[Statement], depth: usize) {
+        for st in stmts {
+            self.statement(st, depth);
+        }
+    }
+
+    fn statement(&mut self, st: &Statement, depth: usize) {
+        match st {
+            Statement::Assign { target, value, line } => {
+                self.line(depth, *line, &format!("{} := {};", expr_to_scl(target), expr_to_scl(value)));
+            }
+            Statement::Call { name, args, line } => {
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|(n, a)| if n.is_empty() { expr_to_scl(a) } else { format!("{} := {}", n, expr_to_scl(a)) })
+                    .collect();
+                self.line(depth, *line, &format!("{}({});", name, args.join(", ")));
+            }
+            Statement::IfStmt { condition, then_branch, else_branch, line } => {
+                self.line(depth, *line, &format!("IF {} THEN", expr_to_scl(condition)));
+                self.block(then_branch, depth + 1);
+                self.else_chain(else_branch, depth);
+                self.line(depth, 0, "END_IF;");
+            }
+            Statement::CaseStmt { expression, cases, else_branch, line } => {
+                self.line(depth, *line, &format!("CASE {} OF", expr_to_scl(expression)));
+                for (labels, body) in cases {
+                    let labels: Vec<String> = labels.iter().map(expr_to_scl).collect();
+                    let at = body.first().map(|s| s.line().saturating_sub(1)).unwrap_or(0);
+                    self.line(depth + 1, at, &format!("{}:", labels.join(", ")));
+                    self.block(body, depth + 2);
+                }
+                if !else_branch.is_empty() {
+                    self.line(depth + 1, 0, "ELSE");
+                    self.block(else_branch, depth + 2);
+                }
+                self.line(depth, 0, "END_CASE;");
+            }
+            Statement::WhileStmt { condition, body, line } => {
+                self.line(depth, *line, &format!("WHILE {} DO", expr_to_scl(condition)));
+                self.block(body, depth + 1);
+                self.line(depth, 0, "END_WHILE;");
+            }
+            Statement::ForStmt { variable, start, end, step, body, line } => {
+                let step = step.as_ref().map(|s| format!(" BY {}", expr_to_scl(s))).unwrap_or_default();
+                let head = format!("FOR {} := {} TO {}{} DO", variable, expr_to_scl(start), expr_to_scl(end), step);
+                self.line(depth, *line, &head);
+                self.block(body, depth + 1);
+                self.line(depth, 0, "END_FOR;");
+            }
+            Statement::Expr { expr, line } => self.line(depth, *line, &format!("{};", expr_to_scl(expr))),
+            Statement::Comment { text, line } => self.line(depth, *line, text),
+            Statement::Return { line } => self.line(depth, *line, "RETURN;"),
+            Statement::Exit { line } => self.line(depth, *line, "EXIT;"),
+            Statement::Continue { line } => self.line(depth, *line, "CONTINUE;"),
+            Statement::Goto { label, line } => self.line(depth, *line, &format!("GOTO {};", label)),
+            Statement::Label { name, line } => self.line(depth, *line, &format!("{}:", name)),
+            Statement::ElseMarker { .. } => {}
+        }
+    }
+
+    /// The parser folds `ELSIF` into a lone nested IF in the else branch;
+    /// print that shape back as `ELSIF` so the round trip is stable.
+    fn else_chain(&mut self, else_branch: &[Statement], depth: usize) {
+        match else_branch {
+            [] => {}
+            [Statement::IfStmt { condition, then_branch, else_branch, line }] => {
+                self.line(depth, *line, &format!("ELSIF {} THEN", expr_to_scl(condition)));
+                self.block(then_branch, depth + 1);
+                self.else_chain(else_branch, depth);
+            }
+            [Statement::ElseMarker { line }] => self.line(depth, *line, "ELSE"),
+            _ => {
+                let at = else_branch[0].line().saturating_sub(1);
+                self.line(depth, at, "ELSE");
+                self.block(else_branch, depth + 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    const SAMPLE: &str = "\
+FUNCTION_BLOCK FB_Conveyor
+BEGIN
+    IF NOT (Start AND Ready) OR Fault THEN
+        Motor := FALSE;
+    ELSIF Speed > 10 * (Gain - 1) THEN
+        Motor := TRUE;
+        Out := Scale(HMI_Speed, 100);
+    ELSE
+        Alarm('stopped');
+    END_IF;
+
+    CASE Mode OF
+        1, 2:
+            Table[Idx] := Speed - (Offset - 3);
+        ELSE
+            RETURN;
+    END_CASE;
+END_FUNCTION_BLOCK
+
+ORGANIZATION_BLOCK OB1
+BEGIN
+    FOR i := 0 TO 9 BY 2 DO
+        WHILE Busy DO
+            IF i > 5 THEN
+                EXIT;
+            END_IF;
+            CONTINUE;
+        END_WHILE;
+    END_FOR;
+    FB_Conveyor();
+END_ORGANIZATION_BLOCK
+";
+
+    #[test]
+    fn parse_print_parse_is_stable() {
+        let first = parse_scl_from_str(SAMPLE).unwrap();
+        let printed = to_scl(&first);
+        let second = parse_scl_from_str(&printed).unwrap();
+        assert_eq!(format!("{:?}", first), format!("{:?}", second));
+        assert_eq!(to_scl(&second), printed);
+    }
+
+    #[test]
+    fn expressions_keep_their_grouping() {
+        let program = parse_scl_from_str(
+            "FUNCTION FC_A\nBEGIN\n    x := a - (b - c) * d;\n    y := (a - b) - c;\n    z := -(a + b) - -1 * -Speed;\nEND_FUNCTION\n",
+        )
+        .unwrap();
+        let printed = to_scl(&program);
+        assert!(printed.contains("z := -(a + b) - -1 * -Speed;"));
+        assert!(printed.contains("x := a - (b - c) * d;"));
+        assert!(printed.contains("y := a - b - c;"));
+    }
+
+    #[test]
+    fn empty_else_survives_a_round_trip() {
+        let src = "FUNCTION FC_A\nBEGIN\n    IF Run THEN\n        Motor := TRUE;\n    ELSE\n    END_IF;\nEND_FUNCTION\n";
+        let first = parse_scl_from_str(src).unwrap();
+        let printed = to_scl(&first);
+        assert!(printed.contains("        Motor := TRUE;\n    ELSE\n    END_IF;"));
+        assert_eq!(format!("{:?}", parse_scl_from_str(&printed).unwrap()), format!("{:?}", first));
+    }
+
+    #[test]
+    fn declarations_survive_a_round_trip() {
+        let src = "FUNCTION FC_A\nVAR_TEMP\n    Name : STRING[16];\n    Cells : ARRAY[0..9, *] OF \"UDT Cell\";\nEND_VAR\n\
+                   BEGIN\n    Name := 'x';\nEND_FUNCTION\n";
+        let first = parse_scl_from_str(src).unwrap();
+        let printed = to_scl(&first);
+        assert!(printed.contains("    Cells : ARRAY[0..9, *] OF \"UDT Cell\";\n"));
+        assert_eq!(format!("{:?}", parse_scl_from_str(&printed).unwrap()), format!("{:?}", first));
+    }
+
+    #[test]
+    fn single_statement_is_unindented_and_unpadded() {
+        let program = parse_scl_from_str("FUNCTION FC_A\nBEGIN\n\n\n    IF x THEN\n        y := 1;\n    END_IF;\nEND_FUNCTION\n").unwrap();
+        let st = &program.functions[0].statements[0];
+        assert_eq!(statement_to_scl(st), "IF x THEN\n    y := 1;\nEND_IF;");
+    }
+}
@@ -0,0 +1,163 @@
+//! `plc-check` — run the checker from the command line (pre-commit hooks, CI).
+//!
+//! Exits with status 1 if any `Error`-severity violation is reported, and 2
+//! if the input or policy cannot be read or the report cannot be written.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use clap::{Parser, ValueEnum};
+
+use plc_secure_checker_lib::parser::parse_file_from_str;
+use plc_secure_checker_lib::report::{ndjson, sarif, text, AnalysisReport};
+use plc_secure_checker_lib::rules::policy::ListMerge;
+use plc_secure_checker_lib::rules::{load_policy, to_wasm_results, Policy, Severity};
+use plc_secure_checker_lib::Analyzer;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    Sarif,
+    Ndjson,
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "plc-check")]
+#[command(version)]
+#[command(about = "Check a Siemens PLC source file against the Top 20 Secure PLC Coding Practices")]
+struct Cli {
+    /// Path to the PLC source file (.scl/.st, .il or PLCOpen .xml)
+    #[arg(short, long)]
+    file: PathBuf,
+
+    /// Optional path to a policy file, JSON or TOML (by extension)
+    #[arg(short, long)]
+    policy: Option<PathBuf>,
+
+    /// Policy file that --policy is laid over; settings --policy leaves
+    /// out come from here
+    #[arg(long)]
+    base_policy: Option<PathBuf>,
+
+    /// Add --policy's lists to the base policy's instead of replacing them
+    #[arg(long, requires = "base_policy")]
+    append_lists: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Print the parsed AST as JSON instead of running the rules
+    #[arg(long)]
+    dump_ast: bool,
+
+    /// With --format json, wrap the results in an object that also holds
+    /// the policy as applied, defaults included
+    #[arg(long)]
+    effective_policy: bool,
+
+    /// Replace the program's names in the findings with stable hashed
+    /// tokens, for reports shared outside the site
+    #[arg(long)]
+    redact_identifiers: bool,
+
+    /// Secret key for the --redact-identifiers tokens. Reports made with
+    /// the same salt share tokens; without one each run draws its own
+    #[arg(long, requires = "redact_identifiers")]
+    redact_salt: Option<String>,
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    // Per-rule timings go to stderr so they never mix with the report.
+    #[cfg(feature = "trace")]
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_writer(std::io::stderr)
+        .init();
+    let file_name = cli.file.display().to_string();
+
+    let source = match fs::read_to_string(&cli.file) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to read '{}': {}", file_name, e);
+            process::exit(2);
+        }
+    };
+
+    let program = match parse_file_from_str(&source, &file_name) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to parse '{}': {}", file_name, e);
+            process::exit(2);
+        }
+    };
+
+    if cli.dump_ast {
+        #[cfg(feature = "ast-serde")]
+        {
+            println!("{}", program.to_debug_json());
+            return;
+        }
+        #[cfg(not(feature = "ast-serde"))]
+        {
+            eprintln!("--dump-ast is not supported by this build (enable the ast-serde feature)");
+            process::exit(2);
+        }
+    }
+
+    let load = |path: Option<&std::path::Path>| {
+        load_policy(path).unwrap_or_else(|e| {
+            eprintln!("Failed to load policy: {}", e);
+            process::exit(2);
+        })
+    };
+    let mut policy = load(cli.policy.as_deref());
+    if let Some(base) = cli.base_policy.as_deref() {
+        let lists = if cli.append_lists { ListMerge::Append } else { ListMerge::Replace };
+        policy = Policy::merge(load(Some(base)), policy, lists);
+    }
+
+    // The same pipeline the WASM entry points run, so both report alike.
+    let mut builder = Analyzer::builder().with_policy(policy).with_redact_identifiers(cli.redact_identifiers);
+    if let Some(salt) = &cli.redact_salt {
+        builder = builder.with_redaction_salt(salt.as_bytes());
+    }
+    let analyzer = builder.build().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(2);
+    });
+    let results = analyzer.check(&program, &source);
+    let file_name = analyzer.report_file_name(&program, &file_name);
+    let has_error = results
+        .iter()
+        .flat_map(|r| &r.violations)
+        .any(|v| v.severity == Severity::Error);
+
+    match cli.format {
+        Format::Text => print!("{}", text::to_text(&results, &file_name)),
+        Format::Sarif => println!("{}", sarif::to_sarif(&results, &file_name)),
+        Format::Ndjson => print!("{}", ndjson::to_ndjson(&to_wasm_results(results), &file_name)),
+        Format::Json if cli.effective_policy => {
+            let report = AnalysisReport { results: to_wasm_results(results), effective_policy: Some(analyzer.policy().effective()) };
+            println!("{}", to_json(&report));
+        }
+        Format::Json => println!("{}", to_json(&to_wasm_results(results))),
+    }
+
+    if has_error {
+        process::exit(1);
+    }
+}
+
+/// Pretty JSON for the report. If it cannot be serialised, exit 2 instead
+/// of printing an empty report that reads as a clean run.
+fn to_json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|e| {
+        eprintln!("Failed to serialise the report: {}", e);
+        process::exit(2);
+    })
+}
@@ -0,0 +1,77 @@
+//! Single-entry result cache for interactive callers.
+//!
+//! The browser re-checks the whole source on every edit. Most of those
+//! calls repeat the previous input (focus changes, re-renders), so the
+//! serialized results of the last call are kept together with a hash of
+//! the input that produced them. Batch callers never touch this.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Hash of everything that influences the analysis output.
+pub fn input_hash(source: &str, policy_json: &str, file_name: &str, selection: &[u8]) -> u64 {
+    let mut h = DefaultHasher::new();
+    source.hash(&mut h);
+    policy_json.trim().hash(&mut h);
+    file_name.hash(&mut h);
+    selection.hash(&mut h);
+    h.finish()
+}
+
+/// Format a hash the way it is handed to JavaScript (u64 does not fit a JS number).
+pub fn hash_to_string(hash: u64) -> String {
+    format!("{:016x}", hash)
+}
+
+struct Entry {
+    hash: u64,
+    results: String,
+}
+
+/// Remembers the outcome of the most recent analysis.
+#[derive(Default)]
+pub struct Cache {
+    last: Option<Entry>,
+}
+
+impl Cache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serialized results for `hash`, if it is the cached input.
+    pub fn results(&self, hash: u64) -> Option<&str> {
+        self.last.as_ref().filter(|e| e.hash == hash).map(|e| e.results.as_str())
+    }
+
+    /// Replace the cached entry.
+    pub fn store(&mut self, hash: u64, results: String) {
+        self.last = Some(Entry { hash, results });
+    }
+
+    pub fn clear(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_latest_input_is_served() {
+        let a = input_hash("x := 1;", "", "a.scl", &[]);
+        let b = input_hash("x := 2;", "", "a.scl", &[]);
+        assert_ne!(a, b);
+        assert_eq!(a, input_hash("x := 1;", "  ", "a.scl", &[]));
+        assert_ne!(a, input_hash("x := 1;", "", "a.scl", &[7]));
+
+        let mut cache = Cache::new();
+        cache.store(a, "[]".into());
+        assert_eq!(cache.results(a), Some("[]"));
+
+        cache.store(b, "[1]".into());
+        assert_eq!(cache.results(a), None);
+        assert_eq!(cache.results(b), Some("[1]"));
+    }
+}
@@ -0,0 +1,77 @@
+//! Decoding for sources uploaded in compact form.
+//!
+//! Large SCL files are sent gzipped and base64-encoded to keep payloads
+//! small. Decoding here rather than in JavaScript keeps it tested and
+//! avoids building a second copy of a large string on the JS side.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+/// Largest source a gzip payload may inflate to. Guards against
+/// decompression bombs; real SCL files are a small fraction of this.
+pub const MAX_DECODED_LEN: u64 = 16 * 1024 * 1024;
+
+/// Decode `data` according to `encoding` (`"base64"` or `"gzip+base64"`,
+/// case-insensitive) into UTF-8 source text.
+pub fn decode_source(data: &str, encoding: &str) -> Result<String, String> {
+    let gzip = match encoding.trim().to_ascii_lowercase().as_str() {
+        "base64" => false,
+        "gzip+base64" => true,
+        other => return Err(format!("Unknown encoding '{}'; expected \"base64\" or \"gzip+base64\"", other)),
+    };
+    // Base64 from line-wrapping encoders may contain newlines.
+    let compact: String = data.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    let bytes = STANDARD.decode(compact).map_err(|e| format!("Invalid base64 input: {}", e))?;
+    let bytes = if gzip { gunzip(&bytes)? } else { bytes };
+    String::from_utf8(bytes).map_err(|e| format!("Decoded source is not valid UTF-8: {}", e))
+}
+
+#[cfg(feature = "gzip")]
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+    let mut out = Vec::new();
+    flate2::read::GzDecoder::new(bytes)
+        .take(MAX_DECODED_LEN + 1)
+        .read_to_end(&mut out)
+        .map_err(|e| format!("Invalid gzip data: {}", e))?;
+    if out.len() as u64 > MAX_DECODED_LEN {
+        return Err(format!("Decompressed source is larger than {} bytes", MAX_DECODED_LEN));
+    }
+    Ok(out)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gunzip(_: &[u8]) -> Result<Vec<u8>, String> {
+    Err("gzip input is not supported by this build (enable the gzip feature)".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_unknown_encoding_and_bad_utf8() {
+        let err = decode_source(&STANDARD.encode("x := 1;"), "zstd").unwrap_err();
+        assert!(err.starts_with("Unknown encoding 'zstd'"));
+        let err = decode_source("not base64!", "zstd").unwrap_err();
+        assert!(err.starts_with("Unknown encoding 'zstd'"));
+        let err = decode_source(&STANDARD.encode([0xff, 0xfe]), "base64").unwrap_err();
+        assert!(err.starts_with("Decoded source is not valid UTF-8"));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_round_trip() {
+        use std::io::Write;
+        let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        enc.write_all(b"FUNCTION FC_A\nBEGIN\nEND_FUNCTION\n").unwrap();
+        let b64 = STANDARD.encode(enc.finish().unwrap());
+        assert_eq!(decode_source(&b64, "gzip+base64").unwrap(), "FUNCTION FC_A\nBEGIN\nEND_FUNCTION\n");
+        assert!(decode_source(&STANDARD.encode("plain"), "gzip+base64").unwrap_err().starts_with("Invalid gzip data"));
+
+        let mut bomb = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        bomb.write_all(&vec![b' '; MAX_DECODED_LEN as usize + 1]).unwrap();
+        let b64 = STANDARD.encode(bomb.finish().unwrap());
+        assert!(decode_source(&b64, "gzip+base64").unwrap_err().starts_with("Decompressed source is larger than"));
+    }
+}
@@ -4,86 +4,689 @@
 //! library layout mirrors the organisation of the checker: the AST,
 //! parsers and rule implementations all live under this crate root.
 
+pub mod analysis;
+pub mod analyzer;
 pub mod ast;
+pub mod cache;
+pub mod decode;
 pub mod parser;
+pub mod report;
 pub mod rules;
+pub mod session;
+
+pub use analyzer::Analyzer;
+pub use parser::{parse_only, ParseError};
 use wasm_bindgen::prelude::*;
+use crate::analyzer::{parse_error_result, parse_input};
 use crate::rules::policy::parse_policy_from_text;
-use crate::rules::Policy;
+use crate::rules::{Policy, Severity};
+use std::cell::RefCell;
+use std::sync::Arc;
+
+thread_local! {
+    static CACHE: RefCell<cache::Cache> = RefCell::new(cache::Cache::new());
+    static SESSIONS: RefCell<session::Sessions> = RefCell::new(session::Sessions::new());
+}
 
 // This is the function that JavaScript will call
 #[wasm_bindgen]
 pub fn check_plc_code(source_code: &str, policy_json: &str, file_name: &str) -> String {
-    // Validate inputs
-    if source_code.trim().is_empty() {
-        let err_result = vec![rules::WasmRuleResult {
-            status: "ERROR".into(),
-            rule_no: 0,
-            rule_name: "Input Error",
-            violation: Some(rules::Violation {
-                rule_no: 0,
-                rule_name: "Input Error",
-                line: 0,
-                reason: "Empty source code provided".into(),
-                suggestion: "Provide valid PLC source code.".into(),
-            }),
-        }];
-        return serde_json::to_string(&err_result).unwrap_or_else(|_| "[]".into());
-    }
-
-    // 1. Parse the PLC program using the appropriate frontend based on file_name
-    let program = match parser::parse_file_from_str(source_code, file_name) {
-        Ok(p) => p,
+    analyze(source_code, policy_json, file_name, &[], Vec::new())
+}
+
+/// [`check_plc_code`] with explain mode: when `explain` is true every
+/// finding also carries its rule's `rationale` and `doc_anchor`, for a
+/// "why this matters" panel. Plain `check_plc_code` leaves them out.
+#[wasm_bindgen]
+pub fn check_plc_code_explained(source_code: &str, policy_json: &str, file_name: &str, explain: bool) -> String {
+    results_json(&analyze_results(source_code, policy_json, file_name, &[], Vec::new(), explain))
+}
+
+/// Same as [`check_plc_code`] but only runs the rules listed in
+/// `rule_numbers_json` (a JSON array such as `[7, 10]`). An empty string or
+/// empty array runs every rule. A list that does not parse or names an
+/// unknown rule is reported as a Rule Selection Error, and every rule runs.
+#[wasm_bindgen]
+pub fn check_plc_code_subset(
+    source_code: &str,
+    policy_json: &str,
+    file_name: &str,
+    rule_numbers_json: &str,
+) -> String {
+    let mut errors: Vec<rules::WasmRuleResult> = Vec::new();
+    let trimmed = rule_numbers_json.trim();
+    let selection: Vec<u8> = if trimmed.is_empty() {
+        Vec::new()
+    } else {
+        match serde_json::from_str(trimmed) {
+            Ok(v) => v,
+            Err(e) => {
+                // Fall back to running everything, but tell the caller why.
+                errors.push(selection_error(format!("Invalid rule number list: {}", e)));
+                Vec::new()
+            }
+        }
+    };
+    let selection = match Analyzer::builder().with_rules(&selection).build() {
+        Ok(_) => selection,
         Err(e) => {
-            // Construct a sentinel error result if the PLC source fails to parse
-            let err_result = vec![rules::WasmRuleResult {
-                status: "ERROR".into(),
-                rule_no: 0,
-                rule_name: "Parse Error",
-                violation: Some(rules::Violation {
-                    rule_no: 0,
-                    rule_name: "Parse Error",
-                    line: 0,
-                    reason: format!("Parse Error: {}", e),
-                    suggestion: "Check file type and syntax.".into(),
-                }),
-            }];
-            return serde_json::to_string(&err_result).unwrap_or_else(|_| "[]".into());
+            errors.push(selection_error(e));
+            Vec::new()
         }
     };
-    // 2. Parse the custom policy JSON. If parsing fails, record an error and
-    // continue with a default/empty policy to avoid crashing.
-    let mut policy = Policy::default();
-    // Collect any policy errors in a separate vector to prepend later
-    let mut errors: Vec<rules::WasmRuleResult> = Vec::new();
-    let trimmed_policy = policy_json.trim();
-    if !trimmed_policy.is_empty() {
-        match parse_policy_from_text(trimmed_policy) {
-            Ok(p) => policy = p,
-            Err(err) => {
-                errors.push(rules::WasmRuleResult {
-                    status: "ERROR".into(),
-                    rule_no: 0,
-                    rule_name: "Policy Parsing Error",
-                    violation: Some(rules::Violation {
-                        rule_no: 0,
-                        rule_name: "Policy Parsing Error",
-                        line: 0,
-                        reason: err,
-                        suggestion: "Fix policy JSON format. See About → Custom Policy example.".into(),
-                    }),
-                });
+    analyze(source_code, policy_json, file_name, &selection, errors)
+}
+
+/// [`check_plc_code`] for uploads that arrive encoded. `encoding` is
+/// `"base64"` or `"gzip+base64"` (the latter needs the `gzip` feature).
+/// Input that cannot be decoded is reported as a single input error.
+#[wasm_bindgen]
+pub fn check_plc_code_encoded(data_b64: &str, encoding: &str, policy_json: &str, file_name: &str) -> String {
+    match decode::decode_source(data_b64, encoding) {
+        Ok(source_code) => check_plc_code(&source_code, policy_json, file_name),
+        Err(reason) => {
+            input_error_json(reason, "Send UTF-8 source as base64, or gzip it first and pass \"gzip+base64\".")
+        }
+    }
+}
+
+/// Check a whole project in one call. `files_json` is an array of
+/// `{"name": .., "source": ..}`; the files are merged into one program
+/// (see [`parser::merge_project`]) so cross-file checks such as OB100
+/// presence see every block, and the rules run once. Findings carry the
+/// `file` and `function` they are in, with lines local to that file. A
+/// file that fails to parse, or a block already defined by an earlier
+/// file, yields an ERROR entry; the rest is still checked.
+#[wasm_bindgen]
+pub fn check_plc_project(files_json: &str, policy_json: &str) -> String {
+    #[derive(serde::Deserialize)]
+    struct SourceFile {
+        name: String,
+        source: String,
+    }
+    let files: Vec<SourceFile> = match serde_json::from_str(files_json) {
+        Ok(files) => files,
+        Err(e) => return input_error_json(format!("Invalid project file list: {}", e), "Pass a JSON array of {\"name\", \"source\"} objects."),
+    };
+    let files: Vec<(String, String)> = files.into_iter().map(|f| (f.name, f.source)).collect();
+    let merged = parser::merge_project(&files);
+
+    let mut errors: Vec<rules::WasmRuleResult> = merged
+        .errors
+        .iter()
+        .map(|(file, e)| {
+            let mut err = parse_error_result(e.line, format!("Parse Error: {}", e.message));
+            if let Some(v) = err.violation.as_mut() {
+                v.file = Some(file.clone());
             }
+            err
+        })
+        .collect();
+    if merged.program.functions.is_empty() {
+        if errors.is_empty() {
+            return input_error_json("No PLC blocks found in the project".into(), "Provide at least one non-empty source file.");
         }
+        return results_json(&errors);
     }
+    let policy = parse_policy_or_default(policy_json, &mut errors);
+    let mut results = match analyzer(Arc::new(policy), &[], false) {
+        Ok(analyzer) => analyzer.run_project(&merged),
+        Err(e) => vec![policy_error(e)],
+    };
+    for v in results.iter_mut().filter_map(|r| r.violation.as_mut()) {
+        let Some((file, line)) = merged.locate(v.line).filter(|_| v.line > 0) else { continue };
+        let shift = v.line - line;
+        v.file = Some(file.to_string());
+        v.line = line;
+        if let Some(end) = v.end_line.as_mut() {
+            *end -= shift;
+        }
+        if let Some(fix) = v.fix.as_mut() {
+            fix.start_line -= shift;
+            fix.end_line -= shift;
+        }
+    }
+    errors.append(&mut results);
+    results_json(&errors)
+}
 
-    // 3. Run all rules using the parsed program and policy
-    let mut results = rules::run_all_for_wasm(&program, &policy);
-    // 4. If we have policy parsing errors, prepend them to the results
-    if !errors.is_empty() {
-        errors.append(&mut results);
-        serde_json::to_string(&errors).unwrap_or_else(|_| "[]".into())
-    } else {
-        serde_json::to_string(&results).unwrap_or_else(|_| "[]".into())
+/// Incremental variant of [`check_plc_code`] for check-as-you-type callers.
+///
+/// Returns `{"hash": .., "unchanged": .., "results": [..]}`. Repeating the
+/// last input is served from a single-entry cache without re-parsing.
+/// Pass the `hash` from the previous response as `previous_hash`;
+/// `unchanged` is true when it still matches, so the UI can skip
+/// re-rendering. Pass an empty string on the first call.
+#[wasm_bindgen]
+pub fn check_plc_code_incremental(
+    source_code: &str,
+    policy_json: &str,
+    file_name: &str,
+    previous_hash: &str,
+) -> String {
+    let hash = cache::input_hash(source_code, policy_json, file_name, &[]);
+    let hash_str = cache::hash_to_string(hash);
+    CACHE.with(|c| {
+        let mut c = c.borrow_mut();
+        let unchanged = previous_hash == hash_str;
+        if c.results(hash).is_none() {
+            c.store(hash, analyze(source_code, policy_json, file_name, &[], Vec::new()));
+        }
+        let results = c.results(hash).unwrap_or("[]");
+        format!(r#"{{"hash":"{}","unchanged":{},"results":{}}}"#, hash_str, unchanged, results)
+    })
+}
+
+/// Badge-sized summary of [`check_plc_code`]:
+/// `{total_rules, passed, violated, errors, worst_severity}`. Runs the same
+/// pipeline but returns only the counts. If the summary cannot be
+/// serialised the result is an Internal Error entry list instead.
+#[wasm_bindgen]
+pub fn summarize(source_code: &str, policy_json: &str, file_name: &str) -> String {
+    let results = analyze_results(source_code, policy_json, file_name, &[], Vec::new(), false);
+    serde_json::to_string(&report::Summary::from_results(&results))
+        .unwrap_or_else(|e| results_json(&[internal_error_result(format!("Could not serialise the summary: {}", e))]))
+}
+
+/// The rule catalog as a JSON array of
+/// `{no, name, description, rationale, default_severity, requires_policy}`, so UIs
+/// can list the checks without keeping their own copy. If the catalog
+/// cannot be serialised the result is an Internal Error entry list instead.
+#[wasm_bindgen]
+pub fn list_rules() -> String {
+    serde_json::to_string(rules::registry::RULES)
+        .unwrap_or_else(|e| results_json(&[internal_error_result(format!("Could not serialise the rule catalog: {}", e))]))
+}
+
+/// The blocks of a source file as a JSON array of
+/// `{name, kind, line, statement_count}`, in source order, for a program
+/// outline. Only parses; no rules run. `statement_count` includes nested
+/// statements and leaves out comments. A file that does not parse at all
+/// returns its Parse Error entries as [`check_plc_code`] would; one that
+/// parses in part lists the blocks that did, followed by those entries.
+#[wasm_bindgen]
+pub fn outline(source_code: &str, file_name: &str) -> String {
+    #[derive(serde::Serialize)]
+    #[serde(untagged)]
+    enum Entry<'a> {
+        Block { name: &'a str, kind: ast::FunctionKind, line: usize, statement_count: usize },
+        Error(&'a rules::WasmRuleResult),
+    }
+
+    let (program, errors) = match parse_input(source_code, file_name) {
+        Ok(parsed) => parsed,
+        Err(only) => return results_json(&only),
+    };
+    let blocks = program.functions.iter().map(|f| Entry::Block {
+        name: &f.name,
+        kind: f.kind,
+        line: f.line,
+        statement_count: ast::statement_count(&f.statements),
+    });
+    let entries: Vec<Entry> = blocks.chain(errors.iter().map(Entry::Error)).collect();
+    serde_json::to_string(&entries)
+        .unwrap_or_else(|e| results_json(&[internal_error_result(format!("Could not serialise the outline: {}", e))]))
+}
+
+/// Check `policy_json` on its own, for live feedback in a policy editor.
+/// Returns `{"ok": true}` or `{"ok": false, "error": .., "line": ..,
+/// "column": ..}`. `line` and `column` (1-based, in `policy_json` as
+/// given) are left out for errors without a position, such as an invalid
+/// memory area. An empty string is the default policy and valid.
+#[wasm_bindgen]
+pub fn validate_policy(policy_json: &str) -> String {
+    #[derive(serde::Serialize)]
+    struct PolicyCheck {
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        line: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        column: Option<usize>,
+    }
+
+    let result = if policy_json.trim().is_empty() { Ok(Policy::default()) } else { parse_policy_from_text(policy_json) };
+    let check = match result {
+        Ok(_) => PolicyCheck { ok: true, error: None, line: None, column: None },
+        Err(error) => {
+            let location = error_location(policy_json);
+            PolicyCheck { ok: false, line: location.map(|l| l.0), column: location.map(|l| l.1), error: Some(error) }
+        }
+    };
+    serde_json::to_string(&check).unwrap_or_else(|_| r#"{"ok":false}"#.into())
+}
+
+/// Where serde_json stopped reading `policy_json`. `None` if the JSON
+/// deserializes (the error is semantic) or the error has no position.
+fn error_location(policy_json: &str) -> Option<(usize, usize)> {
+    let e = serde_json::from_str::<Policy>(policy_json).err()?;
+    Some((e.line(), e.column())).filter(|&(line, _)| line > 0)
+}
+
+/// Parse `source_code` and `policy_json` once and keep the result for
+/// [`run_rule`]. Returns an opaque handle; release it with
+/// [`release_handle`] when done.
+#[wasm_bindgen]
+pub fn parse_to_handle(source_code: &str, policy_json: &str, file_name: &str) -> u32 {
+    let mut errors = Vec::new();
+    let (program, policy) = match parse_input(source_code, file_name) {
+        Ok((program, parse_errors)) => {
+            errors.extend(parse_errors);
+            let policy = parse_policy_or_default(policy_json, &mut errors);
+            (Some(program), policy)
+        }
+        Err(only) => {
+            errors = only;
+            (None, Policy::default())
+        }
+    };
+    let session = session::Session { source: source_code.to_string(), program, policy: Arc::new(policy), errors };
+    SESSIONS.with(|s| s.borrow_mut().insert(session))
+}
+
+/// Input, parse and policy errors found by [`parse_to_handle`], as the same
+/// JSON entries [`check_plc_code`] would put first.
+#[wasm_bindgen]
+pub fn handle_errors(handle: u32) -> String {
+    SESSIONS.with(|s| match s.borrow().get(handle) {
+        Some(session) => results_json(&session.errors),
+        None => unknown_handle(handle),
+    })
+}
+
+/// Run one rule against a parsed handle and return its entries as JSON.
+/// Rules that do not apply to the policy's platform, and every rule when
+/// nothing could be parsed, return `[]`. A rule number that does not exist
+/// returns a Rule Selection Error entry. Calling this for each rule after
+/// [`handle_errors`] gives the same entries as [`check_plc_code`], which
+/// orders the findings of all rules by line.
+#[wasm_bindgen]
+pub fn run_rule(handle: u32, rule_no: u8) -> String {
+    run_rule_explained(handle, rule_no, false)
+}
+
+/// [`run_rule`] with the explain mode of [`check_plc_code_explained`].
+#[wasm_bindgen]
+pub fn run_rule_explained(handle: u32, rule_no: u8, explain: bool) -> String {
+    SESSIONS.with(|s| {
+        let sessions = s.borrow();
+        let Some(session) = sessions.get(handle) else {
+            return unknown_handle(handle);
+        };
+        if rules::registry::lookup(rule_no).is_none() {
+            return results_json(&[selection_error(format!("Unknown rule number {}", rule_no))]);
+        }
+        let results = match &session.program {
+            Some(program) => run_checks(program, Arc::clone(&session.policy), &session.source, &[rule_no], explain),
+            None => Vec::new(),
+        };
+        results_json(&results)
+    })
+}
+
+/// Free a handle from [`parse_to_handle`]. Returns false if it was not open.
+#[wasm_bindgen]
+pub fn release_handle(handle: u32) -> bool {
+    SESSIONS.with(|s| s.borrow_mut().remove(handle))
+}
+
+/// Shared pipeline behind the WASM entry points. `errors` holds sentinel
+/// results gathered by the caller; they are prepended to the output.
+fn analyze(
+    source_code: &str,
+    policy_json: &str,
+    file_name: &str,
+    selection: &[u8],
+    errors: Vec<rules::WasmRuleResult>,
+) -> String {
+    results_json(&analyze_results(source_code, policy_json, file_name, selection, errors, false))
+}
+
+/// [`analyze`] before serialisation: parse, policy and selection
+/// errors first, then the rule entries.
+fn analyze_results(
+    source_code: &str,
+    policy_json: &str,
+    file_name: &str,
+    selection: &[u8],
+    mut errors: Vec<rules::WasmRuleResult>,
+    explain: bool,
+) -> Vec<rules::WasmRuleResult> {
+    let (program, parse_errors) = match parse_input(source_code, file_name) {
+        Ok(parsed) => parsed,
+        Err(only) => return only,
+    };
+    errors.extend(parse_errors);
+    let policy = parse_policy_or_default(policy_json, &mut errors);
+    let mut results = run_checks(&program, Arc::new(policy), source_code, selection, explain);
+
+    // If we have parse, policy or selection errors, prepend them to the results
+    errors.append(&mut results);
+    errors
+}
+
+/// Parse the custom policy JSON. If parsing fails, record an error and
+/// continue with a default/empty policy to avoid crashing.
+fn parse_policy_or_default(policy_json: &str, errors: &mut Vec<rules::WasmRuleResult>) -> Policy {
+    let trimmed_policy = policy_json.trim();
+    if trimmed_policy.is_empty() {
+        return Policy::default();
+    }
+    parse_policy_from_text(trimmed_policy).unwrap_or_else(|err| {
+        errors.push(policy_error(err));
+        Policy::default()
+    })
+}
+
+/// A Policy Error entry for `reason`.
+fn policy_error(reason: String) -> rules::WasmRuleResult {
+    sentinel(rules::registry::POLICY_ERROR, reason, "Fix policy JSON format. See About → Custom Policy example.")
+}
+
+/// Run the selected rules using the parsed program and policy, honouring
+/// any `plc-checker:ignore` pragmas in the source. A policy that does not
+/// validate is reported as a single Policy Error entry.
+fn run_checks(
+    program: &ast::Program,
+    policy: Arc<Policy>,
+    source_code: &str,
+    selection: &[u8],
+    explain: bool,
+) -> Vec<rules::WasmRuleResult> {
+    analyzer(policy, selection, explain)
+        .map(|analyzer| analyzer.run(program, source_code))
+        .unwrap_or_else(|e| vec![policy_error(e)])
+}
+
+/// A Rule Selection Error entry for `reason`.
+fn selection_error(reason: String) -> rules::WasmRuleResult {
+    sentinel(rules::registry::SELECTION_ERROR, reason, "Pass a JSON array of rule numbers, e.g. [7, 10].")
+}
+
+/// The analyzer the WASM entry points run.
+fn analyzer(policy: Arc<Policy>, selection: &[u8], explain: bool) -> Result<Analyzer, String> {
+    Analyzer::builder().with_policy(policy).with_rules(selection).with_explanations(explain).build()
+}
+
+fn unknown_handle(handle: u32) -> String {
+    input_error_json(
+        format!("Unknown analysis handle {}", handle),
+        "Call parse_to_handle first and do not use a handle after release_handle.",
+    )
+}
+
+/// A single Input Error entry as the JSON result list.
+fn input_error_json(reason: String, suggestion: &str) -> String {
+    results_json(&[sentinel(rules::registry::INPUT_ERROR, reason, suggestion)])
+}
+
+/// Serialise result entries for the WASM API. Should serialisation fail,
+/// the caller gets a single Internal Error entry rather than `[]`, which
+/// would read as "no findings".
+fn results_json(results: &[rules::WasmRuleResult]) -> String {
+    serde_json::to_string(results).unwrap_or_else(|e| {
+        let err = [internal_error_result(format!("Could not serialise the analysis results: {}", e))];
+        serde_json::to_string(&err).unwrap_or_else(|_| INTERNAL_ERROR_JSON.into())
+    })
+}
+
+/// [`internal_error_result`] written out by hand, for when even that
+/// cannot be serialised.
+const INTERNAL_ERROR_JSON: &str = r#"[{"status":"ERROR","rule_no":0,"rule_name":"Internal Error","violation":{"rule_no":0,"rule_name":"Internal Error","line":0,"reason":"Could not serialise the analysis results","suggestion":"Please report this input to the maintainers.","severity":"Error"}}]"#;
+
+fn internal_error_result(reason: String) -> rules::WasmRuleResult {
+    sentinel(rules::registry::INTERNAL_ERROR, reason, "Please report this input to the maintainers.")
+}
+
+/// An Error entry for pseudo-rule `name` (rule 0, line 0): problems with
+/// the input or the call rather than findings in the program.
+pub(crate) fn sentinel(name: &'static str, reason: String, suggestion: &str) -> rules::WasmRuleResult {
+    rules::WasmRuleResult {
+        status: rules::RuleStatus::Error,
+        rule_no: 0,
+        rule_name: name,
+        violation: Some(rules::Violation {
+            rule_no: 0,
+            rule_name: name,
+            line: 0,
+            end_line: None,
+            reason,
+            suggestion: suggestion.into(),
+            severity: Severity::Error,
+            snippet: None,
+            fix: None,
+            function: None,
+            file: None,
+            rationale: None,
+            doc_anchor: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_policy_reports_where_the_json_is_wrong() {
+        let check = |policy: &str| serde_json::from_str::<serde_json::Value>(&validate_policy(policy)).unwrap();
+        assert_eq!(check(r#"{ "pairs": [["A", "B"]], "platform": "S7" }"#), serde_json::json!({ "ok": true }));
+        assert_eq!(check("  "), serde_json::json!({ "ok": true }));
+
+        let unknown = check("{\n  \"platform\": \"S7\",\n  \"pairz\": []\n}");
+        assert_eq!(unknown["ok"], false);
+        assert!(unknown["error"].as_str().unwrap().contains("unknown field 'pairz'"));
+        assert_eq!((unknown["line"].as_u64(), unknown["column"].as_u64()), (Some(3), Some(9)));
+
+        let syntax = check("{\n  \"pairs\": [[\"A\", \"B\"]\n  \"platform\": \"S7\"\n}");
+        assert_eq!(syntax["ok"], false);
+        assert_eq!(syntax["line"], 3);
+
+        // Semantic errors have no position.
+        let semantic = check(r#"{ "max_nesting": 0 }"#);
+        assert_eq!(semantic["error"], "Invalid policy: max_nesting must be at least 1");
+        assert!(semantic.get("line").is_none() && semantic.get("column").is_none());
+    }
+
+    #[test]
+    fn error_location_comes_from_the_json_parser() {
+        assert_eq!(error_location("{\n  \"pairs\": [,]\n}"), Some((2, 13)));
+        assert_eq!(error_location(r#"{ "max_nesting": 0 }"#), None);
+    }
+
+    #[test]
+    fn outline_lists_blocks_without_running_rules() {
+        let src = "FUNCTION FC_Scale\nBEGIN\n    // scale\n    y := x * 2;\nEND_FUNCTION\n\
+                   ORGANIZATION_BLOCK OB1\nBEGIN\n    IF Run THEN\n        FC_Scale();\n    ELSE\n        y := 0;\n    END_IF;\nEND_ORGANIZATION_BLOCK\n";
+        let blocks: serde_json::Value = serde_json::from_str(&outline(src, "a.scl")).unwrap();
+        assert_eq!(
+            blocks,
+            serde_json::json!([
+                { "name": "FC_Scale", "kind": "FC", "line": 1, "statement_count": 1 },
+                { "name": "OB1", "kind": "OB1", "line": 6, "statement_count": 3 },
+            ])
+        );
+
+        let broken: Vec<serde_json::Value> = serde_json::from_str(&outline("FUNCTION FC_A\nBEGIN\n    x := ;\n", "a.scl")).unwrap();
+        assert!(broken.iter().all(|r| r["rule_name"] == rules::registry::PARSE_ERROR && r["status"] == "ERROR"));
+
+        let partial = "FUNCTION FC_A\nBEGIN\n    x := 1;\nEND_FUNCTION\nFUNCTION FC_B\nBEGIN\n    x := ;\nEND_FUNCTION\n";
+        let entries: Vec<serde_json::Value> = serde_json::from_str(&outline(partial, "a.scl")).unwrap();
+        assert_eq!(entries[0], serde_json::json!({ "name": "FC_A", "kind": "FC", "line": 1, "statement_count": 1 }));
+        assert_eq!((entries.len(), &entries[1]["rule_name"]), (2, &serde_json::json!(rules::registry::PARSE_ERROR)));
+    }
+
+    #[test]
+    fn subset_reports_unknown_rule_numbers() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    Avg := Sum / Count;\nEND_ORGANIZATION_BLOCK\n";
+        let results: Vec<serde_json::Value> = serde_json::from_str(&check_plc_code_subset(src, "", "a.scl", "[4, 99]")).unwrap();
+        assert_eq!(results[0]["rule_name"], "Rule Selection Error");
+        assert_eq!(results[0]["violation"]["reason"], "Unknown rule number(s) 99");
+        assert!(results.len() > 2, "every rule runs after a bad selection");
+
+        let picked: Vec<serde_json::Value> = serde_json::from_str(&check_plc_code_subset(src, "", "a.scl", "[4]")).unwrap();
+        assert!(picked.iter().all(|r| r["rule_no"] == 4));
+    }
+
+    #[test]
+    fn summary_counts_rules_and_errors() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    Avg := Sum / Count;\nEND_ORGANIZATION_BLOCK\n";
+        let summary: serde_json::Value = serde_json::from_str(&summarize(src, "", "a.scl")).unwrap();
+        let results: Vec<serde_json::Value> = serde_json::from_str(&check_plc_code(src, "", "a.scl")).unwrap();
+        let rules: std::collections::BTreeSet<u64> = results.iter().map(|r| r["rule_no"].as_u64().unwrap()).collect();
+        assert_eq!(summary["total_rules"], rules.len());
+        let counted: u64 = ["passed", "violated", "not_applicable"].iter().map(|k| summary[k].as_u64().unwrap()).sum();
+        assert_eq!(counted, rules.len() as u64);
+        // Rules 7, 10 and 36 need policy input the empty policy lacks.
+        let skipped: Vec<u64> =
+            results.iter().filter(|r| r["status"] == "NOT APPLICABLE").map(|r| r["rule_no"].as_u64().unwrap()).collect();
+        assert_eq!(skipped, vec![7, 10, 36]);
+        assert_eq!(summary["not_applicable"], 3);
+        assert_eq!(summary["errors"], 0);
+        assert_ne!(summary["worst_severity"], serde_json::Value::Null);
+
+        let broken: serde_json::Value = serde_json::from_str(&summarize("IF THEN", "", "a.scl")).unwrap();
+        assert_eq!(broken["errors"], 1);
+        assert_eq!(broken["total_rules"], 0);
+        assert_eq!(broken["worst_severity"], "Error");
+    }
+
+    #[test]
+    fn explain_mode_adds_rationale_and_doc_anchor() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    Avg := Sum / Count;\nEND_ORGANIZATION_BLOCK\n";
+        let rule4 = |json: &str| -> serde_json::Value {
+            let results: Vec<serde_json::Value> = serde_json::from_str(json).unwrap();
+            results.into_iter().find(|r| r["rule_no"] == 4).unwrap()["violation"].clone()
+        };
+        let plain = rule4(&check_plc_code(src, "", "a.scl"));
+        assert!(plain.get("rationale").is_none() && plain.get("doc_anchor").is_none());
+        assert_eq!(check_plc_code_explained(src, "", "a.scl", false), check_plc_code(src, "", "a.scl"));
+
+        let explained = rule4(&check_plc_code_explained(src, "", "a.scl", true));
+        assert_eq!(explained["rationale"], rules::registry::RULE4.rationale);
+        assert_eq!(explained["doc_anchor"], "rule-4-use-plc-flags-as-integrity-checks");
+    }
+
+    #[test]
+    fn encoded_source_matches_plain_check() {
+        use base64::Engine;
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    Avg := Sum / Count;\nEND_ORGANIZATION_BLOCK\n";
+        let b64 = base64::engine::general_purpose::STANDARD.encode(src);
+        assert_eq!(check_plc_code_encoded(&b64, "base64", "", "a.scl"), check_plc_code(src, "", "a.scl"));
+
+        let bad: serde_json::Value = serde_json::from_str(&check_plc_code_encoded("not base64!", "base64", "", "a.scl")).unwrap();
+        assert_eq!(bad[0]["status"], "ERROR");
+        assert_eq!(bad[0]["rule_name"], rules::registry::INPUT_ERROR);
+    }
+
+    #[test]
+    fn project_findings_name_their_file_and_block() {
+        let files = serde_json::json!([
+            { "name": "main.scl", "source": "ORGANIZATION_BLOCK OB1\nBEGIN\n    FB_Calc();\nEND_ORGANIZATION_BLOCK\n" },
+            { "name": "bad.scl", "source": "FUNCTION FC_X\nBEGIN\n    y := ;\nEND_FUNCTION\n" },
+            { "name": "calc.scl", "source": "FUNCTION_BLOCK FB_Calc\nBEGIN\n    Avg := Sum\n        / Count;\nEND_FUNCTION_BLOCK\n" },
+        ]);
+        let out: Vec<serde_json::Value> = serde_json::from_str(&check_plc_project(&files.to_string(), "")).unwrap();
+        assert_eq!(out[0]["status"], "ERROR");
+        assert_eq!(out[0]["violation"]["file"], "bad.scl");
+        assert_eq!(out[0]["violation"]["line"], 3);
+
+        let division = out.iter().find(|r| r["rule_no"] == 4 && r["status"] == "NOT FOLLOWED").unwrap();
+        assert_eq!(division["violation"]["file"], "calc.scl");
+        assert_eq!(division["violation"]["function"], "FB_Calc");
+        assert_eq!(division["violation"]["line"], 3);
+        assert_eq!(division["violation"]["end_line"], 4);
+        // Fixes are located in the merged source, so the right lines were read.
+        let fix = &division["violation"]["fix"];
+        assert_eq!((fix["start_line"].clone(), fix["start_col"].clone()), (3.into(), 5.into()));
+        assert_eq!((fix["end_line"].clone(), fix["end_col"].clone()), (4.into(), 17.into()));
+        assert!(fix["replacement"].as_str().unwrap().starts_with("IF Count <> 0 THEN\n        Avg := Sum / Count;"));
+
+        // An annotation at the end of one file does not cover the next one.
+        let files = serde_json::json!([
+            { "name": "a.scl", "source": "FUNCTION FC_A\nBEGIN\nEND_FUNCTION\n// @PlausibilityCheck\n" },
+            { "name": "b.scl", "source": "FUNCTION FC_B\nBEGIN\n    Motor_Speed := HMI_Speed;\nEND_FUNCTION\n" },
+        ]);
+        let out: Vec<serde_json::Value> = serde_json::from_str(&check_plc_project(&files.to_string(), "")).unwrap();
+        let unchecked = out.iter().find(|r| r["rule_no"] == 11).unwrap();
+        assert_eq!(unchecked["status"], "NOT FOLLOWED");
+        assert_eq!((&unchecked["violation"]["file"], &unchecked["violation"]["line"]), (&"b.scl".into(), &3.into()));
+
+        let bad: serde_json::Value = serde_json::from_str(&check_plc_project("{}", "")).unwrap();
+        assert_eq!(bad[0]["rule_name"], rules::registry::INPUT_ERROR);
+    }
+
+    #[test]
+    fn incremental_check_reports_unchanged_input() {
+        let src = "FUNCTION FC_A\nBEGIN\n    x := 1;\nEND_FUNCTION\n";
+        let first: serde_json::Value = serde_json::from_str(&check_plc_code_incremental(src, "", "a.scl", "")).unwrap();
+        assert_eq!(first["unchanged"], false);
+        let hash = first["hash"].as_str().unwrap();
+
+        let again: serde_json::Value = serde_json::from_str(&check_plc_code_incremental(src, "", "a.scl", hash)).unwrap();
+        assert_eq!(again["unchanged"], true);
+        assert_eq!(again["results"], first["results"]);
+        let direct: serde_json::Value = serde_json::from_str(&check_plc_code(src, "", "a.scl")).unwrap();
+        assert_eq!(again["results"], direct);
+
+        let edited: serde_json::Value =
+            serde_json::from_str(&check_plc_code_incremental(&src.replace('1', "2"), "", "a.scl", hash)).unwrap();
+        assert_eq!(edited["unchanged"], false);
+        assert_ne!(edited["hash"], again["hash"]);
+    }
+
+    #[test]
+    fn rule_by_rule_run_matches_the_one_shot_check() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    x := a / b;\n    Motor_Out := Motor_Out;\nEND_ORGANIZATION_BLOCK\nFUNCTION Broken\nBEGIN\n    x := ;\nEND_FUNCTION\n";
+        let policy = r#"{ "platform": "S7" }"#;
+        let handle = parse_to_handle(src, policy, "a.scl");
+        let mut stepped: Vec<serde_json::Value> = serde_json::from_str(&handle_errors(handle)).unwrap();
+        let mut findings: Vec<serde_json::Value> = Vec::new();
+        for rule in rules::registry::RULES {
+            let entries: Vec<serde_json::Value> = serde_json::from_str(&run_rule(handle, rule.no)).unwrap();
+            findings.extend(entries);
+        }
+        // The one-shot check orders the findings of all rules by line.
+        findings.sort_by_key(|r| (r["violation"]["line"].as_u64().unwrap_or(0), r["rule_no"].as_u64()));
+        stepped.extend(findings);
+        let direct: Vec<serde_json::Value> = serde_json::from_str(&check_plc_code(src, policy, "a.scl")).unwrap();
+        assert_eq!(stepped, direct);
+        assert!(stepped.iter().any(|r| r["rule_name"] == rules::registry::PARSE_ERROR));
+
+        let unknown: Vec<serde_json::Value> = serde_json::from_str(&run_rule(handle, 99)).unwrap();
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0]["rule_name"], rules::registry::SELECTION_ERROR);
+        assert_eq!(unknown[0]["violation"]["reason"], "Unknown rule number 99");
+
+        assert!(release_handle(handle));
+        assert!(!release_handle(handle));
+        let gone: serde_json::Value = serde_json::from_str(&run_rule(handle, 1)).unwrap();
+        assert_eq!(gone[0]["violation"]["reason"], format!("Unknown analysis handle {}", handle));
+    }
+
+    #[test]
+    fn rule_catalog_lists_every_rule_once() {
+        let catalog: Vec<serde_json::Value> = serde_json::from_str(&list_rules()).unwrap();
+        let numbers: Vec<u64> = catalog.iter().map(|r| r["no"].as_u64().unwrap()).collect();
+        let unique: std::collections::BTreeSet<u64> = numbers.iter().copied().collect();
+        assert_eq!(unique.len(), numbers.len());
+        assert!(rules::RULES.iter().all(|r| unique.contains(&(r.meta.no as u64))));
+        assert!(numbers.len() >= 19);
+
+        let needs_policy: Vec<u64> =
+            catalog.iter().filter(|r| r["requires_policy"] == true).map(|r| r["no"].as_u64().unwrap()).collect();
+        assert_eq!(needs_policy, vec![7, 10, 36]);
+        assert_eq!(catalog[0]["default_severity"], "Info");
+        assert!(catalog.iter().all(|r| !r["description"].as_str().unwrap().is_empty()));
+    }
+
+    #[test]
+    fn internal_error_fallback_matches_the_serialised_sentinel() {
+        let sentinel = [internal_error_result("Could not serialise the analysis results".into())];
+        let expected: serde_json::Value = serde_json::from_str(&results_json(&sentinel)).unwrap();
+        let fallback: serde_json::Value = serde_json::from_str(INTERNAL_ERROR_JSON).unwrap();
+        assert_eq!(fallback, expected);
+        assert_eq!(fallback[0]["status"], "ERROR");
     }
-}
\ No newline at end of file
+}
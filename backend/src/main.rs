@@ -18,7 +18,7 @@ struct Cli {
     /// Path to the Siemens PLC source file (.scl/.st or PLCOpen .xml)
     input: PathBuf,
 
-    /// Optional path to policy.json (used by Rule 7 & Rule 10)
+    /// Optional path to a policy file, JSON or TOML (by extension)
     #[arg(short, long)]
     policy: Option<PathBuf>,
 }
@@ -1,9 +1,10 @@
 //! Simplified parser for Instruction List (IL / AWL).
 //! Translates accumulator-based logic (LD/ADD/ST) into the unified AST.
+//! S7 AWL loads (`L a; L b; >I`) are modelled with a second accumulator.
 
 use std::{collections::HashMap, fs};
 use std::path::Path;
-use crate::ast::{BinOp, Expression, Function, FunctionKind, Program, Statement, UnaryOp};
+use crate::ast::{is_empty_block, BinOp, Expression, Function, FunctionKind, Program, Statement, UnaryOp};
 
 pub fn parse_il(path: &Path) -> Result<Program, String> {
     let src = fs::read_to_string(path).map_err(|e| format!("read error: {e}"))?;
@@ -14,23 +15,96 @@ pub fn parse_il_from_str(src: &str) -> Result<Program, String> {
     // This is a more advanced parser that handles labels and jumps.
     // It's still simplified and won't handle all IL complexities.
     let lines: Vec<&str> = src.lines().collect();
-    let labels = find_labels(&lines);
+    let blocks = find_blocks(&lines);
 
-    let statements = parse_statements_from_il(&lines, &labels, 0, lines.len())?;
+    // Without block headers the whole file is one implicit program.
+    if blocks.is_empty() {
+        let labels = find_labels(&lines, 0, lines.len());
+        let statements = parse_statements_from_il(&lines, &labels, 0, lines.len())?;
+        // Nothing but blank lines and comments: there is no program at all.
+        if is_empty_block(&statements) {
+            return Ok(Program { functions: vec![] });
+        }
+        let main_func = Function {
+            name: "IL_Program".to_string(),
+            kind: FunctionKind::Program,
+            statements,
+            line: 1,
+            regions: vec![],
+            declarations: vec![],
+        };
+        return Ok(Program { functions: vec![main_func] });
+    }
 
-    let main_func = Function {
-        name: "IL_Program".to_string(),
-        kind: FunctionKind::Program,
-        statements,
-        line: 1,
-    };
+    let mut functions = Vec::new();
+    for block in blocks {
+        // Labels are local to a block; AWL exports reuse names like M001.
+        let labels = find_labels(&lines, block.body_start, block.end);
+        let statements = parse_statements_from_il(&lines, &labels, block.body_start, block.end)?;
+        functions.push(Function {
+            name: block.name,
+            kind: block.kind,
+            statements,
+            line: block.line,
+            regions: vec![],
+            declarations: vec![],
+        });
+    }
+    Ok(Program { functions })
+}
 
-    Ok(Program { functions: vec![main_func] })
+/// A `FUNCTION_BLOCK FB1 ... END_FUNCTION_BLOCK` (or FC/OB/PROGRAM/DB)
+/// range of lines. `body_start..end` excludes the header and END line.
+struct IlBlock {
+    name: String,
+    kind: FunctionKind,
+    closer: &'static str,
+    line: usize,
+    body_start: usize,
+    end: usize,
 }
 
-fn find_labels(lines: &[&str]) -> HashMap<String, usize> {
-    let mut labels = HashMap::new();
+const BLOCK_HEADERS: &[(&str, &str)] = &[
+    ("ORGANIZATION_BLOCK", "END_ORGANIZATION_BLOCK"),
+    ("FUNCTION_BLOCK", "END_FUNCTION_BLOCK"),
+    ("FUNCTION", "END_FUNCTION"),
+    ("PROGRAM", "END_PROGRAM"),
+    ("DATA_BLOCK", "END_DATA_BLOCK"),
+];
+
+/// Block headers, in file order. A block without its END line runs to the
+/// next header or the end of the file.
+fn find_blocks(lines: &[&str]) -> Vec<IlBlock> {
+    let mut blocks: Vec<IlBlock> = Vec::new();
     for (i, line) in lines.iter().enumerate() {
+        let mut words = line.split_whitespace();
+        let first = words.next().unwrap_or("").to_ascii_uppercase();
+        if let Some(open) = blocks.last_mut().filter(|b| b.end == lines.len()) {
+            if first.trim_end_matches(';') == open.closer {
+                open.end = i;
+                continue;
+            }
+        }
+        let Some(&(header, closer)) = BLOCK_HEADERS.iter().find(|(h, _)| *h == first) else { continue };
+        if let Some(open) = blocks.last_mut().filter(|b| b.end == lines.len()) {
+            open.end = i;
+        }
+        let name = words.next().unwrap_or("").trim_matches('"').to_string();
+        let kind = match header {
+            "ORGANIZATION_BLOCK" => super::ob_kind(&name),
+            "FUNCTION_BLOCK" => FunctionKind::FB,
+            "FUNCTION" => FunctionKind::FC,
+            "DATA_BLOCK" => FunctionKind::DataBlock,
+            _ => FunctionKind::Program,
+        };
+        blocks.push(IlBlock { name, kind, closer, line: i + 1, body_start: i + 1, end: lines.len() });
+    }
+    blocks
+}
+
+fn find_labels(lines: &[&str], start: usize, end: usize) -> HashMap<String, usize> {
+    let mut labels = HashMap::new();
+    for (i, line) in lines.iter().enumerate().take(end).skip(start) {
         if line.trim().ends_with(':') {
             let label = line.trim().trim_end_matches(':').to_string();
             labels.insert(label, i);
@@ -39,6 +113,15 @@ fn find_labels(lines: &[&str]) -> HashMap<String, usize> {
     labels
 }
 
+/// Lines that structure an AWL source without being instructions: block
+/// attributes, `NETWORK` separators and their titles, `BEGIN`.
+fn is_block_furniture(instruction: &str) -> bool {
+    matches!(
+        instruction,
+        "NETWORK" | "TITLE" | "TITLE=" | "VERSION" | "AUTHOR" | "FAMILY" | "NAME" | "BEGIN" | "KNOW_HOW_PROTECT"
+    ) || instruction.starts_with("TITLE=")
+}
+
 fn parse_statements_from_il(
     lines: &[&str],
     labels: &HashMap<String, usize>,
@@ -47,13 +130,29 @@ fn parse_statements_from_il(
 ) -> Result<Vec<Statement>, String> {
     let mut stmts = Vec::new();
     let mut current_result: Option<Expression> = None;
+    let mut accu2: Option<Expression> = None;
     let mut i = start;
 
     while i < end {
-        let line = lines[i].trim();
         let line_no = i + 1;
+        let line = match split_comment(lines[i]) {
+            ("", Some(text)) => {
+                if text.starts_with("(*") && !text.contains("*)") {
+                    // Multi-line block comment: keep it as one node on its first line.
+                    let close = (i + 1..end).find(|&j| lines[j].contains("*)")).unwrap_or(end - 1);
+                    let text = lines[i..=close].iter().map(|l| l.trim()).collect::<Vec<_>>().join("\n");
+                    stmts.push(Statement::Comment { text, line: line_no });
+                    i = close + 1;
+                } else {
+                    stmts.push(Statement::Comment { text: text.to_string(), line: line_no });
+                    i += 1;
+                }
+                continue;
+            }
+            (code, _) => code,
+        };
 
-        if line.is_empty() || line.starts_with("//") || line.ends_with(':') {
+        if line.is_empty() || line.ends_with(':') {
             i += 1;
             continue;
         }
@@ -62,21 +161,53 @@ fn parse_statements_from_il(
         let instruction = parts.next().unwrap_or("").to_uppercase();
         let operand_str = parts.next();
 
+        if instruction.starts_with("VAR") {
+            // Declarations are not instructions; skip to END_VAR.
+            while i < end && !lines[i].trim().to_ascii_uppercase().starts_with("END_VAR") {
+                i += 1;
+            }
+            i += 1;
+            continue;
+        }
+        if is_block_furniture(&instruction) {
+            // A new network starts from empty accumulators.
+            current_result = None;
+            accu2 = None;
+            i += 1;
+            continue;
+        }
+
         match instruction.as_str() {
             "LD" | "LDN" => {
                 if let Some(op) = operand_str {
                     let expr = parse_operand(op, line_no);
                     current_result = if instruction == "LDN" {
-                        Some(Expression::UnaryOp { op: UnaryOp::Not, expr: Box::new(expr), line: line_no })
+                        Some(Expression::UnaryOp { op: UnaryOp::Not, expr: Box::new(expr), line: line_no, end_line: line_no })
                     } else {
                         Some(expr)
                     };
                 }
             }
+            "L" => {
+                // S7 load: the previous ACCU1 moves to ACCU2.
+                if let Some(op) = operand_str {
+                    accu2 = current_result.replace(parse_operand(op, line_no));
+                }
+            }
+            "T" => {
+                // S7 transfer keeps ACCU1, so the value can be stored again.
+                if let (Some(target_var), Some(value_expr)) = (operand_str, current_result.as_ref()) {
+                    stmts.push(Statement::Assign {
+                        target: Expression::path(target_var, line_no),
+                        value: value_expr.clone(),
+                        line: line_no,
+                    });
+                }
+            }
             "ST" => {
                 if let (Some(target_var), Some(value_expr)) = (operand_str, current_result.take()) {
                     let stmt = Statement::Assign {
-                        target: Expression::Identifier(target_var.to_string()),
+                        target: Expression::path(target_var, line_no),
                         value: value_expr,
                         line: line_no,
                     };
@@ -86,20 +217,33 @@ fn parse_statements_from_il(
             "JMPC" | "JMPNC" => {
                 if let (Some(label), Some(condition)) = (operand_str, current_result.take()) {
                     let target_line = *labels.get(label).unwrap_or(&end);
-                    let (then_branch, next_i) = if instruction == "JMPC" {
-                        (parse_statements_from_il(lines, labels, i + 1, target_line)?, target_line)
-                    } else {
-                        (vec![], i + 1) // JMPNC skips the next block
-                    };
-                    let else_branch = if instruction == "JMPNC" {
-                        parse_statements_from_il(lines, labels, i + 1, target_line)?
+                    // Only forward jumps skip a block; backward jumps (loops) are not modelled.
+                    if target_line > i {
+                        // The skipped block runs only when the jump is not taken.
+                        let condition = if instruction == "JMPC" { negate(condition, line_no) } else { condition };
+                        let then_branch = parse_statements_from_il(lines, labels, i + 1, target_line)?;
+                        stmts.push(Statement::IfStmt { condition, then_branch, else_branch: vec![], line: line_no });
+                        i = target_line;
+                        continue;
+                    }
+                }
+            }
+            "RET" => {
+                stmts.push(Statement::Return { line: line_no });
+            }
+            "RETC" | "RETCN" => {
+                if let Some(cond) = current_result.take() {
+                    let condition = if instruction == "RETCN" {
+                        Expression::UnaryOp { op: UnaryOp::Not, expr: Box::new(cond), line: line_no, end_line: line_no }
                     } else {
-                        vec![]
+                        cond
                     };
-
-                    stmts.push(Statement::IfStmt { condition, then_branch, else_branch, line: line_no });
-                    i = next_i;
-                    continue;
+                    stmts.push(Statement::IfStmt {
+                        condition,
+                        then_branch: vec![Statement::Return { line: line_no }],
+                        else_branch: vec![],
+                        line: line_no,
+                    });
                 }
             }
             "JMP" => {
@@ -107,14 +251,33 @@ fn parse_statements_from_il(
                     i = *labels.get(label).unwrap_or(&i); // Unconditional jump
                 }
             }
+            _ if operand_str.is_none() && get_accu_op(&instruction).is_some() => {
+                // `L a; L b; >I` compares ACCU2 with ACCU1: `a > b`.
+                if let (Some(left), Some(right), Some(op)) = (accu2.take(), current_result.take(), get_accu_op(&instruction)) {
+                    current_result = Some(Expression::BinaryOp {
+                        op,
+                        line: start_line(&left, line_no),
+                        left: Box::new(left),
+                        right: Box::new(right),
+                        end_line: line_no,
+                    });
+                }
+            }
             _ => { // Handle arithmetic
                 if let (Some(right_op), Some(left_expr)) = (operand_str, current_result.take()) {
                     if let Some(op_kind) = get_binop(&instruction) {
+                        let right = parse_operand(right_op, line_no);
+                        let right = if matches!(instruction.as_str(), "ANDN" | "ORN" | "XORN") {
+                            Expression::UnaryOp { op: UnaryOp::Not, expr: Box::new(right), line: line_no, end_line: line_no }
+                        } else {
+                            right
+                        };
                         current_result = Some(Expression::BinaryOp {
                             op: op_kind,
+                            line: start_line(&left_expr, line_no),
                             left: Box::new(left_expr),
-                            right: Box::new(parse_operand(right_op, line_no)),
-                            line: line_no,
+                            right: Box::new(right),
+                            end_line: line_no,
                         });
                     } else {
                         current_result = Some(left_expr); // Not an op we handle, pass through
@@ -127,6 +290,8 @@ fn parse_statements_from_il(
     Ok(stmts)
 }
 
+/// IEC IL operator for `s`. The `N` forms (`ANDN`, `ORN`, `XORN`) map to
+/// the plain operator; the caller negates their operand.
 fn get_binop(s: &str) -> Option<BinOp> {
     match s {
         "ADD" => Some(BinOp::Add),
@@ -135,7 +300,10 @@ fn get_binop(s: &str) -> Option<BinOp> {
         "DIV" => Some(BinOp::Div),
         "AND" | "ANDN" => Some(BinOp::And),
         "OR" | "ORN" => Some(BinOp::Or),
+        // The AST has no XOR; on BOOL operands it is the same as `<>`.
+        "XOR" | "XORN" => Some(BinOp::Neq),
         "EQ" => Some(BinOp::Eq),
+        "NE" => Some(BinOp::Neq),
         "GE" => Some(BinOp::Ge),
         "GT" => Some(BinOp::Gt),
         "LE" => Some(BinOp::Le),
@@ -144,6 +312,63 @@ fn get_binop(s: &str) -> Option<BinOp> {
     }
 }
 
+/// Split a line into its instruction and its comment. `//`, `(* *)` and
+/// `;` all start a comment; in S7 AWL `;` also ends the instruction, so
+/// `L #Idx; // index` is the instruction `L #Idx`.
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let line = line.trim();
+    let start = ["//", "(*", ";"].iter().filter_map(|m| line.find(m)).min();
+    match start {
+        Some(at) => {
+            let comment = &line[at..];
+            (line[..at].trim(), Some(comment).filter(|c| *c != ";"))
+        }
+        None => (line, None),
+    }
+}
+
+/// S7 accumulator instructions (`>I`, `==R`, `+D`, ...) that combine ACCU2
+/// with ACCU1. The type suffix does not matter to the AST.
+fn get_accu_op(s: &str) -> Option<BinOp> {
+    let op = s.strip_suffix(['I', 'R', 'D'])?;
+    match op {
+        "+" => Some(BinOp::Add),
+        "-" => Some(BinOp::Sub),
+        "*" => Some(BinOp::Mul),
+        "/" => Some(BinOp::Div),
+        "==" => Some(BinOp::Eq),
+        "<>" => Some(BinOp::Neq),
+        ">" => Some(BinOp::Gt),
+        ">=" => Some(BinOp::Ge),
+        "<" => Some(BinOp::Lt),
+        "<=" => Some(BinOp::Le),
+        _ => None,
+    }
+}
+
+/// `NOT cond`, folding a negated comparison into its opposite operator so
+/// guards read as `Divisor <> 0` rather than `NOT (Divisor = 0)`.
+fn negate(cond: Expression, line: usize) -> Expression {
+    match cond {
+        Expression::BinaryOp { op, left, right, line, end_line } if op.is_comparison() => {
+            Expression::BinaryOp { op: op.negated(), left, right, line, end_line }
+        }
+        other => {
+            let end_line = other.span().1.max(line);
+            Expression::UnaryOp { op: UnaryOp::Not, expr: Box::new(other), line, end_line }
+        }
+    }
+}
+
+/// Line an expression built over several instructions starts on: that of
+/// its first operand, if known.
+fn start_line(first: &Expression, fallback: usize) -> usize {
+    match first.span().0 {
+        0 => fallback,
+        line => line.min(fallback),
+    }
+}
+
 /// Helper to parse an operand into a literal or a variable reference.
 fn parse_operand(op: &str, line: usize) -> Expression {
     if let Ok(num) = op.parse::<i64>() {
@@ -153,6 +378,98 @@ fn parse_operand(op: &str, line: usize) -> Expression {
     } else if op.eq_ignore_ascii_case("FALSE") {
         Expression::BoolLiteral(false, line)
     } else {
-        Expression::Identifier(op.to_string())
+        Expression::path(op, line)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_headers_become_separate_functions() {
+        let src = "ORGANIZATION_BLOCK OB100\nTITLE = Startup\nBEGIN\nNETWORK\nTITLE = Reset\n    LD FALSE\n    ST Motor_Out\n\
+                   END_ORGANIZATION_BLOCK\n\nFUNCTION_BLOCK \"FB_Pump\"\nVAR_INPUT\n    Start : BOOL;\nEND_VAR\nBEGIN\n\
+                   NETWORK\n    LD Start\n    JMPC M001\n    LD 1\n    ST Count\nM001:\nNETWORK\n    LD Start\n    ST Pump_Out\n\
+                   END_FUNCTION_BLOCK\nORGANIZATION_BLOCK OB1\n    LD Run\n    JMPC M001\n    LD 0\n    ST Idle\nM001:\n";
+        let program = parse_il_from_str(src).unwrap();
+        let blocks: Vec<(&str, FunctionKind, usize, usize)> =
+            program.functions.iter().map(|f| (f.name.as_str(), f.kind, f.line, f.statements.len())).collect();
+        assert_eq!(
+            blocks,
+            vec![
+                ("OB100", FunctionKind::OB100, 1, 1),
+                ("FB_Pump", FunctionKind::FB, 10, 2),
+                ("OB1", FunctionKind::OB1, 25, 1),
+            ]
+        );
+        // The jump in OB1 resolves to its own M001, not the one in FB_Pump.
+        let Statement::IfStmt { then_branch, .. } = &program.functions[2].statements[0] else { panic!() };
+        assert!(matches!(then_branch[..], [Statement::Assign { line: 29, .. }]));
+    }
+
+    #[test]
+    fn headerless_source_is_one_program() {
+        let program = parse_il_from_str("LD Start\nST Motor\n").unwrap();
+        assert_eq!(program.functions.len(), 1);
+        assert_eq!(program.functions[0].name, "IL_Program");
+        assert_eq!(program.functions[0].kind, FunctionKind::Program);
+    }
+
+    #[test]
+    fn comparison_feeds_the_conditional_jump() {
+        let src = "L Idx\nL 9\n>I\nJMPC M001\nL Table[Idx]\nT Value\nM001:\nL Count\nL 1\n+I\nT Count\n";
+        let program = parse_il_from_str(src).unwrap();
+        let stmts = &program.functions[0].statements;
+        assert_eq!(stmts.len(), 2);
+        let Statement::IfStmt { condition, then_branch, .. } = &stmts[0] else { panic!() };
+        // The block after the jump runs when the comparison is false.
+        assert_eq!(crate::rules::utils::expr_text(condition), "Idx <= 9");
+        assert_eq!(then_branch.len(), 1);
+        let Statement::Assign { value, .. } = &stmts[1] else { panic!() };
+        assert_eq!(crate::rules::utils::expr_text(value), "Count + 1");
+    }
+
+    #[test]
+    fn not_equal_guard_is_kept() {
+        let src = "LD Count\nNE 0\nJMPNC Skip\nLD Sum\nDIV Count\nST Avg\nSkip:\nL Idx\nL 0\n<>I\nJMPC Done\nLD 1\nST Flag\nDone:\n\
+                   LD Start\nXOR Stop\nANDN Fault\nST Run\n";
+        let program = parse_il_from_str(src).unwrap();
+        let stmts = &program.functions[0].statements;
+        let Statement::IfStmt { condition, .. } = &stmts[0] else { panic!() };
+        assert_eq!(crate::rules::utils::expr_text(condition), "Count <> 0");
+        let Statement::IfStmt { condition, .. } = &stmts[1] else { panic!() };
+        assert_eq!(crate::rules::utils::expr_text(condition), "Idx = 0");
+        let Statement::Assign { value, .. } = &stmts[2] else { panic!() };
+        assert_eq!(crate::rules::utils::expr_text(value), "Start <> Stop AND NOT Fault");
+        // Rule 4 accepts the `<>` guard around the division.
+        assert!(crate::rules::rule4::check(&program).violations.is_empty());
+    }
+
+    #[test]
+    fn comments_are_kept_and_do_not_become_code() {
+        let src = "; @PlausibilityCheck speed range\nLD HMI_Speed ; from panel\nST Motor_Speed\n(* reset\n   the counter *)\n\
+                   L 0; // clear\nT Count;\n// done\n";
+        let program = parse_il_from_str(src).unwrap();
+        let stmts = &program.functions[0].statements;
+        let comments: Vec<(&str, usize)> = stmts
+            .iter()
+            .filter_map(|s| match s {
+                Statement::Comment { text, line } => Some((text.as_str(), *line)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            comments,
+            vec![("; @PlausibilityCheck speed range", 1), ("(* reset\nthe counter *)", 4), ("// done", 8)]
+        );
+        let targets: Vec<String> = stmts
+            .iter()
+            .filter_map(|s| match s {
+                Statement::Assign { target, line, .. } => Some(format!("{}@{}", target.name().unwrap(), line)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(targets, vec!["Motor_Speed@3", "Count@7"]);
+    }
+}
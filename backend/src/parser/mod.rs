@@ -2,14 +2,62 @@
 //! function dispatches to the appropriate frontend based on file
 //! extension. Both SCL (Structured Text) and PLCOpen XML are supported.
 
+use std::fmt;
 use std::path::Path;
 
-use crate::ast::Program;
+use crate::ast::{FunctionKind, Program};
 
 pub mod scl;
 pub mod plcopen;
 pub mod il;
 
+/// A parse failure tied to a (1-based) source line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Classify an organization block by the number in its name (`OB1`,
+/// `"OB100_Startup"`, `Main_OB82`). The number is read in full, so `OB121`
+/// is never taken for `OB1`; names without a known OB number fall back to
+/// the generic [`FunctionKind::OB`].
+pub fn ob_kind(name: &str) -> FunctionKind {
+    match ob_number(name) {
+        Some(1) => FunctionKind::OB1,
+        Some(82) => FunctionKind::OB82,
+        Some(83) => FunctionKind::OB83,
+        Some(85) => FunctionKind::OB85,
+        Some(86) => FunctionKind::OB86,
+        Some(100) => FunctionKind::OB100,
+        Some(121) => FunctionKind::OB121,
+        _ => FunctionKind::OB,
+    }
+}
+
+/// First `OB<digits>` in `name` that is not the tail of a longer word
+/// (`JOB12` does not count).
+pub fn ob_number(name: &str) -> Option<u32> {
+    let up = name.to_ascii_uppercase();
+    let mut from = 0;
+    while let Some(pos) = up[from..].find("OB") {
+        let start = from + pos;
+        let at_word_start = !up[..start].ends_with(|c: char| c.is_ascii_alphabetic());
+        let digits: String = up[start + 2..].chars().take_while(|c| c.is_ascii_digit()).collect();
+        if at_word_start && !digits.is_empty() {
+            return digits.parse().ok();
+        }
+        from = start + 2;
+    }
+    None
+}
+
 /// Parse a PLC source file into a [`Program`]. The file extension
 /// determines which frontend to use:
 /// - `.scl`, `.st`, `.sclsrc`  -> SCL parser
@@ -48,4 +96,235 @@ pub fn parse_file_from_str(source_code: &str, file_name: &str) -> Result<Program
             other
         )),
     }
-}
\ No newline at end of file
+}
+
+/// Like [`parse_file_from_str`], but frontends that can recover (currently
+/// SCL) return whatever parsed together with per-block errors. `Err` is only
+/// returned when nothing usable could be produced.
+pub fn parse_file_partial_from_str(
+    source_code: &str,
+    file_name: &str,
+) -> Result<(Program, Vec<ParseError>), String> {
+    let ext = Path::new(file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+
+    match ext.as_str() {
+        "scl" | "st" | "sclsrc" => Ok(scl::parse_scl_partial(source_code)),
+        _ => parse_file_from_str(source_code, file_name).map(|p| (p, Vec::new())),
+    }
+}
+
+/// Parse a single source without running any rules. Unlike
+/// [`parse_file_partial_from_str`] this is strict: the first block that fails
+/// to parse is reported, so tooling never sees a silently truncated AST.
+pub fn parse_only(source_code: &str, file_name: &str) -> Result<Program, ParseError> {
+    match parse_file_partial_from_str(source_code, file_name) {
+        Ok((_, errors)) if !errors.is_empty() => Err(errors[0].clone()),
+        Ok((program, _)) => Ok(program),
+        Err(message) => Err(ParseError { line: 0, message }),
+    }
+}
+
+/// Parse every `(file name, source)` pair of a project and merge all blocks
+/// into one [`Program`], so presence checks (OB100, OB82/86/121, ...) see
+/// the whole project rather than a single file. When two files define a
+/// block with the same name the first one is kept and the second is
+/// reported as an error. Errors from all files are collected; messages are
+/// prefixed with the file they came from. Lines stay local to each block's
+/// file; use [`merge_project`] for one numbering across the files.
+pub fn parse_project(files: &[(String, String)]) -> Result<Program, Vec<ParseError>> {
+    let merged = merge(files, false);
+    if merged.errors.is_empty() {
+        Ok(merged.program)
+    } else {
+        Err(merged
+            .errors
+            .into_iter()
+            .map(|(file, e)| ParseError { line: e.line, message: format!("{}: {}", file, e.message) })
+            .collect())
+    }
+}
+
+/// A project parsed file by file and merged into one program whose lines
+/// are numbered as if the files had been concatenated in order.
+#[derive(Debug, Clone)]
+pub struct MergedProject {
+    pub program: Program,
+    /// The sources concatenated in the merged numbering, for rules and
+    /// reporters that read source lines.
+    pub source: String,
+    /// `(file name, first merged line)` for each file, in order.
+    pub files: Vec<(String, usize)>,
+    /// Parse errors per file; their lines are local to the file.
+    pub errors: Vec<(String, ParseError)>,
+}
+
+impl MergedProject {
+    /// The file a merged line belongs to and the line within that file.
+    pub fn locate(&self, line: usize) -> Option<(&str, usize)> {
+        self.files
+            .iter()
+            .rev()
+            .find(|(_, first)| *first <= line)
+            .map(|(name, first)| (name.as_str(), line - first + 1))
+    }
+
+    /// The first merged line of each file, in order, for
+    /// [`AnalysisContext::file_starts`](crate::rules::AnalysisContext::file_starts).
+    pub fn file_starts(&self) -> Vec<usize> {
+        self.files.iter().map(|(_, first)| *first).collect()
+    }
+}
+
+/// Like [`parse_project`], but keeps whatever parsed next to the errors so
+/// one broken file does not stop the rest from being checked, and numbers
+/// the lines of all files as in [`MergedProject::source`].
+pub fn merge_project(files: &[(String, String)]) -> MergedProject {
+    merge(files, true)
+}
+
+/// Parse and merge `files`, shifting each file's lines past the files
+/// before it if `renumber` is set.
+fn merge(files: &[(String, String)], renumber: bool) -> MergedProject {
+    let mut merged = MergedProject {
+        program: Program { functions: Vec::new() },
+        source: String::new(),
+        files: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    let mut offset = 0;
+    for (name, source) in files {
+        merged.files.push((name.clone(), offset + 1));
+        merged.source.push_str(source);
+        if !source.is_empty() && !source.ends_with('\n') {
+            merged.source.push('\n');
+        }
+        let shift = if renumber { offset } else { 0 };
+        offset += source.lines().count();
+        match parse_file_partial_from_str(source, name) {
+            Ok((mut program, file_errors)) => {
+                merged.errors.extend(file_errors.into_iter().map(|e| (name.clone(), e)));
+                program.shift_lines(shift);
+                for f in program.functions {
+                    if merged.program.functions.iter().any(|g| g.name.eq_ignore_ascii_case(&f.name)) {
+                        let message = format!("duplicate block '{}' ignored, keeping the first definition", f.name);
+                        merged.errors.push((name.clone(), ParseError { line: f.line - shift, message }));
+                        continue;
+                    }
+                    merged.program.functions.push(f);
+                }
+            }
+            Err(e) => merged.errors.push((name.clone(), ParseError { line: 0, message: e })),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, src: &str) -> (String, String) {
+        (name.to_string(), src.to_string())
+    }
+
+    #[test]
+    fn merges_blocks_and_keeps_first_duplicate() {
+        let files = vec![
+            file("main.scl", "ORGANIZATION_BLOCK OB1\nBEGIN\n    x := 1;\nEND_ORGANIZATION_BLOCK\n"),
+            file("startup.scl", "ORGANIZATION_BLOCK OB100\nBEGIN\n    y := 0;\nEND_ORGANIZATION_BLOCK\n"),
+            file("copy.scl", "\n\nORGANIZATION_BLOCK OB1\nBEGIN\n    z := 2;\nEND_ORGANIZATION_BLOCK\n"),
+        ];
+        let merged = merge_project(&files);
+        let names: Vec<&str> = merged.program.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["OB1", "OB100"]);
+        assert_eq!(merged.program.functions[0].line, 1);
+        assert_eq!(merged.errors.len(), 1);
+        let (file, e) = &merged.errors[0];
+        assert_eq!((file.as_str(), e.line), ("copy.scl", 3));
+        assert_eq!(e.message, "duplicate block 'OB1' ignored, keeping the first definition");
+
+        let errors = parse_project(&files).unwrap_err();
+        assert_eq!(errors[0].message, "copy.scl: duplicate block 'OB1' ignored, keeping the first definition");
+    }
+
+    #[test]
+    fn merged_lines_map_back_to_their_file() {
+        let files = vec![
+            file("main.scl", "ORGANIZATION_BLOCK OB1\nBEGIN\n    x := 1;\nEND_ORGANIZATION_BLOCK"),
+            file("broken.scl", "FUNCTION FC_A\nBEGIN\n    y := ;\nEND_FUNCTION\n"),
+            file("fb.scl", "\nFUNCTION_BLOCK FB_A\nBEGIN\n    z := 2;\nEND_FUNCTION_BLOCK\n"),
+        ];
+        let merged = merge_project(&files);
+        let blocks: Vec<(&str, usize)> = merged.program.functions.iter().map(|f| (f.name.as_str(), f.line)).collect();
+        assert_eq!(blocks, vec![("OB1", 1), ("FB_A", 10)]);
+        assert_eq!(merged.program.functions[1].statements[0].line(), 12);
+        assert_eq!(merged.source.lines().nth(11), Some("    z := 2;"));
+        assert_eq!(merged.locate(12), Some(("fb.scl", 4)));
+        assert_eq!(merged.locate(3), Some(("main.scl", 3)));
+        assert_eq!(merged.errors.len(), 1);
+        assert_eq!(merged.errors[0].0, "broken.scl");
+
+        let program = parse_project(&[files[0].clone(), files[2].clone()]).unwrap();
+        let blocks: Vec<(&str, usize)> = program.functions.iter().map(|f| (f.name.as_str(), f.line)).collect();
+        assert_eq!(blocks, vec![("OB1", 1), ("FB_A", 2)]);
+    }
+
+    #[test]
+    fn ob_numbers_are_matched_exactly() {
+        assert_eq!(ob_kind("OB1"), FunctionKind::OB1);
+        assert_eq!(ob_kind("OB100"), FunctionKind::OB100);
+        assert_eq!(ob_kind("OB121"), FunctionKind::OB121);
+        assert_eq!(ob_kind("\"OB121_ProgError\""), FunctionKind::OB121);
+        assert_eq!(ob_kind("Main_ob1"), FunctionKind::OB1);
+        assert_eq!(ob_kind("OB10"), FunctionKind::OB);
+        assert_eq!(ob_kind("JOB1"), FunctionKind::OB);
+        assert_eq!(ob_kind("Cyclic"), FunctionKind::OB);
+    }
+
+    #[test]
+    fn errors_name_their_file() {
+        let files = vec![
+            file("ok.scl", "ORGANIZATION_BLOCK OB1\nBEGIN\n    x := 1;\nEND_ORGANIZATION_BLOCK\n"),
+            file("notes.txt", "hello"),
+        ];
+        let errors = parse_project(&files).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.starts_with("notes.txt: "));
+    }
+
+    #[test]
+    fn parse_only_is_strict_and_dumps_the_ast() {
+        let program = parse_only("FUNCTION FC_A\nBEGIN\n    x := T[i];\nEND_FUNCTION\n", "a.scl").unwrap();
+        assert_eq!(program.functions[0].name, "FC_A");
+        #[cfg(feature = "ast-serde")]
+        {
+            let json: serde_json::Value = serde_json::from_str(&program.to_debug_json()).unwrap();
+            assert!(json["functions"][0]["statements"][0]["Assign"]["value"]["Index"].is_object());
+        }
+
+        let broken = "FUNCTION FC_A\nBEGIN\n    x := 1;\nEND_FUNCTION\nFUNCTION FC_B\nBEGIN\n    y := ;\nEND_FUNCTION\n";
+        assert!(parse_only(broken, "a.scl").is_err());
+        assert_eq!(parse_only("", "a.txt").unwrap_err().line, 0);
+    }
+
+    #[test]
+    fn empty_and_comment_only_sources_are_empty_programs() {
+        let sources = [
+            ("a.scl", ""),
+            ("a.scl", "  \n\t\n"),
+            ("a.scl", "// nothing yet\n(* header\n   comment *)\n"),
+            ("a.awl", "// nothing yet\n\n"),
+            ("a.xml", ""),
+        ];
+        for (name, src) in sources {
+            assert!(parse_file_from_str(src, name).unwrap().functions.is_empty(), "{} {:?}", name, src);
+            let (program, errors) = parse_file_partial_from_str(src, name).unwrap();
+            assert!(program.functions.is_empty() && errors.is_empty(), "{} {:?}", name, src);
+        }
+    }
+}
@@ -45,19 +45,7 @@ pub fn parse_plcopen_from_str(src: &str) -> Result<Program, String> {
                             if v.contains("functionblock") {
                                 kind = FunctionKind::FB;
                             } else if v.contains("program") {
-                                if fname.to_uppercase().contains("OB100") {
-                                    kind = FunctionKind::OB100;
-                                } else if fname.to_uppercase().contains("OB1") {
-                                    kind = FunctionKind::OB1;
-                                } else if fname.to_uppercase().contains("OB86") {
-                                    kind = FunctionKind::OB86;
-                                } else if fname.to_uppercase().contains("OB82") {
-                                    kind = FunctionKind::OB82;
-                                } else if fname.to_uppercase().contains("OB121") {
-                                    kind = FunctionKind::OB121;
-                                } else {
-                                    kind = FunctionKind::OB;
-                                }
+                                kind = super::ob_kind(&fname);
                             } else {
                                 kind = FunctionKind::FC;
                             }
@@ -68,6 +56,8 @@ pub fn parse_plcopen_from_str(src: &str) -> Result<Program, String> {
                         kind,
                         statements: vec![],
                         line: 0, // Line numbers are less precise in XML
+                        regions: vec![],
+                        declarations: vec![],
                     });
                 } else if e.name().as_ref().eq_ignore_ascii_case(b"block") {
                     if let Some(f) = current_func.as_mut() {
@@ -89,7 +79,7 @@ pub fn parse_plcopen_from_str(src: &str) -> Result<Program, String> {
                     if let Some((var, val)) = read_variable_assignment(&mut reader, e)? {
                         if let Some(f) = current_func.as_mut() {
                             f.statements.push(Statement::Assign {
-                                target: Expression::Identifier(var),
+                                target: Expression::path(&var, 0),
                                 value: val,
                                 line: 0,
                             });
@@ -97,11 +87,9 @@ pub fn parse_plcopen_from_str(src: &str) -> Result<Program, String> {
                     }
                 }
             }
-            Ok(Event::End(e)) => {
-                if e.name().as_ref().eq_ignore_ascii_case(b"pou") {
-                    if let Some(f) = current_func.take() {
-                        program.functions.push(f);
-                    }
+            Ok(Event::End(e)) if e.name().as_ref().eq_ignore_ascii_case(b"pou") => {
+                if let Some(f) = current_func.take() {
+                    program.functions.push(f);
                 }
             }
              Err(e) => {
@@ -134,13 +122,9 @@ fn read_variable_assignment(
             Ok(Event::Start(e)) if e.name().as_ref().eq_ignore_ascii_case(b"value") => {
                 value_text = read_element_text(reader)?;
             }
-            Ok(Event::End(_)) => {
-                // This end tag could be for <value> or <variable>
-                // We break after finding a value to handle nested structures correctly.
-                if value_text.is_some() {
-                    break;
-                }
-            }
+            // This end tag could be for <value> or <variable>
+            // We break after finding a value to handle nested structures correctly.
+            Ok(Event::End(_)) if value_text.is_some() => break,
             Ok(Event::Eof) => break,
             Err(e) => return Err(format!("XML parse error: {e}")),
             _ => {}
@@ -156,7 +140,7 @@ fn read_variable_assignment(
                 if let Ok(n) = val.parse::<i64>() {
                     Expression::NumberLiteral(n, 0)
                 } else {
-                    Expression::Identifier(val)
+                    Expression::path(&val, 0)
                 }
             }
         };
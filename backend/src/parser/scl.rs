@@ -5,14 +5,16 @@
 use std::fs;
 use std::path::Path;
 use pest::Parser;
+use pest::error::LineColLocation;
 use pest::iterators::{Pair, Pairs};
 use pest::pratt_parser::{Assoc, Op, PrattParser};
 use lazy_static::lazy_static;
 
-use crate::ast::{Program, Function, FunctionKind, Statement, Expression, BinOp, UnaryOp};
+use crate::ast::{Program, Function, FunctionKind, Region, Statement, Expression, BinOp, UnaryOp, Declaration, DataType, VarSection};
+use super::ParseError;
 
 #[derive(pest_derive::Parser)]
-#[grammar = r"C:\Users\z005653n\Desktop\plc_practices_checker-master\backend\src\parser\scl.pest"]
+#[grammar = "parser/scl.pest"]
 struct SCLParser;
 
 // Operator precedence parser for expressions.
@@ -25,7 +27,7 @@ lazy_static! {
             .op(Op::infix(COMPARISON_OP, Assoc::Left))
             .op(Op::infix(ADD, Assoc::Left) | Op::infix(SUB, Assoc::Left))
             .op(Op::infix(MUL, Assoc::Left) | Op::infix(DIV, Assoc::Left))
-            .op(Op::prefix(NOT))
+            .op(Op::prefix(NOT) | Op::prefix(NEG))
     };
 }
 
@@ -36,6 +38,62 @@ pub fn parse_scl(path: &Path) -> Result<Program, String> {
 
 pub fn parse_scl_from_str(src: &str) -> Result<Program, String> {
     let pairs = SCLParser::parse(Rule::program, src).map_err(|e| e.to_string())?;
+    check_numbers(&pairs).map_err(|e| e.to_string())?;
+    Ok(Program { functions: collect_functions(pairs) })
+}
+
+/// Parse SCL source one block at a time. A block that fails to parse is
+/// reported as a [`ParseError`] (with its absolute line) while the blocks
+/// around it are still returned, so one bad FB doesn't hide the rest.
+pub fn parse_scl_partial(src: &str) -> (Program, Vec<ParseError>) {
+    let mut functions = Vec::new();
+    let mut errors = Vec::new();
+
+    for chunk in split_blocks(src) {
+        // Pad with newlines so pest reports lines relative to the whole file.
+        let padded = format!("{}{}", "\n".repeat(chunk.first_line - 1), chunk.text);
+        match SCLParser::parse(Rule::program, &padded) {
+            Ok(pairs) => match check_numbers(&pairs) {
+                Ok(()) => functions.extend(collect_functions(pairs)),
+                Err(e) => errors.push(ParseError {
+                    message: match chunk.header {
+                        Some(h) => format!("in block '{}': {}", h, e.message),
+                        None => e.message,
+                    },
+                    ..e
+                }),
+            },
+            Err(e) => {
+                let line = match e.line_col {
+                    LineColLocation::Pos((l, _)) | LineColLocation::Span((l, _), _) => l,
+                };
+                let message = match chunk.header {
+                    Some(h) => format!("in block '{}': {}", h, e.variant.message()),
+                    None => e.variant.message().to_string(),
+                };
+                errors.push(ParseError { line, message });
+            }
+        }
+    }
+
+    (Program { functions }, errors)
+}
+
+/// Reject integer literals that do not fit an `i64`, so building the
+/// expressions cannot fail.
+fn check_numbers(pairs: &Pairs<Rule>) -> Result<(), ParseError> {
+    for pair in pairs.clone().flatten().filter(|p| p.as_rule() == Rule::number) {
+        if pair.as_str().parse::<i64>().is_err() {
+            return Err(ParseError {
+                line: pair.as_span().start_pos().line_col().0,
+                message: format!("number literal {} is out of range", pair.as_str()),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn collect_functions(pairs: Pairs<Rule>) -> Vec<Function> {
     let mut functions = Vec::new();
 
     for pair in pairs {
@@ -43,7 +101,11 @@ pub fn parse_scl_from_str(src: &str) -> Result<Program, String> {
             for decl in pair.into_inner() {
                 if matches!(
                     decl.as_rule(),
-                    Rule::program_block | Rule::function_block | Rule::function | Rule::organization_block
+                    Rule::program_block
+                        | Rule::function_block
+                        | Rule::function
+                        | Rule::organization_block
+                        | Rule::data_block
                 ) {
                     functions.push(build_function(decl));
                 }
@@ -51,7 +113,48 @@ pub fn parse_scl_from_str(src: &str) -> Result<Program, String> {
         }
     }
 
-    Ok(Program { functions })
+    functions
+}
+
+/// A slice of the source starting at a block header (or the preamble
+/// before the first header).
+struct BlockChunk<'a> {
+    first_line: usize,
+    header: Option<&'a str>,
+    text: &'a str,
+}
+
+/// Split `src` at lines that open a PROGRAM/FUNCTION/FUNCTION_BLOCK/
+/// ORGANIZATION_BLOCK/DATA_BLOCK. This is a line-based pre-pass, so a header hidden in a
+/// multi-line comment may start a spurious chunk; that only costs precision
+/// in the error report, not correctness of the blocks that do parse.
+fn split_blocks(src: &str) -> Vec<BlockChunk<'_>> {
+    const HEADERS: &[&str] = &["ORGANIZATION_BLOCK", "FUNCTION_BLOCK", "FUNCTION", "PROGRAM", "DATA_BLOCK"];
+
+    let mut starts: Vec<(usize, usize, &str)> = Vec::new(); // (byte offset, line, header)
+    let mut offset = 0;
+    for (i, line) in src.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        let upper = trimmed.to_ascii_uppercase();
+        let is_header = HEADERS.iter().any(|h| {
+            upper.starts_with(h) && upper[h.len()..].starts_with(|c: char| c.is_whitespace())
+        });
+        if is_header {
+            starts.push((offset, i + 1, trimmed));
+        }
+        offset += line.len();
+    }
+
+    let mut chunks = Vec::new();
+    let first = starts.first().map(|s| s.0).unwrap_or(src.len());
+    if first > 0 || starts.is_empty() {
+        chunks.push(BlockChunk { first_line: 1, header: None, text: &src[..first] });
+    }
+    for (idx, &(start, line, header)) in starts.iter().enumerate() {
+        let end = starts.get(idx + 1).map(|s| s.0).unwrap_or(src.len());
+        chunks.push(BlockChunk { first_line: line, header: Some(header), text: &src[start..end] });
+    }
+    chunks
 }
 
 fn build_function(pair: Pair<Rule>) -> Function {
@@ -60,54 +163,121 @@ fn build_function(pair: Pair<Rule>) -> Function {
     // **FIX for E0382**: Get the rule *before* consuming the pair with `into_inner()`.
     let rule = pair.as_rule();
     
-    let mut inner = pair.into_inner();
-    let name_pair = inner.next().unwrap();
-    let name = name_pair.as_str().to_string();
-    let statements = inner.next().map(build_statements).unwrap_or_default();
+    let regions = pair.clone().into_inner().flatten()
+        .filter(|p| p.as_rule() == Rule::region)
+        .map(build_region)
+        .collect();
+
+    let mut name = String::new();
+    let mut statements = Vec::new();
+    let mut declarations = Vec::new();
+    for part in pair.into_inner() {
+        match part.as_rule() {
+            Rule::identifier => name = part.as_str().trim_matches('"').to_string(),
+            Rule::var_section => {
+                let mut inner = part.into_inner();
+                let section = inner.next().map_or(VarSection::Var, |kw| VarSection::from_keyword(kw.as_str()));
+                declarations.extend(
+                    inner.filter(|p| p.as_rule() == Rule::var_decl).flat_map(|d| build_declarations(d, section)),
+                );
+            }
+            Rule::statement_list => statements = build_statements(part),
+            _ => {}
+        }
+    }
 
     // Use the saved `rule` to determine the function kind.
     let kind = match rule {
         Rule::program_block => FunctionKind::Program,
         Rule::function_block => FunctionKind::FB,
         Rule::function => FunctionKind::FC,
-        Rule::organization_block => {
-            let uc_name = name.to_uppercase();
-            if uc_name.contains("OB100") { FunctionKind::OB100 }
-            else if uc_name.contains("OB1") { FunctionKind::OB1 }
-            else if uc_name.contains("OB86") { FunctionKind::OB86 }
-            else if uc_name.contains("OB82") { FunctionKind::OB82 }
-            else if uc_name.contains("OB121") { FunctionKind::OB121 }
-            else { FunctionKind::OB }
-        },
+        Rule::data_block => FunctionKind::DataBlock,
+        Rule::organization_block => super::ob_kind(&name),
         _ => unreachable!(),
     };
 
-    Function { name, kind, statements, line }
+    Function { name, kind, statements, line, regions, declarations }
+}
+
+/// `a, b : T;` declares each name with the same type.
+fn build_declarations(pair: Pair<Rule>, section: VarSection) -> Vec<Declaration> {
+    let line = pair.as_span().start_pos().line_col().0;
+    let mut names = Vec::new();
+    let mut data_type = None;
+    for part in pair.into_inner() {
+        match part.as_rule() {
+            Rule::identifier => names.push(part.as_str().to_string()),
+            Rule::var_init => {}
+            _ => data_type = Some(build_type(part)),
+        }
+    }
+    let data_type = data_type.unwrap();
+    names.into_iter().map(|name| Declaration { name, data_type: data_type.clone(), section, line }).collect()
+}
+
+fn build_type(pair: Pair<Rule>) -> DataType {
+    match pair.as_rule() {
+        Rule::string_type => DataType::String(pair.into_inner().next().and_then(|n| n.as_str().parse().ok())),
+        Rule::array_type => {
+            let mut dims = Vec::new();
+            let mut element = DataType::Named(String::new());
+            for part in pair.into_inner() {
+                if part.as_rule() == Rule::array_range {
+                    let mut bounds = part.into_inner().map(|b| b.as_str().parse::<i64>().ok());
+                    let (lo, hi) = (bounds.next().flatten(), bounds.next().flatten());
+                    dims.push(lo.zip(hi));
+                } else {
+                    element = build_type(part);
+                }
+            }
+            DataType::Array { dims, element: Box::new(element) }
+        }
+        _ => DataType::Named(pair.as_str().trim_matches('"').to_string()),
+    }
+}
+
+fn build_region(pair: Pair<Rule>) -> Region {
+    let (line, _) = pair.as_span().start_pos().line_col();
+    // The span may run into trailing whitespace; count lines up to the
+    // last token instead.
+    let end_line = line + pair.as_str().trim_end().matches('\n').count();
+    let raw = pair.into_inner().next().unwrap().into_inner().next().unwrap().as_str();
+    let name = raw.split("//").next().unwrap_or_default().trim().trim_matches('"').to_string();
+    Region { name, line, end_line }
 }
 
+/// Regions only label lines, so their statements are spliced into the
+/// enclosing list.
 fn build_statements(pair: Pair<Rule>) -> Vec<Statement> {
-    pair.into_inner().map(build_statement).collect()
+    let mut out = Vec::new();
+    for st in pair.into_inner() {
+        if st.as_rule() == Rule::region {
+            out.extend(build_statements(st.into_inner().nth(1).unwrap()));
+        } else {
+            out.push(build_statement(st));
+        }
+    }
+    out
 }
 
 fn build_statement(pair: Pair<Rule>) -> Statement {
     let line = pair.as_span().start_pos().line_col().0;
-    let inner_pair = pair.into_inner().next().unwrap();
-    match inner_pair.as_rule() {
+    match pair.as_rule() {
         Rule::assignment_statement => {
-            let mut inner = inner_pair.into_inner();
+            let mut inner = pair.into_inner();
             let target = build_expr_tree(inner.next().unwrap().into_inner());
             let value = build_expr_tree(inner.next().unwrap().into_inner());
             Statement::Assign { target, value, line }
         }
         Rule::if_statement => {
-            let mut inner = inner_pair.into_inner();
-            let condition = build_expr_tree(inner.next().unwrap().into_inner());
+            let mut inner = pair.into_inner();
+            let condition = build_condition(inner.next().unwrap());
             let then_branch = build_statements(inner.next().unwrap());
             let else_branch = build_else_chain(inner);
             Statement::IfStmt { condition, then_branch, else_branch, line }
         }
         Rule::case_statement => {
-            let mut inner = inner_pair.into_inner();
+            let mut inner = pair.into_inner();
             let expression = Box::new(build_expr_tree(inner.next().unwrap().into_inner()));
             let mut cases = Vec::new();
             let mut else_branch = Vec::new();
@@ -121,7 +291,7 @@ fn build_statement(pair: Pair<Rule>) -> Statement {
                         let body = build_statements(case_inner.next().unwrap());
                         cases.push((labels, body));
                     }
-                    Rule::ELSE => {
+                    Rule::else_clause => {
                         else_branch = build_statements(case_pair.into_inner().next().unwrap())
                     }
                     _ => {}
@@ -129,16 +299,42 @@ fn build_statement(pair: Pair<Rule>) -> Statement {
             }
             Statement::CaseStmt { expression, cases, else_branch, line }
         }
-        Rule::call_statement => {
-            let call_expr = build_expr_tree(inner_pair.into_inner());
-            if let Expression::FuncCall { name, args, line } = call_expr {
-                let mapped_args = args.into_iter().map(|arg| ("".to_string(), arg)).collect();
-                Statement::Call { name, args: mapped_args, line }
-            } else {
-                unreachable!("call_statement did not contain a FuncCall expression")
+        Rule::return_statement => Statement::Return { line },
+        Rule::exit_statement => Statement::Exit { line },
+        Rule::continue_statement => Statement::Continue { line },
+        Rule::while_statement => {
+            let mut inner = pair.into_inner();
+            let condition = build_condition(inner.next().unwrap());
+            let body = build_statements(inner.next().unwrap());
+            Statement::WhileStmt { condition, body, line }
+        }
+        Rule::for_statement => {
+            let mut inner = pair.into_inner();
+            let variable = inner.next().unwrap().as_str().to_string();
+            let start = build_expr_tree(inner.next().unwrap().into_inner());
+            let end = build_expr_tree(inner.next().unwrap().into_inner());
+            let mut step = None;
+            let mut body = Vec::new();
+            for part in inner {
+                match part.as_rule() {
+                    Rule::for_step => step = Some(build_expr_tree(part.into_inner().next().unwrap().into_inner())),
+                    Rule::statement_list => body = build_statements(part),
+                    _ => {}
+                }
             }
+            Statement::ForStmt { variable, start, end, step, body, line }
+        }
+        Rule::goto_statement => Statement::Goto { label: pair.into_inner().next().unwrap().as_str().to_string(), line },
+        Rule::label_statement => Statement::Label { name: pair.into_inner().next().unwrap().as_str().to_string(), line },
+        Rule::call_statement => {
+            // Built from the call itself rather than the expression tree so
+            // parameter names (`PT := ...`) are kept.
+            let mut inner = pair.into_inner().next().unwrap().into_inner();
+            let name = callee_name(inner.next().unwrap().as_str());
+            let args = inner.next().map(build_args).unwrap_or_default();
+            Statement::Call { name, args, line }
         }
-        _ => unreachable!("Unexpected statement rule: {:?}", inner_pair.as_rule()),
+        _ => unreachable!("Unexpected statement rule: {:?}", pair.as_rule()),
     }
 }
 
@@ -146,10 +342,10 @@ fn build_statement(pair: Pair<Rule>) -> Statement {
 fn build_else_chain(mut pairs: Pairs<Rule>) -> Vec<Statement> {
     if let Some(next_part) = pairs.next() {
         match next_part.as_rule() {
-            Rule::ELSIF => {
+            Rule::elsif_clause => {
                 let elseif_line = next_part.as_span().start_pos().line_col().0;
                 let mut elseif_parts = next_part.into_inner();
-                let elseif_cond = build_expr_tree(elseif_parts.next().unwrap().into_inner());
+                let elseif_cond = build_condition(elseif_parts.next().unwrap());
                 let elseif_then = build_statements(elseif_parts.next().unwrap());
                 // The rest of the original pairs form the `else` for this `elsif`.
                 let nested_else = build_else_chain(pairs);
@@ -161,9 +357,12 @@ fn build_else_chain(mut pairs: Pairs<Rule>) -> Vec<Statement> {
                     line: elseif_line,
                 }]
             }
-            Rule::ELSE => {
-                // This is the final else, just build its statements.
-                build_statements(next_part.into_inner().next().unwrap())
+            Rule::else_clause => {
+                // This is the final else, just build its statements. An
+                // empty ELSE keeps a marker so it can be told from no ELSE.
+                let line = next_part.as_span().start_pos().line_col().0;
+                let stmts = build_statements(next_part.into_inner().next().unwrap());
+                if stmts.is_empty() { vec![Statement::ElseMarker { line }] } else { stmts }
             }
             _ => vec![], // Should not happen with a valid grammar.
         }
@@ -174,21 +373,56 @@ fn build_else_chain(mut pairs: Pairs<Rule>) -> Vec<Statement> {
 }
 
 
-fn build_args(pair: Pair<Rule>) -> Vec<Expression> {
-    pair.into_inner().map(|arg_pair| {
-        let inner = arg_pair.into_inner().next().unwrap();
-        build_expr_tree(inner.into_inner())
+/// Call arguments as `(parameter, value)`; positional arguments have an
+/// empty parameter name.
+fn build_args(pair: Pair<Rule>) -> Vec<(String, Expression)> {
+    pair.into_inner().map(|arg_pair| match arg_pair.as_rule() {
+        Rule::named_arg => {
+            let mut inner = arg_pair.into_inner();
+            let param = inner.next().unwrap().as_str().to_string();
+            (param, build_expr_tree(inner.next().unwrap().into_inner()))
+        }
+        _ => (String::new(), build_expr_tree(arg_pair.into_inner())),
     }).collect()
 }
 
+/// Callee text without the symbol quotes and the `#` local prefix, so
+/// `"Inst".Run` and `#Inst.Run` both become `Inst.Run`.
+fn callee_name(text: &str) -> String {
+    text.trim_start_matches('#').replace('"', "")
+}
+
+/// An IF/ELSIF/WHILE condition. A mistyped `x := y` becomes a
+/// `BinOp::Assign` node so rules can report it.
+fn build_condition(pair: Pair<Rule>) -> Expression {
+    let mut inner = pair.into_inner();
+    let (left, line, _) = build_spanned(inner.next().unwrap().into_inner());
+    match inner.next() {
+        Some(value) => {
+            let (right, _, end_line) = build_spanned(value.into_inner());
+            Expression::BinaryOp { op: BinOp::Assign, left: Box::new(left), right: Box::new(right), line, end_line }
+        }
+        None => left,
+    }
+}
+
 fn build_expr_tree(pairs: Pairs<Rule>) -> Expression {
+    build_spanned(pairs).0
+}
+
+/// The expression with the first and last line it covers, which compound
+/// expressions take from their operands rather than from the operator.
+fn build_spanned(pairs: Pairs<Rule>) -> (Expression, usize, usize) {
     PRATT_PARSER
         .map_primary(|primary| {
-            let line = primary.as_span().start_pos().line_col().0;
-            match primary.as_rule() {
-                Rule::number => Expression::NumberLiteral(primary.as_str().parse().unwrap(), line),
+            let span = primary.as_span();
+            let (line, end_line) = (span.start_pos().line_col().0, span.end_pos().line_col().0);
+            let expr = match primary.as_rule() {
+                // In range: see `check_numbers`.
+                Rule::number => Expression::NumberLiteral(primary.as_str().parse().unwrap_or_default(), line),
                 Rule::boolean => Expression::BoolLiteral(primary.as_str().eq_ignore_ascii_case("TRUE"), line),
-                Rule::identifier | Rule::memory_identifier => Expression::Identifier(primary.as_str().to_string()),
+                Rule::identifier => Expression::path(primary.as_str(), line),
+                Rule::memory_identifier => Expression::Identifier(primary.as_str().to_string()),
                 Rule::string => {
                     let raw = primary.as_str();
                     let inner = &raw[1..raw.len() - 1]; // Trim quotes
@@ -196,30 +430,37 @@ fn build_expr_tree(pairs: Pairs<Rule>) -> Expression {
                 }
                 Rule::array_access => {
                     let mut inner = primary.into_inner();
-                    let base = Box::new(Expression::Identifier(inner.next().unwrap().as_str().to_string()));
+                    let base = Box::new(Expression::path(inner.next().unwrap().as_str(), line));
                     let index = Box::new(build_expr_tree(inner.next().unwrap().into_inner()));
-                    Expression::Index { base, index, line }
+                    Expression::Index { base, index, line, end_line }
                 }
                 Rule::function_call => {
                     let mut inner = primary.into_inner();
-                    let name = inner.next().unwrap().as_str().to_string();
-                    let args = inner.next().map(build_args).unwrap_or_default();
-                    Expression::FuncCall { name, args, line }
+                    let name = callee_name(inner.next().unwrap().as_str());
+                    // Function values in expressions are positional in the AST.
+                    let args = inner.next().map(build_args).unwrap_or_default().into_iter().map(|(_, a)| a).collect();
+                    Expression::FuncCall { name, args, line, end_line }
                 }
-                Rule::expression => build_expr_tree(primary.into_inner()), // For parentheses
+                Rule::expression => return build_spanned(primary.into_inner()), // For parentheses
                 _ => unreachable!("Unexpected primary rule: {:?}", primary.as_rule()),
-            }
+            };
+            (expr, line, end_line)
         })
-        .map_prefix(|op, rhs| {
+        .map_prefix(|op, (rhs, _, end_line)| {
             let line = op.as_span().start_pos().line_col().0;
             let op_type = match op.as_rule() {
                 Rule::NOT => UnaryOp::Not,
+                Rule::NEG => UnaryOp::Neg,
                 _ => unreachable!(),
             };
-            Expression::UnaryOp { op: op_type, expr: Box::new(rhs), line }
+            let expr = match (op_type, rhs) {
+                // `-5` is a literal, so range checks still see a number.
+                (UnaryOp::Neg, Expression::NumberLiteral(n, l)) => Expression::NumberLiteral(-n, l),
+                (op, rhs) => Expression::UnaryOp { op, expr: Box::new(rhs), line, end_line },
+            };
+            (expr, line, end_line)
         })
-        .map_infix(|lhs, op, rhs| {
-            let line = op.as_span().start_pos().line_col().0;
+        .map_infix(|(lhs, line, _), op, (rhs, _, end_line)| {
             let op_type = match op.as_rule() {
                 Rule::ADD => BinOp::Add,
                 Rule::SUB => BinOp::Sub,
@@ -238,7 +479,232 @@ fn build_expr_tree(pairs: Pairs<Rule>) -> Expression {
                 Rule::OR => BinOp::Or,
                 _ => unreachable!(),
             };
-            Expression::BinaryOp { op: op_type, left: Box::new(lhs), right: Box::new(rhs), line }
+            (Expression::BinaryOp { op: op_type, left: Box::new(lhs), right: Box::new(rhs), line, end_line }, line, end_line)
         })
         .parse(pairs)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_block_does_not_hide_good_ones() {
+        let src = "FUNCTION_BLOCK FB_Good\nBEGIN\n    x := 1;\nEND_FUNCTION_BLOCK\n\n\
+                   FUNCTION_BLOCK FB_Bad\nBEGIN\n    y := ;\nEND_FUNCTION_BLOCK\n\n\
+                   ORGANIZATION_BLOCK OB1\nBEGIN\n    z := 2;\nEND_ORGANIZATION_BLOCK\n";
+        assert!(parse_scl_from_str(src).is_err());
+
+        let (program, errors) = parse_scl_partial(src);
+        let names: Vec<&str> = program.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["FB_Good", "OB1"]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 8);
+        assert!(errors[0].message.contains("FB_Bad"));
+        // Lines of later blocks stay absolute.
+        assert_eq!(program.functions[1].line, 11);
+    }
+
+    #[test]
+    fn out_of_range_number_is_a_parse_error() {
+        let src = "FUNCTION FC_Big\nBEGIN\n    x := 99999999999999999999;\nEND_FUNCTION\n\
+                   FUNCTION FC_Ok\nBEGIN\n    y := 9223372036854775807;\nEND_FUNCTION\n";
+        let err = parse_scl_from_str(src).unwrap_err();
+        assert_eq!(err, "line 3: number literal 99999999999999999999 is out of range");
+
+        let (program, errors) = parse_scl_partial(src);
+        assert_eq!(program.functions.len(), 1);
+        assert_eq!(errors[0].line, 3);
+        assert!(errors[0].message.starts_with("in block 'FUNCTION FC_Big'"), "{}", errors[0].message);
+    }
+
+    #[test]
+    fn early_return_inside_if() {
+        let src = "FUNCTION FC_Scale\nBEGIN\n    IF Idx > 9 THEN\n        RETURN;\n    END_IF;\n    Return_Code := 0;\nEND_FUNCTION\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let stmts = &program.functions[0].statements;
+        assert!(matches!(&stmts[0], Statement::IfStmt { then_branch, .. }
+            if matches!(then_branch[..], [Statement::Return { line: 4 }])));
+        assert!(stmts[0].early_exit_condition().is_some());
+        assert!(matches!(&stmts[1], Statement::Assign { .. }));
+    }
+
+    #[test]
+    fn loops_with_exit_and_continue() {
+        let src = "FUNCTION FC_Scan\nBEGIN\n    WHILE i < 10 DO\n        IF Done THEN\n            EXIT;\n        END_IF;\n        \
+                   i := i + 1;\n    END_WHILE;\n    FOR j := 0 TO 9 BY 2 DO\n        CONTINUE;\n    END_FOR;\nEND_FUNCTION\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let stmts = &program.functions[0].statements;
+        let Statement::WhileStmt { body, .. } = &stmts[0] else { panic!("expected WHILE") };
+        assert!(body[0].early_exit_condition().is_some());
+        assert!(matches!(body[1], Statement::Assign { line: 7, .. }));
+        let Statement::ForStmt { variable, step, body, .. } = &stmts[1] else { panic!("expected FOR") };
+        assert_eq!(variable, "j");
+        assert!(step.is_some());
+        assert!(matches!(body[..], [Statement::Continue { line: 10 }]));
+    }
+
+    #[test]
+    fn fault_obs_and_data_blocks_get_their_own_kind() {
+        let src = "ORGANIZATION_BLOCK OB83\nBEGIN\nEND_ORGANIZATION_BLOCK\n\
+                   ORGANIZATION_BLOCK OB85\nBEGIN\nEND_ORGANIZATION_BLOCK\n\
+                   ORGANIZATION_BLOCK OB30\nBEGIN\nEND_ORGANIZATION_BLOCK\n\
+                   DATA_BLOCK DB_Recipe\nBEGIN\n    Speed := 100;\nEND_DATA_BLOCK\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let kinds: Vec<FunctionKind> = program.functions.iter().map(|f| f.kind).collect();
+        assert_eq!(kinds, vec![FunctionKind::OB83, FunctionKind::OB85, FunctionKind::OB, FunctionKind::DataBlock]);
+        assert_eq!(program.functions[3].statements.len(), 1);
+    }
+
+    #[test]
+    fn startup_and_error_obs_are_not_taken_for_ob1() {
+        let src = "ORGANIZATION_BLOCK OB121\nBEGIN\nEND_ORGANIZATION_BLOCK\n\
+                   ORGANIZATION_BLOCK OB100\nBEGIN\nEND_ORGANIZATION_BLOCK\n\
+                   ORGANIZATION_BLOCK OB1\nBEGIN\nEND_ORGANIZATION_BLOCK\n";
+        let kinds: Vec<FunctionKind> = parse_scl_from_str(src).unwrap().functions.iter().map(|f| f.kind).collect();
+        assert_eq!(kinds, vec![FunctionKind::OB121, FunctionKind::OB100, FunctionKind::OB1]);
+    }
+
+    #[test]
+    fn nested_and_instance_qualified_calls_keep_their_arguments() {
+        let src = "FUNCTION FC_A\nBEGIN\n    Foo(Bar(Baz(x), 2), y);\n    \"Conveyor\".Timer.TON(IN := Run);\n                       Timers[i].TON(IN := Run);\n    #Inst.Start();\nEND_FUNCTION\n";
+        let stmts = parse_scl_from_str(src).unwrap().functions.remove(0).statements;
+
+        let Statement::Call { name, args, .. } = &stmts[0] else { panic!("expected a call") };
+        assert_eq!((name.as_str(), args.len()), ("Foo", 2));
+        let Expression::FuncCall { name, args, .. } = &args[0].1 else { panic!("expected a nested call") };
+        assert_eq!(name, "Bar");
+        assert!(matches!(&args[0], Expression::FuncCall { name, args, .. } if name == "Baz" && args.len() == 1));
+        assert!(matches!(args[1], Expression::NumberLiteral(2, _)));
+
+        let names: Vec<&str> = stmts[1..]
+            .iter()
+            .map(|st| match st {
+                Statement::Call { name, .. } => name.as_str(),
+                _ => panic!("expected a call"),
+            })
+            .collect();
+        assert_eq!(names, vec!["Conveyor.Timer.TON", "Timers[i].TON", "Inst.Start"]);
+    }
+
+    #[test]
+    fn named_call_arguments_keep_their_parameter() {
+        let src = "FUNCTION_BLOCK FB_A\nBEGIN\n    Delay.TON(IN := Start, PT := Preset);\n    Log(Msg, 3);\nEND_FUNCTION_BLOCK\n";
+        let stmts = parse_scl_from_str(src).unwrap().functions.remove(0).statements;
+        let params = |st: &Statement| match st {
+            Statement::Call { args, .. } => args.iter().map(|(p, a)| (p.clone(), a.name())).collect::<Vec<_>>(),
+            _ => panic!("expected a call"),
+        };
+        assert_eq!(params(&stmts[0]), vec![("IN".into(), Some("Start".into())), ("PT".into(), Some("Preset".into()))]);
+        assert_eq!(params(&stmts[1]), vec![(String::new(), Some("Msg".into())), (String::new(), None)]);
+    }
+
+    #[test]
+    fn var_sections_record_names_and_types() {
+        let src = "FUNCTION FC_Fmt : VOID\nVAR_INPUT\n    Src, Alt : STRING[20];\n    Table : ARRAY[0..9] OF INT;\nEND_VAR\n\
+                   VAR_TEMP\n    Buf : STRING;\n    Grid : ARRAY[1..N, 0..3] OF \"UDT_Cell\";\n    Count : INT := 0;\nEND_VAR\n\
+                   BEGIN\n    Buf := Src;\nEND_FUNCTION\n";
+        let f = parse_scl_from_str(src).unwrap().functions.remove(0);
+        let decls: Vec<(&str, usize, &DataType)> = f.declarations.iter().map(|d| (d.name.as_str(), d.line, &d.data_type)).collect();
+        let int = || Box::new(DataType::Named("INT".into()));
+        assert_eq!(
+            decls,
+            vec![
+                ("Src", 3, &DataType::String(Some(20))),
+                ("Alt", 3, &DataType::String(Some(20))),
+                ("Table", 4, &DataType::Array { dims: vec![Some((0, 9))], element: int() }),
+                ("Buf", 7, &DataType::String(None)),
+                ("Grid", 8, &DataType::Array { dims: vec![None, Some((0, 3))], element: Box::new(DataType::Named("UDT_Cell".into())) }),
+                ("Count", 9, &DataType::Named("INT".into())),
+            ]
+        );
+        assert_eq!(f.declaration("buf").and_then(|d| d.data_type.string_capacity()), Some(254));
+        assert_eq!(f.declaration("Alt").map(|d| d.section), Some(VarSection::Input));
+        assert_eq!(f.declaration("Count").map(|d| d.section), Some(VarSection::Temp));
+        assert_eq!(f.statements.len(), 1);
+    }
+
+    #[test]
+    fn dotted_names_become_member_access() {
+        let src = "FUNCTION FC_A\nBEGIN\n    Motor.Speed := DB_Recipe.Arr[Cfg.Idx];\nEND_FUNCTION\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let Statement::Assign { target, value, .. } = &program.functions[0].statements[0] else { panic!() };
+        match target {
+            Expression::Member { base, field, line } => {
+                assert!(matches!(&**base, Expression::Identifier(b) if b == "Motor"));
+                assert_eq!((field.as_str(), *line), ("Speed", 3));
+            }
+            other => panic!("expected member access, got {:?}", other),
+        }
+        assert_eq!(target.name().as_deref(), Some("Motor.Speed"));
+        let Expression::Index { base, index, .. } = value else { panic!() };
+        assert_eq!(base.name().as_deref(), Some("DB_Recipe.Arr"));
+        assert_eq!(index.name().as_deref(), Some("Cfg.Idx"));
+        assert_eq!(crate::rules::utils::expr_text(value), "DB_Recipe.Arr[Cfg.Idx]");
+    }
+
+    #[test]
+    fn multi_line_block_comment_between_statements() {
+        let src = "FUNCTION FC_A\nBEGIN\n    Speed := 0;\n    (* @PlausibilityCheck\n       HMI_Speed is limited to 0..1500 *)\n    \
+                   Speed := HMI_Speed;\nEND_FUNCTION\n";
+        let stmts = parse_scl_from_str(src).unwrap().functions.remove(0).statements;
+        let lines: Vec<usize> = stmts.iter().map(|s| s.line()).collect();
+        assert_eq!(lines, vec![3, 6]);
+    }
+
+    #[test]
+    fn local_prefix_and_quoted_symbols_are_kept() {
+        let src = "FUNCTION_BLOCK \"FB_Recipe\"\nVAR_TEMP\n    temp : INT;\nEND_VAR\nBEGIN\n    \
+                   #temp := \"DB_Recipe\".Value;\n    \"Data Block\".\"Set Point\" := #temp + \"DB.Limits\".Max;\n\
+                   END_FUNCTION_BLOCK\n";
+        let f = parse_scl_from_str(src).unwrap().functions.remove(0);
+        assert_eq!(f.name, "FB_Recipe");
+        let Statement::Assign { target, value, .. } = &f.statements[0] else { panic!("expected an assignment") };
+        assert_eq!(target.name().as_deref(), Some("#temp"));
+        assert!(f.declaration(&target.name().unwrap()).is_some());
+        assert_eq!(crate::rules::utils::expr_text(value), "\"DB_Recipe\".Value");
+        let Statement::Assign { target, value, .. } = &f.statements[1] else { panic!("expected an assignment") };
+        assert_eq!(crate::rules::utils::expr_text(target), "\"Data Block\".\"Set Point\"");
+        assert_eq!(crate::rules::utils::expr_text(value), "#temp + \"DB.Limits\".Max");
+    }
+
+    #[test]
+    fn multi_line_expressions_span_their_operands() {
+        let src = "FUNCTION FC_A\nBEGIN\n    IF Level > 10\n       AND Divisor <> 0 THEN\n        \
+                   x := LIMIT(MN := 0,\n                   IN := y, MX := 9);\n    END_IF;\nEND_FUNCTION\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let Statement::IfStmt { condition, then_branch, .. } = &program.functions[0].statements[0] else { panic!() };
+        assert_eq!(condition.span(), (3, 4));
+        assert_eq!(condition.line(), 3);
+        let Statement::Assign { value, .. } = &then_branch[0] else { panic!() };
+        assert_eq!(value.span(), (5, 6));
+    }
+
+    #[test]
+    fn goto_jumps_forward_to_labels() {
+        let src = "FUNCTION_BLOCK FB_Legacy\nLABEL\n    Skip, Done;\nEND_LABEL\nVAR\n    Mode : INT;\nEND_VAR\nBEGIN\n    \
+                   IF Bypass THEN\n        GOTO Skip;\n    END_IF;\n    \
+                   CASE Mode OF\n        MODE_RUN:\n            x := 1;\n        MODE_STOP:\n            x := 0;\n    END_CASE;\n    \
+                   GOTO Done;\n    y := 2;\nSkip:\n    z := 3;\nDone:\n    ;\nEND_FUNCTION_BLOCK\n";
+        let f = parse_scl_from_str(src).unwrap().functions.remove(0);
+        assert_eq!(f.declarations.len(), 1);
+        let Statement::IfStmt { then_branch, .. } = &f.statements[0] else { panic!("expected IF") };
+        assert!(matches!(&then_branch[..], [Statement::Goto { label, line: 10 }] if label == "Skip"));
+        // A GOTO may land further down, so it does not guard what follows.
+        assert!(f.statements[0].early_exit_condition().is_none());
+        let Statement::CaseStmt { cases, .. } = &f.statements[1] else { panic!("expected CASE") };
+        assert_eq!(cases.len(), 2);
+        assert!(matches!(&f.statements[2], Statement::Goto { label, line: 18 } if label == "Done"));
+        assert!(matches!(&f.statements[4], Statement::Label { name, line: 20 } if name == "Skip"));
+        assert!(matches!(&f.statements[6], Statement::Label { name, line: 22 } if name == "Done"));
+        // `Skip:` can be reached past the GOTO, so nothing is cut off.
+        assert_eq!(crate::ast::reachable(&f.statements).len(), 7);
+    }
+
+    #[test]
+    fn grammar_is_found_relative_to_the_crate() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/parser/scl.pest");
+        assert!(path.is_file(), "SCL grammar missing at {}", path.display());
+        assert!(SCLParser::parse(Rule::program, "FUNCTION FC_A\nBEGIN\nEND_FUNCTION\n").is_ok());
+    }
+}
@@ -0,0 +1,189 @@
+//! Baseline ("ratchet") support: record today's findings once, then only
+//! report findings that are new relative to that record.
+//!
+//! A finding is fingerprinted by rule number, a normalized reason and a
+//! coarse line bucket. Two fingerprints match when everything but the bucket
+//! is equal and the buckets are at most one apart, so unrelated edits that
+//! shift code by a few lines don't resurrect old findings.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rules::{RuleResult, Violation};
+
+/// Lines per bucket used for fuzzy line matching.
+const LINE_BUCKET: usize = 10;
+const BASELINE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub rule_no: u8,
+    pub reason: String,
+    pub line_bucket: usize,
+}
+
+impl Fingerprint {
+    pub fn of(v: &Violation) -> Self {
+        Self {
+            rule_no: v.rule_no,
+            reason: normalize_reason(&v.reason),
+            line_bucket: v.line / LINE_BUCKET,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BaselineFile {
+    pub version: u32,
+    pub fingerprints: Vec<Fingerprint>,
+}
+
+impl BaselineFile {
+    /// Capture every violation in `results` as the accepted baseline.
+    pub fn from_results(results: &[RuleResult]) -> Self {
+        let mut fingerprints: Vec<Fingerprint> = results
+            .iter()
+            .flat_map(|r| r.violations.iter().map(Fingerprint::of))
+            .collect();
+        fingerprints.sort_by(|a, b| {
+            (a.rule_no, a.line_bucket, &a.reason).cmp(&(b.rule_no, b.line_bucket, &b.reason))
+        });
+        Self { version: BASELINE_VERSION, fingerprints }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".into())
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, String> {
+        let b: BaselineFile = serde_json::from_str(s).map_err(|e| format!("Invalid baseline JSON: {}", e))?;
+        if b.version != BASELINE_VERSION {
+            return Err(format!("Unsupported baseline version {}", b.version));
+        }
+        Ok(b)
+    }
+}
+
+/// Return `current` with every violation already recorded in `baseline`
+/// removed. Each baseline entry absorbs at most one current finding, so a
+/// second copy of a known problem is still reported as new.
+pub fn filter_new(current: &[RuleResult], baseline: &BaselineFile) -> Vec<RuleResult> {
+    // (rule, reason) -> remaining buckets that may still absorb a finding
+    let mut known: HashMap<(u8, String), Vec<usize>> = HashMap::new();
+    for fp in &baseline.fingerprints {
+        known.entry((fp.rule_no, fp.reason.clone())).or_default().push(fp.line_bucket);
+    }
+
+    current
+        .iter()
+        .map(|r| {
+            let violations: Vec<Violation> = r
+                .violations
+                .iter()
+                .filter(|v| {
+                    let fp = Fingerprint::of(v);
+                    let Some(buckets) = known.get_mut(&(fp.rule_no, fp.reason)) else {
+                        return true;
+                    };
+                    // Prefer the exact bucket, then a neighbour.
+                    let pos = buckets
+                        .iter()
+                        .position(|b| *b == fp.line_bucket)
+                        .or_else(|| buckets.iter().position(|b| b.abs_diff(fp.line_bucket) <= 1));
+                    match pos {
+                        Some(i) => {
+                            buckets.swap_remove(i);
+                            false
+                        }
+                        None => true,
+                    }
+                })
+                .cloned()
+                .collect();
+            let mut kept = RuleResult { rule_no: r.rule_no, rule_name: r.rule_name, status: r.status, violations };
+            kept.update_status();
+            kept
+        })
+        .collect()
+}
+
+/// Case- and whitespace-insensitive reason with numeric literals masked,
+/// so counts embedded in messages ("complexity 53 exceeds 50") don't break
+/// matching. Digits inside names such as `Tank2` or `%MW100` are kept.
+fn normalize_reason(reason: &str) -> String {
+    let text: Vec<char> = reason.split_whitespace().collect::<Vec<_>>().join(" ").chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < text.len() {
+        let c = text[i];
+        let in_word = i > 0 && (text[i - 1].is_alphanumeric() || text[i - 1] == '_');
+        if c.is_ascii_digit() && !in_word {
+            out.push('#');
+            // The rest of the literal, including a decimal part.
+            while i + 1 < text.len()
+                && (text[i + 1].is_ascii_digit() || (text[i + 1] == '.' && text.get(i + 2).is_some_and(char::is_ascii_digit)))
+            {
+                i += 1;
+            }
+        } else {
+            out.extend(c.to_lowercase());
+        }
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Severity;
+
+    fn result(lines: &[usize]) -> RuleResult {
+        RuleResult::violations(
+            lines
+                .iter()
+                .map(|&line| Violation {
+                    rule_no: 9,
+                    rule_name: "Validate indirections",
+                    line,
+                    end_line: None,
+                    reason: "Array indexed by variable 'i' without bounds check".into(),
+                    suggestion: String::new(),
+                    severity: Severity::Warning,
+                    snippet: None,
+                    fix: None,
+                    function: None,
+                    file: None,
+                    rationale: None,
+                    doc_anchor: None,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn shifted_findings_stay_suppressed_and_new_ones_surface() {
+        let baseline = BaselineFile::from_results(&[result(&[18])]);
+        let baseline = BaselineFile::from_json(&baseline.to_json()).unwrap();
+
+        // Same finding moved down a few lines plus a genuinely new copy.
+        let filtered = filter_new(&[result(&[21, 80])], &baseline);
+        assert_eq!(filtered[0].violations.len(), 1);
+        assert_eq!(filtered[0].violations[0].line, 80);
+
+        let filtered = filter_new(&[result(&[23])], &baseline);
+        assert!(filtered[0].passed());
+    }
+
+    #[test]
+    fn numbers_in_reasons_are_masked() {
+        assert_eq!(
+            normalize_reason("Cyclomatic  complexity 53 exceeds 50"),
+            normalize_reason("cyclomatic complexity 61 exceeds 50")
+        );
+        assert_eq!(normalize_reason("Guard allows 'Tank2' up to 10.5"), "guard allows 'tank2' up to #");
+        assert_ne!(normalize_reason("Tank2 is never read"), normalize_reason("Tank3 is never read"));
+        assert_ne!(normalize_reason("Write to %MW100"), normalize_reason("Write to %MW200"));
+    }
+}
@@ -0,0 +1,153 @@
+//! Self-contained HTML report for audits. Everything (styles included) is
+//! inlined so the file can be attached to a ticket or emailed as-is.
+
+use std::fmt::Write;
+
+use crate::rules::{RuleResult, RuleStatus};
+
+/// Lines of source shown above and below each offending line.
+const CONTEXT_LINES: usize = 2;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.4em; }
+table.summary { border-collapse: collapse; margin-bottom: 2em; }
+table.summary th, table.summary td { border: 1px solid #ccc; padding: 4px 10px; text-align: left; }
+.ok { color: #1a7f37; font-weight: bold; }
+.fail { color: #cf222e; font-weight: bold; }
+.na { color: #888; }
+.violation { border-left: 4px solid #cf222e; padding: 0 1em; margin: 1em 0; }
+pre.snippet { background: #f6f8fa; padding: 6px; overflow-x: auto; }
+pre.snippet .hit { background: #ffebe9; display: block; }
+.ln { color: #888; user-select: none; }
+";
+
+/// Render `results` as a standalone HTML document: a summary table with one
+/// row per rule, then each violation grouped by rule with the offending
+/// line of `source` highlighted in a few lines of context.
+pub fn to_html(results: &[RuleResult], source: &str, file_name: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let total: usize = results.iter().map(|r| r.violations.len()).sum();
+    let mut out = String::new();
+
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(out, "<title>PLC Secure Coding Report - {}</title>", escape(file_name));
+    let _ = writeln!(out, "<style>{}</style>\n</head>\n<body>", STYLE);
+    let _ = writeln!(out, "<h1>PLC Secure Coding Report: {}</h1>", escape(file_name));
+    let _ = writeln!(
+        out,
+        "<p>{} rules checked, {} violations.</p>",
+        results.len(),
+        total
+    );
+
+    out.push_str("<table class=\"summary\">\n<tr><th>Rule</th><th>Name</th><th>Status</th><th>Violations</th></tr>\n");
+    for r in results {
+        let class = match r.status {
+            RuleStatus::Passed => "ok",
+            RuleStatus::NotApplicable => "na",
+            _ => "fail",
+        };
+        let _ = writeln!(
+            out,
+            "<tr><td><a href=\"#rule-{no}\">{no}</a></td><td>{}</td><td class=\"{}\">{}</td><td>{}</td></tr>",
+            escape(r.rule_name),
+            class,
+            r.status.label(),
+            r.violations.len(),
+            no = r.rule_no,
+        );
+    }
+    out.push_str("</table>\n");
+
+    for r in results.iter().filter(|r| !r.violations.is_empty()) {
+        let _ = writeln!(
+            out,
+            "<h2 id=\"rule-{}\">Rule {}: {}</h2>",
+            r.rule_no,
+            r.rule_no,
+            escape(r.rule_name)
+        );
+        for v in &r.violations {
+            out.push_str("<div class=\"violation\">\n");
+            let _ = writeln!(out, "<p><strong>Line {}:</strong> {}</p>", v.line, escape(&v.reason));
+            let _ = writeln!(out, "<p><em>Suggestion:</em> {}</p>", escape(&v.suggestion));
+            if let Some(snippet) = snippet(&lines, v.line) {
+                out.push_str(&snippet);
+            }
+            out.push_str("</div>\n");
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Source context around a 1-based `line`, or `None` when the line is unknown.
+fn snippet(lines: &[&str], line: usize) -> Option<String> {
+    if line == 0 || line > lines.len() {
+        return None;
+    }
+    let first = line.saturating_sub(CONTEXT_LINES).max(1);
+    let last = (line + CONTEXT_LINES).min(lines.len());
+    let mut out = String::from("<pre class=\"snippet\">");
+    for n in first..=last {
+        let text = format!("<span class=\"ln\">{:>5} </span>{}", n, escape(lines[n - 1]));
+        if n == line {
+            let _ = write!(out, "<span class=\"hit\">{}</span>", text);
+        } else {
+            let _ = writeln!(out, "{}", text);
+        }
+    }
+    out.push_str("</pre>\n");
+    Some(out)
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{Severity, Violation};
+
+    #[test]
+    fn highlights_offending_line_and_escapes_source() {
+        let source = "a := 1;\nb := 2;\nIF x < 3 THEN y := z / w; END_IF;\nc := 4;";
+        let results = vec![
+            RuleResult::ok(1, "Modularize PLC Code"),
+            RuleResult::violations(vec![Violation {
+                rule_no: 4,
+                rule_name: "Use PLC flags as integrity checks",
+                line: 3,
+                end_line: None,
+                reason: "Division operation without status-word / zero-divisor guard".into(),
+                suggestion: "Guard the divisor".into(),
+                severity: Severity::Warning,
+                snippet: None,
+                fix: None,
+                function: None,
+                file: None,
+                rationale: None,
+                doc_anchor: None,
+            }]),
+        ];
+        let html = to_html(&results, source, "main.scl");
+        assert!(html.contains("<span class=\"hit\"><span class=\"ln\">    3 </span>IF x &lt; 3 THEN"));
+        assert!(html.contains("id=\"rule-4\""));
+        assert!(html.contains("<td class=\"ok\">OK</td>"));
+        assert!(!html.contains("<script"));
+    }
+}
@@ -0,0 +1,69 @@
+//! Report formatters. Each submodule turns the [`RuleResult`]s produced by
+//! the rules engine into a presentation format for a particular audience.
+//!
+//! [`RuleResult`]: crate::rules::RuleResult
+
+pub mod html;
+pub mod text;
+pub mod baseline;
+pub mod sarif;
+pub mod ndjson;
+
+use serde::Serialize;
+
+use std::collections::BTreeSet;
+
+use crate::rules::{Policy, RuleStatus, Severity, WasmRuleResult};
+
+/// JSON envelope around the results for callers that ask for more than the
+/// bare result list.
+#[derive(Debug, Serialize)]
+pub struct AnalysisReport {
+    pub results: Vec<WasmRuleResult>,
+    /// The policy with defaults filled in (see [`Policy::effective`]).
+    /// Only present when requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_policy: Option<Policy>,
+}
+
+/// Counts for a pass/fail badge, computed from the same entries the full
+/// result list holds.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    /// Rules that ran.
+    pub total_rules: usize,
+    pub passed: usize,
+    /// Rules with at least one finding.
+    pub violated: usize,
+    /// Rules that checked nothing for lack of policy input.
+    pub not_applicable: usize,
+    /// Input, parse, policy and selection errors.
+    pub errors: usize,
+    /// Highest severity among findings and errors; `None` when clean.
+    pub worst_severity: Option<Severity>,
+}
+
+impl Summary {
+    pub fn from_results(results: &[WasmRuleResult]) -> Self {
+        let mut passed = BTreeSet::new();
+        let mut violated = BTreeSet::new();
+        let mut not_applicable = BTreeSet::new();
+        let mut errors = 0;
+        for r in results {
+            match r.status {
+                RuleStatus::Passed => { passed.insert(r.rule_no); }
+                RuleStatus::Violated => { violated.insert(r.rule_no); }
+                RuleStatus::NotApplicable => { not_applicable.insert(r.rule_no); }
+                RuleStatus::Error => errors += 1,
+            }
+        }
+        Summary {
+            total_rules: passed.len() + violated.len() + not_applicable.len(),
+            passed: passed.len(),
+            violated: violated.len(),
+            not_applicable: not_applicable.len(),
+            errors,
+            worst_severity: results.iter().filter_map(|r| r.violation.as_ref()).map(|v| v.severity).max(),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Newline-delimited JSON for log shippers and `jq`: one compact entry per
+//! finding, in the same shape as the entries of `--format json`, e.g.
+//! `{"status":"NOT FOLLOWED","rule_no":4,"rule_name":"...","violation":{...}}`.
+
+use crate::rules::WasmRuleResult;
+
+/// Render one line per entry that carries a finding (violations and
+/// errors); passing and not-applicable rules are left out. Each finding
+/// names its file, so every line stands on its own: findings without one
+/// get `file_name`.
+pub fn to_ndjson(results: &[WasmRuleResult], file_name: &str) -> String {
+    let mut out = String::new();
+    for r in results.iter().filter(|r| r.violation.is_some()) {
+        let mut entry = r.clone();
+        if let Some(v) = entry.violation.as_mut() {
+            v.file.get_or_insert_with(|| file_name.to_string());
+        }
+        // Entries hold only strings, numbers and enums, so they always serialize.
+        out.push_str(&serde_json::to_string(&entry).unwrap_or_else(|_| "{}".into()));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{to_wasm_results, RuleResult, Severity, Violation};
+    use serde_json::Value;
+
+    fn violation(rule_no: u8, line: usize, file: Option<&str>) -> Violation {
+        Violation {
+            rule_no,
+            rule_name: "Test",
+            line,
+            end_line: None,
+            reason: format!("finding \"{}\"\nsecond line", rule_no),
+            suggestion: String::new(),
+            severity: Severity::Warning,
+            snippet: None,
+            fix: None,
+            function: None,
+            file: file.map(String::from),
+            rationale: None,
+            doc_anchor: None,
+        }
+    }
+
+    #[test]
+    fn every_line_is_a_complete_json_finding() {
+        let results = to_wasm_results(vec![
+            RuleResult::ok(1, "Modularize PLC Code"),
+            RuleResult::violations(vec![violation(4, 7, None), violation(4, 42, Some("lib.scl"))]),
+            RuleResult::not_applicable(10, "Assign designated register blocks"),
+        ]);
+        let out = to_ndjson(&results, "main.scl");
+
+        let lines: Vec<Value> = out.lines().map(|l| serde_json::from_str(l).expect("each line is JSON")).collect();
+        assert_eq!(lines.len(), 2);
+        assert!(out.ends_with('\n'));
+        let fields = |l: &Value| {
+            let v = &l["violation"];
+            (l["rule_no"].clone(), v["severity"].clone(), v["line"].clone(), v["file"].clone(), v["reason"].clone())
+        };
+        assert_eq!(
+            fields(&lines[0]),
+            (4.into(), "Warning".into(), 7.into(), "main.scl".into(), "finding \"4\"\nsecond line".into())
+        );
+        assert_eq!(lines[1]["violation"]["file"], "lib.scl");
+        assert_eq!(lines[1]["status"], "NOT FOLLOWED");
+    }
+
+    #[test]
+    fn no_findings_is_empty_output() {
+        assert_eq!(to_ndjson(&to_wasm_results(vec![RuleResult::ok(1, "Modularize PLC Code")]), "main.scl"), "");
+    }
+}
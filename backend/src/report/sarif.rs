@@ -0,0 +1,102 @@
+//! SARIF 2.1.0 output, the format code-scanning services (GitHub, Azure
+//! DevOps, IDE plugins) ingest. Rule ids match the text formatter
+//! (`PLC009`), so findings can be cross-referenced between the two.
+
+use serde_json::{json, Value};
+
+use crate::rules::{registry, RuleResult, Violation};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Render a single-run SARIF log for `file_name`.
+pub fn to_sarif(results: &[RuleResult], file_name: &str) -> String {
+    let rules: Vec<Value> = results
+        .iter()
+        .filter(|r| r.rule_no != 0)
+        .map(|r| {
+            let meta = registry::lookup(r.rule_no);
+            json!({
+                "id": rule_id(r.rule_no),
+                "name": meta.map_or(r.rule_name, |m| m.name),
+                "shortDescription": { "text": meta.map_or(r.rule_name, |m| m.description) },
+            })
+        })
+        .collect();
+
+    let findings: Vec<Value> = results
+        .iter()
+        .flat_map(|r| &r.violations)
+        .map(|v| sarif_result(v, file_name))
+        .collect();
+
+    let log = json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": findings,
+        }],
+    });
+    serde_json::to_string_pretty(&log).unwrap_or_else(|_| "{}".into())
+}
+
+fn rule_id(rule_no: u8) -> String {
+    format!("PLC{:03}", rule_no)
+}
+
+fn sarif_result(v: &Violation, file_name: &str) -> Value {
+    let mut location = json!({ "artifactLocation": { "uri": file_name } });
+    // SARIF lines are 1-based; 0 means "no line known", so leave the region out.
+    if v.line > 0 {
+        location["region"] = json!({ "startLine": v.line });
+    }
+    json!({
+        "ruleId": rule_id(v.rule_no),
+        "level": v.severity.level(),
+        "message": { "text": format!("{} {}", v.reason, v.suggestion).trim_end() },
+        "locations": [{ "physicalLocation": location }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Severity;
+
+    #[test]
+    fn emits_rules_and_located_results() {
+        let results = vec![
+            RuleResult::ok(1, "Modularize PLC code"),
+            RuleResult::violations(vec![Violation {
+                rule_no: 10,
+                rule_name: "Assign designated register blocks",
+                line: 12,
+                end_line: None,
+                reason: "Write to read-only area.".into(),
+                suggestion: "Move the write.".into(),
+                severity: Severity::Error,
+                snippet: None,
+                fix: None,
+                function: None,
+                file: None,
+                rationale: None,
+                doc_anchor: None,
+            }]),
+        ];
+        let log: Value = serde_json::from_str(&to_sarif(&results, "main.scl")).unwrap();
+        let run = &log["runs"][0];
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 2);
+        let r = &run["results"][0];
+        assert_eq!(r["ruleId"], "PLC010");
+        assert_eq!(r["level"], "error");
+        assert_eq!(r["locations"][0]["physicalLocation"]["region"]["startLine"], 12);
+        assert_eq!(r["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], "main.scl");
+    }
+}
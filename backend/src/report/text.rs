@@ -0,0 +1,87 @@
+//! Compiler-style plain-text output for terminals and editors, e.g.
+//! `main.scl:42: warning[PLC011]: Use of sensitive value 'HMI_Speed' ...`.
+
+use std::fmt::Write;
+
+use crate::rules::{RuleResult, RuleStatus, Violation};
+
+/// Render one line per violation, sorted by line then rule number, followed
+/// by a summary line such as `3 violations, 2 rules passed`, plus the
+/// number of rules that were not applicable, if any.
+pub fn to_text(results: &[RuleResult], file_name: &str) -> String {
+    let mut violations: Vec<&Violation> = results.iter().flat_map(|r| &r.violations).collect();
+    violations.sort_by_key(|v| (v.line, v.rule_no));
+
+    let mut out = String::new();
+    for v in &violations {
+        let _ = writeln!(out, "{}", format_violation(v, file_name));
+    }
+
+    let passed = results.iter().filter(|r| r.passed()).count();
+    let _ = write!(
+        out,
+        "{} violation{}, {} rule{} passed",
+        violations.len(),
+        if violations.len() == 1 { "" } else { "s" },
+        passed,
+        if passed == 1 { "" } else { "s" },
+    );
+    let skipped = results.iter().filter(|r| r.status == RuleStatus::NotApplicable).count();
+    if skipped > 0 {
+        let _ = write!(out, ", {} not applicable", skipped);
+    }
+    out.push('\n');
+    out
+}
+
+/// `file:line: level[PLCnnn]: reason`
+pub fn format_violation(v: &Violation, file_name: &str) -> String {
+    format!(
+        "{}:{}: {}[PLC{:03}]: {}",
+        file_name,
+        v.line,
+        v.severity.level(),
+        v.rule_no,
+        v.reason
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Severity;
+
+    fn violation(rule_no: u8, line: usize, severity: Severity) -> Violation {
+        Violation {
+            rule_no,
+            rule_name: "Test",
+            line,
+            end_line: None,
+            reason: format!("finding {}", rule_no),
+            suggestion: String::new(),
+            severity,
+            snippet: None,
+            fix: None,
+            function: None,
+            file: None,
+            rationale: None,
+            doc_anchor: None,
+        }
+    }
+
+    #[test]
+    fn sorts_by_line_then_rule_and_summarizes() {
+        let results = vec![
+            RuleResult::ok(1, "Modularize PLC Code"),
+            RuleResult::not_applicable(10, "Assign designated register blocks"),
+            RuleResult::violations(vec![violation(11, 42, Severity::Warning)]),
+            RuleResult::violations(vec![violation(4, 42, Severity::Error), violation(4, 7, Severity::Info)]),
+        ];
+        let text = to_text(&results, "main.scl");
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "main.scl:7: note[PLC004]: finding 4");
+        assert_eq!(lines[1], "main.scl:42: error[PLC004]: finding 4");
+        assert_eq!(lines[2], "main.scl:42: warning[PLC011]: finding 11");
+        assert_eq!(lines[3], "3 violations, 1 rule passed, 1 not applicable");
+    }
+}
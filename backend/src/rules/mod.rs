@@ -4,8 +4,12 @@ use std::fs;
 use std::path::Path;
 
 use crate::ast::Program;
+use registry::RuleMeta;
 
+pub mod platform;
 pub mod policy;
+pub mod redact;
+pub mod registry;
 pub mod rule1;
 pub mod rule2;
 pub mod rule4;
@@ -22,49 +26,340 @@ pub mod rule17;
 pub mod rule18;
 pub mod rule19;
 pub mod rule20;
+pub mod rule30;
+pub mod rule31;
+pub mod rule32;
+pub mod rule33;
+pub mod rule34;
+pub mod rule35;
+pub mod rule36;
+pub mod rule37;
+pub mod rule38;
+pub mod rule39;
+pub mod rule40;
+pub mod rule41;
+pub mod rule42;
+pub mod rule43;
+pub mod rule44;
+pub mod rule45;
+pub mod rule46;
+pub mod rule47;
+pub mod rule48;
+pub mod rule49;
+pub mod rule50;
+pub mod rule51;
+pub mod suppress;
 pub mod utils;
+pub mod visit;
 
+pub use platform::Platform;
 pub use policy::Policy;
 
 use serde::Serialize;
+use suppress::Suppressions;
+
+/// How serious a finding is. Formatters map these onto their own levels
+/// (e.g. `error`/`warning`/`note` for compiler-style output).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Lower-case level name used by compiler-style and SARIF output.
+    pub fn level(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "note",
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize)]
 pub struct Violation {
     pub rule_no: u8,
     pub rule_name: &'static str,
     pub line: usize,
+    /// Last line of a finding that spans several lines (e.g. a condition
+    /// split over lines); `None` when it is just `line`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
     pub reason: String,
     pub suggestion: String,
+    pub severity: Severity,
+    /// The offending source text: the expression for expression-level
+    /// findings, otherwise the trimmed source line (see [`attach_snippets`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    /// Machine-applicable fix, for findings whose remedy is unambiguous.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<TextEdit>,
+    /// The block the finding is in, filled in by the engine from the line
+    /// (see [`RuleResult::attribute`]). `None` for program-wide findings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    /// The source file, when several files were checked together.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Why the rule matters, from the registry; explain mode only (see
+    /// [`explain`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rationale: Option<&'static str>,
+    /// Anchor of the rule's documentation section; explain mode only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc_anchor: Option<String>,
+}
+
+impl Violation {
+    /// A finding of rule `meta` at `line`, with the optional fields unset;
+    /// set them with struct update syntax where a rule knows more.
+    pub fn new(meta: &RuleMeta, line: usize, reason: impl Into<String>, suggestion: impl Into<String>, severity: Severity) -> Self {
+        Violation {
+            rule_no: meta.no,
+            rule_name: meta.name,
+            line,
+            end_line: None,
+            reason: reason.into(),
+            suggestion: suggestion.into(),
+            severity,
+            snippet: None,
+            fix: None,
+            function: None,
+            file: None,
+            rationale: None,
+            doc_anchor: None,
+        }
+    }
+}
+
+/// A quick-fix edit: replace the source text from `start_line`:`start_col`
+/// to `end_line`:`end_col` (1-based, end column exclusive) with
+/// `replacement`, so the rest of those lines is kept. Rules name the
+/// statement they rewrite with [`TextEdit::replace_statement`];
+/// [`attach_snippets`] then finds its columns in the source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextEdit {
+    pub start_line: usize,
+    pub start_col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+    pub replacement: String,
+    /// The statement to replace, as the pretty-printer writes it, until
+    /// [`attach_snippets`] has located it.
+    #[serde(skip)]
+    original: Option<String>,
+}
+
+impl TextEdit {
+    /// Replace the statement `original` (see [`statement_to_scl`]), which
+    /// lies on lines `first..=last`, with `replacement`. Both are written
+    /// unindented; later lines of the replacement get the indentation of
+    /// the line the statement starts on.
+    ///
+    /// [`statement_to_scl`]: crate::ast::pprint::statement_to_scl
+    pub fn replace_statement(first: usize, last: usize, original: String, replacement: String) -> Self {
+        Self { start_line: first, start_col: 0, end_line: last.max(first), end_col: 0, replacement, original: Some(original) }
+    }
+
+    /// Give the edit the columns of its statement in `lines`, matching the
+    /// printed statement against the source while ignoring whitespace and
+    /// case. `None` when the statement is not found there (for example
+    /// because a comment sits inside it), so no edit overwrites other code.
+    fn locate(mut self, lines: &[&str]) -> Option<Self> {
+        let Some(original) = self.original.take() else { return Some(self) };
+        let mut text = Vec::new();
+        for line_no in self.start_line..=self.end_line {
+            let line = lines.get(line_no.checked_sub(1)?)?;
+            let chars = line.chars().enumerate().filter(|(_, c)| !c.is_whitespace());
+            text.extend(chars.map(|(col, c)| (c.to_ascii_uppercase(), line_no, col + 1)));
+        }
+        let wanted: Vec<char> = original.chars().filter(|c| !c.is_whitespace()).map(|c| c.to_ascii_uppercase()).collect();
+        if wanted.is_empty() {
+            return None;
+        }
+        let at = text.windows(wanted.len()).position(|w| w.iter().map(|(c, _, _)| *c).eq(wanted.iter().copied()))?;
+        let (_, start_line, start_col) = text[at];
+        let (_, end_line, end_col) = text[at + wanted.len() - 1];
+
+        let first = lines[start_line - 1];
+        let indent = &first[..first.len() - first.trim_start().len()];
+        let replacement = self
+            .replacement
+            .lines()
+            .enumerate()
+            .map(|(i, l)| if i == 0 { l.to_string() } else { format!("{}{}", indent, l) })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(TextEdit { start_line, start_col, end_line, end_col: end_col + 1, replacement, original: None })
+    }
+}
+
+/// Outcome of one rule, as the UI and the reports show it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RuleStatus {
+    /// The rule ran and found nothing.
+    #[serde(rename = "OK")]
+    Passed,
+    #[serde(rename = "NOT FOLLOWED")]
+    Violated,
+    /// The rule needs policy input that was not given, so nothing was
+    /// checked (e.g. rule 10 without `memory_areas`).
+    #[serde(rename = "NOT APPLICABLE")]
+    NotApplicable,
+    /// Input, parse, policy or selection error, or a rule that panicked.
+    #[serde(rename = "ERROR")]
+    Error,
+}
+
+impl RuleStatus {
+    /// The serialized form, e.g. `NOT FOLLOWED`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            RuleStatus::Passed => "OK",
+            RuleStatus::Violated => "NOT FOLLOWED",
+            RuleStatus::NotApplicable => "NOT APPLICABLE",
+            RuleStatus::Error => "ERROR",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct WasmRuleResult {
-    pub status: String, // "OK" or "NOT FOLLOWED"
+    pub status: RuleStatus,
     pub rule_no: u8,
     pub rule_name: &'static str,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub violation: Option<Violation>,
 }
 
+impl WasmRuleResult {
+    /// Expand one rule's outcome into UI entries. Every rule yields at
+    /// least one entry so the UI can render a complete checklist: a single
+    /// "OK" (or "NOT APPLICABLE") entry when nothing was found, otherwise
+    /// one "NOT FOLLOWED" entry per violation.
+    pub fn from_rule_result(result: &RuleResult) -> Vec<WasmRuleResult> {
+        if result.violations.is_empty() {
+            return vec![WasmRuleResult {
+                status: result.status,
+                rule_no: result.rule_no,
+                rule_name: result.rule_name,
+                violation: None,
+            }];
+        }
+        result
+            .violations
+            .iter()
+            .map(|v| WasmRuleResult {
+                status: if result.status == RuleStatus::Error { RuleStatus::Error } else { RuleStatus::Violated },
+                rule_no: v.rule_no,
+                rule_name: v.rule_name,
+                violation: Some(v.clone()),
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RuleResult {
-    pub ok: bool,
+    pub rule_no: u8,
+    pub rule_name: &'static str,
+    /// `Passed` or `Violated`, or `NotApplicable` for a rule whose policy
+    /// input is missing. `Error` only when the rule panicked, with the
+    /// panic as its one finding.
+    pub status: RuleStatus,
     pub violations: Vec<Violation>,
 }
 
 impl RuleResult {
-    pub fn ok(_rule_no: u8, _name: &'static str) -> Self {
-        Self { ok: true, violations: vec![] }
+    pub fn ok(rule_no: u8, name: &'static str) -> Self {
+        Self { rule_no, rule_name: name, status: RuleStatus::Passed, violations: vec![] }
     }
     pub fn violations(v: Vec<Violation>) -> Self {
-        Self { ok: v.is_empty(), violations: v }
+        let (rule_no, rule_name) = v.first().map(|f| (f.rule_no, f.rule_name)).unwrap_or((0, ""));
+        let status = if v.is_empty() { RuleStatus::Passed } else { RuleStatus::Violated };
+        Self { rule_no, rule_name, status, violations: v }
+    }
+    /// The rule needs policy input that was not given and checked nothing.
+    pub fn not_applicable(rule_no: u8, name: &'static str) -> Self {
+        Self { rule_no, rule_name: name, status: RuleStatus::NotApplicable, violations: vec![] }
+    }
+
+    /// The rule panicked. The single finding carries `reason` at line 0.
+    pub fn error(rule_no: u8, name: &'static str, reason: String) -> Self {
+        let v = Violation {
+            rule_no,
+            rule_name: name,
+            line: 0,
+            end_line: None,
+            reason,
+            suggestion: "Please report this input to the maintainers; the other rules still ran.".into(),
+            severity: Severity::Error,
+            snippet: None,
+            fix: None,
+            function: None,
+            file: None,
+            rationale: None,
+            doc_anchor: None,
+        };
+        Self { rule_no, rule_name: name, status: RuleStatus::Error, violations: vec![v] }
+    }
+
+    /// True if the rule ran and found nothing.
+    pub fn passed(&self) -> bool {
+        self.status == RuleStatus::Passed
+    }
+
+    /// Bring `status` in line with `violations` after findings were added
+    /// or dropped (suppressions, baselines, custom rules). A rule left with
+    /// no findings passes unless it was not applicable. An error stays one.
+    pub fn update_status(&mut self) {
+        if self.status == RuleStatus::Error {
+            return;
+        }
+        if !self.violations.is_empty() {
+            self.status = RuleStatus::Violated;
+        } else if self.status == RuleStatus::Violated {
+            self.status = RuleStatus::Passed;
+        }
+    }
+
+    /// Name the block each finding falls in: the last function starting at
+    /// or before its line in the same file. Findings a rule already
+    /// attributed, and those without a line, are left alone.
+    pub fn attribute(&mut self, program: &Program, cx: &AnalysisContext) {
+        for v in self.violations.iter_mut().filter(|v| v.function.is_none() && v.line > 0) {
+            let file_start = cx.file_start(v.line);
+            v.function = program
+                .functions
+                .iter()
+                .filter(|f| f.line >= file_start && f.line <= v.line)
+                .max_by_key(|f| f.line)
+                .map(|f| f.name.clone());
+        }
+    }
+
+    /// Sort the findings by line and drop exact repeats (same rule, line
+    /// and reason), which a rule can produce when the walk reaches one
+    /// expression twice. Findings on the same line keep their walk order.
+    /// [`to_wasm_results`] orders the findings of all rules by line.
+    pub fn normalize(&mut self) {
+        self.violations.sort_by_key(|v| (v.line, v.rule_no));
+        let mut seen = std::collections::HashSet::new();
+        self.violations.retain(|v| seen.insert((v.rule_no, v.line, v.reason.clone())));
     }
 }
 
+/// Read the policy file at `policy_path`: TOML if the extension is
+/// `.toml`, JSON otherwise. No path gives the default policy.
 pub fn load_policy(policy_path: Option<&Path>) -> Result<Policy, String> {
     if let Some(p) = policy_path {
+        let is_toml = p.extension().is_some_and(|e| e.eq_ignore_ascii_case("toml"));
         match fs::read_to_string(p) {
-            Ok(s) => serde_json::from_str(&s).map_err(|e| format!("Invalid policy JSON: {}", e)),
+            Ok(s) if is_toml => policy::parse_policy_from_toml(&s),
+            Ok(s) => policy::parse_policy_from_text(&s),
             Err(e) => Err(format!("Could not read policy file: {}", e)),
         }
     } else {
@@ -72,89 +367,546 @@ pub fn load_policy(policy_path: Option<&Path>) -> Result<Policy, String> {
     }
 }
 
+/// Everything a rule may consult besides the program. Built per analysis,
+/// so concurrent analyses of different files share no state.
+pub struct AnalysisContext<'a> {
+    pub policy: &'a Policy,
+    /// Lines of the source the program was parsed from; empty if unknown.
+    pub source_lines: Vec<&'a str>,
+    /// `plc-checker:ignore` pragmas of the source, applied to every result.
+    pub suppressions: Suppressions,
+    /// First line of each file when the source is a merged project (see
+    /// [`MergedProject`](crate::parser::MergedProject)); empty for one file.
+    /// Lookups that scan nearby lines or blocks stay within a file.
+    pub file_starts: Vec<usize>,
+}
+
+impl<'a> AnalysisContext<'a> {
+    pub fn new(policy: &'a Policy) -> Self {
+        Self { policy, source_lines: Vec::new(), suppressions: Suppressions::default(), file_starts: Vec::new() }
+    }
+
+    pub fn with_source(policy: &'a Policy, source: &'a str) -> Self {
+        Self {
+            policy,
+            source_lines: source.lines().collect(),
+            suppressions: Suppressions::from_source(source),
+            file_starts: Vec::new(),
+        }
+    }
+
+    /// First line of the file that `line` is in; 1 for a single file.
+    pub fn file_start(&self, line: usize) -> usize {
+        file_start(&self.file_starts, line)
+    }
+}
+
+/// The largest of `file_starts` at or before `line`, or 1.
+pub(crate) fn file_start(file_starts: &[usize], line: usize) -> usize {
+    file_starts.iter().copied().filter(|&start| start <= line).max().unwrap_or(1)
+}
+
+type CheckFn = fn(&Program, &AnalysisContext) -> RuleResult;
+
+/// One entry of the rule dispatch table.
+pub struct RuleEntry {
+    pub meta: &'static RuleMeta,
+    pub check: CheckFn,
+    /// Platform whose intrinsics the rule depends on; `None` if portable.
+    pub platform: Option<Platform>,
+}
+
+/// A check that can run alongside the built-in rules, e.g. a site-specific
+/// rule registered with [`Analyzer::with_custom_rule`]. Its results go
+/// through the same suppression, severity and reporting steps as the
+/// built-in ones. Rules are `Send + Sync` so an [`Analyzer`] can be shared
+/// between threads.
+///
+/// [`Analyzer`]: crate::analyzer::Analyzer
+/// [`Analyzer::with_custom_rule`]: crate::analyzer::AnalyzerBuilder::with_custom_rule
+pub trait Rule: Send + Sync {
+    /// Rule number shown in reports and used for selection, suppression
+    /// and severity overrides.
+    fn number(&self) -> u8;
+    fn name(&self) -> &'static str;
+    fn check(&self, program: &Program, policy: &Policy) -> RuleResult;
+}
+
+impl std::fmt::Debug for dyn Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Rule {}: {}", self.number(), self.name())
+    }
+}
+
+/// Built-in rules through the [`Rule`] interface. The source text is not
+/// available this way; [`run_in_context`] passes it.
+impl Rule for RuleEntry {
+    fn number(&self) -> u8 {
+        self.meta.no
+    }
+
+    fn name(&self) -> &'static str {
+        self.meta.name
+    }
+
+    fn check(&self, program: &Program, policy: &Policy) -> RuleResult {
+        (self.check)(program, &AnalysisContext::new(policy))
+    }
+}
+
+/// Every rule, in rule-number order, paired with its entry in
+/// [`registry::RULES`]. Adapters normalise the differing `check`
+/// signatures so rules can be selected by number cheaply.
+pub const RULES: &[RuleEntry] = &[
+    RuleEntry { meta: &registry::RULE1, check: |p, _| rule1::check(p), platform: None },
+    RuleEntry { meta: &registry::RULE2, check: |p, _| rule2::check(p), platform: None },
+    RuleEntry { meta: &registry::RULE4, check: |p, _| rule4::check(p), platform: None },
+    RuleEntry { meta: &registry::RULE5, check: |p, cx| rule5::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE6, check: |p, cx| rule6::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE7, check: |p, cx| rule7::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE8, check: rule8::check, platform: None },
+    RuleEntry { meta: &registry::RULE9, check: |p, cx| rule9::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE10, check: |p, cx| rule10::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE11, check: rule11_12::check_rule11, platform: None },
+    RuleEntry { meta: &registry::RULE12, check: rule11_12::check_rule12, platform: None }, // combined
+    RuleEntry { meta: &registry::RULE15, check: |p, cx| rule15::check(p, cx.policy), platform: Some(Platform::S7) },
+    RuleEntry { meta: &registry::RULE16, check: |p, _| rule16::check(p), platform: Some(Platform::S7) },
+    RuleEntry { meta: &registry::RULE17, check: |p, _| rule17::check(p), platform: Some(Platform::S7) },
+    RuleEntry { meta: &registry::RULE18, check: |p, cx| rule18::check(p, cx.policy), platform: Some(Platform::S7) },
+    RuleEntry { meta: &registry::RULE19, check: |p, _| rule19::check(p), platform: Some(Platform::S7) },
+    RuleEntry { meta: &registry::RULE20, check: |p, _| rule20::check(p), platform: None },
+    RuleEntry { meta: &registry::RULE30, check: |p, _| rule30::check(p), platform: None },
+    RuleEntry { meta: &registry::RULE31, check: |p, cx| rule31::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE32, check: |p, _| rule32::check(p), platform: None },
+    RuleEntry { meta: &registry::RULE33, check: |p, cx| rule33::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE34, check: |p, cx| rule34::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE35, check: |p, _| rule35::check(p), platform: None },
+    RuleEntry { meta: &registry::RULE36, check: |p, cx| rule36::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE37, check: |p, cx| rule37::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE38, check: |p, cx| rule38::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE39, check: |p, _| rule39::check(p), platform: None },
+    RuleEntry { meta: &registry::RULE40, check: |p, _| rule40::check(p), platform: None },
+    RuleEntry { meta: &registry::RULE41, check: |p, cx| rule41::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE42, check: rule42::check, platform: None },
+    RuleEntry { meta: &registry::RULE43, check: |p, cx| rule43::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE44, check: |p, _| rule44::check(p), platform: None },
+    RuleEntry { meta: &registry::RULE45, check: |p, cx| rule45::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE46, check: |p, _| rule46::check(p), platform: None },
+    RuleEntry { meta: &registry::RULE47, check: |p, cx| rule47::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE48, check: |p, _| rule48::check(p), platform: None },
+    RuleEntry { meta: &registry::RULE49, check: |p, cx| rule49::check(p, cx.policy), platform: None },
+    RuleEntry { meta: &registry::RULE50, check: |p, _| rule50::check(p), platform: None },
+    RuleEntry { meta: &registry::RULE51, check: |p, cx| rule51::check(p, cx.policy), platform: None },
+];
+
+/// Run every rule and collect one [`RuleResult`] per rule, in rule order.
+/// Each result is stamped with its rule number and name so passing rules
+/// can still be identified by reporters.
+pub fn run_rules(program: &Program, policy: &Policy) -> Vec<RuleResult> {
+    run_selected(program, policy, &[])
+}
+
+/// Like [`run_rules`] but only for the listed rule numbers. An empty
+/// selection means "all rules"; unknown numbers are ignored. Rules tied to
+/// another platform than the policy's are skipped.
+pub fn run_selected(program: &Program, policy: &Policy, selection: &[u8]) -> Vec<RuleResult> {
+    run_in_context(program, &AnalysisContext::new(policy), selection)
+}
+
+/// Like [`run_selected`], with the source text available to rules that
+/// look at it (e.g. annotation comments the parser dropped).
+pub fn run_in_context(program: &Program, cx: &AnalysisContext, selection: &[u8]) -> Vec<RuleResult> {
+    run_entries(RULES, program, cx, selection)
+}
+
+fn run_entries(rules: &[RuleEntry], program: &Program, cx: &AnalysisContext, selection: &[u8]) -> Vec<RuleResult> {
+    applicable(rules, cx, selection)
+        .map(|r| timed(r, || finish(r.meta.no, r.meta.name, program, cx, || (r.check)(program, cx))))
+        .collect()
+}
+
+/// Run the rules in rule order and stop at the first one that reports a
+/// violation, returning that violation. Rules after it are not run, so a
+/// yes/no gate on a large file finishes as early as possible. Platform
+/// gating applies as in [`run_rules`].
+pub fn run_until_first_violation(program: &Program, policy: &Policy) -> Option<Violation> {
+    first_violation_in_context(program, &AnalysisContext::new(policy), &[])
+}
+
+/// Like [`run_until_first_violation`], limited to the selected rules and
+/// with the source text available.
+pub fn first_violation_in_context(program: &Program, cx: &AnalysisContext, selection: &[u8]) -> Option<Violation> {
+    first_violation(RULES, program, cx, selection)
+}
+
+fn first_violation(rules: &[RuleEntry], program: &Program, cx: &AnalysisContext, selection: &[u8]) -> Option<Violation> {
+    applicable(rules, cx, selection)
+        .find_map(|r| timed(r, || finish(r.meta.no, r.meta.name, program, cx, || (r.check)(program, cx))).violations.into_iter().next())
+}
+
+/// Run one rule (see [`isolated`]) and bring its result into reporting
+/// shape: stamped with the rule, normalized, attributed to blocks, and
+/// with the findings the source suppresses dropped. Every path that runs
+/// rules, built-in or custom, goes through here.
+pub(crate) fn finish(
+    rule_no: u8,
+    name: &'static str,
+    program: &Program,
+    cx: &AnalysisContext,
+    check: impl FnOnce() -> RuleResult,
+) -> RuleResult {
+    let mut res = isolated(rule_no, name, check);
+    res.rule_no = rule_no;
+    res.rule_name = name;
+    res.normalize();
+    res.attribute(program, cx);
+    cx.suppressions.apply_to(&mut res);
+    res
+}
+
+/// Run one rule (built-in or custom), turning a panic into an `Error`
+/// result for that rule so the others still report.
+///
+/// This only works where panics unwind. `wasm32-unknown-unknown` builds
+/// with `panic = "abort"` on stable Rust, so in the browser a panicking
+/// rule still ends the whole call; the JS side sees a `RuntimeError:
+/// unreachable` and should report the analysis as failed.
+fn isolated(rule_no: u8, name: &'static str, check: impl FnOnce() -> RuleResult) -> RuleResult {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(check)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".into());
+        RuleResult::error(rule_no, name, format!("Rule {} failed: {}", rule_no, message))
+    })
+}
+
+/// The entries of `rules` that are selected and apply to the policy's platform.
+fn applicable<'a>(
+    rules: &'a [RuleEntry],
+    cx: &'a AnalysisContext,
+    selection: &'a [u8],
+) -> impl Iterator<Item = &'a RuleEntry> + 'a {
+    rules
+        .iter()
+        .filter(move |r| selection.is_empty() || selection.contains(&r.meta.no))
+        .filter(move |r| platform::applies(r.platform, cx.policy))
+}
+
+/// Run one rule inside a `rule` span and log how long it took.
+#[cfg(feature = "trace")]
+fn timed(r: &RuleEntry, check: impl FnOnce() -> RuleResult) -> RuleResult {
+    let _span = tracing::debug_span!("rule", rule_no = r.meta.no).entered();
+    let started = std::time::Instant::now();
+    let res = check();
+    tracing::debug!(rule_no = r.meta.no, elapsed_us = started.elapsed().as_micros() as u64, "rule finished");
+    res
+}
+
+#[cfg(not(feature = "trace"))]
+fn timed(_: &RuleEntry, check: impl FnOnce() -> RuleResult) -> RuleResult {
+    check()
+}
+
 /// Run all rules and print in the exact required format.
 pub fn run_all(program: &Program, policy: &Policy) {
-    macro_rules! print_res {
-        ($no:expr, $name:expr, $res:expr) => {{
-            if $res.ok {
-                println!("##Rule {}: {} -- OK", $no, $name);
-            } else {
-                for v in $res.violations {
-                    println!(
-                        "##Rule {}: {} -- NOT FOLLOWED--Line {}: {} {}",
-                        v.rule_no,
-                        v.rule_name,
-                        v.line,
-                        v.reason,
-                        v.suggestion
-                    );
-                }
+    for res in run_rules(program, policy) {
+        if res.violations.is_empty() {
+            println!("##Rule {}: {} -- {}", res.rule_no, res.rule_name, res.status.label());
+        } else {
+            for v in res.violations {
+                println!(
+                    "##Rule {}: {} -- NOT FOLLOWED--Line {}: {} {}",
+                    v.rule_no,
+                    v.rule_name,
+                    v.line,
+                    v.reason,
+                    v.suggestion
+                );
             }
-        }};
-    }
-
-    print_res!(1,  "Modularize PLC Code", rule1::check(program));
-    print_res!(2,  "Track operating modes", rule2::check(program));
-    print_res!(4,  "Use PLC flags as integrity checks", rule4::check(program));
-    print_res!(5,  "Use checksum integrity checks", rule5::check(program));
-    print_res!(6,  "Validate timers and counters", rule6::check(program, policy));
-    print_res!(7,  "Validate paired inputs/outputs", rule7::check(program, policy));
-    print_res!(8,  "Validate HMI input variables", rule8::check(program, policy));
-    print_res!(9,  "Validate indirections", rule9::check(program));
-    print_res!(10, "Assign designated register blocks", rule10::check(program, policy));
-    print_res!(11, "Plausibility Checks", rule11_12::check_rule11(program));
-    print_res!(12, "Plausibility Checks", rule11_12::check_rule12(program)); // combined
-    print_res!(15, "Define a safe restart state", rule15::check(program, policy));
-    print_res!(16, "Summarize PLC cycle times", rule16::check(program));
-    print_res!(17, "Log PLC uptime", rule17::check(program));
-    print_res!(18, "Log PLC hard stops", rule18::check(program, policy));
-    print_res!(19, "Monitor PLC memory usage", rule19::check(program));
-    print_res!(20, "Trap false alerts", rule20::check(program));
+        }
+    }
 }
 
 pub fn run_all_for_wasm(program: &Program, policy: &Policy) -> Vec<WasmRuleResult> {
-    let mut all_results = Vec::new();
-
-    macro_rules! check_and_collect {
-        ($no:expr, $name:expr, $check_fn:expr) => {
-            let result = $check_fn;
-            if result.ok {
-                all_results.push(WasmRuleResult {
-                    status: "OK".to_string(),
-                    rule_no: $no,
-                    rule_name: $name,
-                    violation: None,
-                });
-            } else {
-                // If there are multiple violations for one rule, create a result for each
-                for v in result.violations {
-                    all_results.push(WasmRuleResult {
-                        status: "NOT FOLLOWED".to_string(),
-                        rule_no: v.rule_no,
-                        rule_name: v.rule_name,
-                        violation: Some(v),
-                    });
-                }
-            }
+    to_wasm_results(run_rules(program, policy))
+}
+
+/// Fill in `snippet` for findings that did not set one from the source
+/// line they point at, so the UI does not have to re-derive it. Fixes get
+/// the columns of the statement they replace (see [`TextEdit::replace_statement`]).
+pub fn attach_snippets(results: &mut [RuleResult], source: &str) {
+    let lines: Vec<&str> = source.lines().collect();
+    for v in results.iter_mut().flat_map(|r| r.violations.iter_mut()) {
+        if v.snippet.is_none() && v.line > 0 {
+            v.snippet = lines.get(v.line - 1).map(|l| l.trim().to_string()).filter(|l| !l.is_empty());
+        }
+        v.fix = v.fix.take().and_then(|fix| fix.locate(&lines));
+    }
+}
+
+/// Explain mode: give every finding of a registered rule its `rationale`
+/// and `doc_anchor`. Custom rules outside the registry are left alone.
+pub fn explain(results: &mut [RuleResult]) {
+    for v in results.iter_mut().flat_map(|r| r.violations.iter_mut()) {
+        if let Some(meta) = registry::lookup(v.rule_no) {
+            v.rationale = Some(meta.rationale);
+            v.doc_anchor = Some(meta.doc_anchor());
+        }
+    }
+}
+
+/// Flatten rule results into the shape the browser UI consumes, ordered by
+/// (line, rule number) across all rules. Entries without a finding (passed
+/// or not applicable rules) count as line 0 and come first.
+pub fn to_wasm_results(results: Vec<RuleResult>) -> Vec<WasmRuleResult> {
+    let mut entries: Vec<WasmRuleResult> = results.iter().flat_map(WasmRuleResult::from_rule_result).collect();
+    entries.sort_by_key(|e| (e.violation.as_ref().map_or(0, |v| v.line), e.rule_no));
+    entries
+}
+
+/// Test fixture: parses `body` as the statement section of a block opened
+/// by `header`, e.g. `"FUNCTION FC_A"` or a header followed by a VAR section.
+/// The body starts on line 3 when the header is a single line.
+#[cfg(test)]
+pub(crate) fn parse_block(header: &str, body: &str) -> Program {
+    let keyword = header.split_whitespace().next().unwrap_or_default();
+    let src = format!("{}\nBEGIN\n{}\nEND_{}\n", header, body, keyword);
+    crate::parser::scl::parse_scl_from_str(&src).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_runs_only_requested_rules() {
+        let program = Program { functions: vec![] };
+        let policy = Policy::default();
+        let picked: Vec<u8> = run_selected(&program, &policy, &[10, 7]).iter().map(|r| r.rule_no).collect();
+        assert_eq!(picked, vec![7, 10]);
+        assert_eq!(run_selected(&program, &policy, &[]).len(), RULES.len());
+    }
+
+    #[test]
+    fn findings_are_sorted_by_line_and_deduplicated() {
+        let src = "FUNCTION FC_A\nBEGIN\n    Avg := Sum / Count;\n    Table[i] := Table[i] + 1;\n    Ratio := Total / Parts;\nEND_FUNCTION\n";
+        let program = crate::parser::scl::parse_scl_from_str(src).unwrap();
+        // Rule 9 reaches `Table[i]` once as the target and once in the value.
+        assert_eq!(rule9::check(&program, &Policy::default()).violations.len(), 2);
+
+        let entries = to_wasm_results(run_selected(&program, &Policy::default(), &[4, 9]));
+        let found: Vec<(u8, usize)> =
+            entries.iter().filter_map(|e| e.violation.as_ref()).map(|v| (v.rule_no, v.line)).collect();
+        assert_eq!(found, vec![(4, 3), (9, 4), (4, 5)]);
+    }
+
+    #[test]
+    fn findings_name_the_block_they_are_in() {
+        let src = "FUNCTION_BLOCK FB_Motor\nBEGIN\n    a := b / c;\n    d := e / f;\nEND_FUNCTION_BLOCK\n\
+                   ORGANIZATION_BLOCK OB1\nBEGIN\n    g := h / i;\nEND_ORGANIZATION_BLOCK\n";
+        let program = crate::parser::scl::parse_scl_from_str(src).unwrap();
+        let results = run_selected(&program, &Policy::default(), &[4]);
+        let blocks: Vec<Option<&str>> = results[0].violations.iter().map(|v| v.function.as_deref()).collect();
+        assert_eq!(blocks, vec![Some("FB_Motor"), Some("FB_Motor"), Some("OB1")]);
+
+        // In a merged project a finding above a file's first block is not
+        // put in the last block of the file before.
+        let policy = Policy::default();
+        let cx = AnalysisContext { file_starts: vec![1, 6], ..AnalysisContext::new(&policy) };
+        let mut res = RuleResult::violations(vec![Violation { line: 7, function: None, ..results[0].violations[0].clone() }]);
+        let program = Program { functions: program.functions.into_iter().take(1).collect() };
+        res.attribute(&program, &cx);
+        assert_eq!(res.violations[0].function, None);
+    }
+
+    #[test]
+    fn first_violation_stops_the_dispatch() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static LATER_RUNS: AtomicUsize = AtomicUsize::new(0);
+        let table = [
+            RuleEntry { meta: &registry::RULE2, check: |_, _| RuleResult::ok(2, ""), platform: None },
+            RuleEntry { meta: &registry::RULE15, check: |_, _| panic!("S7-only rule ran"), platform: Some(Platform::S7) },
+            RuleEntry {
+                meta: &registry::RULE16,
+                check: |_, _| {
+                    let v = |line| Violation {
+                        rule_no: 16,
+                        rule_name: registry::RULE16.name,
+                        line,
+                        end_line: None,
+                        reason: "r".into(),
+                        suggestion: "s".into(),
+                        severity: Severity::Info,
+                        snippet: None,
+                        fix: None,
+                        function: None,
+                        file: None,
+                        rationale: None,
+                        doc_anchor: None,
+                    };
+                    RuleResult::violations(vec![v(4), v(9)])
+                },
+                platform: None,
+            },
+            RuleEntry {
+                meta: &registry::RULE20,
+                check: |_, _| {
+                    LATER_RUNS.fetch_add(1, Ordering::SeqCst);
+                    RuleResult::ok(20, "")
+                },
+                platform: None,
+            },
+        ];
+        let program = Program { functions: vec![] };
+        let codesys = Policy { platform: Some("Codesys".into()), ..Default::default() };
+        let cx = AnalysisContext::new(&codesys);
+
+        let first = first_violation(&table, &program, &cx, &[]).unwrap();
+        assert_eq!((first.rule_no, first.line), (16, 4));
+        assert_eq!(LATER_RUNS.load(Ordering::SeqCst), 0);
+
+        assert!(first_violation(&table, &program, &cx, &[2, 20]).is_none());
+        assert_eq!(LATER_RUNS.load(Ordering::SeqCst), 1);
+
+        // Suppressed findings are skipped here too.
+        let source = "\n\n// plc-checker:ignore rule16\nx := 1;\n";
+        let cx = AnalysisContext::with_source(&codesys, source);
+        let first = first_violation(&table, &program, &cx, &[]).unwrap();
+        assert_eq!((first.rule_no, first.line), (16, 9));
+    }
+
+    #[test]
+    fn a_panicking_rule_becomes_an_error_entry() {
+        let table = [
+            RuleEntry { meta: &registry::RULE1, check: |_, _| panic!("index out of bounds"), platform: None },
+            RuleEntry { meta: &registry::RULE2, check: |_, _| RuleResult::ok(2, ""), platform: None },
+        ];
+        let program = Program { functions: vec![] };
+        let policy = Policy::default();
+        let results = run_entries(&table, &program, &AnalysisContext::new(&policy), &[]);
+
+        let entries = to_wasm_results(results);
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].rule_no, entries[0].status), (1, RuleStatus::Error));
+        assert_eq!(entries[0].violation.as_ref().unwrap().reason, "Rule 1 failed: index out of bounds");
+        assert_eq!((entries[1].rule_no, entries[1].status), (2, RuleStatus::Passed));
+    }
+
+    #[test]
+    fn dispatch_table_matches_the_registry() {
+        let dispatched: Vec<u8> = RULES.iter().map(|r| r.meta.no).collect();
+        let registered: Vec<u8> = registry::RULES.iter().map(|m| m.no).collect();
+        assert_eq!(dispatched, registered);
+
+        let mut names: Vec<&str> = registry::RULES.iter().map(|m| m.name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), registry::RULES.len(), "every rule has its own name");
+    }
+
+    #[test]
+    fn doc_anchors_exist_in_the_readme() {
+        let readme = include_str!("../../README.md");
+        for meta in registry::RULES {
+            assert!(readme.contains(&format!("<a id=\"{}\">", meta.doc_anchor())), "no README anchor for rule {}", meta.no);
+        }
+    }
+
+    #[test]
+    fn passing_rule_becomes_single_ok_entry() {
+        let entries = WasmRuleResult::from_rule_result(&RuleResult::ok(7, "Validate paired inputs/outputs"));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].status, RuleStatus::Passed);
+        assert_eq!(entries[0].rule_no, 7);
+        assert!(entries[0].violation.is_none());
+    }
+
+    #[test]
+    fn each_violation_becomes_its_own_entry() {
+        let v = |line| Violation {
+            rule_no: 9,
+            rule_name: "Validate indirections",
+            line,
+            end_line: None,
+            reason: "r".into(),
+            suggestion: "s".into(),
+            severity: Severity::Warning,
+            snippet: None,
+            fix: None,
+            function: None,
+            file: None,
+            rationale: None,
+            doc_anchor: None,
         };
+        let entries = WasmRuleResult::from_rule_result(&RuleResult::violations(vec![v(3), v(8)]));
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.status == RuleStatus::Violated && e.rule_no == 9));
+        assert_eq!(entries[1].violation.as_ref().unwrap().line, 8);
     }
-    check_and_collect!(1, "Modularize PLC Code", rule1::check(program));
-    check_and_collect!(2, "Track operating modes", rule2::check(program));
-    check_and_collect!(4, "Use PLC flags as integrity checks", rule4::check(program));
-    check_and_collect!(5, "Use checksum integrity checks", rule5::check(program));
-    check_and_collect!(6, "Validate timers and counters", rule6::check(program, policy));
-    check_and_collect!(7, "Validate paired inputs/outputs", rule7::check(program, policy));
-    check_and_collect!(8, "Validate HMI input variables", rule8::check(program, policy));
-    check_and_collect!(9, "Validate indirections", rule9::check(program));
-    check_and_collect!(10, "Assign designated register blocks", rule10::check(program, policy));
-    check_and_collect!(11, "Plausibility Checks", rule11_12::check_rule11(program));
-    check_and_collect!(12, "Plausibility Checks", rule11_12::check_rule12(program)); // combined
-    check_and_collect!(15, "Define a safe restart state", rule15::check(program, policy));
-    check_and_collect!(16, "Summarize PLC cycle times", rule16::check(program));
-    check_and_collect!(17, "Log PLC uptime", rule17::check(program)); 
-    check_and_collect!(18, "Log PLC hard stops", rule18::check(program, policy));
-    check_and_collect!(19, "Monitor PLC memory usage", rule19::check(program));
-    check_and_collect!(20, "Trap false alerts", rule20::check(program));
-    
-    all_results
-} 
+
+    #[test]
+    fn s7_rules_are_skipped_on_codesys() {
+        let program = Program { functions: vec![] };
+        let codesys = Policy { platform: Some("Codesys".into()), ..Default::default() };
+        let ran: Vec<u8> = run_rules(&program, &codesys).iter().map(|r| r.rule_no).collect();
+        assert!(![15, 16, 17, 18, 19].iter().any(|n| ran.contains(n)));
+        assert!(ran.contains(&20));
+
+        let ran_default: Vec<u8> = run_rules(&program, &Policy::default()).iter().map(|r| r.rule_no).collect();
+        assert!([15, 16, 17, 18, 19].iter().all(|n| ran_default.contains(n)));
+    }
+
+    #[test]
+    fn snippets_come_from_source_lines_unless_set() {
+        let v = |line, snippet: Option<&str>| Violation {
+            rule_no: 4,
+            rule_name: "Use PLC flags as integrity checks",
+            line,
+            end_line: None,
+            reason: "r".into(),
+            suggestion: "s".into(),
+            severity: Severity::Warning,
+            snippet: snippet.map(String::from),
+            fix: None,
+            function: None,
+            file: None,
+            rationale: None,
+            doc_anchor: None,
+        };
+        let mut results = vec![RuleResult::violations(vec![v(2, None), v(2, Some("a / b")), v(0, None)])];
+        attach_snippets(&mut results, "BEGIN\n    x := a / b;   \nEND\n");
+        let snippets: Vec<Option<&str>> = results[0].violations.iter().map(|v| v.snippet.as_deref()).collect();
+        assert_eq!(snippets, vec![Some("x := a / b;"), Some("a / b"), None]);
+    }
+
+    #[test]
+    fn fixes_replace_only_their_statement() {
+        let v = Violation {
+            rule_no: 15,
+            rule_name: "Define a safe restart state",
+            line: 2,
+            end_line: None,
+            reason: "r".into(),
+            suggestion: "s".into(),
+            severity: Severity::Error,
+            snippet: None,
+            fix: Some(TextEdit::replace_statement(2, 2, "x := a / b;".into(), "IF b <> 0 THEN\n    x := a / b;\nEND_IF;".into())),
+            function: None,
+            file: None,
+            rationale: None,
+            doc_anchor: None,
+        };
+        let mut results = vec![RuleResult::violations(vec![v.clone()])];
+        attach_snippets(&mut results, "BEGIN\n\ty := 1; x:=a/b; // ratio\nEND\n");
+        let fix = results[0].violations[0].fix.as_ref().unwrap();
+        assert_eq!((fix.start_line, fix.start_col, fix.end_line, fix.end_col), (2, 10, 2, 17));
+        assert_eq!(fix.replacement, "IF b <> 0 THEN\n\t    x := a / b;\n\tEND_IF;");
+
+        // A statement that cannot be found verbatim gets no fix at all.
+        let mut results = vec![RuleResult::violations(vec![v])];
+        attach_snippets(&mut results, "BEGIN\n\tx := a (* half *) / b;\nEND\n");
+        assert!(results[0].violations[0].fix.is_none());
+    }
+}
@@ -0,0 +1,129 @@
+//! Target platforms and the vendor intrinsics platform-specific rules rely
+//! on. Rules 15 to 19 look for Siemens blocks (`OB100`, `OB1_PREV_CYCLE`,
+//! `SFC6`, `OB86`, `SFC24`) that do not exist on CODESYS, so they only run
+//! when the policy's `platform` is S7 or left unset. Rule 43
+//! accepts calls to the standard instructions of the policy's platform.
+
+use super::Policy;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    S7,
+    Codesys,
+}
+
+/// Names a platform uses for its standard instructions and for the
+/// diagnostics the monitoring rules look for, upper-cased. An empty list
+/// means the platform has no known equivalent yet.
+#[derive(Debug, Clone, Copy)]
+pub struct Intrinsics {
+    /// Vendor instructions that need no definition in the program, on top
+    /// of [`IEC_INSTRUCTIONS`] (Rule 43).
+    pub instructions: &'static [&'static str],
+    /// Previous cycle time (Rule 16).
+    pub cycle_time: &'static [&'static str],
+    /// CPU runtime / uptime (Rule 17).
+    pub uptime: &'static [&'static str],
+    /// Memory usage (Rule 19).
+    pub memory: &'static [&'static str],
+}
+
+/// IEC 61131-3 functions and function blocks every platform provides,
+/// upper-cased. `<type>_TO_<type>` conversions are matched by shape (see
+/// [`is_intrinsic`]).
+pub const IEC_INSTRUCTIONS: &[&str] = &[
+    // Timers, counters, edges and flip-flops
+    "TON", "TOF", "TP", "TONR", "CTU", "CTD", "CTUD", "R_TRIG", "F_TRIG", "SR", "RS",
+    // Operators, as PLCopen FBD blocks name them
+    "ADD", "SUB", "MUL", "DIV", "MOD", "EXPT", "AND", "OR", "XOR", "NOT", "EQ", "NE", "GT", "GE", "LT", "LE",
+    // Numeric, selection and bit functions
+    "ABS", "SQRT", "LN", "LOG", "EXP", "SIN", "COS", "TAN", "ASIN", "ACOS", "ATAN", "TRUNC", "ROUND",
+    "CEIL", "FLOOR", "LIMIT", "MIN", "MAX", "SEL", "MUX", "MOVE", "SHL", "SHR", "ROL", "ROR",
+    // String functions
+    "LEN", "LEFT", "RIGHT", "MID", "CONCAT", "INSERT", "DELETE", "REPLACE", "FIND",
+];
+
+/// Elementary types conversion functions are named after, upper-cased.
+const ELEMENTARY_TYPES: &[&str] = &[
+    "BOOL", "BYTE", "WORD", "DWORD", "LWORD", "SINT", "INT", "DINT", "LINT", "USINT", "UINT", "UDINT", "ULINT",
+    "REAL", "LREAL", "TIME", "LTIME", "S5TIME", "DATE", "TOD", "LTOD", "TIME_OF_DAY", "DT", "LDT", "DATE_AND_TIME",
+    "DTL", "STRING", "WSTRING", "CHAR", "WCHAR", "BCD16", "BCD32",
+];
+
+const S7_INTRINSICS: Intrinsics = Intrinsics {
+    instructions: &[
+        // Scaling, block moves and system information
+        "NORM_X", "SCALE_X", "SCALE", "UNSCALE", "MOVE_BLK", "UMOVE_BLK", "FILL_BLK", "UFILL_BLK", "BLKMOV",
+        "RD_SINFO", "RD_SYS_T", "WR_SYS_T", "RD_LOC_T", "TEST_DB", "RUNTIME", "GET_DIAG", "GEN_DIAG",
+        // Communication and distributed I/O
+        "GET", "PUT", "TCON", "TDISCON", "TSEND", "TRCV", "TSEND_C", "TRCV_C", "DPRD_DAT", "DPWR_DAT", "RDREC", "WRREC",
+        // Control
+        "PID_COMPACT", "PID_3STEP", "CONT_C", "CONT_S", "PULSEGEN",
+    ],
+    cycle_time: &["OB1_PREV_CYCLE"],
+    uptime: &["SFC6", "RD_SINFO"],
+    memory: &["SFC24", "TEST_DB"],
+};
+
+const CODESYS_INTRINSICS: Intrinsics = Intrinsics {
+    instructions: &[],
+    cycle_time: &[],
+    uptime: &[],
+    memory: &[],
+};
+
+impl Platform {
+    /// Case-insensitive lookup of a policy `platform` value.
+    pub fn parse(name: &str) -> Option<Platform> {
+        match name.trim().to_ascii_uppercase().as_str() {
+            "S7" => Some(Platform::S7),
+            "CODESYS" => Some(Platform::Codesys),
+            _ => None,
+        }
+    }
+
+    /// The policy's `platform`, if it names one.
+    pub fn of(policy: &Policy) -> Option<Platform> {
+        policy.platform.as_deref().and_then(Platform::parse)
+    }
+
+    pub fn intrinsics(self) -> Intrinsics {
+        match self {
+            Platform::S7 => S7_INTRINSICS,
+            Platform::Codesys => CODESYS_INTRINSICS,
+        }
+    }
+}
+
+/// Whether a rule tied to `required` should run under `policy`. Rules with
+/// no platform always run; so does everything when the policy does not name
+/// a platform, which keeps the default behaviour unchanged.
+pub fn applies(required: Option<Platform>, policy: &Policy) -> bool {
+    match (required, policy.platform.as_deref()) {
+        (None, _) | (_, None) => true,
+        (Some(p), Some(name)) => Platform::parse(name) == Some(p),
+    }
+}
+
+/// True for standard instructions on `platform`, or on any known platform
+/// when it is `None`: the [`IEC_INSTRUCTIONS`], the platform's own
+/// [`Intrinsics::instructions`], conversions between elementary types
+/// (`INT_TO_REAL`) and, on S7, numbered system blocks (`SFC6`, `SFB4`).
+pub fn is_intrinsic(name: &str, platform: Option<Platform>) -> bool {
+    let key = super::utils::ident_key(name);
+    let platforms = match platform {
+        Some(p) => vec![p],
+        None => vec![Platform::S7, Platform::Codesys],
+    };
+    let numbered = platforms.contains(&Platform::S7)
+        && ["SFC", "SFB"]
+            .iter()
+            .any(|p| key.strip_prefix(p).is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit())));
+    let conversion = key
+        .split_once("_TO_")
+        .is_some_and(|(from, to)| ELEMENTARY_TYPES.contains(&from) && ELEMENTARY_TYPES.contains(&to));
+    numbered
+        || conversion
+        || IEC_INSTRUCTIONS.contains(&key.as_str())
+        || platforms.iter().any(|p| p.intrinsics().instructions.contains(&key.as_str()))
+}
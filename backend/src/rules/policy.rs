@@ -1,8 +1,10 @@
-use serde::Deserialize;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Default, Deserialize)]
-#[serde(deny_unknown_fields)]
+use super::utils::{parse_mem_address, DEFAULT_ANNOTATIONS, DEFAULT_ANNOTATION_DEPTH};
 
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Policy {
     /// Pairs for Rule 7 that must not be active simultaneously.
     pub pairs: Option<Vec<[String; 2]>>,
@@ -10,19 +12,305 @@ pub struct Policy {
     pub memory_areas: Option<Vec<MemoryArea>>,
     /// Target platform, e.g. "S7" or "Codesys". Used to gate platform-specific rules.
     pub platform: Option<String>,
+    /// Annotation search used by Rules 8, 11 and 12.
+    pub plausibility: Option<PlausibilityPolicy>,
+    /// Fault OBs Rule 18 requires, e.g. `["OB82", "OB83", "OB85", "OB86", "OB121"]`.
+    /// Defaults to OB82, OB86 and OB121.
+    pub hard_stop_obs: Option<Vec<String>>,
+    /// The code is a reusable library rather than a complete project, so
+    /// the absence of project-level OBs (OB100 for Rule 15, the fault OBs
+    /// for Rule 18) is not reported. OBs that are present are still checked.
+    pub standalone_library: Option<bool>,
+    /// Naming conventions for Rule 36, e.g.
+    /// `[{ "kind": "FB", "pattern": "FB_.*" }]`.
+    pub naming: Option<Vec<NamingRule>>,
+    /// Deepest IF/CASE/loop nesting Rule 38 allows in a block (default 6).
+    pub max_nesting: Option<usize>,
+    /// Extra raw analog input names for Rule 41, as regular expressions
+    /// the whole name must match, e.g. `["PT\\d+_Counts"]`; case-insensitive.
+    pub raw_analog_patterns: Option<Vec<String>>,
+    /// Comment markers Rule 42 reports besides TODO, FIXME and HACK,
+    /// e.g. `["XXX", "TBD"]`; case-insensitive.
+    pub comment_markers: Option<Vec<String>>,
+    /// Blocks Rule 43 treats as defined outside the analysed program,
+    /// e.g. vendor library blocks like `["LGF_Ramp"]`; case-insensitive.
+    pub external_blocks: Option<Vec<String>>,
+    /// Blocks Rule 45 accepts as ramping a setpoint besides those whose
+    /// name contains RAMP or LIMIT, e.g. `["FB_SoftStart"]`; case-insensitive.
+    pub ramp_functions: Option<Vec<String>>,
+    /// Extra retentive names for Rule 47, as regular expressions the whole
+    /// name must match, e.g. `["\"DB_Persist\"\\..*"]`; case-insensitive.
+    pub retain_patterns: Option<Vec<String>>,
+    /// Extra emergency-stop input names for Rule 49, as regular expressions
+    /// the whole name must match, e.g. `["Emergency_.*"]`; case-insensitive.
+    pub estop_patterns: Option<Vec<String>>,
+    /// Name fragments that mark a critical output and an actuator besides
+    /// the built-in ones, e.g. `["VALVE", "HEATER"]`; case-insensitive.
+    /// See `utils::is_identifier_class`.
+    pub critical_output_keywords: Option<Vec<String>>,
+    /// Name fragments that mark an operator-supplied source, and an HMI
+    /// input, besides the built-in ones, e.g. `["SCADA"]`; case-insensitive.
+    pub sensitive_source_keywords: Option<Vec<String>>,
+    /// Extra sensor value names for Rule 51, as regular expressions the
+    /// whole name must match, e.g. `["TT\\d+"]`; case-insensitive.
+    pub sensor_patterns: Option<Vec<String>>,
+    /// Fault flag suffixes Rule 51 accepts besides `_FAULT`, `_ERR`,
+    /// `_ERROR` and `_FAILED`, e.g. `["_ALM"]`; case-insensitive.
+    pub fault_flag_suffixes: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Names of one kind of block or declaration must match `pattern`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct NamingRule {
+    /// Block kind ("FB", "FC", "OB", "DB", "Program") or declaration
+    /// section ("input", "output", "in_out"); case-insensitive.
+    pub kind: String,
+    /// Regular expression the whole name must match, e.g. "FB_.*".
+    pub pattern: String,
+}
+
+/// The `kind` values a [`NamingRule`] accepts.
+pub const NAMING_KINDS: &[&str] = &["FB", "FC", "OB", "DB", "PROGRAM", "INPUT", "OUTPUT", "IN_OUT"];
+
+impl NamingRule {
+    /// `pattern` anchored so that it must match the whole name.
+    pub fn regex(&self) -> Result<Regex, String> {
+        Regex::new(&format!("^(?:{})$", self.pattern))
+            .map_err(|e| format!("Invalid naming pattern '{}': {}", self.pattern, e))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlausibilityPolicy {
+    /// How many lines above a statement an annotation may sit (default 3).
+    pub search_depth: Option<usize>,
+    /// Extra annotation tokens accepted besides `@PlausibilityCheck` and
+    /// `@Validation`, e.g. `["@RangeChecked"]`.
+    pub annotations: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct MemoryArea {
-    /// Address range, e.g. "%MW100-%MW200"
+    /// Address range, e.g. "%MW100-%MW200". Several non-contiguous ranges
+    /// may be listed separated by commas: "%MW100-%MW200,%MW300-%MW350".
     pub address: String,
     /// Access policy: "ReadOnly" | "ReadWrite"
     pub access: String,
 }
 
-/// Example policy JSON embedded as a constant (not in comments).
+/// One contiguous range of a [`MemoryArea`], e.g. `%MW100-%MW200`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressRange {
+    /// Area prefix including the `%`, e.g. "%MW".
+    pub area: String,
+    pub start: i64,
+    pub end: i64,
+}
+
+impl AddressRange {
+    pub fn contains(&self, area: &str, addr: i64) -> bool {
+        self.area.eq_ignore_ascii_case(area) && addr >= self.start && addr <= self.end
+    }
+}
+
+impl MemoryArea {
+    /// Split `address` into its ranges. A single address (`%MW10`) is a
+    /// one-element range. Fails when the start and end of a range use
+    /// different area prefixes (`%MW100-%DB200`) or are not addresses.
+    pub fn ranges(&self) -> Result<Vec<AddressRange>, String> {
+        let mut out = Vec::new();
+        for part in self.address.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (lo, hi) = match part.split_once('-') {
+                Some((lo, hi)) => (lo.trim(), hi.trim()),
+                None => (part, part),
+            };
+            let (start_area, start) = parse_mem_address(lo)
+                .ok_or_else(|| format!("Invalid address '{}' in memory area '{}'", lo, self.address))?;
+            let (end_area, end) = parse_mem_address(hi)
+                .ok_or_else(|| format!("Invalid address '{}' in memory area '{}'", hi, self.address))?;
+            if !start_area.eq_ignore_ascii_case(&end_area) {
+                return Err(format!(
+                    "Address range '{}' mixes area prefixes {} and {}",
+                    part, start_area, end_area
+                ));
+            }
+            if start > end {
+                return Err(format!("Address range '{}' ends before it starts", part));
+            }
+            out.push(AddressRange { area: start_area, start, end });
+        }
+        if out.is_empty() {
+            return Err(format!("Memory area '{}' has no address ranges", self.address));
+        }
+        Ok(out)
+    }
+}
+
+/// How [`Policy::merge`] combines a list set in both policies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListMerge {
+    /// The overlay's list replaces the base's.
+    #[default]
+    Replace,
+    /// The overlay's entries are added after the base's. An entry the
+    /// base already has keeps its place and takes the overlay's value.
+    Append,
+}
+
+impl Policy {
+    /// Lay `overlay` over `base`, field by field. A setting the overlay
+    /// leaves out keeps the base's value. A scalar it sets (`platform`,
+    /// `standalone_library`, `max_nesting`, `plausibility.search_depth`)
+    /// wins. A list it sets is combined as `lists` says. When appending,
+    /// `pairs` match regardless of order and case. An overlay memory area
+    /// replaces the base area with the same address in place, so an
+    /// overlay can change an area's access.
+    pub fn merge(base: Policy, overlay: Policy, lists: ListMerge) -> Policy {
+        fn list<T>(base: Option<Vec<T>>, overlay: Option<Vec<T>>, lists: ListMerge, same: impl Fn(&T, &T) -> bool) -> Option<Vec<T>> {
+            match (base, overlay, lists) {
+                (Some(mut base), Some(overlay), ListMerge::Append) => {
+                    for o in overlay {
+                        match base.iter().position(|b| same(b, &o)) {
+                            Some(i) => base[i] = o,
+                            None => base.push(o),
+                        }
+                    }
+                    Some(base)
+                }
+                (base, overlay, _) => overlay.or(base),
+            }
+        }
+        let eq = |a: &String, b: &String| a.eq_ignore_ascii_case(b);
+        let plausibility = match (base.plausibility, overlay.plausibility) {
+            (Some(b), Some(o)) => Some(PlausibilityPolicy {
+                search_depth: o.search_depth.or(b.search_depth),
+                annotations: list(b.annotations, o.annotations, lists, eq),
+            }),
+            (b, o) => o.or(b),
+        };
+        Policy {
+            pairs: list(base.pairs, overlay.pairs, lists, |a, b| {
+                (eq(&a[0], &b[0]) && eq(&a[1], &b[1])) || (eq(&a[0], &b[1]) && eq(&a[1], &b[0]))
+            }),
+            memory_areas: list(base.memory_areas, overlay.memory_areas, lists, |a, b| {
+                a.address.replace(' ', "").eq_ignore_ascii_case(&b.address.replace(' ', ""))
+            }),
+            platform: overlay.platform.or(base.platform),
+            plausibility,
+            hard_stop_obs: list(base.hard_stop_obs, overlay.hard_stop_obs, lists, eq),
+            standalone_library: overlay.standalone_library.or(base.standalone_library),
+            naming: list(base.naming, overlay.naming, lists, |a, b| a == b),
+            max_nesting: overlay.max_nesting.or(base.max_nesting),
+            raw_analog_patterns: list(base.raw_analog_patterns, overlay.raw_analog_patterns, lists, |a, b| a == b),
+            comment_markers: list(base.comment_markers, overlay.comment_markers, lists, eq),
+            external_blocks: list(base.external_blocks, overlay.external_blocks, lists, eq),
+            ramp_functions: list(base.ramp_functions, overlay.ramp_functions, lists, eq),
+            retain_patterns: list(base.retain_patterns, overlay.retain_patterns, lists, |a, b| a == b),
+            estop_patterns: list(base.estop_patterns, overlay.estop_patterns, lists, |a, b| a == b),
+            critical_output_keywords: list(base.critical_output_keywords, overlay.critical_output_keywords, lists, eq),
+            sensitive_source_keywords: list(base.sensitive_source_keywords, overlay.sensitive_source_keywords, lists, eq),
+            sensor_patterns: list(base.sensor_patterns, overlay.sensor_patterns, lists, |a, b| a == b),
+            fault_flag_suffixes: list(base.fault_flag_suffixes, overlay.fault_flag_suffixes, lists, eq),
+        }
+    }
 
+    /// The policy as the rules apply it: every setting the rules fall back
+    /// on when it is missing is filled in. `platform` stays empty when
+    /// unset, which means no rules are skipped for platform reasons.
+    pub fn effective(&self) -> Policy {
+        let plausibility = self.plausibility.clone().unwrap_or_default();
+        let mut annotations: Vec<String> = DEFAULT_ANNOTATIONS.iter().map(|t| t.to_string()).collect();
+        annotations.extend(plausibility.annotations.into_iter().flatten().map(|t| t.to_ascii_uppercase()));
+        Policy {
+            pairs: Some(self.pairs.clone().unwrap_or_default()),
+            memory_areas: Some(self.memory_areas.clone().unwrap_or_default()),
+            platform: self.platform.clone(),
+            plausibility: Some(PlausibilityPolicy {
+                search_depth: Some(plausibility.search_depth.unwrap_or(DEFAULT_ANNOTATION_DEPTH)),
+                annotations: Some(annotations),
+            }),
+            hard_stop_obs: Some(self.hard_stop_obs.clone().unwrap_or_else(|| {
+                super::rule18::DEFAULT_OBS.iter().map(|ob| ob.to_string()).collect()
+            })),
+            standalone_library: Some(self.is_standalone_library()),
+            naming: Some(self.naming.clone().unwrap_or_default()),
+            max_nesting: Some(self.max_nesting.unwrap_or(super::rule38::DEFAULT_MAX_NESTING)),
+            raw_analog_patterns: Some(self.raw_analog_patterns.clone().unwrap_or_default()),
+            comment_markers: Some(self.comment_markers.clone().unwrap_or_default()),
+            external_blocks: Some(self.external_blocks.clone().unwrap_or_default()),
+            ramp_functions: Some(self.ramp_functions.clone().unwrap_or_default()),
+            retain_patterns: Some(self.retain_patterns.clone().unwrap_or_default()),
+            estop_patterns: Some(self.estop_patterns.clone().unwrap_or_default()),
+            critical_output_keywords: Some(self.critical_output_keywords.clone().unwrap_or_default()),
+            sensitive_source_keywords: Some(self.sensitive_source_keywords.clone().unwrap_or_default()),
+            sensor_patterns: Some(self.sensor_patterns.clone().unwrap_or_default()),
+            fault_flag_suffixes: Some(self.fault_flag_suffixes.clone().unwrap_or_default()),
+        }
+    }
+
+    pub fn is_standalone_library(&self) -> bool {
+        self.standalone_library.unwrap_or(false)
+    }
+
+    /// Semantic checks that serde cannot express (e.g. address prefixes).
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(name) = self.platform.as_deref().filter(|n| super::Platform::parse(n).is_none()) {
+            return Err(format!("Unknown platform '{}' (expected S7 or Codesys)", name));
+        }
+        for area in self.memory_areas.iter().flatten() {
+            area.ranges()?;
+        }
+        for rule in self.naming.iter().flatten() {
+            if !NAMING_KINDS.iter().any(|k| k.eq_ignore_ascii_case(&rule.kind)) {
+                return Err(format!("Unknown naming kind '{}' (expected one of {})", rule.kind, NAMING_KINDS.join(", ")));
+            }
+            rule.regex()?;
+        }
+        name_regexes(&self.raw_analog_patterns, "raw_analog_patterns")?;
+        name_regexes(&self.retain_patterns, "retain_patterns")?;
+        name_regexes(&self.estop_patterns, "estop_patterns")?;
+        name_regexes(&self.sensor_patterns, "sensor_patterns")?;
+        if self.comment_markers.iter().flatten().any(|m| m.trim().is_empty()) {
+            return Err("comment_markers must not contain empty markers".into());
+        }
+        let mut keywords = self.critical_output_keywords.iter().chain(&self.sensitive_source_keywords).flatten();
+        if keywords.any(|k| k.trim().is_empty()) {
+            return Err("critical_output_keywords and sensitive_source_keywords must not contain empty keywords".into());
+        }
+        if self.fault_flag_suffixes.iter().flatten().any(|s| s.trim().is_empty()) {
+            return Err("fault_flag_suffixes must not contain empty suffixes".into());
+        }
+        if self.max_nesting == Some(0) {
+            return Err("max_nesting must be at least 1".into());
+        }
+        for ob in self.hard_stop_obs.iter().flatten() {
+            if super::rule18::fault_ob(ob).is_none() {
+                return Err(format!(
+                    "Unknown hard-stop OB '{}' (expected one of {})",
+                    ob,
+                    super::rule18::FAULT_OBS.iter().map(|(n, _, _)| *n).collect::<Vec<_>>().join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compile one of the policy's name pattern lists (`raw_analog_patterns`,
+/// `sensor_patterns`, ...). Each pattern must match the whole name and,
+/// like SCL identifiers, ignores case; `field` names the list in errors.
+pub fn name_regexes(patterns: &Option<Vec<String>>, field: &str) -> Result<Vec<Regex>, String> {
+    patterns
+        .iter()
+        .flatten()
+        .map(|p| Regex::new(&format!("(?i)^(?:{})$", p)).map_err(|e| format!("Invalid pattern '{}' in {}: {}", p, field, e)))
+        .collect()
+}
+
+/// Example policy JSON embedded as a constant (not in comments).
 pub const EXAMPLE_POLICY_JSON: &str = r#"{
   "pairs": [
     ["Motor_Fwd", "Motor_Rev"],
@@ -30,9 +318,9 @@ pub const EXAMPLE_POLICY_JSON: &str = r#"{
   ],
   "memory_areas": [
     { "address": "%MW100-%MW200", "access": "ReadOnly" },
-    { "address": "%M50-%M80",     "access": "ReadWrite" },
+    { "address": "%M50-%M80",     "access": "ReadWrite" }
+  ],
   "platform": "S7"
-  ]
 }"#;
 
 /// Parse a policy JSON string into a Policy structure. Returns
@@ -43,17 +331,227 @@ pub const EXAMPLE_POLICY_JSON: &str = r#"{
 /// underlying serde parser. Consumers can use this to surface errors
 /// back to the user instead of failing silently.
 pub fn parse_policy_from_text(s: &str) -> Result<Policy, String> {
-    serde_json::from_str::<Policy>(s).map_err(|e| format!("Invalid policy JSON: {}", e))
+    let policy = serde_json::from_str::<Policy>(s).map_err(|e| {
+        let message = e.to_string();
+        format!("Invalid policy JSON: {}", describe_unknown_field(&message).unwrap_or(message))
+    })?;
+    policy.validate().map_err(|e| format!("Invalid policy: {}", e))?;
+    Ok(policy)
+}
+
+/// Parse a policy written in TOML. The fields are those of the JSON form,
+/// so a policy reads the same in either format:
+///
+/// ```toml
+/// platform = "S7"
+/// pairs = [["Motor_Fwd", "Motor_Rev"]]
+///
+/// [[memory_areas]]
+/// address = "%MW100-%MW200"
+/// access = "ReadOnly"
+/// ```
+#[cfg(feature = "toml")]
+pub fn parse_policy_from_toml(s: &str) -> Result<Policy, String> {
+    let policy = toml::from_str::<Policy>(s).map_err(|e| {
+        let message = match describe_unknown_field(e.message()) {
+            Some(described) => e.to_string().replace(e.message(), &described),
+            None => e.to_string(),
+        };
+        format!("Invalid policy TOML: {}", message)
+    })?;
+    policy.validate().map_err(|e| format!("Invalid policy: {}", e))?;
+    Ok(policy)
+}
+
+#[cfg(not(feature = "toml"))]
+pub fn parse_policy_from_toml(_: &str) -> Result<Policy, String> {
+    Err("TOML policies are not supported by this build (enable the toml feature)".into())
+}
+
+/// serde's `unknown field `pairz`, expected one of `pairs`, ...` rewritten
+/// to name the field and list the valid ones plainly. Text after the list
+/// (serde_json's ` at line 3 column 9`) is kept. `None` for any other
+/// message.
+fn describe_unknown_field(message: &str) -> Option<String> {
+    let (field, rest) = message.strip_prefix("unknown field `")?.split_once('`')?;
+    let parts: Vec<&str> = rest.split('`').collect();
+    if parts.len() < 3 {
+        return None;
+    }
+    let valid: Vec<&str> = parts.iter().skip(1).step_by(2).copied().collect();
+    Some(format!("unknown field '{}'{}; valid fields are {}", field, parts[parts.len() - 1], valid.join(", ")))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_and_json_policies_agree() {
+        let toml = r#"
+            # Interlocked outputs
+            pairs = [["Motor_Fwd", "Motor_Rev"], ["Valve_Open", "Valve_Close"]]
+            platform = "S7"
+            max_nesting = 4
+            comment_markers = ["XXX"]
+
+            [plausibility]
+            search_depth = 5
+
+            [[memory_areas]]
+            address = "%MW100-%MW200"
+            access = "ReadOnly"
+
+            [[naming]]
+            kind = "FB"
+            pattern = "FB_.*"
+        "#;
+        let json = r#"{
+            "pairs": [["Motor_Fwd", "Motor_Rev"], ["Valve_Open", "Valve_Close"]],
+            "platform": "S7",
+            "max_nesting": 4,
+            "comment_markers": ["XXX"],
+            "plausibility": { "search_depth": 5 },
+            "memory_areas": [{ "address": "%MW100-%MW200", "access": "ReadOnly" }],
+            "naming": [{ "kind": "FB", "pattern": "FB_.*" }]
+        }"#;
+        assert_eq!(parse_policy_from_toml(toml).unwrap(), parse_policy_from_text(json).unwrap());
+
+        assert!(parse_policy_from_toml("max_nesting = 0").unwrap_err().starts_with("Invalid policy: "));
+        assert!(parse_policy_from_toml("unknown = 1").unwrap_err().starts_with("Invalid policy TOML: "));
+        assert!(parse_policy_from_toml("unknown = 1").unwrap_err().contains("unknown field 'unknown'; valid fields are pairs, "));
+    }
+
+    #[test]
+    fn overlay_replaces_present_fields() {
+        let base = parse_policy_from_text(
+            r#"{ "platform": "S7", "max_nesting": 4, "pairs": [["A", "B"]], "hard_stop_obs": ["OB82"],
+                 "plausibility": { "search_depth": 5, "annotations": ["@Checked"] } }"#,
+        )
+        .unwrap();
+        let overlay = parse_policy_from_text(
+            r#"{ "max_nesting": 8, "pairs": [["C", "D"]], "plausibility": { "annotations": ["@Ranged"] } }"#,
+        )
+        .unwrap();
+        let merged = Policy::merge(base, overlay, ListMerge::Replace);
+        assert_eq!(merged.platform.as_deref(), Some("S7"));
+        assert_eq!(merged.max_nesting, Some(8));
+        assert_eq!(merged.pairs, Some(vec![["C".to_string(), "D".to_string()]]));
+        assert_eq!(merged.hard_stop_obs, Some(vec!["OB82".to_string()]));
+        let plausibility = merged.plausibility.unwrap();
+        assert_eq!(plausibility.search_depth, Some(5));
+        assert_eq!(plausibility.annotations, Some(vec!["@Ranged".to_string()]));
+    }
+
+    #[test]
+    fn overlay_appends_lists() {
+        let base = Policy {
+            pairs: Some(vec![["A".into(), "B".into()]]),
+            memory_areas: Some(vec![area("%MW300"), area("%MW100-%MW200")]),
+            external_blocks: Some(vec!["LGF_Ramp".into()]),
+            ..Default::default()
+        };
+        let overlay = Policy {
+            pairs: Some(vec![["b".into(), "a".into()], ["C".into(), "D".into()]]),
+            memory_areas: Some(vec![MemoryArea { address: "%MW300".into(), access: "ReadWrite".into() }]),
+            comment_markers: Some(vec!["TBD".into()]),
+            ..Default::default()
+        };
+        let merged = Policy::merge(base, overlay, ListMerge::Append);
+        let pairs: Vec<String> = merged.pairs.unwrap().iter().map(|p| p.join("/")).collect();
+        assert_eq!(pairs, vec!["b/a", "C/D"]);
+        let areas: Vec<(String, String)> = merged.memory_areas.unwrap().into_iter().map(|a| (a.address, a.access)).collect();
+        assert_eq!(
+            areas,
+            vec![("%MW300".into(), "ReadWrite".into()), ("%MW100-%MW200".into(), "ReadOnly".into())]
+        );
+        assert_eq!(merged.external_blocks, Some(vec!["LGF_Ramp".to_string()]));
+        assert_eq!(merged.comment_markers, Some(vec!["TBD".to_string()]));
+    }
+
     #[test]
     fn example_policy_json_parses() {
         let p: Policy = serde_json::from_str(EXAMPLE_POLICY_JSON).unwrap();
-        assert!(p.pairs.as_ref().unwrap().len() >= 1);
-        assert!(p.memory_areas.as_ref().unwrap().len() >= 1);
+        assert!(!p.pairs.as_ref().unwrap().is_empty());
+        assert!(!p.memory_areas.as_ref().unwrap().is_empty());
+    }
+
+    fn area(address: &str) -> MemoryArea {
+        MemoryArea { address: address.into(), access: "ReadOnly".into() }
+    }
+
+    #[test]
+    fn multi_range_area_applies_to_each_range() {
+        let ranges = area("%MW100-%MW200, %MW300-%MW350").ranges().unwrap();
+        assert_eq!(ranges.len(), 2);
+        let applies = |a: &str, addr: i64| ranges.iter().any(|r| r.contains(a, addr));
+        assert!(applies("%MW", 150));
+        assert!(applies("%MW", 320));
+        assert!(!applies("%MW", 250));
+        assert!(!applies("%M", 150));
+    }
+
+    #[test]
+    fn mismatched_prefixes_are_rejected() {
+        assert!(area("%MW100-%DB200").ranges().is_err());
+        let err = parse_policy_from_text(
+            r#"{ "memory_areas": [ { "address": "%MW100-%DB200", "access": "ReadOnly" } ] }"#,
+        )
+        .unwrap_err();
+        assert!(err.contains("%MW") && err.contains("%DB"));
+    }
+
+    #[test]
+    fn effective_policy_fills_in_defaults() {
+        let p = parse_policy_from_text(r#"{ "plausibility": { "annotations": ["@RangeChecked"] } }"#).unwrap();
+        let json = serde_json::to_value(p.effective()).unwrap();
+        assert_eq!(json["pairs"], serde_json::json!([]));
+        assert_eq!(json["plausibility"]["search_depth"], 3);
+        assert_eq!(json["plausibility"]["annotations"], serde_json::json!(["@PLAUSIBILITYCHECK", "@VALIDATION", "@RANGECHECKED"]));
+        assert_eq!(json["hard_stop_obs"], serde_json::json!(["OB86", "OB121", "OB82"]));
+        assert_eq!(json["max_nesting"], 6);
+        assert!(json["platform"].is_null());
+    }
+
+    #[test]
+    fn naming_rules_are_validated() {
+        assert!(parse_policy_from_text(r#"{ "naming": [{ "kind": "fb", "pattern": "FB_.*" }] }"#).is_ok());
+        let err = parse_policy_from_text(r#"{ "naming": [{ "kind": "UDT", "pattern": ".*" }] }"#).unwrap_err();
+        assert!(err.contains("'UDT'"));
+        let err = parse_policy_from_text(r#"{ "naming": [{ "kind": "FB", "pattern": "FB_(" }] }"#).unwrap_err();
+        assert!(err.contains("Invalid naming pattern 'FB_('"));
+    }
+
+    #[test]
+    fn name_patterns_match_whole_names_ignoring_case() {
+        let res = name_regexes(&Some(vec!["TT\\d+".into()]), "sensor_patterns").unwrap();
+        assert!(res[0].is_match("tt101") && res[0].is_match("TT101"));
+        assert!(!res[0].is_match("TT101_Scaled"));
+        let err = parse_policy_from_text(r#"{ "sensor_patterns": ["TT("] }"#).unwrap_err();
+        assert!(err.contains("Invalid pattern 'TT(' in sensor_patterns"), "{}", err);
+    }
+
+    #[test]
+    fn unknown_fields_are_named_with_the_valid_ones() {
+        let err = parse_policy_from_text(r#"{ "plausibility": { "depth": 2 } }"#).unwrap_err();
+        assert_eq!(
+            err,
+            "Invalid policy JSON: unknown field 'depth' at line 1 column 27; valid fields are search_depth, annotations"
+        );
+    }
+
+    #[test]
+    fn unknown_platform_is_rejected() {
+        assert!(parse_policy_from_text(r#"{ "platform": "codesys" }"#).is_ok());
+        let err = parse_policy_from_text(r#"{ "platform": "S7-1500" }"#).unwrap_err();
+        assert!(err.contains("Unknown platform 'S7-1500'"), "{}", err);
+    }
+
+    #[test]
+    fn unknown_hard_stop_ob_is_rejected() {
+        assert!(parse_policy_from_text(r#"{ "hard_stop_obs": ["OB83", "ob85"] }"#).is_ok());
+        let err = parse_policy_from_text(r#"{ "hard_stop_obs": ["OB99"] }"#).unwrap_err();
+        assert!(err.contains("OB99"));
     }
 }
@@ -0,0 +1,248 @@
+//! Identifier redaction for reports shared outside the site.
+//!
+//! Every name the program defines or uses (blocks, declarations, each
+//! part of `Motor.Speed`, called blocks other than standard instructions)
+//! is replaced in the findings' text, and the file name, by a token such
+//! as `ID_3f9a01c2d4e5b607`. The token is a keyed hash (HMAC-SHA256) of
+//! the upper-cased name under a salt, so the same tag gets the same token
+//! in every finding and file of a report, and nobody without the salt can
+//! recover a tag by hashing likely names. Reports made with the same salt
+//! share tokens; without a configured salt each redaction draws its own.
+//! Names are matched as whole words ignoring case, as SCL does, while line
+//! numbers and rule information are left as they are.
+
+use std::collections::HashSet;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::ast::{Expression, Program, Statement};
+use super::visit::{walk_expression, walk_function, walk_statement, Visitor};
+use super::{platform, utils, RuleResult};
+
+#[derive(Clone)]
+pub struct Redaction {
+    /// Keys (see [`utils::ident_key`]) of the names to replace.
+    names: HashSet<String>,
+    salt: Vec<u8>,
+}
+
+impl Redaction {
+    /// Redact the names of `program`, hashing them under `salt`. Pass the
+    /// same salt to get matching tokens across reports, or
+    /// [`random_salt`] for tokens that match only within this one.
+    pub fn from_program(program: &Program, salt: &[u8]) -> Self {
+        let mut names = Names::default();
+        for f in &program.functions {
+            names.add(&f.name);
+            f.declarations.iter().for_each(|d| names.add(&d.name));
+            walk_function(&mut names, f);
+        }
+        Redaction { names: names.0, salt: salt.to_vec() }
+    }
+
+    /// The token for `name`: the first 8 bytes of HMAC-SHA256 of its
+    /// upper-cased form, keyed by the salt.
+    pub fn token(&self, name: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.salt).expect("HMAC takes keys of any length");
+        mac.update(utils::ident_key(name).as_bytes());
+        let digest = mac.finalize().into_bytes();
+        let hex: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        format!("ID_{}", hex)
+    }
+
+    /// `file_name` with its stem replaced by a token; the extension is kept
+    /// so readers still see which frontend applied.
+    pub fn file_name(&self, file_name: &str) -> String {
+        match file_name.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() && !ext.contains(['/', '\\']) => format!("{}.{}", self.token(stem), ext),
+            _ => self.token(file_name),
+        }
+    }
+
+    /// Replace the names in each finding's reason, suggestion, snippet,
+    /// fix and block name, and its file name.
+    pub fn apply(&self, results: &mut [RuleResult]) {
+        for v in results.iter_mut().flat_map(|r| r.violations.iter_mut()) {
+            self.redact(&mut v.reason);
+            self.redact(&mut v.suggestion);
+            v.snippet.iter_mut().for_each(|s| self.redact(s));
+            v.function.iter_mut().for_each(|s| self.redact(s));
+            if let Some(fix) = v.fix.as_mut() {
+                self.redact(&mut fix.replacement);
+            }
+            if let Some(file) = v.file.as_mut() {
+                *file = self.file_name(file);
+            }
+        }
+    }
+
+    /// Replace every word of `text` that is one of the names.
+    fn redact(&self, text: &mut String) {
+        if self.names.is_empty() {
+            return;
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text.as_str();
+        while let Some(start) = rest.find(is_word_char) {
+            out.push_str(&rest[..start]);
+            let len = rest[start..].find(|c| !is_word_char(c)).unwrap_or(rest.len() - start);
+            let word = &rest[start..start + len];
+            if self.names.contains(&utils::ident_key(word)) {
+                out.push_str(&self.token(word));
+            } else {
+                out.push_str(word);
+            }
+            rest = &rest[start + len..];
+        }
+        out.push_str(rest);
+        *text = out;
+    }
+}
+
+/// A fresh salt for one report. Native builds seed it from the operating
+/// system; WASM has no such source, so embedders there should configure
+/// their own salt.
+pub fn random_salt() -> Vec<u8> {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+    (0..2).flat_map(|_| RandomState::new().build_hasher().finish().to_le_bytes()).collect()
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Every name as keys, split into the words the tokenizer sees: the
+/// dotted parts, and the words of a quoted name such as `"Tank Level"`.
+#[derive(Default)]
+struct Names(HashSet<String>);
+
+impl Names {
+    fn add(&mut self, name: &str) {
+        for word in name.split(|c| !is_word_char(c)).filter(|w| !w.is_empty()) {
+            self.0.insert(utils::ident_key(word));
+        }
+    }
+
+    fn add_call(&mut self, name: &str) {
+        if !platform::is_intrinsic(name, None) {
+            self.add(name);
+        }
+    }
+}
+
+impl Visitor for Names {
+    fn visit_statement(&mut self, st: &Statement) {
+        match st {
+            // Parameters of standard instructions (`IN`, `PT`) are not site names.
+            Statement::Call { name, args, .. } if !platform::is_intrinsic(name, None) => {
+                self.add(name);
+                args.iter().filter(|(p, _)| !p.is_empty()).for_each(|(p, _)| self.add(p));
+            }
+            Statement::ForStmt { variable, .. } => self.add(variable),
+            _ => {}
+        }
+        walk_statement(self, st);
+    }
+
+    fn visit_expression(&mut self, e: &Expression) {
+        match e {
+            Expression::Identifier(name) if !name.starts_with('%') => self.add(name),
+            Expression::Member { field, .. } => self.add(field),
+            Expression::FuncCall { name, .. } => self.add_call(name),
+            _ => {}
+        }
+        walk_expression(self, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{DataType, Declaration, VarSection};
+    use crate::parser::scl::parse_scl_from_str;
+    use crate::rules::{Severity, Violation};
+
+    fn finding(line: usize, reason: &str, snippet: Option<&str>) -> Violation {
+        Violation {
+            rule_no: 4,
+            rule_name: "Test",
+            line,
+            end_line: None,
+            reason: reason.into(),
+            suggestion: "Check the divisor first.".into(),
+            severity: Severity::Warning,
+            snippet: snippet.map(String::from),
+            fix: None,
+            function: Some("FB_Pump".into()),
+            file: None,
+            rationale: None,
+            doc_anchor: None,
+        }
+    }
+
+    #[test]
+    fn names_get_the_same_token_everywhere() {
+        let src = "FUNCTION_BLOCK FB_Pump\nVAR\n    Flow_Rate : REAL;\nEND_VAR\nBEGIN\n    \
+                   Flow_Rate := \"DB_Plant\".Volume / Cycle_Time;\n    TON(IN := cycle_time > 0);\nEND_FUNCTION_BLOCK\n";
+        let redaction = Redaction::from_program(&parse_scl_from_str(src).unwrap(), b"site key");
+        let mut results = vec![RuleResult::violations(vec![
+            finding(6, "Division by 'Cycle_Time' without a zero check", Some("Flow_Rate := \"DB_Plant\".Volume / Cycle_Time;")),
+            finding(9, "cycle_time may be 0", None),
+            finding(12, "Missing OB100 startup block", None),
+        ])];
+        redaction.apply(&mut results);
+
+        let v = &results[0].violations;
+        let token = |name: &str| redaction.token(name);
+        let cycle = token("Cycle_Time");
+        assert_eq!(v[0].reason, format!("Division by '{}' without a zero check", cycle));
+        assert_eq!(
+            v[0].snippet.as_deref(),
+            Some(format!("{} := \"{}\".{} / {};", token("Flow_Rate"), token("DB_Plant"), token("Volume"), cycle).as_str())
+        );
+        assert_eq!(v[1].reason, format!("{} may be 0", cycle));
+        assert_eq!(v[0].function, Some(token("FB_Pump")));
+        // Findings without names, and everything but the text, are untouched.
+        assert_eq!(v[2].reason, "Missing OB100 startup block");
+        assert_eq!(v[0].suggestion, "Check the divisor first.");
+        assert_eq!(v.iter().map(|v| v.line).collect::<Vec<_>>(), vec![6, 9, 12]);
+    }
+
+    #[test]
+    fn names_match_ignoring_case_and_file_names_are_hidden() {
+        let src = "FUNCTION FC_Pump\nVAR\n    Motor_Out : BOOL;\nEND_VAR\nBEGIN\n    Motor_Out := TRUE;\nEND_FUNCTION\n";
+        let redaction = Redaction::from_program(&parse_scl_from_str(src).unwrap(), b"site key");
+        let mut v = finding(6, "motor_out and MOTOR_OUT are one tag", None);
+        v.file = Some("Pump_Station_3.scl".into());
+        let mut results = vec![RuleResult::violations(vec![v])];
+        redaction.apply(&mut results);
+
+        let v = &results[0].violations[0];
+        let t = redaction.token("Motor_Out");
+        assert_eq!(v.reason, format!("{} and {} are one tag", t, t));
+        assert_eq!(v.file, Some(format!("{}.scl", redaction.token("Pump_Station_3"))));
+    }
+
+    #[test]
+    fn tokens_depend_on_the_salt() {
+        let program = parse_scl_from_str("FUNCTION FC_A\nBEGIN\n    x := 1;\nEND_FUNCTION\n").unwrap();
+        let (a, b) = (Redaction::from_program(&program, b"one"), Redaction::from_program(&program, b"two"));
+        assert_eq!(a.token("Motor"), a.token("MOTOR"));
+        assert_eq!(a.token("Motor"), Redaction::from_program(&program, b"one").token("Motor"));
+        assert_ne!(a.token("Motor"), b.token("Motor"));
+        assert_ne!(random_salt(), random_salt());
+    }
+
+    #[test]
+    fn large_programs_are_redacted() {
+        let mut program = parse_scl_from_str("FUNCTION FC_A\nBEGIN\n    Tag_1 := Tag_19999;\nEND_FUNCTION\n").unwrap();
+        let decls = (0..20_000).map(|i| Declaration { name: format!("Tag_{}", i), data_type: DataType::Named("INT".into()), section: VarSection::Var, line: 3 });
+        program.functions[0].declarations.extend(decls);
+        let redaction = Redaction::from_program(&program, b"k");
+        let mut results = vec![RuleResult::violations(vec![finding(3, "Tag_19999 feeds Tag_1", None)])];
+        redaction.apply(&mut results);
+        assert_eq!(results[0].violations[0].reason, format!("{} feeds {}", redaction.token("Tag_19999"), redaction.token("Tag_1")));
+    }
+}
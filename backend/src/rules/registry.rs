@@ -0,0 +1,408 @@
+//! Rule metadata: the one place rule numbers, names and default severities
+//! are written down. Rules, the dispatch table and the report formatters
+//! all read from here.
+
+use serde::Serialize;
+
+use super::Severity;
+
+/// Static description of one rule.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RuleMeta {
+    pub no: u8,
+    /// Canonical name, shown in every report format.
+    pub name: &'static str,
+    /// Severity of a typical finding; individual findings may differ.
+    pub default_severity: Severity,
+    /// One-line summary of what the rule looks for.
+    pub description: &'static str,
+    /// Why a finding matters, for readers new to the rule. Attached to
+    /// findings in explain mode (see [`super::explain`]).
+    pub rationale: &'static str,
+    /// The rule does nothing unless the policy configures it.
+    pub requires_policy: bool,
+}
+
+impl RuleMeta {
+    /// Anchor of the rule's entry in the README, e.g.
+    /// `"rule-11-plausibility-checks"`. Renaming a rule moves its anchor.
+    pub fn doc_anchor(&self) -> String {
+        let mut anchor = format!("rule-{}", self.no);
+        for word in self.name.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()) {
+            anchor.push('-');
+            anchor.push_str(&word.to_ascii_lowercase());
+        }
+        anchor
+    }
+}
+
+pub const RULE1: RuleMeta = RuleMeta {
+    no: 1,
+    name: "Modularize PLC Code",
+    default_severity: Severity::Info,
+    description: "Flags FCs/FBs whose cyclomatic complexity or statement count is too high.",
+    rationale: "Large, branchy blocks are hard to review and test, so faults and unsafe paths hide in them.",
+    requires_policy: false,
+};
+
+pub const RULE2: RuleMeta = RuleMeta {
+    no: 2,
+    name: "Track operating modes",
+    default_severity: Severity::Warning,
+    description: "Expects a state machine or an explicit mode variable gating the logic.",
+    rationale: "Without an explicit mode, manual and automatic logic can act at the same time and an operator cannot tell what the machine will do.",
+    requires_policy: false,
+};
+
+pub const RULE4: RuleMeta = RuleMeta {
+    no: 4,
+    name: "Use PLC flags as integrity checks",
+    default_severity: Severity::Warning,
+    description: "Divisions must be guarded by status-word flags or a non-zero divisor check.",
+    rationale: "A division by zero or an overflow sets the status word and can stop the CPU or yield garbage that then drives outputs.",
+    requires_policy: false,
+};
+
+pub const RULE5: RuleMeta = RuleMeta {
+    no: 5,
+    name: "Use checksum integrity checks",
+    default_severity: Severity::Warning,
+    description: "Expects a checksum to be computed and compared somewhere in the program.",
+    rationale: "Without a checksum, corrupted or tampered recipe and parameter data is used as if it were valid.",
+    requires_policy: false,
+};
+
+pub const RULE6: RuleMeta = RuleMeta {
+    no: 6,
+    name: "Validate timers and counters",
+    default_severity: Severity::Warning,
+    description: "Timer and counter presets from HMI or DB must be range-checked.",
+    rationale: "A timer or counter preset taken unchecked from an HMI or DB lets one bad value stretch or skip a safety delay.",
+    requires_policy: false,
+};
+
+pub const RULE7: RuleMeta = RuleMeta {
+    no: 7,
+    name: "Validate paired inputs/outputs",
+    default_severity: Severity::Error,
+    description: "Policy-listed output pairs must never both be set TRUE.",
+    rationale: "Driving both outputs of an exclusive pair, such as forward and reverse, can damage the machine the moment it happens.",
+    requires_policy: true,
+};
+
+pub const RULE8: RuleMeta = RuleMeta {
+    no: 8,
+    name: "Validate HMI input variables",
+    default_severity: Severity::Warning,
+    description: "HMI values must be range-checked or annotated before use.",
+    rationale: "HMI values are operator or network input; an unchecked value reaches the process with whatever range it arrived in.",
+    requires_policy: false,
+};
+
+pub const RULE9: RuleMeta = RuleMeta {
+    no: 9,
+    name: "Validate indirections",
+    default_severity: Severity::Warning,
+    description: "Array indices must be bounds-checked before indexing.",
+    rationale: "An out-of-range index reads or overwrites unrelated memory, which on a PLC can stop the CPU or corrupt other data.",
+    requires_policy: false,
+};
+
+pub const RULE10: RuleMeta = RuleMeta {
+    no: 10,
+    name: "Assign designated register blocks",
+    default_severity: Severity::Error,
+    description: "Writes must stay out of memory areas the policy marks read-only.",
+    rationale: "Writing to areas reserved for other purposes silently corrupts data that other code or devices rely on.",
+    requires_policy: true,
+};
+
+pub const RULE11: RuleMeta = RuleMeta {
+    no: 11,
+    name: "Plausibility Checks",
+    default_severity: Severity::Warning,
+    description: "Sensitive values from HMI or recipes need a plausibility check or annotation.",
+    rationale: "A sensitive value that is never checked for plausibility lets one wrong entry drive the process outside its design limits.",
+    requires_policy: false,
+};
+
+pub const RULE12: RuleMeta = RuleMeta {
+    no: 12,
+    name: "Plausibility-Gated Writes",
+    default_severity: Severity::Warning,
+    description: "Writes to actuators and setpoints must be gated by a plausibility flag.",
+    rationale: "Actuators moved before the plausibility of their inputs is confirmed act on values nobody has validated.",
+    requires_policy: false,
+};
+
+pub const RULE15: RuleMeta = RuleMeta {
+    no: 15,
+    name: "Define a safe restart state",
+    default_severity: Severity::Warning,
+    description: "OB100 must exist and put outputs into a safe state on startup.",
+    rationale: "After a restart the outputs keep whatever the program sets first; without OB100 that can be a running motor or an open valve.",
+    requires_policy: false,
+};
+
+pub const RULE16: RuleMeta = RuleMeta {
+    no: 16,
+    name: "Summarize PLC cycle times",
+    default_severity: Severity::Info,
+    description: "Expects the scan cycle time to be recorded for monitoring.",
+    rationale: "Cycle time growth is an early sign of overload or attack and is invisible unless it is recorded.",
+    requires_policy: false,
+};
+
+pub const RULE17: RuleMeta = RuleMeta {
+    no: 17,
+    name: "Log PLC uptime",
+    default_severity: Severity::Info,
+    description: "Expects a monotonic uptime counter reported to HMI, DB or a log.",
+    rationale: "An uptime record shows unexpected restarts, which otherwise go unnoticed between shifts.",
+    requires_policy: false,
+};
+
+pub const RULE18: RuleMeta = RuleMeta {
+    no: 18,
+    name: "Log PLC hard stops",
+    default_severity: Severity::Warning,
+    description: "The fault OBs (OB82, OB86, OB121 by default) must exist and log the fault.",
+    rationale: "Without the fault OBs the CPU stops on a module or program fault, and without logging nobody learns why.",
+    requires_policy: false,
+};
+
+pub const RULE19: RuleMeta = RuleMeta {
+    no: 19,
+    name: "Monitor PLC memory usage",
+    default_severity: Severity::Info,
+    description: "Expects memory usage to be read and reported.",
+    rationale: "Memory exhaustion degrades the controller gradually; reading it lets the plant react before the CPU stops.",
+    requires_policy: false,
+};
+
+pub const RULE20: RuleMeta = RuleMeta {
+    no: 20,
+    name: "Trap false alerts",
+    default_severity: Severity::Warning,
+    description: "Alarms must be debounced or validated before they are raised.",
+    rationale: "Alarms raised from a single noisy sample train operators to ignore them, so real alarms are missed.",
+    requires_policy: false,
+};
+
+pub const RULE30: RuleMeta = RuleMeta {
+    no: 30,
+    name: "Avoid empty branches",
+    default_severity: Severity::Info,
+    description: "Empty IF/ELSE branches often hide deleted or missing handling.",
+    rationale: "An empty branch usually means handling was deleted or never written, and the case it covers is silently ignored.",
+    requires_policy: false,
+};
+
+pub const RULE31: RuleMeta = RuleMeta {
+    no: 31,
+    name: "Avoid self-assignment",
+    default_severity: Severity::Info,
+    description: "X := X does nothing and is usually a typo for a different source.",
+    rationale: "Assigning a variable to itself does nothing, so the value that was meant to be copied is never used.",
+    requires_policy: false,
+};
+
+pub const RULE32: RuleMeta = RuleMeta {
+    no: 32,
+    name: "Protect variables shared with interrupts",
+    default_severity: Severity::Warning,
+    description: "Variables written by an interrupt OB and by other code can race.",
+    rationale: "An interrupt can run between two instructions of the cyclic program, so shared variables can be read half-updated.",
+    requires_policy: false,
+};
+
+pub const RULE33: RuleMeta = RuleMeta {
+    no: 33,
+    name: "Single point of control for outputs",
+    default_severity: Severity::Warning,
+    description: "Each critical output should be written in one place; competing writers race.",
+    rationale: "When several places write the same output, the last writer in the scan wins and the others are silently overridden.",
+    requires_policy: false,
+};
+
+pub const RULE34: RuleMeta = RuleMeta {
+    no: 34,
+    name: "Avoid hardwired conditions",
+    default_severity: Severity::Warning,
+    description: "IF TRUE bypasses a check and IF FALSE disables a block; both are leftover overrides.",
+    rationale: "A hardwired TRUE or FALSE condition is a forgotten bypass: the check it replaced no longer protects anything.",
+    requires_policy: false,
+};
+
+pub const RULE35: RuleMeta = RuleMeta {
+    no: 35,
+    name: "Avoid recursive calls",
+    default_severity: Severity::Error,
+    description: "A block that calls itself, directly or through others, overflows the stack at runtime.",
+    rationale: "PLC stacks are small and fixed; recursion that goes too deep stops the CPU.",
+    requires_policy: false,
+};
+
+pub const RULE36: RuleMeta = RuleMeta {
+    no: 36,
+    name: "Follow naming conventions",
+    default_severity: Severity::Info,
+    description: "Block and interface names must match the naming patterns configured in the policy.",
+    rationale: "Consistent names let reviewers and tools tell inputs, outputs and block kinds apart at a glance.",
+    requires_policy: true,
+};
+
+pub const RULE37: RuleMeta = RuleMeta {
+    no: 37,
+    name: "Clamp HMI setpoints on both sides",
+    default_severity: Severity::Warning,
+    description: "An HMI value written to a setpoint or command must be checked against both a lower and an upper limit.",
+    rationale: "A check on only one side still lets an operator push a setpoint past the other limit.",
+    requires_policy: false,
+};
+
+pub const RULE38: RuleMeta = RuleMeta {
+    no: 38,
+    name: "Limit nesting depth",
+    default_severity: Severity::Info,
+    description: "Deeply nested IF/CASE/loop logic is hard to verify; the allowed depth is set by max_nesting in the policy.",
+    rationale: "Deeply nested logic is hard to follow and test, so paths through it go unreviewed.",
+    requires_policy: false,
+};
+
+pub const RULE39: RuleMeta = RuleMeta {
+    no: 39,
+    name: "Store the previous value for edge detection",
+    default_severity: Severity::Warning,
+    description: "A manual edge check against X_PREV/X_OLD only works if the block also stores X_PREV := X every scan.",
+    rationale: "If the previous value is never stored, the edge never resets and the action fires every scan or never.",
+    requires_policy: false,
+};
+
+pub const RULE40: RuleMeta = RuleMeta {
+    no: 40,
+    name: "Do not assign the FOR loop counter",
+    default_severity: Severity::Error,
+    description: "Writing a FOR loop's control variable inside its body is undefined in IEC 61131-3.",
+    rationale: "Moving the loop counter inside the body changes how often the loop runs and can run past the end of an array.",
+    requires_policy: false,
+};
+
+pub const RULE41: RuleMeta = RuleMeta {
+    no: 41,
+    name: "Scale analog inputs before use",
+    default_severity: Severity::Warning,
+    description: "Raw analog values (%IW, *_RAW, *_AI) must go through NORM_X/SCALE_X or a range check before arithmetic.",
+    rationale: "Raw converter counts are not engineering values; arithmetic on them gives wrong results and misses wire breaks.",
+    requires_policy: false,
+};
+
+pub const RULE42: RuleMeta = RuleMeta {
+    no: 42,
+    name: "Resolve TODO/FIXME/HACK markers",
+    default_severity: Severity::Info,
+    description: "Comments marked TODO, FIXME or HACK are unresolved work; near a critical output write they are a warning.",
+    rationale: "Open TODOs mark work that was knowingly left unfinished, which a safety review has to close or justify.",
+    requires_policy: false,
+};
+
+pub const RULE43: RuleMeta = RuleMeta {
+    no: 43,
+    name: "Call only defined blocks",
+    default_severity: Severity::Warning,
+    description: "Calls to blocks that are neither in the analysed program nor standard instructions point to a missing file or a typo.",
+    rationale: "A call to a block that is not in the analysed set means part of the program was never checked, or the wrong block runs.",
+    requires_policy: false,
+};
+
+pub const RULE44: RuleMeta = RuleMeta {
+    no: 44,
+    name: "Do not overwrite unread values",
+    default_severity: Severity::Warning,
+    description: "A variable assigned again before the first value is read (a dead store) points to leftover code or a mistyped target.",
+    rationale: "A store nothing reads has no effect; when it was meant to drive something, the logic silently does not do what the code suggests.",
+    requires_policy: false,
+};
+
+pub const RULE45: RuleMeta = RuleMeta {
+    no: 45,
+    name: "Ramp speed and position setpoints",
+    default_severity: Severity::Warning,
+    description: "Operator setpoints written to speed or position targets must pass through a ramp or limit block.",
+    rationale: "A setpoint applied in one scan makes a drive jump, straining gears and couplings and surprising anyone near the machine.",
+    requires_policy: false,
+};
+
+pub const RULE46: RuleMeta = RuleMeta {
+    no: 46,
+    name: "Do not mix up = and :=",
+    default_severity: Severity::Info,
+    description: "Comparisons stored in non-BOOL targets and assignments used as conditions are reported as likely typos.",
+    rationale: "One missing or extra colon turns a write into a comparison or a test into a write, and the block still compiles or silently misbehaves.",
+    requires_policy: false,
+};
+
+pub const RULE47: RuleMeta = RuleMeta {
+    no: 47,
+    name: "Guard writes to retentive memory",
+    default_severity: Severity::Warning,
+    description: "Retentive variables must not be written unconditionally on every OB1 scan.",
+    rationale: "Retentive data is persisted to flash; writing it every scan wears the memory out and keeps values that were only meant to be transient.",
+    requires_policy: false,
+};
+
+pub const RULE48: RuleMeta = RuleMeta {
+    no: 48,
+    name: "Assign values of the target's type",
+    default_severity: Severity::Warning,
+    description: "The declared type of an assignment target and the type of the assigned value must agree (no BOOL := INT or REAL := BOOL).",
+    rationale: "Runtimes that coerce mixed BOOL and numeric values silently turn any non-zero count into TRUE or a flag into 1.0, hiding logic errors.",
+    requires_policy: false,
+};
+
+pub const RULE49: RuleMeta = RuleMeta {
+    no: 49,
+    name: "Poll emergency stops on every scan",
+    default_severity: Severity::Warning,
+    description: "OB1 must read an emergency-stop input unconditionally on every scan; e-stops read only inside a branch are reported.",
+    rationale: "An e-stop that is only evaluated in some modes or states is ignored in all the others, so pressing it may not stop the machine.",
+    requires_policy: false,
+};
+
+pub const RULE50: RuleMeta = RuleMeta {
+    no: 50,
+    name: "Do not share a timer or counter instance across loop iterations",
+    default_severity: Severity::Error,
+    description: "Timers, counters and other stateful function blocks must not be called through a single instance inside a FOR or WHILE body.",
+    rationale: "Every iteration drives the same instance, so elapsed times and counts mix the elements together and the outputs only reflect the last one.",
+    requires_policy: false,
+};
+
+pub const RULE51: RuleMeta = RuleMeta {
+    no: 51,
+    name: "Detect stuck and out-of-range sensor signals",
+    default_severity: Severity::Warning,
+    description: "Sensor values used in arithmetic must be compared against a range or their previous value, or have a fault flag, somewhere in the program.",
+    rationale: "A failed sensor often freezes or pins at the end of its range; without a check the logic keeps acting on a value that no longer reflects the process.",
+    requires_policy: false,
+};
+
+/// Every rule, in rule-number order.
+pub const RULES: &[RuleMeta] = &[
+    RULE1, RULE2, RULE4, RULE5, RULE6, RULE7, RULE8, RULE9, RULE10, RULE11, RULE12,
+    RULE15, RULE16, RULE17, RULE18, RULE19, RULE20, RULE30, RULE31, RULE32, RULE33,
+    RULE34, RULE35, RULE36, RULE37, RULE38, RULE39, RULE40, RULE41, RULE42, RULE43,
+    RULE44, RULE45, RULE46, RULE47, RULE48, RULE49, RULE50, RULE51,
+];
+
+/// Metadata for rule `no`, if there is such a rule.
+pub fn lookup(no: u8) -> Option<&'static RuleMeta> {
+    RULES.iter().find(|m| m.no == no)
+}
+
+// Names of the pseudo-rules (number 0) used to report pipeline errors.
+pub const INPUT_ERROR: &str = "Input Error";
+pub const PARSE_ERROR: &str = "Parse Error";
+pub const POLICY_ERROR: &str = "Policy Parsing Error";
+pub const SELECTION_ERROR: &str = "Rule Selection Error";
+pub const INTERNAL_ERROR: &str = "Internal Error";
@@ -1,33 +1,62 @@
 //! Rule 1: Modularize PLC Code. Flag excessive cyclomatic complexity or
-//! statement count in FC/FB/PROGRAM.
+//! statement count in FC/FB/PROGRAM. When complexity is too high the
+//! finding points at the hotspot, the IF/CASE/loop region contributing the
+//! most branches, and the function total is reported alongside it. Blocks
+//! organised with `REGION`s are reported per region instead.
 
-use crate::ast::{FunctionKind, Program, Statement};
-use super::{RuleResult, Violation};
+use crate::ast::{statement_count, Function, FunctionKind, Program, Region, Statement};
+use super::{RuleResult, Severity, Violation};
+use super::registry::RULE1;
 
 pub fn check(program: &Program) -> RuleResult {
     let mut violations = vec![];
 
-    for f in &program.functions {
+    for f in program.code() {
         if matches!(f.kind, FunctionKind::FC | FunctionKind::FB | FunctionKind::Program) {
             let complexity = cyclomatic_complexity(&f.statements);
             let count = statement_count(&f.statements);
-            if complexity > 50 {
-                violations.push(Violation {
-                    rule_no: 1,
-                    rule_name: "Modularize PLC Code",
-                    line: f.line,
-                    reason: format!("Cyclomatic complexity {} exceeds 50", complexity),
-                    suggestion: "Split logic into smaller FC/FBs; reduce branching.".into(),
-                });
+            if complexity > COMPLEXITY_LIMIT {
+                let regions = complex_regions(f);
+                for (region, local) in &regions {
+                    violations.push(Violation::new(
+                        &RULE1,
+                        region.line,
+                        format!(
+                            "Region '{}' has cyclomatic complexity {} (function '{}' total {})",
+                            region.name, local, f.name, complexity
+                        ),
+                        "Extract this region into its own FC/FB.",
+                        Severity::Info,
+                    ));
+                }
+                if let Some((region, local)) = hotspot(&f.statements).filter(|_| regions.is_empty()) {
+                    violations.push(Violation::new(
+                        &RULE1,
+                        region.line(),
+                        format!(
+                            "Region starting here has cyclomatic complexity {} (function '{}' total {})",
+                            local, f.name, complexity
+                        ),
+                        "Extract this region into its own FC/FB.",
+                        Severity::Info,
+                    ));
+                }
+                violations.push(Violation::new(
+                    &RULE1,
+                    f.line,
+                    format!("Cyclomatic complexity {} exceeds {}", complexity, COMPLEXITY_LIMIT),
+                    "Split logic into smaller FC/FBs; reduce branching.",
+                    Severity::Info,
+                ));
             }
-            if count > 500 {
-                violations.push(Violation {
-                    rule_no: 1,
-                    rule_name: "Modularize PLC Code",
-                    line: f.line,
-                    reason: format!("Statement count {} exceeds 500", count),
-                    suggestion: "Refactor large routines into smaller units.".into(),
-                });
+            if count > STATEMENT_LIMIT {
+                violations.push(Violation::new(
+                    &RULE1,
+                    f.line,
+                    format!("Statement count {} exceeds {}", count, STATEMENT_LIMIT),
+                    "Refactor large routines into smaller units.",
+                    Severity::Info,
+                ));
             }
         }
     }
@@ -35,6 +64,89 @@ pub fn check(program: &Program) -> RuleResult {
     RuleResult::violations(violations)
 }
 
+const COMPLEXITY_LIMIT: usize = 50;
+const STATEMENT_LIMIT: usize = 500;
+
+/// The most complex IF/CASE/loop region: start from the top-level statement
+/// with the most branches and descend while a nested region still exceeds
+/// the limit on its own, so the result is as small as possible.
+fn hotspot(stmts: &[Statement]) -> Option<(&Statement, usize)> {
+    let mut best = most_complex(stmts.iter())?;
+    while let Some(inner) = most_complex(nested_regions(best.0)) {
+        if inner.1 <= COMPLEXITY_LIMIT { break; }
+        best = inner;
+    }
+    Some(best)
+}
+
+/// Named regions of `f` over the limit. A region is left out when one
+/// nested inside it is reported already.
+fn complex_regions(f: &Function) -> Vec<(&Region, usize)> {
+    let over: Vec<(&Region, usize)> = f.regions.iter()
+        .map(|r| {
+            let mut stmts = Vec::new();
+            statements_within(&f.statements, r, &mut stmts);
+            (r, 1 + stmts.into_iter().map(|st| count_branches(std::slice::from_ref(st))).sum::<usize>())
+        })
+        .filter(|(_, c)| *c > COMPLEXITY_LIMIT)
+        .collect();
+    over.iter()
+        .filter(|(r, _)| !over.iter().any(|(inner, _)| !std::ptr::eq(*r, *inner) && r.contains(inner.line) && r.contains(inner.end_line)))
+        .copied()
+        .collect()
+}
+
+/// Outermost statements starting inside `region`, at any nesting depth.
+fn statements_within<'a>(stmts: &'a [Statement], region: &Region, out: &mut Vec<&'a Statement>) {
+    for st in stmts {
+        if region.contains(st.line()) {
+            out.push(st);
+            continue;
+        }
+        match st {
+            Statement::IfStmt { then_branch, else_branch, .. } => {
+                statements_within(then_branch, region, out);
+                statements_within(else_branch, region, out);
+            }
+            Statement::CaseStmt { cases, else_branch, .. } => {
+                for (_, body) in cases {
+                    statements_within(body, region, out);
+                }
+                statements_within(else_branch, region, out);
+            }
+            Statement::WhileStmt { body, .. } | Statement::ForStmt { body, .. } => statements_within(body, region, out),
+            _ => {}
+        }
+    }
+}
+
+fn most_complex<'a>(stmts: impl Iterator<Item = &'a Statement>) -> Option<(&'a Statement, usize)> {
+    stmts
+        .map(|st| (st, cyclomatic_complexity(std::slice::from_ref(st))))
+        .filter(|(_, c)| *c > 1)
+        .max_by_key(|(_, c)| *c)
+}
+
+/// Statements directly nested in `st`. An ELSE holding a single IF is the
+/// continuation of an ELSIF chain and belongs to the same region, so its
+/// IF is not offered as a separate one.
+fn nested_regions(st: &Statement) -> Box<dyn Iterator<Item = &Statement> + '_> {
+    match st {
+        Statement::IfStmt { then_branch, else_branch, .. } => {
+            let else_part: &[Statement] = match else_branch.as_slice() {
+                [Statement::IfStmt { .. }] => &[],
+                other => other,
+            };
+            Box::new(then_branch.iter().chain(else_part))
+        }
+        Statement::CaseStmt { cases, else_branch, .. } => {
+            Box::new(cases.iter().flat_map(|(_, body)| body).chain(else_branch))
+        }
+        Statement::WhileStmt { body, .. } | Statement::ForStmt { body, .. } => Box::new(body.iter()),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
 fn cyclomatic_complexity(stmts: &[Statement]) -> usize {
     // Base complexity 1 + branches
     1 + count_branches(stmts)
@@ -69,28 +181,68 @@ fn count_branches_with_depth(stmts: &[Statement], depth: usize) -> usize {
                 }
                 c += count_branches_with_depth(else_branch, depth + 1);
             }
+            Statement::WhileStmt { body, .. } | Statement::ForStmt { body, .. } => {
+                c += 1;
+                c += count_branches_with_depth(body, depth + 1);
+            }
+            // Each early loop exit is an extra edge out of the body.
+            Statement::Exit { .. } | Statement::Continue { .. } => c += 1,
             _ => {}
         }
     }
     c
 }
-fn statement_count(stmts: &[Statement]) -> usize {
-    let mut n = 0usize;
-    for st in stmts {
-        n += 1;
-        match st {
-            Statement::IfStmt { then_branch, else_branch, .. } => {
-                n += statement_count(then_branch);
-                n += statement_count(else_branch);
-            }
-            Statement::CaseStmt { cases, else_branch, .. } => {
-               for (_, branch) in cases {
-                   n += statement_count(branch);
-               }
-               n += statement_count(else_branch);
-            }
-            _ => {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn loops_and_loop_exits_are_branch_points() {
+        let src = "FUNCTION FC_Scan\nBEGIN\n    WHILE Run DO\n        IF Done THEN\n            EXIT;\n        END_IF;\n    END_WHILE;\nEND_FUNCTION\n";
+        let program = parse_scl_from_str(src).unwrap();
+        // 1 + WHILE + IF + EXIT
+        assert_eq!(cyclomatic_complexity(&program.functions[0].statements), 4);
+    }
+
+    #[test]
+    fn points_at_the_most_complex_region() {
+        let mut src = String::from("FUNCTION_BLOCK FB_Big\nBEGIN\n    IF a THEN\n        x := 1;\n    END_IF;\n    IF Mode = 0 THEN\n        y := 0;\n");
+        for i in 1..60 {
+            src.push_str(&format!("    ELSIF Mode = {} THEN\n        y := {};\n", i, i));
+        }
+        src.push_str("    END_IF;\nEND_FUNCTION_BLOCK\n");
+
+        let result = check(&parse_scl_from_str(&src).unwrap());
+        assert_eq!(result.violations.len(), 2);
+        assert_eq!(result.violations[0].line, 6);
+        assert!(result.violations[0].reason.contains("complexity 61"));
+        assert_eq!(result.violations[1].line, 1);
+        assert!(result.violations[1].reason.contains("62"));
+    }
+
+    #[test]
+    fn reports_each_region_over_the_limit_by_name() {
+        let mut src = String::from("FUNCTION_BLOCK FB_Main\nBEGIN\n    REGION Inputs\n");
+        for i in 0..10 {
+            src.push_str(&format!("        IF In{} THEN\n            x := {};\n        END_IF;\n", i, i));
+        }
+        src.push_str("    END_REGION\n    REGION \"Sequence\"\n");
+        for i in 0..55 {
+            src.push_str(&format!("        IF Step = {} THEN\n            Step := {};\n        END_IF;\n", i, i + 1));
         }
+        src.push_str("    END_REGION\nEND_FUNCTION_BLOCK\n");
+
+        let program = parse_scl_from_str(&src).unwrap();
+        let regions = &program.functions[0].regions;
+        assert_eq!((regions[0].name.as_str(), regions[0].line, regions[0].end_line), ("Inputs", 3, 34));
+        assert_eq!((regions[1].name.as_str(), regions[1].line), ("Sequence", 35));
+
+        let result = check(&program);
+        assert_eq!(result.violations.len(), 2);
+        assert_eq!(result.violations[0].line, 35);
+        assert!(result.violations[0].reason.starts_with("Region 'Sequence' has cyclomatic complexity 56"));
+        assert!(result.violations[1].reason.contains("66"));
     }
-    n
-}
\ No newline at end of file
+}
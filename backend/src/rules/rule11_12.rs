@@ -1,30 +1,32 @@
 // Replaced File
 
-use crate::{rules::RuleResult, rules::Violation};
-use crate::ast::{Expression, Program, Statement, BinOp};
-use super::utils;
+use crate::{rules::AnalysisContext, rules::Policy, rules::RuleResult, rules::Severity, rules::Violation};
+use crate::ast::{reachable, Expression, Program, Statement, UnaryOp};
+use super::utils::{self, Guard, IdentifierClass};
 use std::collections::HashSet;
+use super::registry::{RULE11, RULE12};
 
-pub fn check_rule11(program: &Program) -> RuleResult {
-    check_impl(program, Mode::Presence)
+pub fn check_rule11(program: &Program, cx: &AnalysisContext) -> RuleResult {
+    check_impl(program, cx, Mode::Presence)
 }
 
-pub fn check_rule12(program: &Program) -> RuleResult {
-    check_impl(program, Mode::Enforcement)
+pub fn check_rule12(program: &Program, cx: &AnalysisContext) -> RuleResult {
+    check_impl(program, cx, Mode::Enforcement)
 }
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 enum Mode { Presence, Enforcement }
 
-fn check_impl(program: &Program, mode: Mode) -> RuleResult {
+fn check_impl(program: &Program, cx: &AnalysisContext, mode: Mode) -> RuleResult {
+    let search = utils::AnnotationSearch::from_context(cx);
     let mut out = Vec::new();
-    for f in &program.functions {
-        walk_statements(&f.statements, &mut vec![], &mut out, mode);
+    for f in program.code() {
+        walk_statements(&f.statements, &mut vec![], &mut out, mode, &search, cx.policy);
     }
     if out.is_empty() {
         match mode {
-            Mode::Presence    => RuleResult::ok(11, "Plausibility Checks"),
-            Mode::Enforcement => RuleResult::ok(12, "Plausibility Checks"),
+            Mode::Presence    => RuleResult::ok(RULE11.no, RULE11.name),
+            Mode::Enforcement => RuleResult::ok(RULE12.no, RULE12.name),
         }
     } else {
         RuleResult::violations(out)
@@ -33,19 +35,23 @@ fn check_impl(program: &Program, mode: Mode) -> RuleResult {
 
 fn walk_statements<'a>(
     stmts: &'a [Statement],
-    guards: &mut Vec<&'a Expression>,
+    guards: &mut Vec<Guard<'a>>,
     out: &mut Vec<Violation>,
-    mode: Mode
+    mode: Mode,
+    search: &utils::AnnotationSearch<'_>,
+    policy: &Policy,
 ) {
-    for st in stmts {
+    let outer_guards = guards.len();
+    let stmts = reachable(stmts);
+    for (i, st) in stmts.iter().enumerate() {
         match st {
             Statement::Assign { target, value, line } => {
-                if let Expression::Identifier(target_name) = target {
-                    let sensitive_use = expr_has_sensitive_source(value);
-                    let is_sink = is_sensitive_sink(target_name);
+                if let Some(target_name) = target.name() {
+                    let sensitive_use = expr_has_sensitive_source(value, policy);
+                    let is_sink = utils::is_identifier_class(&target_name, IdentifierClass::Actuator, policy);
 
                     if sensitive_use && is_sink {
-                        let has_nearby_annotation = utils::has_plausibility_annotation_above(*line, 3);
+                        let has_nearby_annotation = search.is_annotated(&stmts[..i], *line);
                         
                         let mut value_vars = HashSet::new();
                         collect_vars(value, &mut value_vars);
@@ -55,25 +61,28 @@ fn walk_statements<'a>(
                             Mode::Presence => {
                                 if !(has_nearby_annotation || has_guard_validation) {
                                     out.push(Violation {
-                                        rule_no: 11,
-                                        rule_name: "Plausibility Checks",
-                                        line: *line,
-                                        reason: format!("Use of sensitive value '{}' without plausibility validation", utils::expr_text(value)),
-                                        suggestion: "Add a nearby @PlausibilityCheck or guard with range/authorization before this use.".into(),
+                                        snippet: Some(utils::expr_text(value)),
+                                        ..Violation::new(
+                                            &RULE11,
+                                            *line,
+                                            format!("Use of sensitive value '{}' without plausibility validation", utils::expr_text(value)),
+                                            "Add a nearby @PlausibilityCheck or guard with range/authorization before this use.",
+                                            Severity::Warning,
+                                        )
                                     });
                                 }
                             }
                             Mode::Enforcement => {
                                 if has_nearby_annotation && !has_guard_validation {
-                                     let gated = guard_enforces_flag(guards) || utils::has_plausibility_annotation_above(*line, 1);
+                                     let gated = guard_enforces_flag(guards) || search.is_annotated_within(&stmts[..i], *line, 1);
                                      if !gated {
-                                         out.push(Violation {
-                                            rule_no: 12,
-                                            rule_name: "Plausibility Checks",
-                                            line: *line,
-                                            reason: format!("Plausibility annotation present but not enforced before assigning to '{}'", target_name),
-                                            suggestion: "Use the plausibility result to gate this action (e.g., IF setpointOK THEN ...).".into(),
-                                         });
+                                         out.push(Violation::new(
+                                             &RULE12,
+                                             *line,
+                                             format!("Plausibility annotation present but not enforced before assigning to '{}'", target_name),
+                                             "Use the plausibility result to gate this action (e.g., IF setpointOK THEN ...).",
+                                             Severity::Warning,
+                                         ));
                                      }
                                 }
                             }
@@ -82,31 +91,43 @@ fn walk_statements<'a>(
                 }
             }
             Statement::IfStmt { condition, then_branch, else_branch, .. } => {
-                guards.push(condition);
-                walk_statements(then_branch, guards, out, mode);
+                guards.push(Guard { cond: condition, holds: true });
+                walk_statements(then_branch, guards, out, mode, search, policy);
                 guards.pop();
-                walk_statements(else_branch, guards, out, mode);
+                walk_statements(else_branch, guards, out, mode, search, policy);
             }
             Statement::CaseStmt { cases, else_branch, .. } => {
-                for (_, body) in cases { walk_statements(body, guards, out, mode); }
-                walk_statements(else_branch, guards, out, mode);
+                for (_, body) in cases { walk_statements(body, guards, out, mode, search, policy); }
+                walk_statements(else_branch, guards, out, mode, search, policy);
             }
+            Statement::WhileStmt { condition, body, .. } => {
+                guards.push(Guard { cond: condition, holds: true });
+                walk_statements(body, guards, out, mode, search, policy);
+                guards.pop();
+            }
+            Statement::ForStmt { body, .. } => walk_statements(body, guards, out, mode, search, policy),
             _ => {}
         }
+
+        // Code after `IF cond THEN RETURN; END_IF;` only runs when `cond` is false.
+        if let Some(c) = st.early_exit_condition() {
+            guards.push(Guard { cond: c, holds: false });
+        }
     }
+    guards.truncate(outer_guards);
 }
 
 // Helper functions
 
-fn expr_has_sensitive_source(e: &Expression) -> bool {
+fn expr_has_sensitive_source(e: &Expression, policy: &Policy) -> bool {
     let mut vars = HashSet::new();
     collect_vars(e, &mut vars);
-    vars.iter().any(|v| utils::is_sensitive_variable(v))
+    vars.iter().any(|v| utils::is_identifier_class(v, IdentifierClass::SensitiveSource, policy))
 }
 
 fn collect_vars(e: &Expression, out: &mut HashSet<String>) {
     match e {
-        Expression::Identifier(s) => { out.insert(s.to_ascii_uppercase()); }
+        Expression::Identifier(_) | Expression::Member { .. } => { out.extend(e.name().map(|s| s.to_ascii_uppercase())); }
         Expression::BinaryOp { left, right, .. } => { collect_vars(left, out); collect_vars(right, out); }
         Expression::Index { base, index, .. } => { collect_vars(base, out); collect_vars(index, out); }
         Expression::FuncCall { args, .. } => { for arg in args { collect_vars(arg, out); } }
@@ -114,42 +135,133 @@ fn collect_vars(e: &Expression, out: &mut HashSet<String>) {
     }
 }
 
-fn is_sensitive_sink(target: &str) -> bool {
-    let t = target.to_ascii_uppercase();
-    t.contains("MOTOR") || t.contains("SPEED") || t.contains("SETPOINT") || t.contains("POSITION") || t.contains("CMD") || t.contains("COMMAND") || t.contains("OUTPUT")
+fn is_guarded_by_range(vars: &HashSet<String>, guards: &[Guard]) -> bool {
+    vars.iter().any(|var| guards.iter().any(|guard| utils::is_var_constrained(var, guard.cond)))
 }
 
-fn is_guarded_by_range(vars: &HashSet<String>, guards: &[&Expression]) -> bool {
-    vars.iter().any(|var| guards.iter().any(|guard| is_var_constrained(var, guard)))
+// For Rule 12: checks if any guard establishes a simple flag like `VariableOK`:
+// `IF SetpointOK THEN ...`, or the code after `IF NOT SetpointOK THEN RETURN; END_IF;`.
+fn guard_enforces_flag(guards: &[Guard]) -> bool {
+    guards.iter().any(|g| {
+        let flag = match (g.cond, g.holds) {
+            (Expression::UnaryOp { op: UnaryOp::Not, expr, .. }, false) => expr.name(),
+            (cond, true) => cond.name(),
+            _ => None,
+        };
+        flag.is_some_and(|name| {
+            let up = name.to_ascii_uppercase();
+            up.ends_with("OK") || up.ends_with("VALID") || up.contains("AUTHORIZED")
+        })
+    })
 }
 
-fn is_var_constrained(var_name: &str, g: &Expression) -> bool {
-    match g {
-        Expression::BinaryOp { op, left, right, .. } => {
-            let is_comparison = matches!(op, BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge | BinOp::Eq | BinOp::Neq);
-            if is_comparison {
-                let left_text = utils::expr_text(left).to_ascii_uppercase();
-                let right_text = utils::expr_text(right).to_ascii_uppercase();
-                if (left_text == *var_name && matches!(**right, Expression::NumberLiteral(..))) ||
-                   (right_text == *var_name && matches!(**left, Expression::NumberLiteral(..))) {
-                    return true;
-                }
-            }
-            is_var_constrained(var_name, left) || is_var_constrained(var_name, right)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{Function, FunctionKind};
+    use crate::rules::policy::{PlausibilityPolicy, Policy};
+
+    fn program(comment: &str, comment_line: usize, assign_line: usize) -> Program {
+        Program {
+            functions: vec![Function {
+                name: "OB1".into(),
+                kind: FunctionKind::OB1,
+                line: 1,
+                regions: vec![],
+                declarations: vec![],
+                statements: vec![
+                    Statement::Comment { text: comment.into(), line: comment_line },
+                    Statement::Assign {
+                        target: Expression::Identifier("Motor_Speed".into()),
+                        value: Expression::Identifier("HMI_Speed".into()),
+                        line: assign_line,
+                    },
+                ],
+            }],
         }
-        Expression::UnaryOp { expr, .. } => is_var_constrained(var_name, expr),
-        _ => false,
     }
-}
 
-// For Rule 12: checks if any guard is a simple flag like `VariableOK`
-fn guard_enforces_flag(guards: &[&Expression]) -> bool {
-    guards.iter().any(|g| {
-        if let Expression::Identifier(name) = g {
-            let up = name.to_ascii_uppercase();
-            up.ends_with("OK") || up.ends_with("VALID") || up.contains("AUTHORIZED")
-        } else {
-            false
+    fn policy(search_depth: Option<usize>, annotations: &[&str]) -> Policy {
+        Policy {
+            plausibility: Some(PlausibilityPolicy {
+                search_depth,
+                annotations: Some(annotations.iter().map(|a| a.to_string()).collect()),
+            }),
+            ..Default::default()
         }
-    })
-}
\ No newline at end of file
+    }
+
+    #[test]
+    fn adjacent_comment_node_satisfies_rule11() {
+        let p = program("// @PlausibilityCheck range 0..1500", 2, 3);
+        assert!(check_rule11(&p, &AnalysisContext::new(&Policy::default())).passed());
+    }
+
+    #[test]
+    fn annotation_found_in_source_lines_of_this_analysis() {
+        // The frontend dropped the comment; only the source text has it.
+        let p = program("", 0, 3);
+        let policy = Policy::default();
+        let annotated = "BEGIN\n// @PlausibilityCheck range 0..1500\nMotor_Speed := HMI_Speed;\n";
+        let plain = "BEGIN\n\nMotor_Speed := HMI_Speed;\n";
+
+        let handles: Vec<_> = [(annotated, true), (plain, false)]
+            .into_iter()
+            .map(|(src, expected)| {
+                let (p, policy) = (p.clone(), policy.clone());
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        let cx = AnalysisContext::with_source(&policy, src);
+                        assert_eq!(check_rule11(&p, &cx).passed(), expected);
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn annotation_tokens_and_depth_come_from_policy() {
+        let p = program("// @RangeChecked", 2, 3);
+        let default = Policy::default();
+        assert!(!check_rule11(&p, &AnalysisContext::new(&default)).passed());
+        assert!(check_rule11(&p, &AnalysisContext::new(&policy(None, &["@RangeChecked"]))).passed());
+
+        let far = program("// @Validation", 2, 7);
+        assert!(!check_rule11(&far, &AnalysisContext::new(&default)).passed());
+        assert!(check_rule11(&far, &AnalysisContext::new(&policy(Some(5), &[]))).passed());
+    }
+
+    #[test]
+    fn annotation_in_block_or_semicolon_comment_is_found() {
+        let policy = Policy::default();
+        let scl = "FUNCTION FC_A\nBEGIN\n    (* @PlausibilityCheck\n       range 0..1500 *)\n    Motor_Speed := HMI_Speed;\nEND_FUNCTION\n";
+        let program = crate::parser::scl::parse_scl_from_str(scl).unwrap();
+        assert!(check_rule11(&program, &AnalysisContext::with_source(&policy, scl)).passed());
+
+        // IL comments survive as nodes, so no source text is needed.
+        let il = "; @PlausibilityCheck range 0..1500\nLD HMI_Speed\nST Motor_Speed\n";
+        let program = crate::parser::il::parse_il_from_str(il).unwrap();
+        assert!(check_rule11(&program, &AnalysisContext::new(&policy)).passed());
+        let program = crate::parser::il::parse_il_from_str("LD HMI_Speed\nST Motor_Speed\n").unwrap();
+        assert!(!check_rule11(&program, &AnalysisContext::new(&policy)).passed());
+    }
+
+    #[test]
+    fn early_exit_on_the_flag_itself_does_not_enforce_it() {
+        let run = |exit_cond: &str| {
+            let src = format!(
+                "FUNCTION FC_A\nBEGIN\n    // @PlausibilityCheck range 0..1500\n    IF {} THEN\n        RETURN;\n    END_IF;\n    \
+                 Motor_Speed := HMI_Speed;\nEND_FUNCTION\n",
+                exit_cond
+            );
+            let program = crate::parser::scl::parse_scl_from_str(&src).unwrap();
+            check_rule12(&program, &AnalysisContext::with_source(&policy(Some(5), &[]), &src)).passed()
+        };
+        // Past `IF SetpointOK THEN RETURN;` the setpoint is known to be bad.
+        assert!(!run("SetpointOK"));
+        assert!(run("NOT SetpointOK"));
+    }
+}
@@ -1,121 +1,260 @@
 //! Rule 15: Define a safe restart state.
 //! Verify non-empty OB100 exists and critical outputs are initialized to a safe value (FALSE/0).
+//! Safe assignments in OB1 guarded by a first-scan flag (`FirstScan`,
+//! `OB1_SCAN_1`, ...) are accepted as an equivalent restart state.
+//! With `standalone_library` set in the policy a missing OB100 is not
+//! reported; an OB100 that is present is still checked.
 
+use crate::analysis::{const_fold, ConstValue};
+use crate::ast::pprint::statement_to_scl;
 use crate::ast::{Expression, FunctionKind, Program, Statement};
-use super::{Policy, RuleResult, Violation};
+use super::{utils::{is_first_scan_guard, is_identifier_class, IdentifierClass}, Policy, RuleResult, Severity, TextEdit, Violation};
+use super::registry::RULE15;
 
 pub fn check(program: &Program, policy: &Policy) -> RuleResult {
-    // This is an S7-specific rule. Only run if the platform is configured as S7.
-    let is_s7 = policy.platform.as_deref().unwrap_or("").eq_ignore_ascii_case("S7");
-    if !is_s7 {
-        return RuleResult::ok(15, "Define a safe restart state");
-    }
-
     let mut violations = Vec::new();
 
+    let mut first_scan_safe: Vec<(usize, String)> = Vec::new();
+    let mut first_scan_unsafe: Vec<UnsafeInit> = Vec::new();
+    for f in program.functions.iter().filter(|f| f.kind == FunctionKind::OB1) {
+        walk_first_scan(&f.statements, policy, &mut first_scan_safe, &mut first_scan_unsafe);
+    }
+    for init in first_scan_unsafe {
+        violations.push(Violation {
+            fix: Some(init.fix()),
+            ..Violation::new(
+                &RULE15,
+                init.line,
+                format!("Critical output '{}' initialized UNSAFELY on first scan", init.target),
+                "Initialize critical outputs to FALSE/0 on restart.",
+                Severity::Error,
+            )
+        });
+    }
+    let first_scan_ok = !first_scan_safe.is_empty();
+
     let ob100 = program.functions.iter().find(|f| f.kind == FunctionKind::OB100);
 
     match ob100 {
+        None if first_scan_ok || policy.is_standalone_library() => {}
         None => {
-            violations.push(Violation {
-                rule_no: 15,
-                rule_name: "Define a safe restart state",
-                line: 0,
-                reason: "OB100 (Startup OB) not found".into(),
-                suggestion: "Add OB100 and initialize critical outputs to a safe state.".into(),
-            });
-            return RuleResult::violations(violations);
+            violations.push(Violation::new(
+                &RULE15,
+                0,
+                "OB100 (Startup OB) not found",
+                "Add OB100 and initialize critical outputs to a safe state.",
+                Severity::Warning,
+            ));
         }
-        Some(f) if f.statements.is_empty() => {
-            violations.push(Violation {
-                rule_no: 15,
-                rule_name: "Define a safe restart state",
-                line: f.line,
-                reason: "OB100 exists but is empty".into(),
-                suggestion: "Initialize critical outputs to FALSE/0 in OB100.".into(),
-            });
-            return RuleResult::violations(violations);
+        Some(f) if f.statements.is_empty() && !first_scan_ok => {
+            violations.push(Violation::new(
+                &RULE15,
+                f.line,
+                "OB100 exists but is empty",
+                "Initialize critical outputs to FALSE/0 in OB100.",
+                Severity::Warning,
+            ));
         }
         Some(f) => {
             let mut safe_inits: Vec<(usize, String)> = Vec::new();
-            let mut unsafe_inits: Vec<(usize, String)> = Vec::new();
-            walk_ob100(&f.statements, &mut safe_inits, &mut unsafe_inits);
+            let mut unsafe_inits: Vec<UnsafeInit> = Vec::new();
+            walk_ob100(&f.statements, policy, &mut safe_inits, &mut unsafe_inits);
 
-            for (line, var) in unsafe_inits {
+            for init in unsafe_inits {
                 violations.push(Violation {
-                    rule_no: 15,
-                    rule_name: "Define a safe restart state",
-                    line,
-                    reason: format!("Critical output '{}' initialized UNSAFELY on restart", var),
-                    suggestion: "Initialize critical outputs to FALSE/0 in OB100.".into(),
+                    fix: Some(init.fix()),
+                    ..Violation::new(
+                        &RULE15,
+                        init.line,
+                        format!("Critical output '{}' initialized UNSAFELY on restart", init.target),
+                        "Initialize critical outputs to FALSE/0 in OB100.",
+                        Severity::Error,
+                    )
                 });
             }
 
-            if safe_inits.is_empty() {
-                violations.push(Violation {
-                    rule_no: 15,
-                    rule_name: "Define a safe restart state",
-                    line: f.line,
-                    reason: "OB100 does not initialize any critical output to a safe value".into(),
-                    suggestion: "Set critical outputs to FALSE/0 in OB100.".into(),
-                });
+            if safe_inits.is_empty() && !first_scan_ok {
+                violations.push(Violation::new(
+                    &RULE15,
+                    f.line,
+                    "OB100 does not initialize any critical output to a safe value",
+                    "Set critical outputs to FALSE/0 in OB100.",
+                    Severity::Warning,
+                ));
             }
+        }
+    }
 
-            return RuleResult::violations(violations);
+    if violations.is_empty() {
+        RuleResult::ok(RULE15.no, RULE15.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+/// Look for `IF <first scan flag> THEN ... END_IF` anywhere in OB1 and
+/// collect the initializations made inside it.
+fn walk_first_scan(
+    stmts: &[Statement],
+    policy: &Policy,
+    safe_inits: &mut Vec<(usize, String)>,
+    unsafe_inits: &mut Vec<UnsafeInit>,
+) {
+    for st in stmts {
+        match st {
+            Statement::IfStmt { condition, then_branch, else_branch, .. } => {
+                if is_first_scan_guard(condition) {
+                    walk_ob100(then_branch, policy, safe_inits, unsafe_inits);
+                } else {
+                    walk_first_scan(then_branch, policy, safe_inits, unsafe_inits);
+                }
+                walk_first_scan(else_branch, policy, safe_inits, unsafe_inits);
+            }
+            Statement::CaseStmt { cases, else_branch, .. } => {
+                for (_, body) in cases {
+                    walk_first_scan(body, policy, safe_inits, unsafe_inits);
+                }
+                walk_first_scan(else_branch, policy, safe_inits, unsafe_inits);
+            }
+            _ => {}
         }
     }
 }
 
 fn walk_ob100(
     stmts: &[Statement],
+    policy: &Policy,
     safe_inits: &mut Vec<(usize, String)>,
-    unsafe_inits: &mut Vec<(usize, String)>,
+    unsafe_inits: &mut Vec<UnsafeInit>,
 ) {
     for st in stmts {
         match st {
             Statement::Assign { target, value, line } => {
-                if let Expression::Identifier(name) = target {
-                    if looks_like_critical_output(name) {
+                if let Some(name) = target.name() {
+                    if is_identifier_class(&name, IdentifierClass::CriticalOutput, policy) {
                         if is_safe_expr(value) {
                             safe_inits.push((*line, name.clone()));
-                        } else if is_unsafe_expr(value) {
-                            unsafe_inits.push((*line, name.clone()));
+                        } else if let Some(v) = const_fold(value).filter(|v| !v.is_zero()) {
+                            unsafe_inits.push(UnsafeInit {
+                                line: *line,
+                                last_line: value.span().1,
+                                statement: statement_to_scl(st),
+                                target: name.clone(),
+                                value: v,
+                            });
                         }
                     }
                 }
             }
             Statement::IfStmt { then_branch, else_branch, .. } => {
-                walk_ob100(then_branch, safe_inits, unsafe_inits);
-                walk_ob100(else_branch, safe_inits, unsafe_inits);
+                walk_ob100(then_branch, policy, safe_inits, unsafe_inits);
+                walk_ob100(else_branch, policy, safe_inits, unsafe_inits);
             }
             Statement::CaseStmt { cases, else_branch, .. } => {
                 for (_, body) in cases {
-                    walk_ob100(body, safe_inits, unsafe_inits);
+                    walk_ob100(body, policy, safe_inits, unsafe_inits);
                 }
-                walk_ob100(else_branch, safe_inits, unsafe_inits);
+                walk_ob100(else_branch, policy, safe_inits, unsafe_inits);
             }
             _ => {}
         }
     }
 }
 
-fn looks_like_critical_output(name: &str) -> bool {
-    let up = name.to_ascii_uppercase();
-    up.contains("CRITICAL") || up.contains("SAFE") || up.ends_with("_OUT") || up.contains("MOTOR") || up.contains("OUTPUT")
+/// `FALSE`/`0`, also after folding (`0 + 0`).
+fn is_safe_expr(e: &Expression) -> bool {
+    const_fold(e).is_some_and(|v| v.is_zero())
 }
 
-fn is_safe_expr(e: &Expression) -> bool {
-    match e {
-        Expression::BoolLiteral(false, _) => true,
-        Expression::NumberLiteral(n, _) => *n == 0,
-        _ => false,
+/// A critical output set to a non-zero constant on restart.
+struct UnsafeInit {
+    line: usize,
+    last_line: usize,
+    /// The assignment as [`statement_to_scl`] prints it.
+    statement: String,
+    target: String,
+    value: ConstValue,
+}
+
+impl UnsafeInit {
+    /// Rewrite the assignment to the zero value of the same type.
+    fn fix(&self) -> TextEdit {
+        let safe = match self.value {
+            ConstValue::Bool(_) => "FALSE",
+            ConstValue::Int(_) => "0",
+            ConstValue::Real(_) => "0.0",
+        };
+        TextEdit::replace_statement(self.line, self.last_line, self.statement.clone(), format!("{} := {};", self.target, safe))
     }
 }
 
-fn is_unsafe_expr(e: &Expression) -> bool {
-    match e {
-        Expression::BoolLiteral(true, _) => true,
-        Expression::NumberLiteral(n, _) => *n != 0,
-        _ => false,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    fn s7() -> Policy {
+        Policy { platform: Some("S7".into()), ..Default::default() }
+    }
+
+    #[test]
+    fn first_scan_guarded_init_in_ob1_passes() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    IF FirstScan THEN\n        Motor_Out := FALSE;\n    END_IF;\n\
+                   Motor_Out := Start;\nEND_ORGANIZATION_BLOCK\n";
+        let program = parse_scl_from_str(src).unwrap();
+        assert!(check(&program, &s7()).passed());
+    }
+
+    #[test]
+    fn missing_ob100_without_first_scan_is_still_flagged() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    Motor_Out := FALSE;\nEND_ORGANIZATION_BLOCK\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let result = check(&program, &s7());
+        assert_eq!(result.violations.len(), 1);
+        assert_eq!(result.violations[0].reason, "OB100 (Startup OB) not found");
+    }
+
+    #[test]
+    fn library_code_may_omit_ob100_but_not_leave_it_empty() {
+        let library = Policy { standalone_library: Some(true), ..s7() };
+        let fc = parse_scl_from_str("FUNCTION FC_Mix\nBEGIN\n    Motor_Out := Run;\nEND_FUNCTION\n").unwrap();
+        assert!(check(&fc, &library).passed());
+        assert!(!check(&fc, &s7()).passed());
+
+        let empty = parse_scl_from_str("ORGANIZATION_BLOCK OB100\nBEGIN\nEND_ORGANIZATION_BLOCK\n").unwrap();
+        assert_eq!(check(&empty, &library).violations[0].reason, "OB100 exists but is empty");
+    }
+
+    #[test]
+    fn negative_inits_are_unsafe_and_negative_zero_is_safe() {
+        let ob100 = |init: &str| {
+            let src = format!("ORGANIZATION_BLOCK OB100\nBEGIN\n    Motor_Out := {};\nEND_ORGANIZATION_BLOCK\n", init);
+            check(&parse_scl_from_str(&src).unwrap(), &s7())
+        };
+        assert!(ob100("-0").passed());
+        assert!(ob100("-(2 - 2)").passed());
+        let result = ob100("-1");
+        assert!(result.violations.iter().any(|v| v.severity == Severity::Error && v.line == 3));
+    }
+
+    #[test]
+    fn process_image_output_set_true_on_restart_is_flagged() {
+        let src = "ORGANIZATION_BLOCK OB100\nBEGIN\n    %Q0.0 := TRUE;\n    %QW4 := 0;\n    %I0.1 := TRUE;\nEND_ORGANIZATION_BLOCK\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &s7());
+        assert_eq!(result.violations.len(), 1);
+        let v = &result.violations[0];
+        assert_eq!((v.line, v.severity), (3, Severity::Error));
+        assert_eq!(v.reason, "Critical output '%Q0.0' initialized UNSAFELY on restart");
+        assert_eq!(v.fix, Some(TextEdit::replace_statement(3, 3, "%Q0.0 := TRUE;".into(), "%Q0.0 := FALSE;".into())));
+    }
+
+    #[test]
+    fn unsafe_first_scan_init_is_an_error() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    IF OB1_SCAN_1 THEN\n        Motor_Out := TRUE;\n    END_IF;\nEND_ORGANIZATION_BLOCK\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let result = check(&program, &s7());
+        let v = result.violations.iter().find(|v| v.severity == Severity::Error).unwrap();
+        assert_eq!(v.line, 4);
+        assert_eq!(v.fix, Some(TextEdit::replace_statement(4, 4, "Motor_Out := TRUE;".into(), "Motor_Out := FALSE;".into())));
     }
 }
@@ -1,8 +1,9 @@
 //! Rule 16: Summarize PLC cycle times.
 //! Require OB1 to *capture* OB1_PREV_CYCLE and *emit* it to an HMI/DB/LOG tag.
 
-use crate::ast::{Expression, FunctionKind, Program, Statement};
-use super::{RuleResult, Violation, utils::expr_text};
+use crate::ast::{FunctionKind, Program, Statement};
+use super::{Platform, RuleResult, Severity, Violation, utils::expr_text};
+use super::registry::RULE16;
 
 pub fn check(program: &Program) -> RuleResult {
     let ob1 = program.functions.iter().find(|f| f.kind == FunctionKind::OB1);
@@ -13,19 +14,19 @@ pub fn check(program: &Program) -> RuleResult {
         scan(&f.statements, &mut has_capture, &mut has_emit);
 
         if has_capture && has_emit {
-            RuleResult::ok(16, "Summarize PLC cycle times")
+            RuleResult::ok(RULE16.no, RULE16.name)
         } else {
-            RuleResult::violations(vec![Violation {
-                rule_no: 16,
-                rule_name: "Summarize PLC cycle times",
-                line: f.line,
-                reason: "Cycle-time summary incomplete (capture+emit not both present)".into(),
-                suggestion: "In OB1, move OB1_PREV_CYCLE into an HMI/DB/LOG tag (e.g., HMI_CycleTime := OB1_PREV_CYCLE).".into(),
-            }])
+            RuleResult::violations(vec![Violation::new(
+                &RULE16,
+                f.line,
+                "Cycle-time summary incomplete (capture+emit not both present)",
+                "In OB1, move OB1_PREV_CYCLE into an HMI/DB/LOG tag (e.g., HMI_CycleTime := OB1_PREV_CYCLE).",
+                Severity::Info,
+            )])
         }
     } else {
         // No OB1? Treat as OK for portability (or change to WARN/NOT FOLLOWED per policy)
-        RuleResult::ok(16, "Summarize PLC cycle times")
+        RuleResult::ok(RULE16.no, RULE16.name)
     }
 }
 
@@ -33,11 +34,14 @@ fn scan(stmts: &[Statement], cap: &mut bool, emit: &mut bool) {
     for st in stmts {
         match st {
             Statement::Assign { target, value, .. } => {
-                if let Expression::Identifier(target_name) = target {
+                if let Some(target_name) = target.name() {
                     let v = expr_text(value).to_ascii_uppercase();
                     let t = target_name.to_ascii_uppercase();
-                    if v.contains("OB1_PREV_CYCLE") { *cap = true; }
-                    if (t.contains("HMI") || t.contains("DB") || t.contains("LOG")) && v.contains("OB1_PREV_CYCLE") {
+                    let reads_cycle = Platform::S7.intrinsics().cycle_time.iter().any(|n| v.contains(n));
+                    if reads_cycle { *cap = true; }
+                    // An HMI, DB or log tag here is where diagnostics are reported, not
+                    // operator input, so this is not `utils::IdentifierClass::HmiInput`.
+                    if (t.contains("HMI") || t.contains("DB") || t.contains("LOG")) && reads_cycle {
                         *emit = true;
                     }
                 }
@@ -1,79 +1,141 @@
 // rule17.rs
-use crate::ast::{Expression, Program, Statement};
-use super::{RuleResult, Violation, utils::expr_text};
+use std::collections::HashSet;
+
+use crate::ast::{BinOp, Expression, Program, Statement};
+use super::visit::{walk_program, walk_statement, Visitor};
+use super::{Platform, RuleResult, Severity, Violation, utils::expr_text};
+use super::registry::RULE17;
 
 pub fn check(program: &Program) -> RuleResult {
     let mut violations = vec![];
-    
-    // These flags should be for the whole program, not per-function
-    let mut has_sfc6 = false;
-    let mut sfc6_line = None;
-    let mut monotonic_uptime = false;
-    let mut uptime_reported = false;
-    let mut first_line = 0;
-
-    for f in &program.functions {
-        if first_line == 0 { first_line = f.line; }
-        // First pass: detect SFC6/RD_SINFO and monotonic counter in this function
-        for st in &f.statements {
-            match st {
-                Statement::Call { name, line, .. } => {
-                    let up = name.to_ascii_uppercase();
-                    if up.contains("SFC6") || up.contains("RD_SINFO") {
-                        has_sfc6 = true;
-                        sfc6_line = Some(*line);
-                    }
-                }
-                Statement::Assign { target, value, .. } => {
-                    if let Expression::Identifier(target_name) = target {
-                        let tgt = target_name.to_ascii_uppercase();
-                        let vtxt = expr_text(value).to_ascii_uppercase();
-                        if tgt.contains("UPTIME") && vtxt.contains("UPTIME") && vtxt.contains("+") {
-                            monotonic_uptime = true;
-                        }
-                    }
-                }
-                _ => {}
-            }
+    let first_line = program.functions.first().map(|f| f.line).unwrap_or(0);
+
+    // These flags are for the whole program, not per-function
+    let mut scan = UptimeScan::default();
+    walk_program(&mut scan, program);
+
+    // Either an uptime variable counts itself up, or it is copied from a
+    // counter that does (`Counter := Counter + 1; Uptime := Counter;`).
+    let monotonic_uptime = scan.increments.iter().any(|v| v.contains("UPTIME"))
+        || scan.copies.iter().any(|(target, source)| target.contains("UPTIME") && scan.increments.contains(source));
+
+    // Decide after checking all functions
+    if let Some(sfc6_line) = scan.sfc6_line {
+        if !scan.uptime_reported {
+            violations.push(Violation::new(
+                &RULE17,
+                sfc6_line,
+                "SFC6/RD_SINFO used but uptime not reported",
+                "Assign SFC6/RD_SINFO runtime to an HMI/DB tag for monitoring.",
+                Severity::Info,
+            ));
         }
+    } else if !(monotonic_uptime && scan.uptime_reported) { // If SFC6 path fails, check monotonic path
+        violations.push(Violation::new(
+            &RULE17,
+            first_line,
+            "No monotonic uptime logging detected",
+            "Add an uptime counter (monotonic) and periodically store/log it to HMI/DB.",
+            Severity::Info,
+        ));
+    }
+
+    RuleResult::violations(violations)
+}
 
-        // Second pass: detect reporting to HMI/DB/LOG
-        for st in &f.statements {
-            if let Statement::Assign { target, value, .. } = st {
-                if let Expression::Identifier(target_name) = target {
+/// Facts gathered from every statement of the program. Variable names are
+/// stored upper-cased.
+#[derive(Default)]
+struct UptimeScan {
+    sfc6_line: Option<usize>,
+    /// Variables assigned from themselves plus something (`X := X + 1`).
+    increments: HashSet<String>,
+    /// Plain copies `target := source`.
+    copies: Vec<(String, String)>,
+    uptime_reported: bool,
+}
+
+impl Visitor for UptimeScan {
+    fn visit_statement(&mut self, st: &Statement) {
+        match st {
+            Statement::Call { name, line, .. } => {
+                let up = name.to_ascii_uppercase();
+                if reads_uptime(&up) && self.sfc6_line.is_none() {
+                    self.sfc6_line = Some(*line);
+                }
+            }
+            Statement::Assign { target, value, .. } => {
+                if let Some(target_name) = target.name() {
                     let tgt = target_name.to_ascii_uppercase();
+                    if is_self_increment(&target_name, value) {
+                        self.increments.insert(tgt.clone());
+                    }
+                    if let Some(source) = value.name() {
+                        self.copies.push((tgt.clone(), source.to_ascii_uppercase()));
+                    }
+
                     let vtxt = expr_text(value).to_ascii_uppercase();
+                    // Uptime shown on an HMI tag counts as reported. That tag is a
+                    // display target, unlike the operator inputs of `IdentifierClass::HmiInput`.
                     if (tgt.contains("HMI") || tgt.contains("DB") || tgt.contains("LOG"))
-                        && (vtxt.contains("UPTIME") || vtxt.contains("SFC6") || vtxt.contains("RD_SINFO") || vtxt.contains("RUNTIME"))
+                        && (vtxt.contains("UPTIME") || vtxt.contains("RUNTIME") || reads_uptime(&vtxt))
                     {
-                        uptime_reported = true;
-                        break;
+                        self.uptime_reported = true;
                     }
                 }
             }
+            _ => {}
         }
+        walk_statement(self, st);
     }
+}
 
-    // Decide after checking all functions
-    if has_sfc6 {
-        if !uptime_reported {
-            violations.push(Violation {
-                rule_no: 17,
-                rule_name: "Log PLC uptime",
-                line: sfc6_line.unwrap_or(first_line),
-                reason: "SFC6/RD_SINFO used but uptime not reported".into(),
-                suggestion: "Assign SFC6/RD_SINFO runtime to an HMI/DB tag for monitoring.".into(),
-            });
+/// `target` appears as an operand of an addition on the right-hand side,
+/// e.g. `Uptime := Uptime + 1` or `Uptime := Delta + (Uptime + 1)`.
+fn is_self_increment(target: &str, value: &Expression) -> bool {
+    match value {
+        Expression::BinaryOp { op: BinOp::Add, left, right, .. } => {
+            is_operand(target, left) || is_operand(target, right)
         }
-    } else if !(monotonic_uptime && uptime_reported) { // If SFC6 path fails, check monotonic path
-        violations.push(Violation {
-            rule_no: 17,
-            rule_name: "Log PLC uptime",
-            line: first_line,
-            reason: "No monotonic uptime logging detected".into(),
-            suggestion: "Add an uptime counter (monotonic) and periodically store/log it to HMI/DB.".into(),
-        });
+        _ => false,
     }
+}
 
-    RuleResult::violations(violations)
-}
\ No newline at end of file
+fn is_operand(target: &str, e: &Expression) -> bool {
+    match e {
+        Expression::Identifier(_) | Expression::Member { .. } => {
+            e.name().is_some_and(|name| name.eq_ignore_ascii_case(target))
+        }
+        _ => is_self_increment(target, e),
+    }
+}
+
+/// Whether upper-cased `text` mentions one of the uptime instructions.
+fn reads_uptime(text: &str) -> bool {
+    Platform::S7.intrinsics().uptime.iter().any(|n| text.contains(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::parse_block;
+
+    fn passes(body: &str) -> bool {
+        check(&parse_block("ORGANIZATION_BLOCK OB1", body)).passed()
+    }
+
+    #[test]
+    fn increment_inside_if_is_found() {
+        assert!(passes("IF Tick THEN\n    Uptime := Uptime + Delta;\nEND_IF;\nHMI_Uptime := Uptime;"));
+    }
+
+    #[test]
+    fn counter_copied_into_uptime_is_monotonic() {
+        assert!(passes("Counter := Counter + 1;\nUptime := Counter;\nDB_Log := Uptime;"));
+    }
+
+    #[test]
+    fn plain_assignment_is_not_monotonic() {
+        assert!(!passes("Uptime := Start_Uptime;\nHMI_Uptime := Uptime;"));
+    }
+}
@@ -1,54 +1,81 @@
 //! Rule 18: Log PLC hard stops.
 //! Verify OB86 (Rack Failure), OB121 (Programming Error), OB82 (Diagnostic Interrupt)
-//! exist and contain at least one diagnostic/alarm action.
+//! exist and contain at least one diagnostic/alarm action. Sites that also
+//! rely on OB83 (Insert/Remove Module) or OB85 (Program Execution Error)
+//! list them in the policy's `hard_stop_obs`. With `standalone_library`
+//! set, missing OBs are not reported; OBs that are present are still checked.
 
-use crate::ast::{Expression, FunctionKind, Program, Statement};
-use super::{Policy, RuleResult, Violation};
+use crate::ast::{FunctionKind, Program, Statement};
+use super::{Policy, RuleResult, Severity, Violation};
+use super::registry::RULE18;
 
 pub fn check(program: &Program, policy: &Policy) -> RuleResult {
     // This is an S7-specific rule. Only run if the platform is configured as S7.
     let is_s7 = policy.platform.as_deref().unwrap_or("").eq_ignore_ascii_case("S7");
     if !is_s7 {
-        return RuleResult::ok(18, "Log PLC hard stops");
+        return RuleResult::ok(RULE18.no, RULE18.name);
     }
 
     let mut violations = vec![];
-    check_ob(program, FunctionKind::OB86, "OB86 (Rack Failure)", &mut violations);
-    check_ob(program, FunctionKind::OB121, "OB121 (Programming Error)", &mut violations);
-    check_ob(program, FunctionKind::OB82, "OB82 (Diagnostic Interrupt)", &mut violations);
+    let required: Vec<&str> = match &policy.hard_stop_obs {
+        Some(obs) => obs.iter().map(String::as_str).collect(),
+        None => DEFAULT_OBS.to_vec(),
+    };
+    for (kind, label) in required.iter().filter_map(|n| fault_ob(n)) {
+        check_ob(program, kind, label, policy.is_standalone_library(), &mut violations);
+    }
 
     RuleResult::violations(violations)
 }
 
-fn check_ob(program: &Program, kind: FunctionKind, name: &str, out: &mut Vec<Violation>) {
+/// Fault OBs this rule knows about: policy name, kind and report label.
+pub const FAULT_OBS: &[(&str, FunctionKind, &str)] = &[
+    ("OB82", FunctionKind::OB82, "OB82 (Diagnostic Interrupt)"),
+    ("OB83", FunctionKind::OB83, "OB83 (Insert/Remove Module)"),
+    ("OB85", FunctionKind::OB85, "OB85 (Program Execution Error)"),
+    ("OB86", FunctionKind::OB86, "OB86 (Rack Failure)"),
+    ("OB121", FunctionKind::OB121, "OB121 (Programming Error)"),
+];
+
+pub const DEFAULT_OBS: &[&str] = &["OB86", "OB121", "OB82"];
+
+/// Look up a fault OB by its policy name (case-insensitive).
+pub fn fault_ob(name: &str) -> Option<(FunctionKind, &'static str)> {
+    FAULT_OBS
+        .iter()
+        .find(|(n, _, _)| n.eq_ignore_ascii_case(name.trim()))
+        .map(|&(_, kind, label)| (kind, label))
+}
+
+fn check_ob(program: &Program, kind: FunctionKind, name: &str, library: bool, out: &mut Vec<Violation>) {
     if let Some(f) = program.functions.iter().find(|fb| fb.kind == kind) {
         if f.statements.is_empty() {
-            out.push(Violation {
-                rule_no: 18,
-                rule_name: "Log PLC hard stops",
-                line: f.line,
-                reason: format!("{name} present but empty"),
-                suggestion: "Log/record diagnostics and take safe action in this OB.".into(),
-            });
+            out.push(Violation::new(
+                &RULE18,
+                f.line,
+                format!("{name} present but empty"),
+                "Log/record diagnostics and take safe action in this OB.",
+                Severity::Warning,
+            ));
             return;
         }
         if !has_diag_action(&f.statements) {
-            out.push(Violation {
-                rule_no: 18,
-                rule_name: "Log PLC hard stops",
-                line: f.line,
-                reason: format!("{name} present but no diagnostic/alarm action"),
-                suggestion: "Write a diagnostic/alarm/record action in this OB.".into(),
-            });
+            out.push(Violation::new(
+                &RULE18,
+                f.line,
+                format!("{name} present but no diagnostic/alarm action"),
+                "Write a diagnostic/alarm/record action in this OB.",
+                Severity::Warning,
+            ));
         }
-    } else {
-        out.push(Violation {
-            rule_no: 18,
-            rule_name: "Log PLC hard stops",
-            line: 0,
-            reason: format!("{name} missing or empty"),
-            suggestion: format!("Implement {name} to capture and log diagnostics.").into(),
-        });
+    } else if !library {
+        out.push(Violation::new(
+            &RULE18,
+            0,
+            format!("{name} missing or empty"),
+            format!("Implement {name} to capture and log diagnostics."),
+            Severity::Warning,
+        ));
     }
 }
 
@@ -56,7 +83,7 @@ fn has_diag_action(stmts: &[Statement]) -> bool {
     for st in stmts {
         match st {
             Statement::Assign { target, value, .. } => {
-                if let Expression::Identifier(target_name) = target {
+                if let Some(target_name) = target.name() {
                     let t = target_name.to_ascii_uppercase();
                     let v = super::utils::expr_text(value).to_ascii_uppercase();
                     if t.contains("ALARM") || t.contains("DIAG") || t.contains("FAULT") || v.contains("LOG") {
@@ -70,8 +97,8 @@ fn has_diag_action(stmts: &[Statement]) -> bool {
                     return true;
                 }
             }
-            Statement::IfStmt { then_branch, else_branch, .. } => {
-                if has_diag_action(then_branch) || has_diag_action(else_branch) { return true; }
+            Statement::IfStmt { then_branch, else_branch, .. } if has_diag_action(then_branch) || has_diag_action(else_branch) => {
+                return true;
             }
             Statement::CaseStmt { cases, else_branch, .. } => {
                 for (_, body) in cases {
@@ -84,3 +111,35 @@ fn has_diag_action(stmts: &[Statement]) -> bool {
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    fn reasons(src: &str, obs: Option<&[&str]>) -> Vec<String> {
+        let policy = Policy {
+            platform: Some("S7".into()),
+            hard_stop_obs: obs.map(|o| o.iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
+        };
+        check(&parse_scl_from_str(src).unwrap(), &policy).violations.into_iter().map(|v| v.reason).collect()
+    }
+
+    #[test]
+    fn extra_fault_obs_are_only_checked_when_listed() {
+        let src = "ORGANIZATION_BLOCK OB85\nBEGIN\n    Fault_Code := 85;\nEND_ORGANIZATION_BLOCK\n";
+        assert_eq!(reasons(src, None).len(), 3);
+        assert!(reasons(src, Some(&["OB85"])).is_empty());
+        assert_eq!(reasons(src, Some(&["OB83", "OB85"])), vec!["OB83 (Insert/Remove Module) missing or empty"]);
+    }
+
+    #[test]
+    fn library_mode_only_checks_obs_that_exist() {
+        let src = "ORGANIZATION_BLOCK OB121\nBEGIN\n    Count := Count + 1;\nEND_ORGANIZATION_BLOCK\n";
+        let policy = Policy { platform: Some("S7".into()), standalone_library: Some(true), ..Default::default() };
+        let reasons: Vec<String> =
+            check(&parse_scl_from_str(src).unwrap(), &policy).violations.into_iter().map(|v| v.reason).collect();
+        assert_eq!(reasons, vec!["OB121 (Programming Error) present but no diagnostic/alarm action"]);
+    }
+}
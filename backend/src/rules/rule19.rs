@@ -1,83 +1,146 @@
 // rule19.rs
-use crate::ast::{Expression, Program, Statement};
-use super::{RuleResult, Violation, utils::expr_text};
+use crate::ast::{BinOp, Expression, Program, Statement};
+use super::visit::{walk_function, walk_statement, Visitor};
+use super::{Platform, RuleResult, Severity, Violation};
+use super::registry::RULE19;
 
 pub fn check(program: &Program) -> RuleResult {
     let mut violations = vec![];
     let mut found_any_read = false;
     let mut first_line = 0;
 
-    for f in &program.functions {
+    for f in program.code() {
         if first_line == 0 { first_line = f.line; }
-        let mut read = None;
-        let mut compare = false;
-        let mut emit = false;
+        let mut scan = MemoryScan::default();
+        walk_function(&mut scan, f);
 
-        scan(&f.statements, &mut read, &mut compare, &mut emit);
-
-        if let Some(read_line) = read {
+        if let Some(read_line) = scan.read {
             found_any_read = true;
-            if !(compare && emit) {
-                violations.push(Violation {
-                    rule_no: 19,
-                    rule_name: "Monitor PLC memory usage",
-                    line: read_line,
-                    reason: "Memory usage read but not compared and/or emitted".into(),
-                    suggestion: "Compare memory usage to thresholds and log/assign to HMI/DB.".into(),
-                });
+            if !(scan.compare && scan.emit) {
+                violations.push(Violation::new(
+                    &RULE19,
+                    read_line,
+                    "Memory usage read but not compared and/or emitted",
+                    "Compare memory usage to thresholds and log/assign to HMI/DB.",
+                    Severity::Info,
+                ));
             }
         }
     }
     
     // Only add this violation after checking all functions
     if !found_any_read && first_line > 0 {
-        violations.push(Violation {
-            rule_no: 19,
-            rule_name: "Monitor PLC memory usage",
-            line: first_line,
-            reason: "No evidence of memory monitoring found.".into(),
-            suggestion: "Implement memory monitoring (e.g., using SFC24/TEST_DB) to prevent overflows.".into(),
-        });
+        violations.push(Violation::new(
+            &RULE19,
+            first_line,
+            "No evidence of memory monitoring found.",
+            "Implement memory monitoring (e.g., using SFC24/TEST_DB) to prevent overflows.",
+            Severity::Info,
+        ));
     }
 
     RuleResult::violations(violations)
 }
 
-fn scan(stmts: &[Statement], read: &mut Option<usize>, compare: &mut bool, emit: &mut bool) {
-    for st in stmts {
+#[derive(Default)]
+struct MemoryScan {
+    read: Option<usize>,
+    compare: bool,
+    emit: bool,
+}
+
+impl Visitor for MemoryScan {
+    fn visit_statement(&mut self, st: &Statement) {
         match st {
-            Statement::Call { name, line, .. } => {
-                let up = name.to_ascii_uppercase();
-                if up.contains("SFC24") || up.contains("TEST_DB") {
-                    if read.is_none() { *read = Some(*line); }
-                }
-            }
+            Statement::Call { name, line, .. } if is_memory_read(name) && self.read.is_none() => self.read = Some(*line),
             Statement::Assign { target, value, .. } => {
-                if let Expression::Identifier(target_name) = target {
-                    let tgt = target_name.to_ascii_uppercase();
-                    let vtxt = expr_text(value).to_ascii_uppercase();
-                    if (tgt.contains("HMI") || tgt.contains("DB") || tgt.contains("LOG"))
-                        && (vtxt.contains("SFC24") || vtxt.contains("TEST_DB") || vtxt.contains("MEM"))
-                    {
-                        *emit = true;
-                    }
-                }
-            }
-            Statement::IfStmt { condition, then_branch, else_branch, .. } => {
-                let c = expr_text(condition).to_ascii_uppercase();
-                if c.contains('>') || c.contains('<') {
-                    if c.contains("SFC24") || c.contains("TEST_DB") || c.contains("MEM") || c.contains("%MW") || c.contains("%DB") {
-                        *compare = true;
-                    }
+                let tgt = target.name().unwrap_or_default().to_ascii_uppercase();
+                // The target is where the memory status is published, so it is
+                // matched by name here rather than as an `IdentifierClass`.
+                if (tgt.contains("HMI") || tgt.contains("DB") || tgt.contains("LOG")) && reads(value, is_memory_value) {
+                    self.emit = true;
                 }
-                scan(then_branch, read, compare, emit);
-                scan(else_branch, read, compare, emit);
-            }
-            Statement::CaseStmt { cases, else_branch, .. } => {
-                for (_, body) in cases { scan(body, read, compare, emit); }
-                scan(else_branch, read, compare, emit);
             }
+            Statement::IfStmt { condition, .. } if compares_memory(condition) => self.compare = true,
             _ => {}
         }
+        walk_statement(self, st);
+    }
+}
+
+/// The memory-status instructions.
+fn is_memory_read(name: &str) -> bool {
+    let up = name.to_ascii_uppercase();
+    Platform::S7.intrinsics().memory.iter().any(|n| up.contains(n))
+}
+
+/// A memory-status call, or a variable holding its result (`Mem_Used`).
+fn is_memory_value(name: &str) -> bool {
+    is_memory_read(name) || name.to_ascii_uppercase().contains("MEM")
+}
+
+/// True if `e` has an ordering comparison (`<`, `<=`, `>`, `>=`) with a
+/// memory value or a memory address (`%MW`, `%DB`) on either side.
+/// `<>` and `=` are not thresholds and do not count.
+fn compares_memory(e: &Expression) -> bool {
+    let is_memory = |name: &str| {
+        let up = name.to_ascii_uppercase();
+        is_memory_value(name) || up.starts_with("%MW") || up.starts_with("%DB")
+    };
+    match e {
+        Expression::BinaryOp { op: BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge, left, right, .. }
+            if reads(left, is_memory) || reads(right, is_memory) =>
+        {
+            true
+        }
+        Expression::BinaryOp { left, right, .. } => compares_memory(left) || compares_memory(right),
+        Expression::UnaryOp { expr, .. } => compares_memory(expr),
+        Expression::FuncCall { args, .. } => args.iter().any(compares_memory),
+        _ => false,
     }
-}
\ No newline at end of file
+}
+
+/// True if `e` reads a variable, or calls a function, whose name satisfies `pred`.
+fn reads(e: &Expression, pred: impl Fn(&str) -> bool + Copy) -> bool {
+    if let Some(name) = e.name() {
+        return pred(&name);
+    }
+    match e {
+        Expression::FuncCall { name, args, .. } => pred(name) || args.iter().any(|a| reads(a, pred)),
+        Expression::BinaryOp { left, right, .. } => reads(left, pred) || reads(right, pred),
+        Expression::UnaryOp { expr, .. } => reads(expr, pred),
+        Expression::Index { base, index, .. } => reads(base, pred) || reads(index, pred),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::parse_block;
+
+    fn reasons(body: &str) -> Vec<String> {
+        check(&parse_block("FUNCTION FC_Mem", body)).violations.into_iter().map(|v| v.reason).collect()
+    }
+
+    #[test]
+    fn read_compare_and_emit_pass() {
+        let body = "SFC24(DB_NUMBER := 10, RET_VAL := Ret, DB_LENGTH := Mem_Used);\nIF Mem_Used > 60000 THEN\n    \
+                    HMI_MemAlarm := TRUE;\nEND_IF;\nHMI_MemUsed := Mem_Used;";
+        assert!(reasons(body).is_empty());
+    }
+
+    #[test]
+    fn not_equal_is_not_a_threshold() {
+        let body = "SFC24(DB_NUMBER := 10, RET_VAL := Ret, DB_LENGTH := Mem_Used);\nIF Mem_Used <> 0 THEN\n    \
+                    HMI_MemUsed := Mem_Used;\nEND_IF;";
+        assert_eq!(reasons(body), vec!["Memory usage read but not compared and/or emitted".to_string()]);
+    }
+
+    #[test]
+    fn comparison_elsewhere_in_the_condition_does_not_count() {
+        let body = "SFC24(DB_NUMBER := 10, RET_VAL := Ret, DB_LENGTH := Mem_Used);\n\
+                    IF Count > 3 AND Mem_Used <> 0 THEN\n    HMI_MemUsed := Mem_Used;\nEND_IF;";
+        assert_eq!(reasons(body).len(), 1);
+    }
+}
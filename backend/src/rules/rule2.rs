@@ -1,61 +1,73 @@
 //! Rule 2: Track operating modes.
 
 use crate::ast::*;
-use super::{RuleResult, Violation, utils::expr_text};
+use super::visit::{walk_program, walk_statement, Visitor};
+use super::{RuleResult, Severity, Violation, utils::expr_text};
+use super::registry::RULE2;
 
 pub fn check(program: &Program) -> RuleResult {
-    let mut has_mode = false;
-    let mut first_fn_line = 0usize;
-
-    for f in &program.functions {
-        if first_fn_line == 0 { first_fn_line = f.line; }
-        for st in &f.statements {
-            match st {
-                Statement::Assign { target, .. } => {
-                    if let Expression::Identifier(name) = target {
-                        let n = name.to_ascii_uppercase();
-                        if n.contains("MODE") || n.contains("AUTO") || n.contains("MANUAL") || n.contains("RUNSTATE") {
-                            has_mode = true; break;
-                        }
-                    }
-                }
-                Statement::IfStmt { condition, .. } => {
-                    if condition_uses_mode_var(condition) {
-                        has_mode = true; break;
-                    }
-                }
-                 Statement::CaseStmt { expression, .. } => {
-                    let c = expr_text(expression).to_ascii_uppercase();
-                    // A CASE statement on a variable with "STATE" or "STEP" is a state machine.
-                    if c.contains("MODE") || c.contains("STATE") || c.contains("STEP") {
-                        has_mode = true; break; // This break is for the inner loop
-                    }
-                }
-                _ => {}
-            }
-        }
-        if has_mode { break; }
-    }
+    let first_fn_line = program.functions.first().map(|f| f.line).unwrap_or(0);
+    let mut finder = ModeFinder { has_mode: false };
+    walk_program(&mut finder, program);
+    let has_mode = finder.has_mode;
 
     if has_mode {
-        RuleResult::ok(2, "Track operating modes")
+        RuleResult::ok(RULE2.no, RULE2.name)
     } else {
         RuleResult::violations(vec![Violation{
-            rule_no: 2,
-            rule_name: "Track operating modes".into(),
+            rule_no: RULE2.no,
+            rule_name: RULE2.name,
             line: first_fn_line, // fallback (Program has no .line)
+            end_line: None,
             reason: "No state machine or explicit mode-tracking variable found.".into(),
-            suggestion: "Implement a CASE state machine or guard logic on CPU_MODE/Mode/RunState.".into()
+            suggestion: "Implement a CASE state machine or guard logic on CPU_MODE/Mode/RunState.".into(),
+            severity: Severity::Warning,
+            snippet: None,
+            fix: None,
+            function: None,
+            file: None,
+            rationale: None,
+            doc_anchor: None,
         }])
     }
 }
 
+struct ModeFinder {
+    has_mode: bool,
+}
+
+impl Visitor for ModeFinder {
+    fn visit_statement(&mut self, st: &Statement) {
+        if self.has_mode { return; }
+        match st {
+            Statement::Assign { target, .. } => {
+                let n = target.name().unwrap_or_default().to_ascii_uppercase();
+                if n.contains("MODE") || n.contains("AUTO") || n.contains("MANUAL") || n.contains("RUNSTATE") {
+                    self.has_mode = true;
+                }
+            }
+            Statement::IfStmt { condition, .. } if condition_uses_mode_var(condition) => {
+                self.has_mode = true;
+            }
+            Statement::CaseStmt { expression, .. } => {
+                let c = expr_text(expression).to_ascii_uppercase();
+                // A CASE statement on a variable with "STATE" or "STEP" is a state machine.
+                if c.contains("MODE") || c.contains("STATE") || c.contains("STEP") {
+                    self.has_mode = true;
+                }
+            }
+            _ => {}
+        }
+        walk_statement(self, st);
+    }
+}
+
 /// Recursively check an expression to see if it references a mode-related variable.
 /// This is more robust than converting the expression to text and searching.
 fn condition_uses_mode_var(e: &Expression) -> bool {
     match e {
-        Expression::Identifier(s) => {
-            let up = s.trim().to_ascii_uppercase();
+        Expression::Identifier(_) | Expression::Member { .. } => {
+            let up = e.name().unwrap_or_default().trim().to_ascii_uppercase();
             up.contains("CPU_MODE") || up.contains("MODE") || up.contains("RUNSTATE") // Check for mode-related keywords
         }
         Expression::UnaryOp { expr, .. } => condition_uses_mode_var(expr),
@@ -1,38 +1,41 @@
 //! Rule 20: Trap false alerts.
 //! For each Critical_Alert_* signal, require existence *and usage* of
 //! Critical_Alert_*_False_Negative and Critical_Alert_*_False_Positive.
+//! Names are matched by `utils::ident_key`, so casing may differ.
 
-use crate::ast::{Program, Statement, Expression};
-use super::{RuleResult, Violation};
+use crate::ast::{Program, Statement};
+use super::{utils, RuleResult, Severity, Violation};
 use std::collections::HashSet;
+use super::registry::RULE20;
 
 pub fn check(program: &Program) -> RuleResult {
     let mut violations = vec![];
 
-    for f in &program.functions {
+    for f in program.code() {
         let mut names = HashSet::new();
         let mut lines = vec![];
         collect_names(&f.statements, &mut names, &mut lines);
 
         for (name, ln) in lines {
-            if let Some(prefix) = name.strip_prefix("Critical_Alert_") {
-                if prefix.ends_with("_False_Negative") || prefix.ends_with("_False_Positive") {
+            let key = utils::ident_key(&name);
+            if let Some(prefix) = key.strip_prefix("CRITICAL_ALERT_") {
+                if prefix.ends_with("_FALSE_NEGATIVE") || prefix.ends_with("_FALSE_POSITIVE") {
                     continue;
                 }
-                let fn_var = format!("Critical_Alert_{}_False_Negative", prefix);
-                let fp_var = format!("Critical_Alert_{}_False_Positive", prefix);
+                let fn_var = format!("CRITICAL_ALERT_{}_FALSE_NEGATIVE", prefix);
+                let fp_var = format!("CRITICAL_ALERT_{}_FALSE_POSITIVE", prefix);
 
                 let have_both = names.contains(&fn_var) && names.contains(&fp_var);
                 let used_both = signal_used(&f.statements, &fn_var) && signal_used(&f.statements, &fp_var);
 
                 if !(have_both && used_both) {
-                    violations.push(Violation {
-                        rule_no: 20,
-                        rule_name: "Trap false alerts",
-                        line: ln,
-                        reason: format!("Missing or unused trap variables for '{}'", name),
-                        suggestion: "Define and wire both *_False_Negative and *_False_Positive signals into logic/logs.".into(),
-                    });
+                    violations.push(Violation::new(
+                        &RULE20,
+                        ln,
+                        format!("Missing or unused trap variables for '{}'", name),
+                        "Define and wire both *_False_Negative and *_False_Positive signals into logic/logs.",
+                        Severity::Warning,
+                    ));
                 }
             }
         }
@@ -45,39 +48,52 @@ fn signal_used(stmts: &[Statement], signal: &str) -> bool {
     for st in stmts {
         match st {
             Statement::Assign { target, value, .. } => {
-                if let Expression::Identifier(target_name) = target {
-                    if target_name == signal { return true; }
+                if let Some(target_name) = target.name() {
+                    if utils::ident_key(&target_name) == utils::ident_key(signal) { return true; }
                 }
-                if super::utils::expr_text(value).contains(signal) { return true; } // Check RHS
+                if mentions(&utils::expr_text(value), signal) { return true; } // Check RHS
             }
             Statement::IfStmt { condition, then_branch, else_branch, .. } => {
-                if super::utils::expr_text(condition).contains(signal) { return true; }
+                if mentions(&utils::expr_text(condition), signal) { return true; }
                 if signal_used(then_branch, signal) || signal_used(else_branch, signal) { return true; }
             }
             // labels are Vec<Expression>; check each label's text
             Statement::CaseStmt { cases, else_branch, .. } => {
                 for (labels, body) in cases {
-                    if labels.iter().any(|e| super::utils::expr_text(e).contains(signal)) { return true; }
+                    if labels.iter().any(|e| mentions(&utils::expr_text(e), signal)) { return true; }
                     if signal_used(body, signal) { return true; }
                 }
                 if signal_used(else_branch, signal) { return true; }
             }
-            Statement::Call { name, .. } => {
-                if name == signal { return true; }
-            }
+            Statement::Call { name, .. } if utils::ident_key(name) == utils::ident_key(signal) => return true,
             _ => {}
         }
     }
     false
 }
 
+/// True if `signal` occurs in `text` as a whole identifier, in any case,
+/// so that `Critical_Alert_X_Extra` does not count as a use of
+/// `Critical_Alert_X`.
+fn mentions(text: &str, signal: &str) -> bool {
+    let (text, signal) = (text.to_ascii_uppercase(), utils::ident_key(signal));
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    text.match_indices(signal.as_str()).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + signal.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+/// Every assigned or called name of `stmts`: `names` holds their
+/// [`utils::ident_key`]s, `lines` the original spelling and line.
 fn collect_names(stmts: &[Statement], names: &mut HashSet<String>, lines: &mut Vec<(String, usize)>) {
     for st in stmts {
         match st {
             Statement::Assign { target, line, .. } => {
-                if let Expression::Identifier(name) = target {
-                    names.insert(name.clone());
-                    lines.push((name.clone(), *line));
+                if let Some(name) = target.name() {
+                    names.insert(utils::ident_key(&name));
+                    lines.push((name, *line));
                 }
             }
             Statement::IfStmt { then_branch, else_branch, .. } => {
@@ -89,10 +105,43 @@ fn collect_names(stmts: &[Statement], names: &mut HashSet<String>, lines: &mut V
                 collect_names(else_branch, names, lines);
             }
             Statement::Call { name, line, .. } => {
-                names.insert(name.clone());
+                names.insert(utils::ident_key(name));
                 lines.push((name.clone(), *line));
             }
             _ => {}
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::il::parse_il_from_str;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn il_alert_without_false_positive_trap_is_flagged() {
+        let src = "LD Sensor\nST Critical_Alert_Temp\nLD Check\nST Critical_Alert_Temp_False_Negative\n";
+        let program = parse_il_from_str(src).unwrap();
+        let result = check(&program);
+        assert_eq!(result.violations.len(), 1);
+        assert_eq!(result.violations[0].line, 2);
+    }
+
+    #[test]
+    fn longer_name_is_not_a_use_of_the_signal() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    Log := Critical_Alert_X_Extra;\nEND_ORGANIZATION_BLOCK\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let stmts = &program.functions[0].statements;
+        assert!(!signal_used(stmts, "Critical_Alert_X"));
+        assert!(signal_used(stmts, "Critical_Alert_X_Extra"));
+    }
+
+    #[test]
+    fn trap_variables_match_regardless_of_case() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    Critical_Alert_X := Sensor;\n    \
+                   CRITICAL_ALERT_X_FALSE_NEGATIVE := Check;\n    critical_alert_x_false_positive := Noise;\n    \
+                   Log := critical_alert_x_False_Negative OR Critical_Alert_X_False_Positive;\nEND_ORGANIZATION_BLOCK\n";
+        assert!(check(&parse_scl_from_str(src).unwrap()).violations.is_empty());
+    }
+}
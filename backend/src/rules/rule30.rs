@@ -0,0 +1,61 @@
+//! Rule 30: Avoid empty branches.
+//! Flag `IF` statements whose THEN branch is empty, or that have an `ELSE`
+//! with nothing in it. Empty branches usually mean deleted logic or a
+//! placeholder that never got filled in.
+
+use crate::ast::{is_empty_block, Program, Statement};
+use super::visit::{walk_program, walk_statement, Visitor};
+use super::{RuleResult, Severity, Violation};
+use super::registry::RULE30;
+
+pub fn check(program: &Program) -> RuleResult {
+    let mut finder = EmptyBranches { out: Vec::new() };
+    walk_program(&mut finder, program);
+
+    if finder.out.is_empty() {
+        RuleResult::ok(RULE30.no, RULE30.name)
+    } else {
+        RuleResult::violations(finder.out)
+    }
+}
+
+struct EmptyBranches {
+    out: Vec<Violation>,
+}
+
+impl EmptyBranches {
+    fn report(&mut self, line: usize, reason: &str) {
+        self.out.push(Violation::new(&RULE30, line, reason, "Implement the missing handling or remove the empty branch.", Severity::Info));
+    }
+}
+
+impl Visitor for EmptyBranches {
+    fn visit_statement(&mut self, st: &Statement) {
+        if let Statement::IfStmt { then_branch, else_branch, line, .. } = st {
+            if is_empty_block(then_branch) {
+                self.report(*line, "IF branch is empty");
+            }
+            if !else_branch.is_empty() && is_empty_block(else_branch) {
+                self.report(*line, "ELSE branch is empty");
+            }
+        }
+        walk_statement(self, st);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn empty_then_and_else_are_reported_at_the_if() {
+        let src = "FUNCTION FC_A\nBEGIN\n    IF Fault THEN\n        ;\n    END_IF;\n    \
+                   IF Run THEN\n        Motor := TRUE;\n    ELSE\n    END_IF;\n    \
+                   IF Stop THEN\n        Motor := FALSE;\n    END_IF;\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap());
+        let found: Vec<(usize, &str)> = result.violations.iter().map(|v| (v.line, v.reason.as_str())).collect();
+        assert_eq!(found, vec![(3, "IF branch is empty"), (6, "ELSE branch is empty")]);
+        assert!(result.violations.iter().all(|v| v.severity == Severity::Info));
+    }
+}
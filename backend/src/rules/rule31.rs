@@ -0,0 +1,93 @@
+//! Rule 31: Avoid self-assignment.
+//! `X := X;` has no effect and is almost always a typo for a different
+//! source. It matters most when the target drives an actuator or setpoint.
+
+use crate::ast::{Expression, Program, Statement};
+use super::visit::{walk_program, walk_statement, Visitor};
+use super::utils::{self, IdentifierClass};
+use super::{Policy, RuleResult, Severity, Violation};
+use super::registry::RULE31;
+
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    let mut finder = SelfAssignments { policy, out: Vec::new() };
+    walk_program(&mut finder, program);
+
+    if finder.out.is_empty() {
+        RuleResult::ok(RULE31.no, RULE31.name)
+    } else {
+        RuleResult::violations(finder.out)
+    }
+}
+
+struct SelfAssignments<'p> {
+    policy: &'p Policy,
+    out: Vec<Violation>,
+}
+
+impl Visitor for SelfAssignments<'_> {
+    fn visit_statement(&mut self, st: &Statement) {
+        if let Statement::Assign { target, value, line } = st {
+            if same(target, value) {
+                let text = utils::expr_text(target);
+                let sink = utils::is_identifier_class(&text, IdentifierClass::Actuator, self.policy);
+                self.out.push(Violation::new(
+                    &RULE31,
+                    *line,
+                    format!("'{}' is assigned to itself", text),
+                    "Check the intended source of this assignment, or remove it.",
+                    if sink { Severity::Warning } else { Severity::Info },
+                ));
+            }
+        }
+        walk_statement(self, st);
+    }
+}
+
+/// Whether `a` and `b` are the same expression, ignoring source lines and
+/// the case of names (SCL names are case-insensitive). String literals
+/// compare exactly, so `Mode := 'Mode';` is not a self-assignment.
+fn same(a: &Expression, b: &Expression) -> bool {
+    match (a, b) {
+        (Expression::Identifier(x), Expression::Identifier(y)) => utils::ident_key(x) == utils::ident_key(y),
+        (Expression::Member { base: b1, field: f1, .. }, Expression::Member { base: b2, field: f2, .. }) => {
+            f1.eq_ignore_ascii_case(f2) && same(b1, b2)
+        }
+        (Expression::Index { base: b1, index: i1, .. }, Expression::Index { base: b2, index: i2, .. }) => {
+            same(b1, b2) && same(i1, i2)
+        }
+        (Expression::UnaryOp { op: o1, expr: e1, .. }, Expression::UnaryOp { op: o2, expr: e2, .. }) => o1 == o2 && same(e1, e2),
+        (
+            Expression::BinaryOp { op: o1, left: l1, right: r1, .. },
+            Expression::BinaryOp { op: o2, left: l2, right: r2, .. },
+        ) => o1 == o2 && same(l1, l2) && same(r1, r2),
+        (Expression::FuncCall { name: n1, args: a1, .. }, Expression::FuncCall { name: n2, args: a2, .. }) => {
+            n1.eq_ignore_ascii_case(n2) && a1.len() == a2.len() && a1.iter().zip(a2).all(|(x, y)| same(x, y))
+        }
+        (Expression::NumberLiteral(x, _), Expression::NumberLiteral(y, _)) => x == y,
+        (Expression::BoolLiteral(x, _), Expression::BoolLiteral(y, _)) => x == y,
+        (Expression::StringLiteral(x, _), Expression::StringLiteral(y, _)) => x == y,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn nested_self_assignments_are_found() {
+        let src = "FUNCTION FC_A\nBEGIN\n    Count := Count;\n    IF Run THEN\n        Motor_Speed := MOTOR_speed;\n        \
+                   Data[i] := Data[i];\n        Data[i] := Data[j];\n    END_IF;\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &Policy::default());
+        let found: Vec<(usize, Severity)> = result.violations.iter().map(|v| (v.line, v.severity)).collect();
+        assert_eq!(found, vec![(3, Severity::Info), (5, Severity::Warning), (6, Severity::Info)]);
+    }
+
+    #[test]
+    fn string_literal_named_like_the_target_is_not_a_self_assignment() {
+        let src = "FUNCTION FC_A\nBEGIN\n    Mode := 'Mode';\n    \"DB_Cfg\".Mode := \"db_cfg\".MODE;\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &Policy::default());
+        assert_eq!(result.violations.iter().map(|v| v.line).collect::<Vec<_>>(), vec![4]);
+    }
+}
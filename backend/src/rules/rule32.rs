@@ -0,0 +1,86 @@
+//! Rule 32: Protect variables shared with interrupt OBs.
+//! A variable written both by an interrupt OB (fault OBs, cyclic interrupts
+//! OB30-OB38) and by other code can be overwritten mid-update when the
+//! interrupt preempts a read-modify-write such as `DB.Count := DB.Count + 1`.
+//! Conservative: only direct assignments in the OB bodies are considered,
+//! not writes made by blocks the OB calls. A write to a variable the
+//! writing block declares in `VAR_TEMP` is local to that call and ignored.
+
+use crate::analysis::Writes;
+use crate::ast::{Function, FunctionKind, Program, VarSection};
+use crate::parser::ob_number;
+use super::{RuleResult, Severity, Violation};
+use super::registry::RULE32;
+
+pub fn check(program: &Program) -> RuleResult {
+    let mut violations = Vec::new();
+    for (name, sites) in Writes::collect(program).iter() {
+        let sites: Vec<_> = sites.iter().filter(|s| !is_temp(s.function, name)).collect();
+        let Some(irq) = sites.iter().find(|s| is_interrupt_ob(s.function)) else { continue };
+        let Some(other) = sites.iter().find(|s| !std::ptr::eq(s.function, irq.function)) else { continue };
+        violations.push(Violation::new(
+            &RULE32,
+            irq.line,
+            format!(
+                "'{}' is written in interrupt {} and in {} (line {})",
+                name, irq.function.name, other.function.name, other.line
+            ),
+            "Protect the shared update (e.g. DIS_AIRT/EN_AIRT around it) or give each OB its own variable.",
+            Severity::Warning,
+        ));
+    }
+    violations.sort_by_key(|v| v.line);
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE32.no, RULE32.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+/// True if `name` (or the variable it is a member or element of) is one of
+/// `f`'s own `VAR_TEMP` variables.
+fn is_temp(f: &Function, name: &str) -> bool {
+    let root = name.split(['.', '[']).next().unwrap_or(name);
+    f.declaration(root).is_some_and(|d| d.section == VarSection::Temp)
+}
+
+/// OBs that can preempt the cyclic program.
+fn is_interrupt_ob(f: &Function) -> bool {
+    match f.kind {
+        FunctionKind::OB82 | FunctionKind::OB83 | FunctionKind::OB85 | FunctionKind::OB86 | FunctionKind::OB121 => true,
+        FunctionKind::OB => ob_number(&f.name).is_some_and(|n| (30..=38).contains(&n)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn variable_written_by_cyclic_interrupt_and_ob1_is_reported() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    Data.Count := Data.Count + 1;\n    Local := 1;\nEND_ORGANIZATION_BLOCK\n\
+                   ORGANIZATION_BLOCK OB35\nBEGIN\n    IF Tick THEN\n        data.count := 0;\n    END_IF;\n    Sample := Sensor;\nEND_ORGANIZATION_BLOCK\n\
+                   FUNCTION FC_A\nBEGIN\n    Sample := 0;\n    Local := 2;\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap());
+        let found: Vec<(usize, &str)> = result.violations.iter().map(|v| (v.line, v.reason.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (9, "'Data.Count' is written in interrupt OB35 and in OB1 (line 3)"),
+                (11, "'Sample' is written in interrupt OB35 and in FC_A (line 15)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn temp_variables_of_the_writing_block_are_not_shared() {
+        let src = "ORGANIZATION_BLOCK OB35\nVAR_TEMP\n    Scratch : INT;\nEND_VAR\nBEGIN\n    Scratch := 1;\n    Shared := 1;\n\
+                   END_ORGANIZATION_BLOCK\nORGANIZATION_BLOCK OB1\nBEGIN\n    Scratch := 2;\n    Shared := 2;\nEND_ORGANIZATION_BLOCK\n";
+        let result = check(&parse_scl_from_str(src).unwrap());
+        let reasons: Vec<&str> = result.violations.iter().map(|v| v.reason.as_str()).collect();
+        assert_eq!(reasons, vec!["'Shared' is written in interrupt OB35 and in OB1 (line 12)"]);
+    }
+}
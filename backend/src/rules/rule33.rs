@@ -0,0 +1,69 @@
+//! Rule 33: Give each critical output a single point of control.
+//! A critical output (see `utils::IdentifierClass::CriticalOutput`)
+//! assigned in more than one place ends up with whichever write ran last
+//! in the scan, so two blocks driving the same motor race each other. Writes in different
+//! arms of one IF/CASE never run together and do not compete; restart
+//! initialization (OB100 or a first-scan block) is not counted either.
+
+use crate::analysis::{WriteSite, Writes};
+use crate::ast::{FunctionKind, Program};
+use super::utils::{is_first_scan_guard, is_identifier_class, IdentifierClass};
+use super::{Policy, RuleResult, Severity, Violation};
+use super::registry::RULE33;
+
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    let mut violations = Vec::new();
+    for (name, sites) in Writes::collect(program).iter() {
+        if !is_identifier_class(name, IdentifierClass::CriticalOutput, policy) {
+            continue;
+        }
+        let cyclic: Vec<&WriteSite> = sites.iter().filter(|s| !is_restart_init(s)).collect();
+        let competing: Vec<&WriteSite> = cyclic
+            .iter()
+            .filter(|s| cyclic.iter().any(|o| !std::ptr::eq(**s, *o) && !s.excludes(o)))
+            .copied()
+            .collect();
+        if competing.len() < 2 {
+            continue;
+        }
+        let places: Vec<String> = competing.iter().map(|s| format!("{} line {}", s.function.name, s.line)).collect();
+        violations.push(Violation::new(
+            &RULE33,
+            competing[0].line,
+            format!("'{}' is written in {} places: {}", name, competing.len(), places.join(", ")),
+            "Compute the output in one place (e.g. combine the conditions) and assign it once per scan.",
+            Severity::Warning,
+        ));
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE33.no, RULE33.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+/// OB100, or an OB1 block guarded by a first-scan flag.
+fn is_restart_init(site: &WriteSite) -> bool {
+    site.function.kind == FunctionKind::OB100 || site.conditions().any(is_first_scan_guard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn competing_writers_are_listed_together() {
+        let src = "ORGANIZATION_BLOCK OB100\nBEGIN\n    Motor_Out := FALSE;\nEND_ORGANIZATION_BLOCK\n\
+                   ORGANIZATION_BLOCK OB1\nBEGIN\n    IF FirstScan THEN\n        Motor_Out := FALSE;\n    END_IF;\n    \
+                   IF Auto THEN\n        Motor_Out := Demand;\n        Pump_Out := TRUE;\n    ELSE\n        Motor_Out := Manual;\n        Pump_Out := FALSE;\n    END_IF;\n\
+                   END_ORGANIZATION_BLOCK\nFUNCTION FC_Jog\nBEGIN\n    motor_out := Jog;\n    Counter := 1;\n    Counter := 2;\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &Policy::default());
+        let found: Vec<(usize, &str)> = result.violations.iter().map(|v| (v.line, v.reason.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![(11, "'Motor_Out' is written in 3 places: OB1 line 11, OB1 line 14, FC_Jog line 20")]
+        );
+    }
+}
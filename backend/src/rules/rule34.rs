@@ -0,0 +1,117 @@
+//! Rule 34: Avoid hardwired conditions.
+//! `IF TRUE THEN` bypasses whatever check used to be there, and
+//! `IF FALSE THEN` leaves a block that never runs; both are typical debug
+//! overrides left in production. Any IF whose condition folds to a
+//! constant is reported, as an error when the affected branch writes a
+//! critical output (see `utils::IdentifierClass::CriticalOutput`). For an
+//! always-TRUE condition that is the THEN branch, which runs unguarded, or
+//! else the ELSE branch, which never runs.
+
+use crate::analysis::{const_fold, ConstValue};
+use crate::ast::{Program, Statement};
+use super::visit::{walk_program, walk_statement, walk_statements, Visitor};
+use super::utils::{self, IdentifierClass};
+use super::{Policy, RuleResult, Severity, Violation};
+use super::registry::RULE34;
+
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    let mut finder = ConstantConditions { policy, out: Vec::new() };
+    walk_program(&mut finder, program);
+
+    if finder.out.is_empty() {
+        RuleResult::ok(RULE34.no, RULE34.name)
+    } else {
+        RuleResult::violations(finder.out)
+    }
+}
+
+struct ConstantConditions<'p> {
+    policy: &'p Policy,
+    out: Vec<Violation>,
+}
+
+impl Visitor for ConstantConditions<'_> {
+    fn visit_statement(&mut self, st: &Statement) {
+        if let Statement::IfStmt { condition, then_branch, else_branch, line } = st {
+            if let Some(ConstValue::Bool(always)) = const_fold(condition) {
+                let output = critical_write(then_branch, self.policy);
+                let dead_else = if always && output.is_none() { critical_write(else_branch, self.policy) } else { None };
+                let cond = utils::expr_text(condition);
+                let reason = match (always, &output, &dead_else) {
+                    (true, Some(o), _) => format!("Condition '{}' is always TRUE: the guard on '{}' is bypassed", cond, o),
+                    (true, None, Some(o)) => format!("Condition '{}' is always TRUE: the ELSE block writing '{}' never runs", cond, o),
+                    (true, None, None) => format!("Condition '{}' is always TRUE: the guard is bypassed", cond),
+                    (false, Some(o), _) => format!("Condition '{}' is always FALSE: the safety block writing '{}' never runs", cond, o),
+                    (false, None, _) => format!("Condition '{}' is always FALSE: the block never runs", cond),
+                };
+                self.out.push(Violation::new(
+                    &RULE34,
+                    *line,
+                    reason,
+                    "Restore the real condition, or remove the override before deployment.",
+                    if output.is_some() || dead_else.is_some() { Severity::Error } else { Severity::Warning },
+                ));
+            }
+        }
+        walk_statement(self, st);
+    }
+}
+
+/// The first critical output assigned anywhere in `stmts`.
+fn critical_write(stmts: &[Statement], policy: &Policy) -> Option<String> {
+    let mut finder = CriticalWrite { policy, found: None };
+    walk_statements(&mut finder, stmts);
+    finder.found
+}
+
+struct CriticalWrite<'p> {
+    policy: &'p Policy,
+    found: Option<String>,
+}
+
+impl Visitor for CriticalWrite<'_> {
+    fn visit_statement(&mut self, st: &Statement) {
+        if self.found.is_some() {
+            return;
+        }
+        if let Statement::Assign { target, .. } = st {
+            self.found = target.name().filter(|n| utils::is_identifier_class(n, IdentifierClass::CriticalOutput, self.policy));
+        }
+        walk_statement(self, st);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn bypass_and_dead_block_are_told_apart() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    IF TRUE OR Guard_Closed THEN\n        Motor_Out := Start;\n    END_IF;\n    \
+                   IF FALSE THEN\n        IF Estop THEN\n            Safe_Out := FALSE;\n        END_IF;\n    END_IF;\n    \
+                   IF NOT TRUE THEN\n        Count := 0;\n    END_IF;\n    IF Run THEN\n        Motor_Out := TRUE;\n    END_IF;\n\
+                   END_ORGANIZATION_BLOCK\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &Policy::default());
+        let found: Vec<(usize, &str, Severity)> =
+            result.violations.iter().map(|v| (v.line, v.reason.as_str(), v.severity)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (3, "Condition 'TRUE OR Guard_Closed' is always TRUE: the guard on 'Motor_Out' is bypassed", Severity::Error),
+                (6, "Condition 'FALSE' is always FALSE: the safety block writing 'Safe_Out' never runs", Severity::Error),
+                (11, "Condition 'NOT TRUE' is always FALSE: the block never runs", Severity::Warning),
+            ]
+        );
+    }
+
+    #[test]
+    fn always_true_condition_makes_a_critical_else_dead() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    IF TRUE THEN\n        Count := Count + 1;\n    ELSE\n        \
+                   Motor_Out := FALSE;\n    END_IF;\nEND_ORGANIZATION_BLOCK\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &Policy::default());
+        let v = &result.violations[0];
+        assert_eq!(v.reason, "Condition 'TRUE' is always TRUE: the ELSE block writing 'Motor_Out' never runs");
+        assert_eq!(v.severity, Severity::Error);
+    }
+}
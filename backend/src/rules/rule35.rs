@@ -0,0 +1,61 @@
+//! Rule 35: Avoid recursive calls.
+//! The PLC scan model has no room for unbounded recursion: a block that
+//! calls itself, directly or through other blocks, exhausts the local
+//! data stack and stops the CPU. Cycles come from the call graph, so only
+//! calls to blocks defined in the same program are followed.
+
+use crate::analysis::CallGraph;
+use crate::ast::Program;
+use super::{RuleResult, Severity, Violation};
+use super::registry::RULE35;
+
+pub fn check(program: &Program) -> RuleResult {
+    let graph = CallGraph::build(program);
+    let mut violations = Vec::new();
+    for cycle in graph.cycles() {
+        let Some(closing) = cycle.last() else { continue };
+        let reason = if cycle.len() == 1 {
+            format!("'{}' calls itself", closing.caller)
+        } else {
+            let mut chain: Vec<&str> = cycle.iter().map(|s| s.caller.as_str()).collect();
+            chain.push(cycle[0].caller.as_str());
+            format!("Recursive call cycle: {}", chain.join(" -> "))
+        };
+        violations.push(Violation::new(
+            &RULE35,
+            closing.line,
+            reason,
+            "Rewrite the recursion as a bounded loop, or split the shared logic into a block that does not call back.",
+            Severity::Error,
+        ));
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE35.no, RULE35.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn direct_and_mutual_recursion_are_reported() {
+        let src = "FUNCTION FC_Fact\nBEGIN\n    r := n * FC_Fact(n - 1);\nEND_FUNCTION\n\
+                   FUNCTION_BLOCK FB_A\nBEGIN\n    FB_B();\nEND_FUNCTION_BLOCK\n\
+                   FUNCTION_BLOCK FB_B\nBEGIN\n    TON(IN := Run);\n    IF Retry THEN\n        fb_a();\n    END_IF;\nEND_FUNCTION_BLOCK\n\
+                   ORGANIZATION_BLOCK OB1\nBEGIN\n    FB_A();\n    FC_Fact(5);\nEND_ORGANIZATION_BLOCK\n";
+        let result = check(&parse_scl_from_str(src).unwrap());
+        let found: Vec<(usize, &str)> = result.violations.iter().map(|v| (v.line, v.reason.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![(3, "'FC_Fact' calls itself"), (13, "Recursive call cycle: FB_A -> FB_B -> FB_A")]
+        );
+
+        let acyclic = "FUNCTION FC_A\nBEGIN\n    FC_B();\nEND_FUNCTION\nFUNCTION FC_B\nBEGIN\n    x := 1;\nEND_FUNCTION\n";
+        assert!(check(&parse_scl_from_str(acyclic).unwrap()).passed());
+    }
+}
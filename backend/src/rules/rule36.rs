@@ -0,0 +1,93 @@
+//! Rule 36: Follow the plant's naming conventions.
+//! Policy-driven: each `naming` entry gives a block kind (FB, FC, OB, DB,
+//! Program) or a declaration section (input, output, in_out) and a regex
+//! the whole name must match. Without naming rules in the policy this is
+//! a no-op.
+
+use crate::ast::{FunctionKind, Program, VarSection};
+use super::policy::{NamingRule, Policy};
+use super::{RuleResult, Severity, Violation};
+use super::registry::RULE36;
+
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    if policy.naming.as_ref().is_none_or(|n| n.is_empty()) {
+        return RuleResult::not_applicable(RULE36.no, RULE36.name);
+    }
+    let mut violations = Vec::new();
+    for rule in policy.naming.iter().flatten() {
+        // Patterns are validated when the policy is parsed.
+        let Ok(re) = rule.regex() else { continue };
+        for f in &program.functions {
+            if block_kind_matches(rule, f.kind) && !re.is_match(&f.name) {
+                violations.push(violation(f.line, format!("Block name '{}' does not match the {} naming pattern '{}'", f.name, rule.kind, rule.pattern)));
+            }
+            for d in f.declarations.iter().filter(|d| section_matches(rule, d.section)) {
+                if !re.is_match(&d.name) {
+                    violations.push(violation(
+                        d.line,
+                        format!("{} '{}' of '{}' does not match the naming pattern '{}'", rule.kind, d.name, f.name, rule.pattern),
+                    ));
+                }
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE36.no, RULE36.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+fn block_kind_matches(rule: &NamingRule, kind: FunctionKind) -> bool {
+    match rule.kind.to_ascii_uppercase().as_str() {
+        "FB" => kind == FunctionKind::FB,
+        "FC" => kind == FunctionKind::FC,
+        "DB" => kind == FunctionKind::DataBlock,
+        "PROGRAM" => kind == FunctionKind::Program,
+        "OB" => !matches!(kind, FunctionKind::FB | FunctionKind::FC | FunctionKind::DataBlock | FunctionKind::Program),
+        _ => false,
+    }
+}
+
+fn section_matches(rule: &NamingRule, section: VarSection) -> bool {
+    match rule.kind.to_ascii_uppercase().as_str() {
+        "INPUT" => section == VarSection::Input,
+        "OUTPUT" => section == VarSection::Output,
+        "IN_OUT" => section == VarSection::InOut,
+        _ => false,
+    }
+}
+
+fn violation(line: usize, reason: String) -> Violation {
+    Violation::new(&RULE36, line, reason, "Rename it to follow the naming convention configured in the policy.", Severity::Info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+    use crate::rules::policy::parse_policy_from_text;
+
+    #[test]
+    fn function_block_name_must_match_its_pattern() {
+        let src = "FUNCTION_BLOCK Motor\nVAR_INPUT\n    I_Start : BOOL;\n    Stop : BOOL;\nEND_VAR\nBEGIN\nEND_FUNCTION_BLOCK\n\
+                   FUNCTION_BLOCK FB_Pump\nBEGIN\nEND_FUNCTION_BLOCK\nFUNCTION FC_Scale\nBEGIN\nEND_FUNCTION\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let policy = parse_policy_from_text(
+            r#"{ "naming": [{ "kind": "FB", "pattern": "FB_.*" }, { "kind": "input", "pattern": "I_\\w+" }] }"#,
+        )
+        .unwrap();
+        let result = check(&program, &policy);
+        let found: Vec<(usize, &str)> = result.violations.iter().map(|v| (v.line, v.reason.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, "Block name 'Motor' does not match the FB naming pattern 'FB_.*'"),
+                (4, "input 'Stop' of 'Motor' does not match the naming pattern 'I_\\w+'"),
+            ]
+        );
+
+        assert_eq!(check(&program, &Policy::default()).status, crate::rules::RuleStatus::NotApplicable);
+    }
+}
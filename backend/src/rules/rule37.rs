@@ -0,0 +1,189 @@
+//! Rule 37: Clamp HMI setpoints on both sides.
+//! Rule 8 accepts any range check on an HMI value; a setpoint that is only
+//! checked against its maximum can still be driven negative from the
+//! panel, and vice versa. For every actuator or setpoint (see
+//! `utils::IdentifierClass::Actuator`) assigned from an HMI variable, the
+//! guards in force must give that variable both a lower (`>`/`>=`) and an
+//! upper (`<`/`<=`) bound. Guards are enclosing IF/WHILE conditions, early exits
+//! (`IF X > 100 THEN RETURN; END_IF;`) and clamps
+//! (`IF X > 100 THEN X := 100; END_IF;`). Values with no bound at all are
+//! left to rule 8.
+
+use crate::analysis::{normalize_comparison, ConstValue};
+use crate::ast::{reachable, BinOp, Expression, Program, Statement, UnaryOp};
+use super::rule8::collect_hmi_vars;
+use super::utils::{self, Guard, IdentifierClass};
+use super::{Policy, RuleResult, Severity, Violation};
+use super::registry::RULE37;
+
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    let mut violations = Vec::new();
+
+    for func in program.code() {
+        walk_statements(&func.statements, policy, &mut vec![], &mut violations);
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE37.no, RULE37.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+/// Which sides of a variable's range the guards pin down.
+#[derive(Default)]
+struct Bounds {
+    lower: bool,
+    upper: bool,
+}
+
+fn walk_statements<'a>(stmts: &'a [Statement], policy: &Policy, guards: &mut Vec<Guard<'a>>, out: &mut Vec<Violation>) {
+    let outer_guards = guards.len();
+    for st in reachable(stmts) {
+        match st {
+            Statement::Assign { target, value, line, .. }
+                if target.name().is_some_and(|t| utils::is_identifier_class(&t, IdentifierClass::Actuator, policy)) =>
+            {
+                check_assignment(target, value, *line, policy, guards, out);
+            }
+            Statement::IfStmt { condition, then_branch, else_branch, .. } => {
+                guards.push(Guard { cond: condition, holds: true });
+                walk_statements(then_branch, policy, guards, out);
+                guards.pop();
+                guards.push(Guard { cond: condition, holds: false });
+                walk_statements(else_branch, policy, guards, out);
+                guards.pop();
+            }
+            Statement::CaseStmt { cases, else_branch, .. } => {
+                for (_, body) in cases {
+                    walk_statements(body, policy, guards, out);
+                }
+                walk_statements(else_branch, policy, guards, out);
+            }
+            Statement::WhileStmt { condition, body, .. } => {
+                guards.push(Guard { cond: condition, holds: true });
+                walk_statements(body, policy, guards, out);
+                guards.pop();
+            }
+            Statement::ForStmt { body, .. } => walk_statements(body, policy, guards, out),
+            _ => {}
+        }
+
+        // After an early exit or a clamp the condition no longer holds.
+        if let Some(c) = st.early_exit_condition().or_else(|| clamp_condition(st)) {
+            guards.push(Guard { cond: c, holds: false });
+        }
+    }
+    guards.truncate(outer_guards);
+}
+
+fn check_assignment(target: &Expression, value: &Expression, line: usize, policy: &Policy, guards: &[Guard], out: &mut Vec<Violation>) {
+    let mut hmi_vars = Vec::new();
+    collect_hmi_vars(value, policy, &mut hmi_vars);
+    hmi_vars.dedup();
+
+    for var in hmi_vars {
+        let mut bounds = Bounds::default();
+        for g in guards {
+            collect_bounds(g.cond, g.holds, &var, &mut bounds);
+        }
+        let missing = match (bounds.lower, bounds.upper) {
+            (true, false) => "upper",
+            (false, true) => "lower",
+            _ => continue,
+        };
+        out.push(Violation::new(
+            &RULE37,
+            line,
+            format!("HMI value '{}' has no {} bound before it reaches '{}'", var, missing, utils::expr_text(target)),
+            format!("Check both limits, e.g. IF {0} >= MIN AND {0} <= MAX THEN ...", var),
+            Severity::Warning,
+        ));
+    }
+}
+
+/// The condition of `IF X > 100 THEN X := 100; END_IF;`: an IF without
+/// ELSE whose branch assigns the variable its condition compares.
+fn clamp_condition(st: &Statement) -> Option<&Expression> {
+    let Statement::IfStmt { condition, then_branch, else_branch, .. } = st else {
+        return None;
+    };
+    let (var, _, _) = normalize_comparison(condition)?;
+    let assigns_var = then_branch.iter().any(|s| {
+        matches!(s, Statement::Assign { target, .. } if target.name().is_some_and(|t| t.eq_ignore_ascii_case(&var)))
+    });
+    (else_branch.is_empty() && assigns_var).then_some(condition)
+}
+
+/// Record the sides of `var`'s range that `e` being `holds` bounds: both
+/// sides of a true `AND`, both sides of a false `OR`.
+fn collect_bounds(e: &Expression, holds: bool, var: &str, bounds: &mut Bounds) {
+    match e {
+        Expression::BinaryOp { op: BinOp::And, left, right, .. } if holds => {
+            collect_bounds(left, holds, var, bounds);
+            collect_bounds(right, holds, var, bounds);
+        }
+        Expression::BinaryOp { op: BinOp::Or, left, right, .. } if !holds => {
+            collect_bounds(left, holds, var, bounds);
+            collect_bounds(right, holds, var, bounds);
+        }
+        Expression::UnaryOp { op: UnaryOp::Not, expr, .. } => collect_bounds(expr, !holds, var, bounds),
+        _ => {
+            let Some((name, op, value)) = normalize_comparison(e) else { return };
+            if !name.eq_ignore_ascii_case(var) || matches!(value, ConstValue::Bool(_)) {
+                return;
+            }
+            match if holds { op } else { op.negated() } {
+                BinOp::Gt | BinOp::Ge => bounds.lower = true,
+                BinOp::Lt | BinOp::Le => bounds.upper = true,
+                BinOp::Eq => {
+                    bounds.lower = true;
+                    bounds.upper = true;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::parse_block;
+
+    fn findings(body: &str) -> Vec<(usize, String)> {
+        check(&parse_block("ORGANIZATION_BLOCK OB1", body), &Policy::default()).violations.into_iter().map(|v| (v.line, v.reason)).collect()
+    }
+
+    #[test]
+    fn lower_bound_only_is_flagged() {
+        assert_eq!(
+            findings("IF HMI_Speed >= 0 THEN\n    Speed_Setpoint := HMI_Speed;\nEND_IF;"),
+            vec![(4, "HMI value 'HMI_Speed' has no upper bound before it reaches 'Speed_Setpoint'".to_string())]
+        );
+    }
+
+    #[test]
+    fn upper_bound_only_is_flagged() {
+        assert_eq!(
+            findings("IF HMI_Speed > 1500 THEN\n    HMI_Speed := 1500;\nEND_IF;\nMotor_Speed := HMI_Speed;"),
+            vec![(6, "HMI value 'HMI_Speed' has no lower bound before it reaches 'Motor_Speed'".to_string())]
+        );
+    }
+
+    #[test]
+    fn both_bounds_pass() {
+        assert!(findings("IF HMI_Speed >= 0 AND HMI_Speed <= 1500 THEN\n    Speed_Setpoint := HMI_Speed;\nEND_IF;").is_empty());
+        assert!(findings(
+            "IF HMI_Speed > 1500 THEN\n    HMI_Speed := 1500;\nEND_IF;\nIF HMI_Speed < 0 THEN\n    HMI_Speed := 0;\nEND_IF;\n\
+             Motor_Speed := HMI_Speed;"
+        )
+        .is_empty());
+        assert!(findings("IF NOT (HMI_Pos < 0 OR HMI_Pos > 100) THEN\n    Position_Cmd := HMI_Pos;\nEND_IF;").is_empty());
+    }
+
+    #[test]
+    fn unchecked_values_are_left_to_rule8() {
+        assert!(findings("Speed_Setpoint := HMI_Speed;").is_empty());
+    }
+}
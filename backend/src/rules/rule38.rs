@@ -0,0 +1,107 @@
+//! Rule 38: Limit nesting depth.
+//! Each IF, CASE, WHILE and FOR adds one level around the statements it
+//! contains. A block whose deepest level exceeds the policy's
+//! `max_nesting` (default [`DEFAULT_MAX_NESTING`]) is reported once, at
+//! the first statement on its deepest level.
+
+use crate::ast::{Program, Statement};
+use super::policy::Policy;
+use super::{RuleResult, Severity, Violation};
+use super::registry::RULE38;
+
+/// Nesting depth allowed when the policy does not set `max_nesting`.
+pub const DEFAULT_MAX_NESTING: usize = 6;
+
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    let limit = policy.max_nesting.unwrap_or(DEFAULT_MAX_NESTING);
+    let mut violations = Vec::new();
+
+    for f in program.code() {
+        if let Some((depth, line)) = deepest(&f.statements, 0).filter(|(depth, _)| *depth > limit) {
+            violations.push(Violation::new(
+                &RULE38,
+                line,
+                format!("Nesting depth {} in '{}' exceeds the limit of {}", depth, f.name, limit),
+                "Flatten the logic with early exits or move the inner branches into their own FC.",
+                Severity::Info,
+            ));
+        }
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE38.no, RULE38.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+/// `(depth, line)` of the first statement at the deepest level of `stmts`,
+/// which sit at level `depth`. A control statement with nothing inside
+/// counts as a statement on the level it opens. `None` if `stmts` has no
+/// statements.
+fn deepest(stmts: &[Statement], depth: usize) -> Option<(usize, usize)> {
+    let mut best: Option<(usize, usize)> = None;
+    for st in stmts {
+        let bodies: Vec<&[Statement]> = match st {
+            Statement::Comment { .. } | Statement::ElseMarker { .. } => continue,
+            Statement::IfStmt { then_branch, else_branch, .. } => vec![then_branch, else_branch],
+            Statement::CaseStmt { cases, else_branch, .. } => {
+                cases.iter().map(|(_, body)| body.as_slice()).chain([else_branch.as_slice()]).collect()
+            }
+            Statement::WhileStmt { body, .. } | Statement::ForStmt { body, .. } => vec![body],
+            _ => vec![],
+        };
+        let candidate = if bodies.is_empty() {
+            (depth, st.line())
+        } else {
+            bodies
+                .into_iter()
+                .filter_map(|b| deepest(b, depth + 1))
+                .reduce(|a, b| if b.0 > a.0 { b } else { a })
+                .unwrap_or((depth + 1, st.line()))
+        };
+        if best.is_none_or(|b| candidate.0 > b.0) {
+            best = Some(candidate);
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    /// `levels` nested IFs around an assignment, one per line from line 3.
+    fn nested(levels: usize) -> String {
+        let mut body = String::new();
+        for i in 0..levels {
+            body.push_str(&format!("IF C{} THEN\n", i));
+        }
+        body.push_str("x := 1;\n");
+        body.push_str(&"END_IF;\n".repeat(levels));
+        format!("FUNCTION FC_Deep\nBEGIN\n{}END_FUNCTION\n", body)
+    }
+
+    fn findings(src: &str, policy: &Policy) -> Vec<(usize, String)> {
+        check(&parse_scl_from_str(src).unwrap(), policy).violations.into_iter().map(|v| (v.line, v.reason)).collect()
+    }
+
+    #[test]
+    fn deepest_statement_is_reported_past_the_default_limit() {
+        assert!(findings(&nested(6), &Policy::default()).is_empty());
+        assert_eq!(
+            findings(&nested(7), &Policy::default()),
+            vec![(10, "Nesting depth 7 in 'FC_Deep' exceeds the limit of 6".to_string())]
+        );
+    }
+
+    #[test]
+    fn limit_comes_from_the_policy() {
+        let policy = Policy { max_nesting: Some(2), ..Default::default() };
+        let src = "FUNCTION FC_A\nBEGIN\n    WHILE Run DO\n        CASE Mode OF\n            1: x := 1;\n            \
+                   2: FOR i := 0 TO 9 DO\n                    y := i;\n                END_FOR;\n        END_CASE;\n    \
+                   END_WHILE;\nEND_FUNCTION\n";
+        assert_eq!(findings(src, &policy), vec![(7, "Nesting depth 3 in 'FC_A' exceeds the limit of 2".to_string())]);
+    }
+}
@@ -0,0 +1,136 @@
+//! Rule 39: Store the previous value used for edge detection.
+//! A hand-written edge check (`IF Start AND NOT Start_Prev THEN`) compares
+//! a signal with a companion holding its value from the last scan, named
+//! like the signal plus `_PREV` or `_OLD`. Unless the same block also runs
+//! `Start_Prev := Start;`, the companion never changes and the edge fires
+//! on every scan (or never). Each IF/WHILE condition reading such a pair
+//! without that store is reported.
+
+use crate::ast::{Expression, Program, Statement};
+use super::visit::{walk_expression, walk_statement, Visitor};
+use super::{RuleResult, Severity, Violation};
+use super::registry::RULE39;
+
+/// Name suffixes that mark a previous-scan companion, upper-cased.
+const PREVIOUS_SUFFIXES: &[&str] = &["_PREV", "_OLD"];
+
+pub fn check(program: &Program) -> RuleResult {
+    let mut violations = Vec::new();
+
+    for f in program.code() {
+        let mut scan = EdgeScan::default();
+        scan.visit_function(f);
+        for edge in &scan.edges {
+            let stored = scan.stores.iter().any(|(target, value)| {
+                target.eq_ignore_ascii_case(&edge.previous) && value.eq_ignore_ascii_case(&edge.signal)
+            });
+            if !stored {
+                violations.push(Violation::new(
+                    &RULE39,
+                    edge.line,
+                    format!(
+                        "Edge check compares '{}' with '{}', but '{}' is never set to '{}' in '{}'",
+                        edge.signal, edge.previous, edge.previous, edge.signal, f.name
+                    ),
+                    format!("Store the current value after the check: {} := {};", edge.previous, edge.signal),
+                    Severity::Warning,
+                ));
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE39.no, RULE39.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+/// A condition reading both `signal` and its companion `previous`.
+struct Edge {
+    signal: String,
+    previous: String,
+    line: usize,
+}
+
+/// Edge conditions and plain `target := value` copies of one block.
+#[derive(Default)]
+struct EdgeScan {
+    edges: Vec<Edge>,
+    stores: Vec<(String, String)>,
+}
+
+impl Visitor for EdgeScan {
+    fn visit_statement(&mut self, st: &Statement) {
+        match st {
+            Statement::IfStmt { condition, line, .. } | Statement::WhileStmt { condition, line, .. } => {
+                let mut names = Names::default();
+                names.visit_expression(condition);
+                for previous in &names.0 {
+                    let Some(signal) = signal_of(previous) else { continue };
+                    if let Some(signal) = names.0.iter().find(|n| n.eq_ignore_ascii_case(signal)) {
+                        self.edges.push(Edge { signal: signal.clone(), previous: previous.clone(), line: *line });
+                    }
+                }
+            }
+            Statement::Assign { target, value, .. } => {
+                if let (Some(target), Some(value)) = (target.name(), value.name()) {
+                    self.stores.push((target, value));
+                }
+            }
+            _ => {}
+        }
+        walk_statement(self, st);
+    }
+}
+
+/// The signal a `*_PREV`/`*_OLD` companion belongs to.
+fn signal_of(previous: &str) -> Option<&str> {
+    let upper = previous.to_ascii_uppercase();
+    PREVIOUS_SUFFIXES
+        .iter()
+        .find(|s| upper.len() > s.len() && upper.ends_with(*s))
+        .map(|s| &previous[..previous.len() - s.len()])
+}
+
+/// Every variable an expression reads.
+#[derive(Default)]
+struct Names(Vec<String>);
+
+impl Visitor for Names {
+    fn visit_expression(&mut self, e: &Expression) {
+        match e.name() {
+            Some(name) => self.0.push(name),
+            None => walk_expression(self, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::parse_block;
+
+    fn findings(body: &str) -> Vec<(usize, String)> {
+        check(&parse_block("FUNCTION_BLOCK FB_Edge", body)).violations.into_iter().map(|v| (v.line, v.reason)).collect()
+    }
+
+    #[test]
+    fn edge_without_stored_previous_value_is_flagged() {
+        assert_eq!(
+            findings("IF Start AND NOT Start_Prev THEN\n    Count := Count + 1;\nEND_IF;\nStart_Prev := FALSE;"),
+            vec![(3, "Edge check compares 'Start' with 'Start_Prev', but 'Start_Prev' is never set to 'Start' in 'FB_Edge'".to_string())]
+        );
+    }
+
+    #[test]
+    fn stored_previous_value_passes() {
+        assert!(findings("IF Start AND NOT Start_Prev THEN\n    Count := Count + 1;\nEND_IF;\nStart_Prev := Start;").is_empty());
+        assert!(findings("IF NOT Level.Hi AND Level.Hi_OLD THEN\n    Alarm := TRUE;\nEND_IF;\nLevel.Hi_old := Level.Hi;").is_empty());
+    }
+
+    #[test]
+    fn companion_without_its_signal_is_not_an_edge() {
+        assert!(findings("IF Start_Prev THEN\n    Count := 0;\nEND_IF;").is_empty());
+    }
+}
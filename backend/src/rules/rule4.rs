@@ -2,13 +2,16 @@
 //! Flag any `/` operations that are *not* inside a conditional checking
 //! status word flags (e.g., SW.OV=0 AND SW.OS=0) or zero divisor.
 
+use crate::analysis::const_fold;
+use crate::ast::pprint::statement_to_scl;
 use crate::ast::{BinOp, Expression, Program, Statement};
-use super::{utils, RuleResult, Violation};
+use super::{utils, RuleResult, Severity, TextEdit, Violation};
+use super::registry::RULE4;
 
 pub fn check(program: &Program) -> RuleResult {
     let mut violations = vec![];
 
-    for f in &program.functions {
+    for f in program.code() {
         collect_div_violations(&f.statements, /*guarded*/ false, &mut violations);
     }
 
@@ -27,7 +30,28 @@ fn collect_div_violations(stmts: &[Statement], guarded: bool, out: &mut Vec<Viol
             }
 
             Statement::Assign { value, line, .. } | Statement::Expr { expr: value, line } => {
+                let before = out.len();
                 find_divs(value, *line, guarded, out);
+                // A single division by a variable has an obvious fix: guard
+                // the statement with a zero check on the divisor.
+                if out.len() == before + 1 {
+                    if let Some(divisor) = divisor_of(value).filter(|d| const_fold(d).is_none()) {
+                        let fixed = Statement::IfStmt {
+                            condition: Expression::BinaryOp {
+                                op: BinOp::Neq,
+                                left: Box::new(divisor.clone()),
+                                right: Box::new(Expression::NumberLiteral(0, *line)),
+                                line: *line,
+                                end_line: *line,
+                            },
+                            then_branch: vec![st.clone()],
+                            else_branch: vec![],
+                            line: *line,
+                        };
+                        let last = value.span().1;
+                        out[before].fix = Some(TextEdit::replace_statement(*line, last, statement_to_scl(st), statement_to_scl(&fixed)));
+                    }
+                }
             }
             _ => {}
         }
@@ -36,16 +60,18 @@ fn collect_div_violations(stmts: &[Statement], guarded: bool, out: &mut Vec<Viol
 
 fn find_divs(expr: &Expression, line: usize, guarded: bool, out: &mut Vec<Violation>) {
     match expr {
-        Expression::BinaryOp { op: BinOp::Div, .. } => {
-            if !guarded {
-                out.push(Violation {
-                    rule_no: 4,
-                    rule_name: "Use PLC flags as integrity checks",
+        Expression::BinaryOp { op: BinOp::Div, .. } if !guarded => {
+            out.push(Violation {
+                end_line: Some(expr.span().1).filter(|end| *end > line),
+                snippet: Some(utils::expr_text(expr)),
+                ..Violation::new(
+                    &RULE4,
                     line,
-                    reason: "Division operation without status-word / zero-divisor guard".into(),
-                    suggestion: "Wrap division inside IF SW.OV=0 AND SW.OS=0 AND divisor<>0 THEN ...".into(),
-                });
-            }
+                    "Division operation without status-word / zero-divisor guard",
+                    "Wrap division inside IF SW.OV=0 AND SW.OS=0 AND divisor<>0 THEN ...",
+                    Severity::Warning,
+                )
+            });
             // Don't recurse into children of a division; one violation is enough.
         }
         Expression::BinaryOp { left, right, .. } => {
@@ -60,6 +86,17 @@ fn find_divs(expr: &Expression, line: usize, guarded: bool, out: &mut Vec<Violat
     }
 }
 
+/// Right operand of the first division in `expr`, in the order
+/// [`find_divs`] reports them.
+fn divisor_of(expr: &Expression) -> Option<&Expression> {
+    match expr {
+        Expression::BinaryOp { op: BinOp::Div, right, .. } => Some(right),
+        Expression::BinaryOp { left, right, .. } => divisor_of(left).or_else(|| divisor_of(right)),
+        Expression::Index { base, index, .. } => divisor_of(base).or_else(|| divisor_of(index)),
+        _ => None,
+    }
+}
+
 /// Checks if an expression is a valid guard for a division operation.
 /// This is a simplified check; a more robust implementation would parse the
 /// divisor from the guarded block and ensure it's the one being checked.
@@ -68,4 +105,41 @@ fn is_division_guard(e: &Expression) -> bool {
     let has_sw_check = text.contains("SW.OV=0") && text.contains("SW.OS=0");
     let has_zero_check = text.contains("<>0") || text.contains("!=0");
     has_sw_check || has_zero_check
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::il::parse_il_from_str;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn single_division_gets_a_zero_guard_fix() {
+        let src = "FUNCTION FC_A\nBEGIN\n    Ratio := Total / (Count - 1);\n    Avg := Sum / 4;\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap());
+        assert_eq!(result.violations.len(), 2);
+        let fix = result.violations[0].fix.as_ref().unwrap();
+        assert_eq!((fix.start_line, fix.end_line), (3, 3));
+        assert_eq!(fix.replacement, "IF Count - 1 <> 0 THEN\n    Ratio := Total / (Count - 1);\nEND_IF;");
+        assert!(result.violations[1].fix.is_none());
+    }
+
+    #[test]
+    fn division_over_several_lines_reports_the_range() {
+        let src = "FUNCTION FC_A\nBEGIN\n    Avg := Sum\n           / Count;\n    Half := Sum / 2;\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap());
+        let lines: Vec<(usize, Option<usize>)> = result.violations.iter().map(|v| (v.line, v.end_line)).collect();
+        assert_eq!(lines, vec![(3, Some(4)), (5, None)]);
+        let fix = result.violations[0].fix.as_ref().unwrap();
+        assert_eq!((fix.start_line, fix.end_line), (3, 4));
+    }
+
+    #[test]
+    fn il_compare_and_jump_guards_the_division() {
+        let guarded = "L Divisor\nL 0\n==I\nJMPC M001\nL Total\nL Divisor\n/I\nT Ratio\nM001:\n";
+        assert!(check(&parse_il_from_str(guarded).unwrap()).passed());
+
+        let unguarded = "L Total\nL Divisor\n/I\nT Ratio\n";
+        assert!(!check(&parse_il_from_str(unguarded).unwrap()).passed());
+    }
+}
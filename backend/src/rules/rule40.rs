@@ -0,0 +1,82 @@
+//! Rule 40: Do not assign the FOR loop counter.
+//! Writing the control variable inside the loop body changes the iteration
+//! count behind the loop header's back, and IEC 61131-3 leaves the result
+//! undefined. Any assignment to the counter of an enclosing FOR loop, at
+//! any depth of the body, is reported.
+
+use crate::ast::{Program, Statement};
+use super::visit::{walk_program, walk_statement, Visitor};
+use super::{utils, RuleResult, Severity, Violation};
+use super::registry::RULE40;
+
+pub fn check(program: &Program) -> RuleResult {
+    let mut finder = CounterWrites { counters: Vec::new(), out: Vec::new() };
+    walk_program(&mut finder, program);
+
+    if finder.out.is_empty() {
+        RuleResult::ok(RULE40.no, RULE40.name)
+    } else {
+        RuleResult::violations(finder.out)
+    }
+}
+
+struct CounterWrites {
+    /// Control variables of the FOR loops around the current statement.
+    counters: Vec<String>,
+    out: Vec<Violation>,
+}
+
+impl Visitor for CounterWrites {
+    fn visit_statement(&mut self, st: &Statement) {
+        match st {
+            Statement::ForStmt { variable, .. } => {
+                self.counters.push(variable.clone());
+                walk_statement(self, st);
+                self.counters.pop();
+                return;
+            }
+            Statement::Assign { target, line, .. } => {
+                let name = target.name().unwrap_or_default();
+                if let Some(counter) = self.counters.iter().find(|c| same_variable(c, &name)) {
+                    self.out.push(Violation::new(
+                        &RULE40,
+                        *line,
+                        format!("FOR loop counter '{}' is assigned inside the loop body", counter),
+                        "Use a separate variable, or leave the loop with EXIT instead of moving the counter.",
+                        Severity::Error,
+                    ));
+                }
+            }
+            _ => {}
+        }
+        walk_statement(self, st);
+    }
+}
+
+/// Names compare by [`utils::ident_key`], so `FOR #i := ...` and `i := ...`
+/// refer to the same counter.
+fn same_variable(a: &str, b: &str) -> bool {
+    utils::ident_key(a) == utils::ident_key(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn counter_writes_in_the_body_are_flagged() {
+        let src = "FUNCTION FC_A\nBEGIN\n    FOR i := 0 TO 9 DO\n        IF Table[i] = 0 THEN\n            i := 9;\n        END_IF;\n        \
+                   FOR j := 0 TO 3 DO\n            #i := i + j;\n            Sum := Sum + j;\n        END_FOR;\n    END_FOR;\n    \
+                   i := 0;\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap());
+        let found: Vec<(usize, &str)> = result.violations.iter().map(|v| (v.line, v.reason.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (5, "FOR loop counter 'i' is assigned inside the loop body"),
+                (8, "FOR loop counter 'i' is assigned inside the loop body"),
+            ]
+        );
+    }
+}
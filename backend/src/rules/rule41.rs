@@ -0,0 +1,207 @@
+//! Rule 41: Scale or range-check analog inputs before use.
+//! A raw analog value (a peripheral word such as `%IW64`, or a name ending
+//! in `_RAW` or `_AI`) is a converter count, not an engineering value.
+//! Using it in arithmetic is reported unless an enclosing guard or early
+//! exit range-checks it (see `utils::is_var_constrained`) or an earlier
+//! statement of the block passes it to `NORM_X`, `SCALE_X` or `SCALE`.
+//! The policy's `raw_analog_patterns` add site-specific names.
+
+use regex::Regex;
+
+use crate::ast::{reachable, BinOp, Expression, Program, Statement};
+use super::policy::{self, Policy};
+use super::{utils, RuleResult, Severity, Violation};
+use super::registry::RULE41;
+
+/// Conversion blocks whose inputs count as scaled from then on.
+const SCALING_CALLS: &[&str] = &["NORM_X", "SCALE_X", "SCALE"];
+
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    let raw = match RawAnalog::from_policy(policy) {
+        Ok(raw) => raw,
+        Err(e) => return RuleResult::error(RULE41.no, RULE41.name, e),
+    };
+    let mut violations = Vec::new();
+
+    for f in program.code() {
+        let mut walk = Walk { raw: &raw, scaled: Vec::new(), out: &mut violations };
+        walk.statements(&f.statements, &mut Vec::new());
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE41.no, RULE41.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+/// Which names are raw analog values. Rule 51 counts them as sensor
+/// values too.
+pub(crate) struct RawAnalog {
+    extra: Vec<Regex>,
+}
+
+impl RawAnalog {
+    /// The built-in names plus the policy's `raw_analog_patterns`.
+    pub(crate) fn from_policy(policy: &Policy) -> Result<Self, String> {
+        Ok(RawAnalog { extra: policy::name_regexes(&policy.raw_analog_patterns, "raw_analog_patterns")? })
+    }
+
+    pub(crate) fn matches(&self, name: &str) -> bool {
+        let key = utils::ident_key(name);
+        key.starts_with("%IW") || key.starts_with("%PIW") || key.ends_with("_RAW") || key.ends_with("_AI")
+            || self.extra.iter().any(|re| re.is_match(name))
+    }
+}
+
+struct Walk<'r, 'o> {
+    raw: &'r RawAnalog,
+    /// Keys of the variables already passed to a scaling call.
+    scaled: Vec<String>,
+    out: &'o mut Vec<Violation>,
+}
+
+impl Walk<'_, '_> {
+    fn statements<'a>(&mut self, stmts: &'a [Statement], guards: &mut Vec<&'a Expression>) {
+        let outer_guards = guards.len();
+        for st in reachable(stmts) {
+            match st {
+                Statement::Assign { value, line, .. } => self.expression(value, *line, guards),
+                Statement::Call { name, args, line } => {
+                    if is_scaling(name) {
+                        self.scaled.extend(args.iter().filter_map(|(_, a)| a.name()).map(|n| utils::ident_key(&n)));
+                    } else {
+                        args.iter().for_each(|(_, a)| self.expression(a, *line, guards));
+                    }
+                }
+                Statement::IfStmt { condition, then_branch, else_branch, line } => {
+                    self.expression(condition, *line, guards);
+                    // Either branch knows which side of the comparison the value is on.
+                    guards.push(condition);
+                    self.statements(then_branch, guards);
+                    self.statements(else_branch, guards);
+                    guards.pop();
+                }
+                Statement::CaseStmt { cases, else_branch, .. } => {
+                    for (_, body) in cases {
+                        self.statements(body, guards);
+                    }
+                    self.statements(else_branch, guards);
+                }
+                Statement::WhileStmt { condition, body, line } => {
+                    self.expression(condition, *line, guards);
+                    guards.push(condition);
+                    self.statements(body, guards);
+                    guards.pop();
+                }
+                Statement::ForStmt { body, .. } => self.statements(body, guards),
+                _ => {}
+            }
+
+            // Code after `IF cond THEN RETURN; END_IF;` only runs when the check passed.
+            if let Some(c) = st.early_exit_condition() {
+                guards.push(c);
+            }
+        }
+        guards.truncate(outer_guards);
+    }
+
+    /// Report raw operands of arithmetic in `e`, then note the inputs of
+    /// any scaling call it makes.
+    fn expression(&mut self, e: &Expression, line: usize, guards: &[&Expression]) {
+        let mut operands = Vec::new();
+        raw_operands(e, self.raw, &mut operands);
+        operands.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        for name in operands {
+            let scaled = self.scaled.contains(&utils::ident_key(&name));
+            if scaled || guards.iter().any(|g| utils::is_var_constrained(&name, g)) {
+                continue;
+            }
+            self.out.push(Violation::new(
+                &RULE41,
+                line,
+                format!("Raw analog input '{}' is used in arithmetic without scaling or a range check", name),
+                "Convert it with NORM_X/SCALE_X (or SCALE) first, or range-check it before use.",
+                Severity::Warning,
+            ));
+        }
+        scaling_inputs(e, &mut self.scaled);
+    }
+}
+
+fn is_scaling(name: &str) -> bool {
+    SCALING_CALLS.iter().any(|s| s.eq_ignore_ascii_case(name))
+}
+
+fn is_arithmetic(op: BinOp) -> bool {
+    matches!(op, BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div)
+}
+
+/// Raw analog names used directly as an operand of `+ - * /` in `e`.
+/// Arguments of scaling calls are conversions, not uses.
+fn raw_operands(e: &Expression, raw: &RawAnalog, out: &mut Vec<String>) {
+    match e {
+        Expression::BinaryOp { op, left, right, .. } => {
+            if is_arithmetic(*op) {
+                out.extend([left, right].into_iter().filter_map(|side| side.name()).filter(|n| raw.matches(n)));
+            }
+            raw_operands(left, raw, out);
+            raw_operands(right, raw, out);
+        }
+        Expression::UnaryOp { expr, .. } => raw_operands(expr, raw, out),
+        Expression::Index { index, .. } => raw_operands(index, raw, out),
+        Expression::FuncCall { name, args, .. } if !is_scaling(name) => {
+            args.iter().for_each(|a| raw_operands(a, raw, out));
+        }
+        _ => {}
+    }
+}
+
+/// Keys of the plain variables passed to scaling calls anywhere in `e`.
+fn scaling_inputs(e: &Expression, out: &mut Vec<String>) {
+    match e {
+        Expression::FuncCall { name, args, .. } => {
+            if is_scaling(name) {
+                out.extend(args.iter().filter_map(|a| a.name()).map(|n| utils::ident_key(&n)));
+            }
+            args.iter().for_each(|a| scaling_inputs(a, out));
+        }
+        Expression::BinaryOp { left, right, .. } => {
+            scaling_inputs(left, out);
+            scaling_inputs(right, out);
+        }
+        Expression::UnaryOp { expr, .. } => scaling_inputs(expr, out),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::parse_block;
+    use crate::rules::policy::parse_policy_from_text;
+
+    fn lines(body: &str, policy: &Policy) -> Vec<usize> {
+        check(&parse_block("FUNCTION FC_Analog", body), policy).violations.iter().map(|v| v.line).collect()
+    }
+
+    #[test]
+    fn raw_values_in_arithmetic_are_flagged() {
+        assert_eq!(lines("Temp := Temp_Raw / 10;\nLevel := (%IW64 - 5530) / 221;\nCopy := Flow_AI;", &Policy::default()), vec![3, 4]);
+    }
+
+    #[test]
+    fn scaled_or_guarded_values_pass() {
+        let body = "Norm := NORM_X(MIN := 0, VALUE := Temp_Raw, MAX := 27648);\nTemp := Temp_Raw / 10;\n\
+                    IF Flow_AI >= 0 AND Flow_AI <= 27648 THEN\n    Flow := Flow_AI / 276;\nEND_IF;\n\
+                    IF Level_Raw > 27648 THEN\n    RETURN;\nEND_IF;\nLevel := Level_Raw / 276;";
+        assert!(lines(body, &Policy::default()).is_empty());
+    }
+
+    #[test]
+    fn policy_adds_raw_analog_names() {
+        let policy = parse_policy_from_text(r#"{ "raw_analog_patterns": ["PT\\d+_Counts"] }"#).unwrap();
+        assert_eq!(lines("Pressure := PT101_Counts / 100;\nOther := PT101 / 100;", &policy), vec![3]);
+        assert!(lines("Pressure := PT101_Counts / 100;", &Policy::default()).is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+//! Rule 42: Resolve TODO/FIXME/HACK markers.
+//! A comment carrying one of these markers (or one of the policy's
+//! `comment_markers`) is unfinished work a safety review will ask about.
+//! Each is reported on the comment's first line as Info, or as a Warning
+//! when the next statement writes a critical output. Comments come from
+//! `Statement::Comment` nodes and, for frontends that drop comments, from
+//! the source lines.
+
+use std::collections::HashSet;
+
+use crate::ast::{Program, Statement};
+use super::visit::{walk_program, walk_statement, Visitor};
+use super::utils::{self, IdentifierClass};
+use super::{AnalysisContext, Policy, RuleResult, Severity, Violation};
+use super::registry::RULE42;
+
+/// Markers reported without any policy, upper-cased.
+pub const DEFAULT_MARKERS: &[&str] = &["TODO", "FIXME", "HACK"];
+
+pub fn check(program: &Program, cx: &AnalysisContext) -> RuleResult {
+    let markers: Vec<String> = DEFAULT_MARKERS
+        .iter()
+        .map(|m| m.to_string())
+        .chain(cx.policy.comment_markers.iter().flatten().map(|m| m.trim().to_ascii_uppercase()))
+        .filter(|m| !m.is_empty())
+        .collect();
+
+    let mut scan = Scan { policy: cx.policy, comments: Vec::new(), statements: Vec::new() };
+    walk_program(&mut scan, program);
+    let seen: HashSet<usize> = scan.comments.iter().map(|(line, _)| *line).collect();
+    scan.comments.extend(source_comments(&cx.source_lines).into_iter().filter(|(line, _)| !seen.contains(line)));
+    scan.comments.sort_by_key(|(line, _)| *line);
+
+    let mut violations = Vec::new();
+    for (line, text) in &scan.comments {
+        let Some(marker) = markers.iter().find(|m| has_marker(text, m)) else { continue };
+        let shown = text.lines().find(|l| has_marker(l, marker)).unwrap_or(text).trim();
+        let (severity, reason) = match scan.critical_write_after(*line) {
+            Some(target) => (
+                Severity::Warning,
+                format!("Unresolved {} right before a write to critical output '{}': {}", marker, target, shown),
+            ),
+            None => (Severity::Info, format!("Unresolved {} in comment: {}", marker, shown)),
+        };
+        violations.push(Violation::new(
+            &RULE42,
+            *line,
+            reason,
+            "Resolve the open item or move it to the issue tracker before release.",
+            severity,
+        ));
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE42.no, RULE42.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+/// Comments and the statements around them.
+struct Scan<'p> {
+    policy: &'p Policy,
+    /// `(line, text)` of each `Statement::Comment`.
+    comments: Vec<(usize, String)>,
+    /// `(line, critical output written)` of every other statement.
+    statements: Vec<(usize, Option<String>)>,
+}
+
+impl Scan<'_> {
+    /// The critical output the first statement after `line` writes, if any.
+    fn critical_write_after(&self, line: usize) -> Option<&str> {
+        let next = self.statements.iter().map(|(l, _)| *l).filter(|l| *l > line).min()?;
+        self.statements.iter().filter(|(l, _)| *l == next).find_map(|(_, target)| target.as_deref())
+    }
+}
+
+impl Visitor for Scan<'_> {
+    fn visit_statement(&mut self, st: &Statement) {
+        match st {
+            Statement::Comment { text, line } => self.comments.push((*line, text.clone())),
+            Statement::ElseMarker { .. } => {}
+            Statement::Assign { target, line, .. } => {
+                let critical = target.name().filter(|n| utils::is_identifier_class(n, IdentifierClass::CriticalOutput, self.policy));
+                self.statements.push((*line, critical));
+            }
+            _ => self.statements.push((st.line(), None)),
+        }
+        walk_statement(self, st);
+    }
+}
+
+/// True if `marker` appears in `text` as a word of its own, ignoring case.
+fn has_marker(text: &str, marker: &str) -> bool {
+    let upper = text.to_ascii_uppercase();
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    upper
+        .match_indices(marker)
+        .any(|(at, _)| !upper[..at].ends_with(is_word) && !upper[at + marker.len()..].starts_with(is_word))
+}
+
+/// `(first line, text)` of the `//` and `(* *)` comments in `lines`.
+/// Comment openers inside string literals are not told apart.
+fn source_comments(lines: &[&str]) -> Vec<(usize, String)> {
+    let mut out = Vec::new();
+    let mut open: Option<(usize, String)> = None;
+    for (i, line) in lines.iter().enumerate() {
+        let mut rest = *line;
+        loop {
+            if let Some((_, text)) = &mut open {
+                match rest.find("*)") {
+                    Some(end) => {
+                        text.push_str(&rest[..end + 2]);
+                        rest = &rest[end + 2..];
+                    }
+                    None => {
+                        text.push_str(rest);
+                        text.push('\n');
+                        break;
+                    }
+                }
+                out.extend(open.take());
+                continue;
+            }
+            match (rest.find("(*"), rest.find("//")) {
+                (Some(block), slash) if slash.is_none_or(|s| block < s) => {
+                    open = Some((i + 1, "(*".to_string()));
+                    rest = &rest[block + 2..];
+                }
+                (_, Some(slash)) => {
+                    out.push((i + 1, rest[slash..].to_string()));
+                    break;
+                }
+                _ => break,
+            }
+        }
+    }
+    // An unterminated block comment runs to the end of the file.
+    out.extend(open);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{il::parse_il_from_str, scl::parse_scl_from_str};
+    use crate::rules::Policy;
+
+    fn findings(src: &str, program: &Program, policy: &Policy) -> Vec<(usize, Severity, String)> {
+        let cx = AnalysisContext::with_source(policy, src);
+        check(program, &cx).violations.into_iter().map(|v| (v.line, v.severity, v.reason)).collect()
+    }
+
+    #[test]
+    fn markers_in_scl_comments_are_reported() {
+        let src = "FUNCTION FC_A\nBEGIN\n    // TODO: confirm ramp time\n    Speed := 10;\n    \
+                   (* FIXME remove\n       bypass *)\n    Motor_Out := TRUE;\n    x := 1; // hack around HMI bug\n    \
+                   // TODOS and HACKSAW are not markers\nEND_FUNCTION\n";
+        let program = parse_scl_from_str(src).unwrap();
+        assert_eq!(
+            findings(src, &program, &Policy::default()),
+            vec![
+                (3, Severity::Info, "Unresolved TODO in comment: // TODO: confirm ramp time".to_string()),
+                (
+                    5,
+                    Severity::Warning,
+                    "Unresolved FIXME right before a write to critical output 'Motor_Out': (* FIXME remove".to_string()
+                ),
+                (8, Severity::Info, "Unresolved HACK in comment: // hack around HMI bug".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn comment_nodes_are_not_reported_twice() {
+        let src = "// TODO check interlock\nLD Start\nST Count\n";
+        let program = parse_il_from_str(src).unwrap();
+        assert_eq!(
+            findings(src, &program, &Policy::default()),
+            vec![(1, Severity::Info, "Unresolved TODO in comment: // TODO check interlock".to_string())]
+        );
+    }
+
+    #[test]
+    fn policy_adds_markers() {
+        let src = "FUNCTION FC_A\nBEGIN\n    // TBD: limits\n    x := 1;\nEND_FUNCTION\n";
+        let program = parse_scl_from_str(src).unwrap();
+        assert!(findings(src, &program, &Policy::default()).is_empty());
+        let policy = Policy { comment_markers: Some(vec!["tbd".into()]), ..Default::default() };
+        assert_eq!(findings(src, &program, &policy).len(), 1);
+    }
+}
@@ -0,0 +1,102 @@
+//! Rule 43: Call only blocks that are defined.
+//! A call whose target is neither a block of the analysed program, a
+//! standard instruction of the policy's `platform` (every known platform
+//! when it is unset; see `platform::is_intrinsic`), nor one of the policy's
+//! `external_blocks` usually means a file is missing from the analysed
+//! set or the name has a typo. Calls through an instance (`Timer1(...)`
+//! with `Timer1 : TON` declared, `#Inst.Start()`, `"DB".Fb()`) are not
+//! checked, since the instance's type decides what runs.
+
+use crate::analysis::CallGraph;
+use crate::ast::Program;
+use super::platform::{self, Platform};
+use super::policy::Policy;
+use super::{utils, RuleResult, Severity, Violation};
+use super::registry::RULE43;
+
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    let graph = CallGraph::build(program);
+    let external: Vec<String> = policy.external_blocks.iter().flatten().map(|b| utils::ident_key(b)).collect();
+    let target = Platform::of(policy);
+    let defined = |name: &str| program.functions.iter().any(|f| utils::ident_key(&f.name) == utils::ident_key(name));
+    let mut violations = Vec::new();
+
+    let mut seen = Vec::new();
+    for f in &program.functions {
+        // Blocks defined twice share one entry in the call graph.
+        if seen.contains(&utils::ident_key(&f.name)) {
+            continue;
+        }
+        seen.push(utils::ident_key(&f.name));
+        for site in graph.callees(&f.name) {
+            let name = site.callee.as_str();
+            let through_instance = name.contains(['.', '[']) || f.declaration(name).is_some();
+            if through_instance || defined(name) || platform::is_intrinsic(name, target) || external.contains(&utils::ident_key(name)) {
+                continue;
+            }
+            violations.push(Violation::new(
+                &RULE43,
+                site.line,
+                format!("Call to '{}', which is not defined in the analysed program", name),
+                "Add the file that defines it, fix the name, or list it under external_blocks in the policy.",
+                Severity::Warning,
+            ));
+        }
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE43.no, RULE43.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    fn findings(src: &str, policy: &Policy) -> Vec<(usize, String)> {
+        check(&parse_scl_from_str(src).unwrap(), policy).violations.into_iter().map(|v| (v.line, v.reason)).collect()
+    }
+
+    #[test]
+    fn calls_to_unknown_blocks_are_flagged() {
+        let src = "FUNCTION_BLOCK FB_Line\nVAR\n    Delay : TON;\nEND_VAR\nBEGIN\n    FC_Scale(x);\n    FB_Pmup(Start := Run);\n    \
+                   Delay(IN := Run, PT := Preset);\n    #Conveyor.Start();\n    y := LIMIT(MN := 0, IN := y, MX := 10) + FC_Ramp(y);\n    \
+                   SFC6(RET_VAL := r);\n    z := INT_TO_REAL(y);\nEND_FUNCTION_BLOCK\n\
+                   FUNCTION FC_Scale\nBEGIN\n    x := 1;\nEND_FUNCTION\n";
+        assert_eq!(
+            findings(src, &Policy::default()),
+            vec![
+                (7, "Call to 'FB_Pmup', which is not defined in the analysed program".to_string()),
+                (10, "Call to 'FC_Ramp', which is not defined in the analysed program".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_conversions_between_elementary_types_are_standard() {
+        let src = "FUNCTION FC_A\nBEGIN\n    x := DINT_TO_REAL(y);\n    Move_To_Home(x);\n    z := TO_STRING(x);\nEND_FUNCTION\n";
+        let lines: Vec<usize> = findings(src, &Policy::default()).into_iter().map(|(l, _)| l).collect();
+        assert_eq!(lines, vec![4, 5]);
+    }
+
+    #[test]
+    fn vendor_instructions_follow_the_platform() {
+        let src = "FUNCTION FC_A\nBEGIN\n    y := NORM_X(MIN := 0, VALUE := x, MAX := 27648);\n    SFC6(RET_VAL := r);\n    \
+                   t := TON(IN := Run, PT := Preset);\nEND_FUNCTION\n";
+        assert!(findings(src, &Policy::default()).is_empty());
+        let codesys = Policy { platform: Some("CODESYS".into()), ..Default::default() };
+        let lines: Vec<usize> = findings(src, &codesys).into_iter().map(|(l, _)| l).collect();
+        assert_eq!(lines, vec![3, 4]);
+    }
+
+    #[test]
+    fn policy_lists_external_blocks() {
+        let src = "FUNCTION FC_A\nBEGIN\n    LGF_Ramp(x);\nEND_FUNCTION\n";
+        assert_eq!(findings(src, &Policy::default()).len(), 1);
+        let policy = Policy { external_blocks: Some(vec!["lgf_ramp".into()]), ..Default::default() };
+        assert!(findings(src, &policy).is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+//! Rule 44: Do not overwrite a value before it is read.
+//! In `x := a; x := b;` the first store is dead: nothing can see it. That
+//! is usually leftover code or a mistyped target. Only straight-line runs
+//! of statements are analysed. IF/CASE/loops, RETURN/EXIT/CONTINUE and
+//! calls to blocks that may read global state end the run, and each
+//! branch or loop body is a run of its own. The dead (first) store is
+//! reported.
+
+use std::collections::HashMap;
+
+use crate::ast::{reachable, Expression, Program, Statement};
+use super::{platform, utils, RuleResult, Severity, Violation};
+use super::registry::RULE44;
+
+pub fn check(program: &Program) -> RuleResult {
+    let mut violations = Vec::new();
+    for f in program.code() {
+        region(&f.statements, &mut violations);
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE44.no, RULE44.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+/// Report the dead stores in one statement list, then in each nested body.
+fn region(stmts: &[Statement], out: &mut Vec<Violation>) {
+    // Key of each stored variable not read since -> (name, line of the store).
+    let mut pending: HashMap<String, (String, usize)> = HashMap::new();
+    for st in reachable(stmts) {
+        match st {
+            Statement::Assign { target, value, line } => {
+                let mut reads = Reads::default();
+                reads.expression(value);
+                let name = target.name().filter(|n| !utils::ident_key(n).starts_with("%P"));
+                if name.is_none() {
+                    // `A[i] := ...` reads `i` and writes only part of `A`.
+                    reads.expression(target);
+                }
+                reads.apply(&mut pending);
+                let Some(name) = name else { continue };
+                if let Some((dead, dead_line)) = pending.insert(utils::ident_key(&name), (name.clone(), *line)) {
+                    out.push(violation(&dead, dead_line, *line));
+                }
+            }
+            Statement::Expr { expr, .. } => {
+                let mut reads = Reads::default();
+                reads.expression(expr);
+                reads.apply(&mut pending);
+            }
+            Statement::Comment { .. } | Statement::ElseMarker { .. } => {}
+            // Control flow and calls end the straight-line run.
+            _ => pending.clear(),
+        }
+        match st {
+            Statement::IfStmt { then_branch, else_branch, .. } => {
+                region(then_branch, out);
+                region(else_branch, out);
+            }
+            Statement::CaseStmt { cases, else_branch, .. } => {
+                cases.iter().for_each(|(_, body)| region(body, out));
+                region(else_branch, out);
+            }
+            Statement::WhileStmt { body, .. } | Statement::ForStmt { body, .. } => region(body, out),
+            _ => {}
+        }
+    }
+}
+
+/// Variables an expression reads, and whether it calls a block that may
+/// read anything.
+#[derive(Default)]
+struct Reads {
+    names: Vec<String>,
+    opaque_call: bool,
+}
+
+impl Reads {
+    fn expression(&mut self, e: &Expression) {
+        if let Some(name) = e.name() {
+            self.names.push(utils::ident_key(&name));
+            return;
+        }
+        match e {
+            Expression::Member { base, .. } => self.expression(base),
+            Expression::UnaryOp { expr, .. } => self.expression(expr),
+            Expression::BinaryOp { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expression::Index { base, index, .. } => {
+                self.expression(base);
+                self.expression(index);
+            }
+            Expression::FuncCall { name, args, .. } => {
+                self.opaque_call |= !platform::is_intrinsic(name, None);
+                args.iter().for_each(|a| self.expression(a));
+            }
+            _ => {}
+        }
+    }
+
+    /// Drop the pending stores these reads observe. Reading `Motor` sees a
+    /// store to `Motor.Speed`, and reading `Motor.Speed` one to `Motor`.
+    fn apply(&self, pending: &mut HashMap<String, (String, usize)>) {
+        if self.opaque_call {
+            pending.clear();
+            return;
+        }
+        let overlaps = |a: &str, b: &str| a == b || a.starts_with(&format!("{}.", b)) || b.starts_with(&format!("{}.", a));
+        pending.retain(|key, _| !self.names.iter().any(|r| overlaps(key, r)));
+    }
+}
+
+fn violation(name: &str, line: usize, overwritten_at: usize) -> Violation {
+    Violation::new(
+        &RULE44,
+        line,
+        format!("Value stored in '{}' is overwritten on line {} before it is read", name, overwritten_at),
+        "Remove the first assignment, or check whether one of the two targets is mistyped.",
+        Severity::Warning,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::parse_block;
+
+    fn lines(body: &str) -> Vec<usize> {
+        check(&parse_block("FUNCTION FC_A", body)).violations.iter().map(|v| v.line).collect()
+    }
+
+    #[test]
+    fn overwritten_stores_are_flagged() {
+        let body = "Speed := 10;\nRamp := 2;\nSpeed := 20;\nMotor.Run := TRUE;\nMotor.Run := FALSE;\n\
+                    IF Start THEN\n    Count := 0;\n    Count := 1;\nEND_IF;";
+        assert_eq!(lines(body), vec![3, 6, 9]);
+    }
+
+    #[test]
+    fn reads_and_control_flow_keep_the_store() {
+        let body = "Speed := 10;\nOut := Speed * 2;\nSpeed := 20;\n\
+                    Count := 0;\nCount := Count + 1;\n\
+                    Level := 1;\nIF Start THEN\n    Level := 2;\nEND_IF;\nLevel := 3;\n\
+                    Motor.Run := TRUE;\nCopy := Motor;\nMotor.Run := FALSE;\n\
+                    Flag := TRUE;\nFC_Log();\nFlag := FALSE;\n\
+                    Table[i] := 1;\nTable[i] := 2;\n\
+                    Done := TRUE;\nx := ABS(Done);\nDone := FALSE;";
+        assert!(lines(body).is_empty());
+    }
+}
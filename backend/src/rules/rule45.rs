@@ -0,0 +1,146 @@
+//! Rule 45: Ramp speed and position setpoints.
+//! Writing an operator setpoint (an HMI, recipe or parameter value, see
+//! `utils::IdentifierClass::SensitiveSource`) straight to a speed or position target
+//! makes the drive jump to it in one scan. The write is accepted when the
+//! value goes through a ramp or limit block (a call whose name contains
+//! `RAMP` or `LIMIT`, or one of the policy's `ramp_functions`), or when it
+//! steps the target by a bounded amount: `Speed := Speed + LIMIT(...)`
+//! or `Speed := Speed - 5`.
+
+use crate::ast::{BinOp, Expression, Program, Statement};
+use super::policy::Policy;
+use super::visit::{walk_program, walk_statement, Visitor};
+use super::utils::{self, IdentifierClass};
+use super::{RuleResult, Severity, Violation};
+use super::registry::RULE45;
+
+/// Name fragments of the targets this rule watches, upper-cased.
+const MOTION_TARGETS: &[&str] = &["SPEED", "VELOCITY", "RPM", "POSITION"];
+
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    let extra: Vec<String> = policy.ramp_functions.iter().flatten().map(|f| utils::ident_key(f)).collect();
+    let mut scan = SetpointWrites { policy, ramps: Ramps { extra }, out: Vec::new() };
+    walk_program(&mut scan, program);
+
+    if scan.out.is_empty() {
+        RuleResult::ok(RULE45.no, RULE45.name)
+    } else {
+        RuleResult::violations(scan.out)
+    }
+}
+
+/// Which calls count as ramping.
+struct Ramps {
+    /// Keys of the policy's `ramp_functions`.
+    extra: Vec<String>,
+}
+
+impl Ramps {
+    fn matches(&self, name: &str) -> bool {
+        let key = utils::ident_key(name);
+        key.contains("RAMP") || key.contains("LIMIT") || self.extra.contains(&key)
+    }
+
+    /// True if `e` calls a ramp anywhere.
+    fn ramped(&self, e: &Expression) -> bool {
+        match e {
+            Expression::FuncCall { name, args, .. } => self.matches(name) || args.iter().any(|a| self.ramped(a)),
+            Expression::BinaryOp { left, right, .. } => self.ramped(left) || self.ramped(right),
+            Expression::UnaryOp { expr, .. } => self.ramped(expr),
+            _ => false,
+        }
+    }
+
+    /// `target + delta` or `target - delta` with a constant or ramped delta.
+    fn bounded_step(&self, target: &str, value: &Expression) -> bool {
+        let Expression::BinaryOp { op: BinOp::Add | BinOp::Sub, left, right, .. } = value else { return false };
+        let is_target = |e: &Expression| e.name().is_some_and(|n| utils::ident_key(&n) == utils::ident_key(target));
+        let bounded = |e: &Expression| matches!(e, Expression::NumberLiteral(..)) || self.ramped(e);
+        (is_target(left) && bounded(right)) || (is_target(right) && bounded(left))
+    }
+}
+
+struct SetpointWrites<'p> {
+    policy: &'p Policy,
+    ramps: Ramps,
+    out: Vec<Violation>,
+}
+
+impl Visitor for SetpointWrites<'_> {
+    fn visit_statement(&mut self, st: &Statement) {
+        if let Statement::Assign { target, value, line } = st {
+            let name = target.name().unwrap_or_default();
+            if is_motion_target(&name, self.policy) && !self.ramps.ramped(value) && !self.ramps.bounded_step(&name, value) {
+                if let Some(source) = operator_setpoint(value, self.policy) {
+                    self.out.push(Violation::new(
+                        &RULE45,
+                        *line,
+                        format!("Setpoint '{}' is written to '{}' without a ramp", source, name),
+                        "Pass the setpoint through a ramp block (e.g. LGF_Ramp or a RAMP FB) so the drive accelerates gradually.",
+                        Severity::Warning,
+                    ));
+                }
+            }
+        }
+        walk_statement(self, st);
+    }
+}
+
+fn is_motion_target(name: &str, policy: &Policy) -> bool {
+    let key = utils::ident_key(name);
+    MOTION_TARGETS.iter().any(|t| key.contains(t)) && !utils::is_identifier_class(name, IdentifierClass::SensitiveSource, policy)
+}
+
+/// The first operator setpoint `e` reads, also through conversions.
+fn operator_setpoint(e: &Expression, policy: &Policy) -> Option<String> {
+    if let Some(name) = e.name() {
+        return Some(name).filter(|n| utils::is_identifier_class(n, IdentifierClass::SensitiveSource, policy));
+    }
+    match e {
+        Expression::BinaryOp { left, right, .. } => operator_setpoint(left, policy).or_else(|| operator_setpoint(right, policy)),
+        Expression::UnaryOp { expr, .. } => operator_setpoint(expr, policy),
+        Expression::FuncCall { args, .. } => args.iter().find_map(|a| operator_setpoint(a, policy)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::parse_block;
+
+    fn lines(body: &str, policy: &Policy) -> Vec<usize> {
+        check(&parse_block("FUNCTION_BLOCK FB_Drive", body), policy).violations.iter().map(|v| v.line).collect()
+    }
+
+    #[test]
+    fn direct_setpoint_writes_are_flagged() {
+        let body = "Motor_Speed := HMI_Speed;\nIF Enable THEN\n    Axis_Position := Recipe_Position * 10;\nEND_IF;\n\
+                    Conveyor_Speed := Conveyor_Speed + (HMI_Speed - Conveyor_Speed);\nMotor_Run := HMI_Start;";
+        assert_eq!(lines(body, &Policy::default()), vec![3, 5, 7]);
+    }
+
+    #[test]
+    fn ramped_or_stepped_writes_pass() {
+        let body = "Motor_Speed := LGF_Ramp(HMI_Speed, 50);\n\
+                    Fan_Speed := Fan_Speed + LIMIT(MN := -5, IN := HMI_Fan_Speed - Fan_Speed, MX := 5);\n\
+                    Pump_Speed := Pump_Speed + 2;\nSpeed_Limit := 100;\nHMI_Speed_Display := Motor_Speed;";
+        assert!(lines(body, &Policy::default()).is_empty());
+    }
+
+    #[test]
+    fn policy_adds_ramp_functions() {
+        let body = "Motor_Speed := FB_SoftStart(HMI_Speed);";
+        assert_eq!(lines(body, &Policy::default()), vec![3]);
+        let policy = Policy { ramp_functions: Some(vec!["fb_softstart".into()]), ..Default::default() };
+        assert!(lines(body, &policy).is_empty());
+    }
+
+    #[test]
+    fn setpoint_names_are_operator_sources() {
+        assert_eq!(lines("Drive_Speed := Speed_Setpoint;", &Policy::default()), vec![3]);
+        let policy = Policy { sensitive_source_keywords: Some(vec!["scada".into()]), ..Default::default() };
+        assert_eq!(lines("Drive_Speed := Scada_Speed;", &Policy::default()), Vec::<usize>::new());
+        assert_eq!(lines("Drive_Speed := Scada_Speed;", &policy), vec![3]);
+    }
+}
@@ -0,0 +1,104 @@
+//! Rule 46: Do not mix up `=` and `:=`.
+//! `x := y = z;` stores a comparison result, which is usually a typo for a
+//! chained or plain assignment. It is reported when the right-hand side is
+//! a lone `=` between two plain operands and the target is not declared
+//! `BOOL` in the block (a BOOL target is an ordinary flag update). The
+//! reverse, `IF x := y THEN`, is not valid SCL; the SCL parser keeps it as
+//! a `BinOp::Assign` condition so it can be reported here instead of
+//! failing the file.
+
+use crate::ast::{BinOp, DataType, Expression, Function, Program, Statement};
+use super::visit::{walk_function, walk_program, walk_statement, Visitor};
+use super::{utils, RuleResult, Severity, Violation};
+use super::registry::RULE46;
+
+pub fn check(program: &Program) -> RuleResult {
+    let mut scan = Typos { bools: Vec::new(), out: Vec::new() };
+    walk_program(&mut scan, program);
+
+    if scan.out.is_empty() {
+        RuleResult::ok(RULE46.no, RULE46.name)
+    } else {
+        RuleResult::violations(scan.out)
+    }
+}
+
+struct Typos {
+    /// Keys of the variables declared `BOOL` in the current block.
+    bools: Vec<String>,
+    out: Vec<Violation>,
+}
+
+impl Visitor for Typos {
+    fn visit_function(&mut self, f: &Function) {
+        self.bools = f
+            .declarations
+            .iter()
+            .filter(|d| matches!(&d.data_type, DataType::Named(t) if t.eq_ignore_ascii_case("BOOL")))
+            .map(|d| utils::ident_key(&d.name))
+            .collect();
+        walk_function(self, f);
+    }
+
+    fn visit_statement(&mut self, st: &Statement) {
+        match st {
+            Statement::Assign { target, value, line } => {
+                let name = target.name().unwrap_or_default();
+                if is_bare_equality(value) && !self.bools.contains(&utils::ident_key(&name)) {
+                    self.out.push(violation(
+                        *line,
+                        format!("'{}' is assigned the comparison '{}'", name, utils::expr_text(value)),
+                        "If a plain assignment was meant, drop the '='. If the flag is intended, declare the target as BOOL.",
+                    ));
+                }
+            }
+            Statement::IfStmt { condition, line, .. } | Statement::WhileStmt { condition, line, .. }
+                if matches!(condition, Expression::BinaryOp { op: BinOp::Assign, .. }) =>
+            {
+                self.out.push(violation(
+                    *line,
+                    format!("Condition '{}' is an assignment", utils::expr_text(condition)),
+                    "Use '=' to compare.",
+                ));
+            }
+            _ => {}
+        }
+        walk_statement(self, st);
+    }
+}
+
+/// `a = b` with no other operator on either side.
+fn is_bare_equality(e: &Expression) -> bool {
+    let plain = |e: &Expression| {
+        e.name().is_some()
+            || matches!(e, Expression::NumberLiteral(..) | Expression::BoolLiteral(..) | Expression::StringLiteral(..))
+    };
+    matches!(e, Expression::BinaryOp { op: BinOp::Eq, left, right, .. } if plain(left) && plain(right))
+}
+
+fn violation(line: usize, reason: String, suggestion: &str) -> Violation {
+    Violation::new(&RULE46, line, reason, suggestion, Severity::Info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::parse_block;
+
+    fn lines(body: &str) -> Vec<usize> {
+        check(&parse_block("FUNCTION_BLOCK FB_A\nVAR\n    Done : BOOL;\nEND_VAR", body)).violations.iter().map(|v| v.line).collect()
+    }
+
+    #[test]
+    fn comparisons_stored_in_non_bool_targets_are_flagged() {
+        let body = "Speed := Preset = 0;\nDone := Count = 10;\nLevel := (a = b) AND c;\nTotal := a + b;";
+        assert_eq!(lines(body), vec![6]);
+    }
+
+    #[test]
+    fn assignments_used_as_conditions_are_flagged() {
+        let body = "IF Mode := 2 THEN\n    x := 1;\nELSIF Mode = 3 THEN\n    x := 2;\nEND_IF;\n\
+                    WHILE Busy := TRUE DO\n    y := 1;\nEND_WHILE;";
+        assert_eq!(lines(body), vec![6, 11]);
+    }
+}
@@ -0,0 +1,137 @@
+//! Rule 47: Guard writes to retentive memory.
+//! Retentive data is saved to non-volatile memory, so writing it on
+//! every OB1 scan wears the flash and persists values that were only
+//! meant to be transient. A write in OB1 (an assignment, or the output of
+//! `MOVE`, `MOVE_BLK`, `FILL_BLK` and the like) is reported when no
+//! enclosing IF/CASE/WHILE and no earlier early exit guards it. A name
+//! is retentive when one of its path parts ends in `_RETAIN` or starts
+//! with `RETAIN_`, or matches one of the policy's `retain_patterns`.
+
+use regex::Regex;
+
+use crate::ast::{reachable, Expression, FunctionKind, Program, Statement};
+use super::policy::{self, Policy};
+use super::{utils, RuleResult, Severity, Violation};
+use super::registry::RULE47;
+
+/// Block moves and the parameter each writes, upper-cased.
+const BLOCK_WRITES: &[(&str, &str)] = &[
+    ("MOVE", "OUT"),
+    ("MOVE_BLK", "OUT"),
+    ("UMOVE_BLK", "OUT"),
+    ("FILL_BLK", "OUT"),
+    ("UFILL_BLK", "OUT"),
+    ("BLKMOV", "DSTBLK"),
+];
+
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    let extra = match policy::name_regexes(&policy.retain_patterns, "retain_patterns") {
+        Ok(extra) => extra,
+        Err(e) => return RuleResult::error(RULE47.no, RULE47.name, e),
+    };
+    let retain = Retentive { extra };
+    let mut violations = Vec::new();
+
+    for f in program.functions.iter().filter(|f| f.kind == FunctionKind::OB1) {
+        unguarded_writes(&f.statements, &retain, &mut violations);
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE47.no, RULE47.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+/// Which names are retentive.
+struct Retentive {
+    extra: Vec<Regex>,
+}
+
+impl Retentive {
+    fn matches(&self, name: &str) -> bool {
+        let key = utils::ident_key(name);
+        key.split('.').any(|part| part.ends_with("_RETAIN") || part.starts_with("RETAIN_"))
+            || self.extra.iter().any(|re| re.is_match(name))
+    }
+}
+
+/// Report retentive writes in `stmts` that run on every scan. IF, CASE
+/// and WHILE bodies are guarded and not entered; FOR bodies run
+/// unconditionally and are. An early exit guards the rest of the list.
+fn unguarded_writes(stmts: &[Statement], retain: &Retentive, out: &mut Vec<Violation>) {
+    for st in reachable(stmts) {
+        match st {
+            Statement::Assign { target, line, .. } => {
+                if let Some(name) = written(target).filter(|n| retain.matches(n)) {
+                    out.push(violation(&name, *line));
+                }
+            }
+            Statement::Call { name, args, line } => {
+                let Some((_, param)) = BLOCK_WRITES.iter().find(|(block, _)| block.eq_ignore_ascii_case(name)) else {
+                    continue;
+                };
+                let targets = args.iter().filter(|(p, _)| p.eq_ignore_ascii_case(param)).filter_map(|(_, a)| written(a));
+                for target in targets.filter(|n| retain.matches(n)) {
+                    out.push(violation(&target, *line));
+                }
+            }
+            Statement::ForStmt { body, .. } => unguarded_writes(body, retain, out),
+            _ => {}
+        }
+        if st.early_exit_condition().is_some() {
+            return;
+        }
+    }
+}
+
+/// The variable a write target stores into; `Table[i]` writes `Table`.
+fn written(target: &Expression) -> Option<String> {
+    match target {
+        Expression::Index { base, .. } => written(base),
+        _ => target.name(),
+    }
+}
+
+fn violation(name: &str, line: usize) -> Violation {
+    Violation::new(
+        &RULE47,
+        line,
+        format!("Retentive '{}' is written on every OB1 scan", name),
+        "Write it only when the value changes or on an edge (R_TRIG), e.g. IF New <> Saved THEN Saved := New; END_IF.",
+        Severity::Warning,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::parse_block;
+    use crate::rules::policy::parse_policy_from_text;
+
+    fn lines(body: &str, policy: &Policy) -> Vec<usize> {
+        check(&parse_block("ORGANIZATION_BLOCK OB1", body), policy).violations.iter().map(|v| v.line).collect()
+    }
+
+    #[test]
+    fn unguarded_retentive_writes_are_flagged() {
+        let body = "Hours_Retain := Hours;\nRetain_Data.Count := Count;\n\
+                    MOVE_BLK(IN := Recipe[0], COUNT := 10, OUT := Recipe_Retain[0]);\n\
+                    FOR i := 0 TO 3 DO\n    Totals_RETAIN[i] := Totals[i];\nEND_FOR;\nCount := 0;";
+        assert_eq!(lines(body, &Policy::default()), vec![3, 4, 5, 7]);
+    }
+
+    #[test]
+    fn guarded_writes_pass() {
+        let body = "IF Hours <> Hours_Retain THEN\n    Hours_Retain := Hours;\nEND_IF;\n\
+                    IF NOT Save THEN\n    RETURN;\nEND_IF;\nRetain_Data.Count := Count;";
+        assert!(lines(body, &Policy::default()).is_empty());
+    }
+
+    #[test]
+    fn policy_adds_retentive_names() {
+        let policy = parse_policy_from_text(r#"{ "retain_patterns": ["\"DB_Persist\"\\..*"] }"#).unwrap();
+        assert_eq!(lines("\"DB_Persist\".Hours := Hours;", &policy), vec![3]);
+        assert!(lines("\"DB_Persist\".Hours := Hours;", &Policy::default()).is_empty());
+    }
+}
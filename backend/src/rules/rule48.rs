@@ -0,0 +1,131 @@
+//! Rule 48: Assign values of the target's type.
+//! `Run := Count + 1` or `Level := Run` mixes BOOL and numbers, which
+//! some runtimes reject and others silently coerce. When the block
+//! declares the target and the value's type is certain (see
+//! `analysis::infer_type`), the two must agree; an integer stored in a
+//! REAL is the one implicit widening allowed. Targets the block does not
+//! declare fall back to literals: one that is assigned TRUE/FALSE
+//! somewhere in the block is taken as a BOOL, and storing a number other
+//! than 0 or 1 in it is reported.
+
+use crate::analysis::{declared_type, infer_type, ValueType};
+use crate::ast::{Expression, Function, Program, Statement};
+use super::visit::{walk_function, walk_statement, Visitor};
+use super::{utils, RuleResult, Severity, Violation};
+use super::registry::RULE48;
+
+pub fn check(program: &Program) -> RuleResult {
+    let mut violations = Vec::new();
+    for f in program.code() {
+        let mut flags = BoolLiteralTargets { f, keys: Vec::new() };
+        walk_function(&mut flags, f);
+        let mut scan = Mismatches { f, flags: flags.keys, out: &mut violations };
+        walk_function(&mut scan, f);
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE48.no, RULE48.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+/// Keys of the undeclared targets assigned `TRUE` or `FALSE` in the block.
+struct BoolLiteralTargets<'f> {
+    f: &'f Function,
+    keys: Vec<String>,
+}
+
+impl Visitor for BoolLiteralTargets<'_> {
+    fn visit_statement(&mut self, st: &Statement) {
+        if let Statement::Assign { target, value: Expression::BoolLiteral(..), .. } = st {
+            if let Some(name) = target.name().filter(|n| self.f.declaration(n).is_none()) {
+                self.keys.push(utils::ident_key(&name));
+            }
+        }
+        walk_statement(self, st);
+    }
+}
+
+struct Mismatches<'f, 'o> {
+    f: &'f Function,
+    flags: Vec<String>,
+    out: &'o mut Vec<Violation>,
+}
+
+impl Visitor for Mismatches<'_, '_> {
+    fn visit_statement(&mut self, st: &Statement) {
+        if let Statement::Assign { target, value, line } = st {
+            let name = utils::expr_text(target);
+            let reason = match (declared_type(self.f, target), infer_type(self.f, value)) {
+                (Some(to), Some(from)) if !compatible(to, from) => {
+                    Some(format!("'{}' is {} but is assigned a{} {} value", name, article(to), an(from), from.label()))
+                }
+                (None, _) if self.flags.contains(&utils::ident_key(&name)) => match value {
+                    Expression::NumberLiteral(n, _) if *n != 0 && *n != 1 => {
+                        Some(format!("'{}' is used as a BOOL but is assigned {}", name, n))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some(reason) = reason {
+                self.out.push(Violation::new(
+                    &RULE48,
+                    *line,
+                    reason,
+                    "Convert explicitly (e.g. INT_TO_BOOL, a comparison such as x <> 0, or SEL) or fix the target.",
+                    Severity::Warning,
+                ));
+            }
+        }
+        walk_statement(self, st);
+    }
+}
+
+/// Whether a `from` value may be stored in a `to` target without an
+/// explicit conversion.
+fn compatible(to: ValueType, from: ValueType) -> bool {
+    to == from || (to == ValueType::Real && from == ValueType::Integer)
+}
+
+/// "a BOOL", "an integer".
+fn article(t: ValueType) -> String {
+    format!("a{} {}", an(t), t.label())
+}
+
+fn an(t: ValueType) -> &'static str {
+    if t == ValueType::Integer { "n" } else { "" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::parse_block;
+
+    fn findings(body: &str) -> Vec<(usize, String)> {
+        let header = "FUNCTION_BLOCK FB_A\nVAR\n    Run : BOOL;\n    Count : INT;\n    Level : REAL;\n    Name : STRING;\nEND_VAR";
+        check(&parse_block(header, body)).violations.into_iter().map(|v| (v.line, v.reason)).collect()
+    }
+
+    #[test]
+    fn declared_types_must_agree() {
+        let body = "Run := Count + 1;\nLevel := Run;\nCount := Level * 2;\nName := 5;\n\
+                    Level := Count;\nRun := Count > 3;\nCount := REAL_TO_INT(Level);";
+        assert_eq!(
+            findings(body),
+            vec![
+                (9, "'Run' is a BOOL but is assigned an integer value".to_string()),
+                (10, "'Level' is a REAL but is assigned a BOOL value".to_string()),
+                (11, "'Count' is an integer but is assigned a REAL value".to_string()),
+                (12, "'Name' is a STRING but is assigned an integer value".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn undeclared_flags_fall_back_to_literals() {
+        let body = "Pump_On := FALSE;\nIF Start THEN\n    Pump_On := 5;\nEND_IF;\nPump_On := 1;\nSpeed := 5;\nMotor.Speed := Run;";
+        assert_eq!(findings(body), vec![(11, "'Pump_On' is used as a BOOL but is assigned 5".to_string())]);
+    }
+}
@@ -0,0 +1,189 @@
+//! Rule 49: Poll emergency-stop inputs on every scan.
+//! An e-stop read only inside `IF Mode = AUTO THEN ...` is ignored in
+//! every other mode. OB1 must read at least one e-stop input where it
+//! runs on every scan: in a top-level statement, or in the condition or
+//! selector of a top-level IF/CASE/WHILE, before any early exit. Each
+//! e-stop input OB1 reads only inside branches, loop bodies or after an
+//! early exit is reported, and so is an OB1 with no unconditional e-stop
+//! read at all. A name is an e-stop input when one of its path parts
+//! contains `ESTOP` or `E_STOP` or starts with `SAFETY_`, or it matches
+//! one of the policy's `estop_patterns`.
+
+use regex::Regex;
+
+use crate::ast::{reachable, Expression, FunctionKind, Program, Statement};
+use super::policy::{self, Policy};
+use super::visit::{walk_expression, Visitor};
+use super::{utils, RuleResult, Severity, Violation};
+use super::registry::RULE49;
+
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    let extra = match policy::name_regexes(&policy.estop_patterns, "estop_patterns") {
+        Ok(extra) => extra,
+        Err(e) => return RuleResult::error(RULE49.no, RULE49.name, e),
+    };
+    let estop = EStop { extra };
+    let mut violations = Vec::new();
+
+    for f in program.functions.iter().filter(|f| f.kind == FunctionKind::OB1) {
+        let mut reads = Reads { estop: &estop, every_scan: Vec::new(), guarded: Vec::new() };
+        reads.statements(&f.statements, false);
+
+        let mut reported: Vec<String> = Vec::new();
+        for (name, line) in &reads.guarded {
+            let key = utils::ident_key(name);
+            if reported.contains(&key) || reads.every_scan.iter().any(|(n, _)| utils::ident_key(n) == key) {
+                continue;
+            }
+            reported.push(key);
+            violations.push(violation(
+                *line,
+                format!("Emergency-stop input '{}' is only read inside a conditional branch of {}", name, f.name),
+                "Read it on every scan, outside any mode or state guard, e.g. Stop_All := NOT E_Stop; at the top of OB1.",
+            ));
+        }
+        if reads.every_scan.is_empty() {
+            violations.push(violation(
+                f.line,
+                format!("{} does not read an emergency-stop input on every scan", f.name),
+                "Poll the e-stop unconditionally in OB1, or list the site's e-stop tags under estop_patterns in the policy.",
+            ));
+        }
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE49.no, RULE49.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+/// Which names are e-stop inputs.
+struct EStop {
+    extra: Vec<Regex>,
+}
+
+impl EStop {
+    fn matches(&self, name: &str) -> bool {
+        let key = utils::ident_key(name);
+        key.split('.').any(|part| part.contains("ESTOP") || part.contains("E_STOP") || part.starts_with("SAFETY_"))
+            || self.extra.iter().any(|re| re.is_match(name))
+    }
+}
+
+/// E-stop reads in OB1 with their lines, split by whether they run on
+/// every scan.
+struct Reads<'e> {
+    estop: &'e EStop,
+    every_scan: Vec<(String, usize)>,
+    guarded: Vec<(String, usize)>,
+}
+
+impl Reads<'_> {
+    fn statements(&mut self, stmts: &[Statement], mut guarded: bool) {
+        for st in reachable(stmts) {
+            let line = st.line();
+            match st {
+                Statement::Assign { target, value, .. } => {
+                    if let Expression::Index { index, .. } = target {
+                        self.expression(index, line, guarded);
+                    }
+                    self.expression(value, line, guarded);
+                }
+                Statement::Call { args, .. } => args.iter().for_each(|(_, a)| self.expression(a, line, guarded)),
+                Statement::Expr { expr, .. } => self.expression(expr, line, guarded),
+                Statement::IfStmt { condition, then_branch, else_branch, .. } => {
+                    self.expression(condition, line, guarded);
+                    self.statements(then_branch, true);
+                    self.statements(else_branch, true);
+                }
+                Statement::CaseStmt { expression, cases, else_branch, .. } => {
+                    self.expression(expression, line, guarded);
+                    cases.iter().for_each(|(_, body)| self.statements(body, true));
+                    self.statements(else_branch, true);
+                }
+                Statement::WhileStmt { condition, body, .. } => {
+                    self.expression(condition, line, guarded);
+                    self.statements(body, true);
+                }
+                Statement::ForStmt { start, end, body, .. } => {
+                    self.expression(start, line, guarded);
+                    self.expression(end, line, guarded);
+                    self.statements(body, true);
+                }
+                _ => {}
+            }
+            // Code after `IF cond THEN RETURN; END_IF` does not run on every scan.
+            guarded |= st.early_exit_condition().is_some();
+        }
+    }
+
+    fn expression(&mut self, e: &Expression, line: usize, guarded: bool) {
+        let mut names = Names(Vec::new());
+        names.visit_expression(e);
+        let found = names.0.into_iter().filter(|n| self.estop.matches(n)).map(|n| (n, line));
+        if guarded {
+            self.guarded.extend(found);
+        } else {
+            self.every_scan.extend(found);
+        }
+    }
+}
+
+/// The variables an expression reads.
+struct Names(Vec<String>);
+
+impl Visitor for Names {
+    fn visit_expression(&mut self, e: &Expression) {
+        match e.name() {
+            Some(name) => self.0.push(name),
+            None => walk_expression(self, e),
+        }
+    }
+}
+
+fn violation(line: usize, reason: String, suggestion: &str) -> Violation {
+    Violation::new(&RULE49, line, reason, suggestion, Severity::Warning)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::parse_block;
+    use crate::rules::policy::parse_policy_from_text;
+
+    fn findings(body: &str, policy: &Policy) -> Vec<(usize, String)> {
+        check(&parse_block("ORGANIZATION_BLOCK OB1", body), policy).violations.into_iter().map(|v| (v.line, v.reason)).collect()
+    }
+
+    #[test]
+    fn estops_read_only_under_a_mode_guard_are_flagged() {
+        let body = "IF Mode = 1 THEN\n    IF E_Stop THEN\n        Motor := FALSE;\n    END_IF;\nEND_IF;\n\
+                    CASE Mode OF\n    2:\n        Halt := NOT \"DB_IO\".Safety_Door;\nEND_CASE;";
+        assert_eq!(
+            findings(body, &Policy::default()),
+            vec![
+                (4, "Emergency-stop input 'E_Stop' is only read inside a conditional branch of OB1".to_string()),
+                (10, "Emergency-stop input '\"DB_IO\".Safety_Door' is only read inside a conditional branch of OB1".to_string()),
+                (1, "OB1 does not read an emergency-stop input on every scan".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unconditional_polls_pass() {
+        let body = "IF E_Stop OR Guard_Open THEN\n    Motor := FALSE;\nEND_IF;\nIF Mode = 1 THEN\n    Lamp := E_Stop;\nEND_IF;";
+        assert!(findings(body, &Policy::default()).is_empty());
+        assert!(findings("Stop_All := NOT EStop_Main;", &Policy::default()).is_empty());
+        // After an early exit the read no longer happens on every scan.
+        let late = findings("IF NOT Enabled THEN\n    RETURN;\nEND_IF;\nStop_All := NOT E_Stop;", &Policy::default());
+        assert_eq!(late.len(), 2);
+    }
+
+    #[test]
+    fn policy_adds_estop_names() {
+        let policy = parse_policy_from_text(r#"{ "estop_patterns": ["Emergency_.*"] }"#).unwrap();
+        assert!(findings("Stop_All := NOT Emergency_Halt;", &policy).is_empty());
+        assert_eq!(findings("Stop_All := NOT Emergency_Halt;", &Policy::default()).len(), 1);
+    }
+}
@@ -1,45 +1,59 @@
 //! Rule 5: Use checksum integrity checks.
 //! Heuristic: if sensitive data (e.g. recipe) is used, there must be
-//! evidence of a checksum/CRC comparison that can raise an alarm.
+//! evidence of a checksum/CRC comparison that can raise an alarm, or of a
+//! call to a verification block (`VerifyCRC`, `CheckIntegrity`, ...). A check
+//! inside a routine the function calls directly also counts.
 
+use crate::analysis::CallGraph;
 use crate::ast::{Expression, Program, Statement};
-use super::{RuleResult, Violation, utils}; // Use central utility
+use super::utils::{self, IdentifierClass};
+use super::{Policy, RuleResult, Severity, Violation};
+use super::registry::RULE5;
 
-pub fn check(program: &Program) -> RuleResult {
-    for f in &program.functions {
-        if function_uses_sensitive_data(&f.statements) && !has_integrity_check(&f.statements) {
-            return RuleResult::violations(vec![Violation {
-                rule_no: 5,
-                rule_name: "Use checksum integrity checks",
-                line: f.line,
-                reason: format!("Function '{}' uses recipe/parameter data without a visible integrity check.", f.name),
-                suggestion: "Verify a checksum/CRC for recipe data and raise an alarm on mismatch before using the data.".into(),
-            }]);
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    let graph = CallGraph::build(program);
+    for f in program.code() {
+        if function_uses_sensitive_data(&f.statements, policy)
+            && !has_integrity_check(&f.statements)
+            && !callee_checks_integrity(program, &graph, &f.name)
+        {
+            return RuleResult::violations(vec![Violation::new(
+                &RULE5,
+                f.line,
+                format!("Function '{}' uses recipe/parameter data without a visible integrity check.", f.name),
+                "Verify a checksum/CRC for recipe data and raise an alarm on mismatch before using the data.",
+                Severity::Warning,
+            )]);
         }
     }
-    RuleResult::ok(5, "Use checksum integrity checks")
+    RuleResult::ok(RULE5.no, RULE5.name)
 }
 
+/// One level of inter-procedural lookup: the validation may live in a
+/// block the function delegates to.
+fn callee_checks_integrity(program: &Program, graph: &CallGraph, caller: &str) -> bool {
+    graph
+        .callees(caller)
+        .iter()
+        .filter_map(|site| graph.resolve(program, site))
+        .any(|callee| has_integrity_check(&callee.statements))
+}
 
-fn function_uses_sensitive_data(stmts: &[Statement]) -> bool {
+fn function_uses_sensitive_data(stmts: &[Statement], policy: &Policy) -> bool {
     for st in stmts {
         match st {
-            Statement::Assign { value, .. } => {
-                if expr_contains_sensitive_vars(value) { return true; }
-            }
-            Statement::Call { args, .. } => {
-                if args.iter().any(|(_, val)| expr_contains_sensitive_vars(val)) { return true; }
-            }
+            Statement::Assign { value, .. } if expr_contains_sensitive_vars(value, policy) => return true,
+            Statement::Call { args, .. } if args.iter().any(|(_, val)| expr_contains_sensitive_vars(val, policy)) => return true,
             Statement::IfStmt { condition, then_branch, else_branch, .. } => {
-                if expr_contains_sensitive_vars(condition) { return true; }
-                if function_uses_sensitive_data(then_branch) { return true; }
-                if function_uses_sensitive_data(else_branch) { return true; }
+                if expr_contains_sensitive_vars(condition, policy) { return true; }
+                if function_uses_sensitive_data(then_branch, policy) { return true; }
+                if function_uses_sensitive_data(else_branch, policy) { return true; }
             }
             Statement::CaseStmt { cases, else_branch, .. } => {
                 for (_, case_stmts) in cases {
-                    if function_uses_sensitive_data(case_stmts) { return true; }
+                    if function_uses_sensitive_data(case_stmts, policy) { return true; }
                 }
-                if function_uses_sensitive_data(else_branch) { return true; }
+                if function_uses_sensitive_data(else_branch, policy) { return true; }
             }
             _ => {}
         }
@@ -47,21 +61,25 @@ fn function_uses_sensitive_data(stmts: &[Statement]) -> bool {
     false
 }
 
-fn expr_contains_sensitive_vars(e: &Expression) -> bool {
+fn expr_contains_sensitive_vars(e: &Expression, policy: &Policy) -> bool {
     let mut vars = Vec::new();
     find_vars(e, &mut vars);
-    vars.iter().any(|v| utils::is_sensitive_variable(v))
+    vars.iter().any(|v| utils::is_identifier_class(v, IdentifierClass::SensitiveSource, policy))
 }
 
 fn has_integrity_check(stmts: &[Statement]) -> bool {
     for st in stmts {
+        // A dedicated verification block, e.g. `VerifyCRC(Recipe)`.
+        if let Statement::Call { name, .. } = st {
+            if is_integrity_routine(name) { return true; }
+        }
         if let Statement::IfStmt { condition, then_branch, .. } = st {
             let c = utils::expr_text(condition).to_ascii_uppercase();
             let mentions_sens = c.contains("CHECKSUM") || c.contains("CRC");
             let is_compare = c.contains("<>") || c.contains("!=");
             let sets_alarm = then_branch.iter().any(|s| {
                 if let Statement::Assign { target, .. } = s {
-                    if let Expression::Identifier(name) = target {
+                    if let Some(name) = target.name() {
                         return name.to_ascii_uppercase().contains("ALARM");
                     }
                 }
@@ -80,9 +98,14 @@ fn has_integrity_check(stmts: &[Statement]) -> bool {
     false
 }
 
+fn is_integrity_routine(name: &str) -> bool {
+    let up = name.to_ascii_uppercase();
+    up.contains("CRC") || up.contains("CHECKSUM") || up.contains("INTEGRITY")
+}
+
 fn find_vars(e: &Expression, out: &mut Vec<String>) {
     match e {
-        Expression::Identifier(s) => out.push(s.clone()),
+        Expression::Identifier(_) | Expression::Member { .. } => out.extend(e.name()),
         Expression::BinaryOp { left, right, .. } => {
             find_vars(left, out);
             find_vars(right, out);
@@ -98,4 +121,38 @@ fn find_vars(e: &Expression, out: &mut Vec<String>) {
         }
         _ => {}
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn integrity_check_in_callee_is_accepted() {
+        let src = "FUNCTION FC_Verify\nBEGIN\n    IF Recipe_Checksum <> Calc_CRC THEN\n        Alarm := TRUE;\n    END_IF;\nEND_FUNCTION\n\
+                   ORGANIZATION_BLOCK OB1\nBEGIN\n    FC_Verify();\n    Speed := Recipe_Speed;\nEND_ORGANIZATION_BLOCK\n";
+        let program = parse_scl_from_str(src).unwrap();
+        assert!(check(&program, &Policy::default()).passed());
+
+        let unchecked = parse_scl_from_str(
+            "ORGANIZATION_BLOCK OB1\nBEGIN\n    Speed := Recipe_Speed;\nEND_ORGANIZATION_BLOCK\n",
+        )
+        .unwrap();
+        assert!(!check(&unchecked, &Policy::default()).passed());
+    }
+
+    #[test]
+    fn verification_call_counts_as_integrity_check() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    IF Load THEN\n        CheckIntegrity(Recipe_Data);\n    END_IF;\n\
+                   Speed := Recipe_Speed;\nEND_ORGANIZATION_BLOCK\n";
+        let program = parse_scl_from_str(src).unwrap();
+        assert!(check(&program, &Policy::default()).passed());
+    }
+
+    #[test]
+    fn setpoint_sources_need_an_integrity_check() {
+        let program = parse_scl_from_str("ORGANIZATION_BLOCK OB1\nBEGIN\n    Drive_Speed := Speed_Setpoint;\nEND_ORGANIZATION_BLOCK\n").unwrap();
+        assert!(!check(&program, &Policy::default()).passed());
+    }
+}
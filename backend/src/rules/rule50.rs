@@ -0,0 +1,123 @@
+//! Rule 50: Do not call one timer or counter instance inside a loop.
+//! A function block keeps its state in its instance, so calling
+//! `TON(IN := Run[i], ...)` in a FOR or WHILE body runs every iteration
+//! through the same timer: each call restarts or advances the one elapsed
+//! time, and the outputs only reflect the last element. Calls inside loop
+//! bodies are reported when they go to a standard stateful block (timers,
+//! counters, edge detectors, flip-flops, controllers), to an instance
+//! declared with such a type or with a function block of the program, or
+//! to a function block of the program directly. Calls through an indexed
+//! instance (`Delays[i](...)`) use a separate instance per element and
+//! pass.
+
+use crate::ast::{DataType, Function, FunctionKind, Program, Statement};
+use super::visit::{walk_function, walk_statement, Visitor};
+use super::{utils, RuleResult, Severity, Violation};
+use super::registry::RULE50;
+
+/// Standard function blocks that keep state between calls, upper-cased.
+const STATEFUL_BLOCKS: &[&str] = &[
+    "TON", "TOF", "TP", "TONR", "CTU", "CTD", "CTUD", "R_TRIG", "F_TRIG", "SR", "RS",
+    "PID_COMPACT", "PID_3STEP", "CONT_C", "CONT_S", "PULSEGEN",
+];
+
+pub fn check(program: &Program) -> RuleResult {
+    let mut violations = Vec::new();
+    for f in program.code() {
+        let mut scan = LoopCalls { program, f, loops: 0, out: &mut violations };
+        walk_function(&mut scan, f);
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE50.no, RULE50.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+struct LoopCalls<'p, 'o> {
+    program: &'p Program,
+    f: &'p Function,
+    /// How many FOR/WHILE bodies enclose the current statement.
+    loops: usize,
+    out: &'o mut Vec<Violation>,
+}
+
+impl LoopCalls<'_, '_> {
+    /// Whether `TYPE` is a standard stateful block or a function block of
+    /// the program.
+    fn stateful_type(&self, type_name: &str) -> bool {
+        let key = utils::ident_key(type_name.trim_matches('"'));
+        STATEFUL_BLOCKS.contains(&key.as_str())
+            || self
+                .program
+                .functions
+                .iter()
+                .any(|b| b.kind == FunctionKind::FB && utils::ident_key(b.name.trim_matches('"')) == key)
+    }
+
+    /// Whether a call to `name` goes to a single stateful instance.
+    fn stateful_call(&self, name: &str) -> bool {
+        if name.contains('[') {
+            return false;
+        }
+        if let Some(d) = self.f.declaration(name) {
+            return matches!(&d.data_type, DataType::Named(t) if self.stateful_type(t));
+        }
+        // `TON(...)`, `Delay.TON(...)`, `FB_Dose(...)`.
+        name.rsplit('.').next().is_some_and(|last| self.stateful_type(last))
+    }
+}
+
+impl Visitor for LoopCalls<'_, '_> {
+    fn visit_statement(&mut self, st: &Statement) {
+        match st {
+            Statement::Call { name, line, .. } if self.loops > 0 && self.stateful_call(name) => {
+                self.out.push(Violation::new(
+                    &RULE50,
+                    *line,
+                    format!("'{}' is called inside a loop, so every iteration shares one instance", name),
+                    "Declare an array of instances and call one per element, e.g. Delays : ARRAY[1..8] OF TON; Delays[i](IN := Run[i], PT := Preset);",
+                    Severity::Error,
+                ));
+            }
+            Statement::ForStmt { .. } | Statement::WhileStmt { .. } => {
+                self.loops += 1;
+                walk_statement(self, st);
+                self.loops -= 1;
+                return;
+            }
+            _ => {}
+        }
+        walk_statement(self, st);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    fn findings(src: &str) -> Vec<(usize, String)> {
+        check(&parse_scl_from_str(src).unwrap()).violations.into_iter().map(|v| (v.line, v.reason)).collect()
+    }
+
+    #[test]
+    fn stateful_calls_in_loops_are_flagged() {
+        let src = "FUNCTION_BLOCK FB_Line\nVAR\n    Delay : TON;\n    Dose : FB_Dose;\n    Delays : ARRAY[1..8] OF TON;\nEND_VAR\nBEGIN\n\
+                   FOR i := 1 TO 8 DO\n    TON(IN := Run[i], PT := Preset);\n    Delay(IN := Run[i], PT := Preset);\n    \
+                   Delays[i](IN := Run[i], PT := Preset);\n    IF Run[i] THEN\n        Dose(Start := TRUE);\n    END_IF;\n    \
+                   FC_Scale(x := i);\nEND_FOR;\nWHILE Busy DO\n    CTU(CU := Pulse);\nEND_WHILE;\nTON(IN := Run[1], PT := Preset);\n\
+                   END_FUNCTION_BLOCK\n\
+                   FUNCTION_BLOCK FB_Dose\nBEGIN\n    x := 1;\nEND_FUNCTION_BLOCK\n";
+        assert_eq!(
+            findings(src),
+            vec![
+                (9, "'TON' is called inside a loop, so every iteration shares one instance".to_string()),
+                (10, "'Delay' is called inside a loop, so every iteration shares one instance".to_string()),
+                (13, "'Dose' is called inside a loop, so every iteration shares one instance".to_string()),
+                (18, "'CTU' is called inside a loop, so every iteration shares one instance".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Rule 51: Detect stuck and out-of-range sensor signals.
+//! A broken sensor or cut wire usually shows up as a value that freezes
+//! or pins at the end of its range, and the arithmetic built on it carries
+//! on as if nothing happened. A sensor value (a raw analog input as Rule 41
+//! sees it, a name ending in `_SENSOR`, or one of the policy's
+//! `sensor_patterns`) used in `+ - * /` is reported unless the program
+//! somewhere compares it, against a limit or against its previous value
+//! to spot a freeze. A fault flag set from such a comparison, in its value
+//! or an enclosing guard, covers the other sensor values of its stem:
+//! `Temp_Fault := Temp_AI > 27648;` also covers `Temp_Sensor`. The fault
+//! suffixes are `_FAULT`, `_ERR`, `_ERROR` and `_FAILED`, plus the
+//! policy's `fault_flag_suffixes`.
+
+use std::collections::HashSet;
+
+use regex::Regex;
+
+use crate::ast::{reachable, BinOp, Expression, Program, Statement};
+use super::policy::{self, Policy};
+use super::rule41::RawAnalog;
+use super::visit::{walk_expression, walk_program, Visitor};
+use super::{utils, RuleResult, Severity, Violation};
+use super::registry::RULE51;
+
+/// Suffixes of fault flags, upper-cased.
+const FAULT_SUFFIXES: &[&str] = &["_FAULT", "_ERR", "_ERROR", "_FAILED"];
+
+/// Name suffixes that mark a sensor value, upper-cased. Stripping one
+/// gives the stem fault flags are named after.
+const SENSOR_SUFFIXES: &[&str] = &["_SENSOR", "_AI", "_RAW"];
+
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    let sensors = match Sensors::from_policy(policy) {
+        Ok(sensors) => sensors,
+        Err(e) => return RuleResult::error(RULE51.no, RULE51.name, e),
+    };
+    let fault_suffixes: Vec<String> = FAULT_SUFFIXES
+        .iter()
+        .map(|s| s.to_string())
+        .chain(policy.fault_flag_suffixes.iter().flatten().map(|s| s.trim().to_ascii_uppercase()))
+        .collect();
+
+    let mut compared = Comparisons { sensors: &sensors, found: HashSet::new() };
+    walk_program(&mut compared, program);
+    let mut flags = FaultFlags { sensors: &sensors, fault_suffixes: &fault_suffixes, stems: HashSet::new() };
+    for f in program.code() {
+        flags.statements(&f.statements, &mut Vec::new());
+    }
+    let mut reads = ArithmeticReads { sensors: &sensors, found: Vec::new() };
+    walk_program(&mut reads, program);
+
+    let mut violations = Vec::new();
+    let mut reported = HashSet::new();
+    for (name, line) in reads.found {
+        let key = utils::ident_key(&name);
+        let checked = compared.found.contains(&key) || flags.stems.contains(&key) || flags.stems.contains(&stem(&key));
+        if checked || !reported.insert((key, line)) {
+            continue;
+        }
+        violations.push(Violation::new(
+            &RULE51,
+            line,
+            format!("Sensor value '{}' is used in arithmetic, but the program never checks it for a stuck or out-of-range signal", name),
+            "Compare it against its valid range (or its previous value to spot a freeze) and set a fault flag, e.g. Temp_Fault := Temp_Sensor < 0 OR Temp_Sensor > 27648;",
+            Severity::Warning,
+        ));
+    }
+
+    if violations.is_empty() {
+        RuleResult::ok(RULE51.no, RULE51.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+/// Which names are sensor values.
+struct Sensors {
+    raw: RawAnalog,
+    extra: Vec<Regex>,
+}
+
+impl Sensors {
+    fn from_policy(policy: &Policy) -> Result<Self, String> {
+        Ok(Sensors {
+            raw: RawAnalog::from_policy(policy)?,
+            extra: policy::name_regexes(&policy.sensor_patterns, "sensor_patterns")?,
+        })
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.raw.matches(name) || utils::ident_key(name).ends_with("_SENSOR") || self.extra.iter().any(|re| re.is_match(name))
+    }
+}
+
+/// `TEMP_SENSOR` -> `TEMP`; keys without a sensor suffix are returned as is.
+fn stem(key: &str) -> String {
+    SENSOR_SUFFIXES.iter().find_map(|s| key.strip_suffix(s)).unwrap_or(key).to_string()
+}
+
+/// Keys of the sensors compared against something other than a boolean
+/// literal.
+struct Comparisons<'s> {
+    sensors: &'s Sensors,
+    found: HashSet<String>,
+}
+
+impl Visitor for Comparisons<'_> {
+    fn visit_expression(&mut self, e: &Expression) {
+        if let Expression::BinaryOp { op, left, right, .. } = e {
+            if op.is_comparison() {
+                for (side, other) in [(left, right), (right, left)] {
+                    let name = side.name().filter(|n| self.sensors.matches(n));
+                    if let Some(name) = name.filter(|_| !matches!(**other, Expression::BoolLiteral(..))) {
+                        self.found.insert(utils::ident_key(&name));
+                    }
+                }
+            }
+        }
+        walk_expression(self, e);
+    }
+}
+
+/// Stems of the fault flags the program sets from a comparison of a
+/// sensor with the same stem, as keys.
+struct FaultFlags<'s> {
+    sensors: &'s Sensors,
+    fault_suffixes: &'s [String],
+    stems: HashSet<String>,
+}
+
+impl FaultFlags<'_> {
+    fn statements<'a>(&mut self, stmts: &'a [Statement], guards: &mut Vec<&'a Expression>) {
+        let outer_guards = guards.len();
+        for st in reachable(stmts) {
+            match st {
+                Statement::Assign { target, value, .. } => {
+                    if let Some(flag) = target.name().and_then(|n| self.flag_stem(&n)) {
+                        let mut compared = Comparisons { sensors: self.sensors, found: HashSet::new() };
+                        compared.visit_expression(value);
+                        guards.iter().for_each(|g| compared.visit_expression(g));
+                        if compared.found.iter().any(|s| *s == flag || stem(s) == flag) {
+                            self.stems.insert(flag);
+                        }
+                    }
+                }
+                Statement::IfStmt { condition, then_branch, else_branch, .. } => {
+                    guards.push(condition);
+                    self.statements(then_branch, guards);
+                    self.statements(else_branch, guards);
+                    guards.pop();
+                }
+                Statement::CaseStmt { cases, else_branch, .. } => {
+                    for (_, body) in cases {
+                        self.statements(body, guards);
+                    }
+                    self.statements(else_branch, guards);
+                }
+                Statement::WhileStmt { condition, body, .. } => {
+                    guards.push(condition);
+                    self.statements(body, guards);
+                    guards.pop();
+                }
+                Statement::ForStmt { body, .. } => self.statements(body, guards),
+                _ => {}
+            }
+            if let Some(c) = st.early_exit_condition() {
+                guards.push(c);
+            }
+        }
+        guards.truncate(outer_guards);
+    }
+
+    /// `TEMP_FAULT` -> `TEMP`, for a name with a fault suffix.
+    fn flag_stem(&self, name: &str) -> Option<String> {
+        let key = utils::ident_key(name);
+        self.fault_suffixes.iter().find_map(|s| key.strip_suffix(s.as_str())).map(String::from)
+    }
+}
+
+/// Sensor values used directly as an operand of `+ - * /`, with the line
+/// of the operation.
+struct ArithmeticReads<'s> {
+    sensors: &'s Sensors,
+    found: Vec<(String, usize)>,
+}
+
+impl Visitor for ArithmeticReads<'_> {
+    fn visit_expression(&mut self, e: &Expression) {
+        if let Expression::BinaryOp { op: BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div, left, right, line, .. } = e {
+            let operands = [left, right].into_iter().filter_map(|side| side.name()).filter(|n| self.sensors.matches(n));
+            self.found.extend(operands.map(|n| (n, *line)));
+        }
+        walk_expression(self, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+    use crate::rules::policy::parse_policy_from_text;
+
+    fn lines(src: &str, policy: &Policy) -> Vec<usize> {
+        check(&parse_scl_from_str(src).unwrap(), policy).violations.iter().map(|v| v.line).collect()
+    }
+
+    #[test]
+    fn unchecked_sensor_arithmetic_is_flagged() {
+        let src = "FUNCTION FC_Analog\nBEGIN\n    Temp := Temp_Sensor / 10 + Temp_Sensor;\n    Level := (%IW64 - 5530) / 221;\n    \
+                   Copy := Flow_AI;\n    Speed := Speed_Setpoint * 2;\nEND_FUNCTION\n";
+        assert_eq!(lines(src, &Policy::default()), vec![3, 4]);
+    }
+
+    #[test]
+    fn range_checks_and_fault_flags_anywhere_in_the_program_pass() {
+        let src = "FUNCTION FC_Diag\nBEGIN\n    IF %IW64 > 27648 THEN\n        Alarm := TRUE;\n    END_IF;\n    \
+                   Stuck := Flow_AI = Flow_AI_Prev;\n    Temp_Fault := Temp_AI > 27648;\n    \
+                   IF Press_Sensor_Raw < 0 THEN\n        Press_Sensor_Err := TRUE;\n    END_IF;\nEND_FUNCTION\n\
+                   FUNCTION FC_Scale\nBEGIN\n    Level := (%IW64 - 5530) / 221;\n    Flow := Flow_AI * 2;\n    \
+                   Temp := Temp_Sensor / 10;\n    Press := Press_Sensor / 10;\nEND_FUNCTION\n";
+        assert!(lines(src, &Policy::default()).is_empty());
+    }
+
+    #[test]
+    fn fault_flags_not_set_from_the_sensor_do_not_count() {
+        let src = "FUNCTION FC_Diag\nBEGIN\n    Temp_Fault := Temp_Ok = FALSE;\n    Press_Sensor_Err := Wire_Break;\n    \
+                   IF Level_AI > 100 THEN\n        Temp_Fault := TRUE;\n    END_IF;\n    \
+                   Temp := Temp_Sensor / 10;\n    Press := Press_Sensor / 10;\nEND_FUNCTION\n";
+        assert_eq!(lines(src, &Policy::default()), vec![8, 9]);
+    }
+
+    #[test]
+    fn policy_adds_sensor_names_and_fault_suffixes() {
+        let src = "FUNCTION FC_A\nBEGIN\n    Temp := tt101 / 10;\n    Press := Press_Sensor / 10;\n    \
+                   Press_Sensor_Alm := Press_Sensor_AI > 27648;\nEND_FUNCTION\n";
+        let policy = parse_policy_from_text(r#"{ "sensor_patterns": ["TT\\d+"], "fault_flag_suffixes": ["_ALM"] }"#).unwrap();
+        assert_eq!(lines(src, &policy), vec![3]);
+        assert_eq!(lines(src, &Policy::default()), vec![4]);
+    }
+}
@@ -1,45 +1,106 @@
 use crate::ast::{Program, Statement, Expression};
 use crate::rules::policy::Policy;
-use crate::rules::{RuleResult, Violation};
+use crate::rules::utils::{self, IdentifierClass};
+use crate::rules::visit::{walk_program, walk_statement, Visitor};
+use crate::rules::{RuleResult, Severity, Violation};
+use crate::rules::registry::RULE6;
 
 /// Rule 6: Validate timers and counters
-pub fn check(program: &Program, _policy: &Policy) -> RuleResult {
-    let mut violations = Vec::new();
-
-    fn expr_has_hmi(expr: &Expression) -> bool {
-        match expr {
-            Expression::Identifier(name) => name.to_uppercase().contains("HMI"),
-            Expression::FuncCall { args, .. } => args.iter().any(expr_has_hmi),
-            Expression::BinaryOp { left, right, .. } => expr_has_hmi(left) || expr_has_hmi(right),
-            Expression::Index { base, index, .. } => expr_has_hmi(base) || expr_has_hmi(index),
-            _ => false,
-        }
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    let mut timers = TimerPresets { policy, violations: Vec::new() };
+    walk_program(&mut timers, program);
+
+    if timers.violations.is_empty() {
+        RuleResult::ok(RULE6.no, RULE6.name)
+    } else {
+        RuleResult::violations(timers.violations)
     }
+}
+
+struct TimerPresets<'p> {
+    policy: &'p Policy,
+    violations: Vec<Violation>,
+}
 
-    for func in &program.functions {
-        for stmt in &func.statements {
-            if let Statement::Call { name, args, line } = stmt {
-                let lname = name.to_lowercase();
-                if lname.contains("tp") || lname.contains("ton") || lname.contains("tof") {
-                    for (_, arg_expr) in args {
-                        if expr_has_hmi(arg_expr) {
-                            violations.push(Violation {
-                                rule_no: 6,
-                                rule_name: "Validate timers and counters",
-                                line: *line,
-                                reason: "Timer preset sourced from HMI without plausibility check".into(),
-                                suggestion: "Precede timer assignment with a numeric range check".into(),
-                            });
-                        }
+impl Visitor for TimerPresets<'_> {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        if let Statement::Call { name, args, line } = stmt {
+            let lname = name.to_lowercase();
+            let is_timer = lname.contains("tp") || lname.contains("ton") || lname.contains("tof");
+            // CTU, CTD and CTUD
+            let is_counter = lname.contains("ctu") || lname.contains("ctd");
+            if is_timer || is_counter {
+                let kind = if is_timer { "Timer" } else { "Counter" };
+                for arg_expr in presets(args) {
+                    if expr_has_hmi(arg_expr, self.policy) {
+                        self.violations.push(Violation::new(
+                            &RULE6,
+                            *line,
+                            format!("{} preset sourced from HMI without plausibility check", kind),
+                            format!("Precede {} assignment with a numeric range check", kind.to_lowercase()),
+                            Severity::Warning,
+                        ));
                     }
                 }
             }
         }
+        walk_statement(self, stmt);
     }
+}
 
-    if violations.is_empty() {
-        RuleResult::ok(6, "Validate timers and counters")
-    } else {
-        RuleResult::violations(violations)
+/// The preset arguments of a timer/counter call: `PT` (timers) or `PV`
+/// (counters) when the call names its parameters, otherwise every argument.
+fn presets(args: &[(String, Expression)]) -> Vec<&Expression> {
+    if args.iter().all(|(param, _)| param.is_empty()) {
+        return args.iter().map(|(_, a)| a).collect();
+    }
+    args.iter()
+        .filter(|(param, _)| param.eq_ignore_ascii_case("PT") || param.eq_ignore_ascii_case("PV"))
+        .map(|(_, a)| a)
+        .collect()
+}
+
+fn expr_has_hmi(expr: &Expression, policy: &Policy) -> bool {
+    match expr {
+        Expression::Identifier(_) | Expression::Member { .. } => {
+            expr.name().is_some_and(|name| utils::is_identifier_class(&name, IdentifierClass::HmiInput, policy))
+        }
+        Expression::FuncCall { args, .. } => args.iter().any(|a| expr_has_hmi(a, policy)),
+        Expression::BinaryOp { left, right, .. } => expr_has_hmi(left, policy) || expr_has_hmi(right, policy),
+        Expression::Index { base, index, .. } => expr_has_hmi(base, policy) || expr_has_hmi(index, policy),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn finds_timer_inside_if() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    IF Run THEN\n        TON(HMI_Preset);\n    END_IF;\nEND_ORGANIZATION_BLOCK\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let result = check(&program, &Policy::default());
+        assert_eq!(result.violations.len(), 1);
+        assert_eq!(result.violations[0].line, 4);
+    }
+
+    #[test]
+    fn finds_counter_preset_from_hmi() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    CTU(CU := Pulse, PV := HMI_Count);\nEND_ORGANIZATION_BLOCK\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let result = check(&program, &Policy::default());
+        assert_eq!(result.violations.len(), 1);
+        assert!(result.violations[0].reason.starts_with("Counter preset"));
+    }
+
+    #[test]
+    fn only_the_named_preset_is_inspected() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    Delay.TON(IN := HMI_Start, PT := Preset);\n    \
+                   Delay.TON(IN := Start, PT := HMI_Preset);\nEND_ORGANIZATION_BLOCK\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &Policy::default());
+        let lines: Vec<usize> = result.violations.iter().map(|v| v.line).collect();
+        assert_eq!(lines, vec![4]);
     }
 }
@@ -1,22 +1,23 @@
 // rule7.rs
 use crate::ast::{Program, Statement, Expression};
 use crate::rules::policy::Policy;
-use crate::rules::{RuleResult, Violation};
+use crate::rules::{utils, RuleResult, Severity, Violation};
+use crate::rules::registry::RULE7;
 
 /// Rule 7: Validate paired inputs/outputs
 pub fn check(program: &Program, policy: &Policy) -> RuleResult {
+    if policy.pairs.as_ref().is_none_or(|p| p.is_empty()) {
+        return RuleResult::not_applicable(RULE7.no, RULE7.name);
+    }
     let mut violations = Vec::new();
 
-    for func in &program.functions {
-        let mut true_assignments: Vec<(&str, usize)> = Vec::new();
+    for func in program.code() {
+        // Keyed by `utils::ident_key`, so the policy's spelling need not match the source's.
+        let mut true_assignments: Vec<(String, usize)> = Vec::new();
         for stmt in &func.statements {
-            if let Statement::Assign { target, value, line } = stmt {
-                if let Expression::BoolLiteral(val, _) = value {
-                    if *val {
-                        if let Expression::Identifier(name) = target {
-                            true_assignments.push((name.as_str(), *line));
-                        }
-                    }
+            if let Statement::Assign { target, value: Expression::BoolLiteral(true, _), line } = stmt {
+                if let Some(name) = target.name() {
+                    true_assignments.push((utils::ident_key(&name), *line));
                 }
             }
         }
@@ -24,32 +25,48 @@ pub fn check(program: &Program, policy: &Policy) -> RuleResult {
         for pair in policy.pairs.iter().flatten() {
             let a = &pair[0];
             let b = &pair[1];
+            let (a_key, b_key) = (utils::ident_key(a), utils::ident_key(b));
 
             let mut a_found_line: Option<usize> = None;
             let mut b_found_line: Option<usize> = None;
 
             // Use two separate checks instead of if/else-if to find both items
             for (name, line) in &true_assignments {
-                if *name == a.as_str() {
+                if *name == a_key {
                     a_found_line = Some(*line);
                 }
-                if *name == b.as_str() {
+                if *name == b_key {
                     b_found_line = Some(*line);
                 }
             }
 
             if let (Some(line1), Some(_)) = (a_found_line, b_found_line) {
-                violations.push(Violation {
-                    rule_no: 7,
-                    rule_name: "Validate paired inputs/outputs",
-                    // Report the line of the first variable in the pair
-                    line: line1,
-                    reason: format!("Paired outputs {} and {} both set to TRUE", a, b),
-                    suggestion: "Add mutual exclusion logic (e.g., IF/ELSE) to prevent both outputs being active".into(),
-                });
+                // Report the line of the first variable in the pair
+                violations.push(Violation::new(
+                    &RULE7,
+                    line1,
+                    format!("Paired outputs {} and {} both set to TRUE", a, b),
+                    "Add mutual exclusion logic (e.g., IF/ELSE) to prevent both outputs being active",
+                    Severity::Error,
+                ));
             }
         }
     }
     
     RuleResult::violations(violations)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn pair_names_match_regardless_of_case() {
+        let policy = Policy { pairs: Some(vec![["Motor_Fwd".into(), "MOTOR_REV".into()]]), ..Default::default() };
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n    MOTOR_FWD := TRUE;\n    motor_rev := TRUE;\nEND_ORGANIZATION_BLOCK\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &policy);
+        let found: Vec<(usize, &str)> = result.violations.iter().map(|v| (v.line, v.reason.as_str())).collect();
+        assert_eq!(found, vec![(3, "Paired outputs Motor_Fwd and MOTOR_REV both set to TRUE")]);
+    }
+}
@@ -1,40 +1,147 @@
-use crate::ast::{Program, Statement, Expression};
-use crate::rules::policy::Policy;
-use crate::rules::{RuleResult, Violation};
+use crate::ast::{reachable, Program, Statement, Expression};
+use crate::rules::utils::{self, Guard, IdentifierClass};
+use crate::rules::{AnalysisContext, Policy, RuleResult, Severity, Violation};
+use crate::rules::registry::RULE8;
 
 /// Rule 8: Validate HMI input variables
-pub fn check(program: &Program, _policy: &Policy) -> RuleResult {
+///
+/// An assignment reading an HMI variable is accepted when every HMI variable
+/// it reads is range-checked by an enclosing IF, or by the IF immediately
+/// before it (the clamp idiom `IF HMI_X > 100 THEN HMI_X := 100; END_IF;`),
+/// or when a `@PlausibilityCheck` annotation sits just above it (see
+/// [`utils::AnnotationSearch`]).
+pub fn check(program: &Program, cx: &AnalysisContext) -> RuleResult {
+    let search = utils::AnnotationSearch::from_context(cx);
     let mut violations = Vec::new();
 
-    fn expr_has_hmi(expr: &Expression) -> bool {
-        match expr {
-            Expression::Identifier(name) => name.to_uppercase().contains("HMI"),
-            Expression::FuncCall { args, .. } => args.iter().any(expr_has_hmi),
-            Expression::BinaryOp { left, right, .. } => expr_has_hmi(left) || expr_has_hmi(right),
-            Expression::Index { base, index, .. } => expr_has_hmi(base) || expr_has_hmi(index),
-            _ => false,
-        }
+    for func in program.code() {
+        walk_statements(&func.statements, cx.policy, &mut vec![], &mut violations, &search);
     }
 
-    for func in &program.functions {
-        for stmt in &func.statements {
-            if let Statement::Assign { target: _, value, line } = stmt {
-                if expr_has_hmi(value) {
-                    violations.push(Violation {
-                        rule_no: 8,
-                        rule_name: "Validate HMI input variables".into(),
-                        line: *line,
-                        reason: "HMI input variable used without plausibility checks".into(),
-                        suggestion: "Add plausibility checks (range limits or comments) before assignment".into(),
-                    });
+    if violations.is_empty() {
+        RuleResult::ok(RULE8.no, RULE8.name)
+    } else {
+        RuleResult::violations(violations)
+    }
+}
+
+fn walk_statements<'a>(
+    stmts: &'a [Statement],
+    policy: &Policy,
+    guards: &mut Vec<Guard<'a>>,
+    out: &mut Vec<Violation>,
+    search: &utils::AnnotationSearch<'_>,
+) {
+    // Condition of the IF directly preceding the current statement, if any.
+    let mut preceding: Option<&'a Expression> = None;
+
+    let outer_guards = guards.len();
+    let stmts = reachable(stmts);
+    for (i, stmt) in stmts.iter().enumerate() {
+        match stmt {
+            Statement::Assign { value, line, .. } => {
+                let mut hmi_vars = Vec::new();
+                collect_hmi_vars(value, policy, &mut hmi_vars);
+                let validated = hmi_vars.iter().all(|v| {
+                    guards.iter().map(|g| g.cond).chain(preceding).any(|g| utils::is_var_constrained(v, g))
+                });
+
+                if !validated && !search.is_annotated(&stmts[..i], *line) {
+                    out.push(Violation::new(
+                        &RULE8,
+                        *line,
+                        "HMI input variable used without plausibility checks",
+                        "Add plausibility checks (range limits or comments) before assignment",
+                        Severity::Warning,
+                    ));
                 }
             }
+            Statement::IfStmt { condition, then_branch, else_branch, .. } => {
+                guards.push(Guard { cond: condition, holds: true });
+                walk_statements(then_branch, policy, guards, out, search);
+                guards.pop();
+                walk_statements(else_branch, policy, guards, out, search);
+            }
+            Statement::CaseStmt { cases, else_branch, .. } => {
+                for (_, body) in cases { walk_statements(body, policy, guards, out, search); }
+                walk_statements(else_branch, policy, guards, out, search);
+            }
+            Statement::WhileStmt { condition, body, .. } => {
+                guards.push(Guard { cond: condition, holds: true });
+                walk_statements(body, policy, guards, out, search);
+                guards.pop();
+            }
+            Statement::ForStmt { body, .. } => walk_statements(body, policy, guards, out, search),
+            _ => {}
+        }
+
+        preceding = match stmt {
+            Statement::IfStmt { condition, .. } => Some(condition),
+            _ => None,
+        };
+
+        // Code after `IF cond THEN RETURN; END_IF;` only runs when `cond` is false.
+        if let Some(c) = stmt.early_exit_condition() {
+            guards.push(Guard { cond: c, holds: false });
         }
     }
+    guards.truncate(outer_guards);
+}
 
-    if violations.is_empty() {
-        RuleResult::ok(8, "Validate HMI input variables")
-    } else {
-        RuleResult::violations(violations)
+/// Names of the HMI variables (see [`IdentifierClass::HmiInput`]) read by `expr`.
+pub(crate) fn collect_hmi_vars(expr: &Expression, policy: &Policy, out: &mut Vec<String>) {
+    match expr {
+        Expression::Identifier(_) | Expression::Member { .. } => {
+            out.extend(expr.name().filter(|name| utils::is_identifier_class(name, IdentifierClass::HmiInput, policy)));
+        }
+        Expression::FuncCall { args, .. } => args.iter().for_each(|a| collect_hmi_vars(a, policy, out)),
+        Expression::BinaryOp { left, right, .. } => {
+            collect_hmi_vars(left, policy, out);
+            collect_hmi_vars(right, policy, out);
+        }
+        Expression::Index { base, index, .. } => {
+            collect_hmi_vars(base, policy, out);
+            collect_hmi_vars(index, policy, out);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{parse_block, Policy};
+
+    fn violation_lines(body: &str) -> Vec<usize> {
+        let program = parse_block("ORGANIZATION_BLOCK OB1", body);
+        check(&program, &AnalysisContext::new(&Policy::default())).violations.iter().map(|v| v.line).collect()
+    }
+
+    #[test]
+    fn guarded_and_clamped_uses_pass() {
+        assert!(violation_lines("IF HMI_Speed >= 0 AND HMI_Speed <= 1500 THEN\n    Speed := HMI_Speed;\nEND_IF;").is_empty());
+        assert!(violation_lines("IF HMI_Speed > 1500 THEN\n    HMI_Speed := 1500;\nEND_IF;\nSpeed := HMI_Speed;").is_empty());
+    }
+
+    #[test]
+    fn negative_setpoint_limits_count_as_range_checks() {
+        assert!(violation_lines("IF HMI_Temp >= -40 AND HMI_Temp <= 120 THEN\n    Temp := HMI_Temp;\nEND_IF;").is_empty());
+    }
+
+    #[test]
+    fn unguarded_use_is_flagged() {
+        assert_eq!(violation_lines("Speed := HMI_Speed;\nIF Run THEN\n    Pos := HMI_Pos;\nEND_IF;"), vec![3, 5]);
+    }
+
+    #[test]
+    fn member_access_counts_as_one_variable() {
+        assert_eq!(violation_lines("Drive.Speed := HMI.Speed;"), vec![3]);
+        assert!(violation_lines("IF HMI.Speed < 1500 THEN\n    Drive.Speed := HMI.Speed;\nEND_IF;").is_empty());
+    }
+
+    #[test]
+    fn range_check_before_an_early_exit_covers_the_rest() {
+        assert!(violation_lines("IF HMI_Speed > 1500 THEN\n    RETURN;\nEND_IF;\nIF Run THEN\n    Speed := HMI_Speed;\nEND_IF;").is_empty());
+        assert_eq!(violation_lines("IF NOT Run THEN\n    RETURN;\nEND_IF;\nSpeed := HMI_Speed;"), vec![6]);
     }
-}
\ No newline at end of file
+}
@@ -1,108 +1,434 @@
 //! Rule 9: Validate indirections (array indexing and unsafe calls).
 //! Flag any MyArray[IndexVar] that is not guarded by range checks.
-//! Also flag calls to known unsafe functions like strcpy.
+//! Also flag calls to known unsafe functions like strcpy. String copies
+//! (`S_MOVE`, `STRCPY`, `CONCAT`) into a declared `STRING[n]` are only
+//! flagged when the source may not fit or comes from the HMI; without a
+//! declaration for the destination `S_MOVE`/`STRCPY` are always flagged.
+//! A guarded index needs both a lower and an upper bound, as in rule 37.
+//! When the array is declared with literal bounds, the guard must also
+//! keep the index inside them. A FOR loop's own bounds do not count as a
+//! guard for its counter (see `walk_statements`).
 
-use crate::ast::{BinOp, Expression, Program, Statement};
-use super::{RuleResult, Violation, utils::expr_text};
+use crate::analysis::{normalize_comparison, ConstValue};
+use crate::ast::{reachable, BinOp, DataType, Expression, Function, Program, Statement, UnaryOp};
+use super::utils::{expr_text, is_identifier_class, is_var_constrained, Guard, IdentifierClass};
+use super::{Policy, RuleResult, Severity, Violation};
+use super::registry::RULE9;
 
-pub fn check(program: &Program) -> RuleResult {
+pub fn check(program: &Program, policy: &Policy) -> RuleResult {
     let mut violations = vec![];
 
-    for f in &program.functions {
-        walk_statements(&f.statements, &mut vec![], &mut violations);
+    for f in program.code() {
+        walk_statements(Scope { f, policy }, &f.statements, &mut vec![], &mut violations);
     }
 
     RuleResult::violations(violations)
 }
 
-fn walk_statements<'a>(stmts: &'a [Statement], guards: &mut Vec<&'a Expression>, out: &mut Vec<Violation>) {
-    const UNSAFE_FUNCTIONS: &[&str] = &["STRCPY", "MEMCPY", "S_MOVE"];
+/// The block being checked and the policy in force.
+#[derive(Clone, Copy)]
+struct Scope<'a> {
+    f: &'a Function,
+    policy: &'a Policy,
+}
+
+const UNSAFE_FUNCTIONS: &[&str] = &["STRCPY", "MEMCPY", "S_MOVE"];
 
-    for st in stmts {
+fn walk_statements<'a>(sc: Scope, stmts: &'a [Statement], guards: &mut Vec<Guard<'a>>, out: &mut Vec<Violation>) {
+    let outer_guards = guards.len();
+    for st in reachable(stmts) {
         match st {
             Statement::IfStmt { condition, then_branch, else_branch, .. } => {
                 // The condition guards the `then` branch.
-                guards.push(condition);
-                walk_statements(then_branch, guards, out);
+                guards.push(Guard { cond: condition, holds: true });
+                walk_statements(sc, then_branch, guards, out);
                 guards.pop();
 
                 // The `else` branch is walked with the original guards, but not the new one.
-                walk_statements(else_branch, guards, out);
+                walk_statements(sc, else_branch, guards, out);
+            }
+            Statement::WhileStmt { condition, body, .. } => {
+                // The loop condition holds throughout the body.
+                guards.push(Guard { cond: condition, holds: true });
+                walk_statements(sc, body, guards, out);
+                guards.pop();
+            }
+            // The header is not a guard on the counter: the bounds may be
+            // variables, `lo > hi` or a negative BY, and the body may write
+            // the counter (rule 40). An index by the counter needs its own
+            // range check.
+            Statement::ForStmt { start, end, body, line, .. } => {
+                find_violations_in_expr(sc, start, *line, guards, out);
+                find_violations_in_expr(sc, end, *line, guards, out);
+                walk_statements(sc, body, guards, out);
             }
             Statement::Assign { target, value, line, .. } => {
-                find_violations_in_expr(target, *line, guards, out);
-                find_violations_in_expr(value, *line, guards, out);
+                find_violations_in_expr(sc, target, *line, guards, out);
+                match value {
+                    // `Dst := CONCAT(a, b)` copies into the assignment target.
+                    Expression::FuncCall { name, args, .. } => {
+                        let args: Vec<(&str, &Expression)> = args.iter().map(|a| ("", a)).collect();
+                        scan_call(sc, name, &args, Some(target), *line, guards, out);
+                    }
+                    _ => find_violations_in_expr(sc, value, *line, guards, out),
+                }
             }
             Statement::Expr { expr, line, .. } => {
-                find_violations_in_expr(expr, *line, guards, out);
+                find_violations_in_expr(sc, expr, *line, guards, out);
             }
-            Statement::Call { name, line, .. } => {
-                let name_up = name.to_ascii_uppercase();
-                if UNSAFE_FUNCTIONS.iter().any(|&f| name_up.contains(f)) {
-                    out.push(Violation {
-                        rule_no: 9,
-                        rule_name: "Validate indirections",
-                        line: *line,
-                        reason: format!("Call to potentially unsafe function '{}'", name),
-                        suggestion: "Ensure destination buffer size is checked before calling memory copy functions.".into(),
-                    });
-                }
+            Statement::Call { name, args, line } => {
+                let args: Vec<(&str, &Expression)> = args.iter().map(|(p, a)| (p.as_str(), a)).collect();
+                scan_call(sc, name, &args, None, *line, guards, out);
             }
             _ => {}
         }
+
+        // Code after `IF cond THEN RETURN; END_IF;` only runs when `cond` is false.
+        if let Some(c) = st.early_exit_condition() {
+            guards.push(Guard { cond: c, holds: false });
+        }
     }
+    guards.truncate(outer_guards);
 }
 
-fn find_violations_in_expr(e: &Expression, line: usize, guards: &[&Expression], out: &mut Vec<Violation>) {
+fn find_violations_in_expr(sc: Scope, e: &Expression, line: usize, guards: &[Guard], out: &mut Vec<Violation>) {
     match e {
         Expression::Index { base, index, .. } => {
-            if let Expression::Identifier(idx_name) = &**index {
-                let is_guarded = guards.iter().any(|g| is_var_constrained(idx_name, g));
-                if !is_guarded {
+            if let Some(idx_name) = index.name() {
+                let is_guarded = guards.iter().any(|g| is_var_constrained(&idx_name, g.cond));
+                let declared = base.name().and_then(|b| declared_bounds(sc.f, &b).map(|bounds| (b, bounds)));
+                let known = known_range(&idx_name, guards);
+                let reason = match (declared, known) {
+                    _ if !is_guarded => Some(format!("Array indexed by variable '{}' without bounds check", idx_name)),
+                    (Some((array, (lo, hi))), _) => bounds_problem(&idx_name, known, &array, lo, hi),
+                    (None, (_, None)) => Some(format!("Index '{}' has no upper bound check", idx_name)),
+                    (None, (None, _)) => Some(format!("Index '{}' has no lower bound check", idx_name)),
+                    (None, _) => None,
+                };
+                if let Some(reason) = reason {
                     out.push(Violation {
-                        rule_no: 9,
-                        rule_name: "Validate indirections",
-                        line,
-                        reason: format!("Array indexed by variable '{}' without bounds check", idx_name),
-                        suggestion: "Validate index against array bounds before access (e.g., IF index < LIMIT THEN...).".into(),
+                        snippet: Some(expr_text(e)),
+                        ..Violation::new(
+                            &RULE9,
+                            line,
+                            reason,
+                            "Validate index against array bounds before access (e.g., IF index < LIMIT THEN...).",
+                            Severity::Warning,
+                        )
                     });
                 }
             }
             // Recurse
-            find_violations_in_expr(base, line, guards, out);
-            find_violations_in_expr(index, line, guards, out);
+            find_violations_in_expr(sc, base, line, guards, out);
+            find_violations_in_expr(sc, index, line, guards, out);
         }
         Expression::BinaryOp { left, right, .. } => {
-            find_violations_in_expr(left, line, guards, out);
-            find_violations_in_expr(right, line, guards, out);
+            find_violations_in_expr(sc, left, line, guards, out);
+            find_violations_in_expr(sc, right, line, guards, out);
         }
-        Expression::FuncCall { args, .. } => {
-            for arg in args {
-                find_violations_in_expr(arg, line, guards, out);
-            }
+        Expression::FuncCall { name, args, .. } => {
+            let args: Vec<(&str, &Expression)> = args.iter().map(|a| ("", a)).collect();
+            scan_call(sc, name, &args, None, line, guards, out);
         }
         _ => {}
     }
 }
 
-/// Checks if a guard expression `g` places a constraint on a variable `var_name`.
-fn is_var_constrained(var_name: &str, g: &Expression) -> bool {
-    match g {
-        Expression::BinaryOp { op, left, right, .. } => {
-            // Look for `var_name <op> literal` or `literal <op> var_name`
-            let is_comparison = matches!(op, BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge | BinOp::Eq | BinOp::Neq);
-            if is_comparison {
-                let left_text = expr_text(left).trim().to_string();
-                let right_text = expr_text(right).trim().to_string();
-                if (left_text.eq_ignore_ascii_case(var_name.trim()) && matches!(**right, Expression::NumberLiteral(..))) ||
-                   (right_text.eq_ignore_ascii_case(var_name.trim()) && matches!(**left, Expression::NumberLiteral(..))) {
-                    return true;
-                }
+/// `[lo..hi]` of a one-dimensional array declared with literal bounds.
+fn declared_bounds(f: &Function, array: &str) -> Option<(i64, i64)> {
+    match &f.declaration(array)?.data_type {
+        DataType::Array { dims, .. } if dims.len() == 1 => dims[0],
+        _ => None,
+    }
+}
+
+/// `(min, max)` of `idx` as far as the guards prove it.
+fn known_range(idx: &str, guards: &[Guard]) -> (Option<i64>, Option<i64>) {
+    let mut known = (None, None);
+    for g in guards {
+        index_range(g.cond, g.holds, idx, &mut known);
+    }
+    known
+}
+
+/// Compare what the guards prove about `idx` with the declared bounds of
+/// `array`. Catches off-by-one guards such as `IF i <= 10` for `[0..9]`.
+fn bounds_problem(idx: &str, known: (Option<i64>, Option<i64>), array: &str, lo: i64, hi: i64) -> Option<String> {
+    match known {
+        (_, None) => Some(format!("Index '{}' has no upper bound check for '{}' [{}..{}]", idx, array, lo, hi)),
+        (_, Some(max)) if max > hi => {
+            Some(format!("Guard allows index '{}' up to {}, but '{}' is declared [{}..{}]", idx, max, array, lo, hi))
+        }
+        (None, _) => Some(format!("Index '{}' has no lower bound check for '{}' [{}..{}]", idx, array, lo, hi)),
+        (Some(min), _) if min < lo => {
+            Some(format!("Guard allows index '{}' down to {}, but '{}' is declared [{}..{}]", idx, min, array, lo, hi))
+        }
+        _ => None,
+    }
+}
+
+/// Narrow `(min, max)` of `var` by the comparisons that `e` being `holds`
+/// implies: both sides of a true `AND`, both sides of a false `OR`.
+fn index_range(e: &Expression, holds: bool, var: &str, range: &mut (Option<i64>, Option<i64>)) {
+    match e {
+        Expression::BinaryOp { op: BinOp::And, left, right, .. } if holds => {
+            index_range(left, holds, var, range);
+            index_range(right, holds, var, range);
+        }
+        Expression::BinaryOp { op: BinOp::Or, left, right, .. } if !holds => {
+            index_range(left, holds, var, range);
+            index_range(right, holds, var, range);
+        }
+        Expression::UnaryOp { op: UnaryOp::Not, expr, .. } => index_range(expr, !holds, var, range),
+        _ => {
+            let Some((name, op, ConstValue::Int(c))) = normalize_comparison(e) else { return };
+            if !name.eq_ignore_ascii_case(var) {
+                return;
+            }
+            let op = if holds { op } else { op.negated() };
+            let (min, max) = match op {
+                BinOp::Lt => (None, Some(c.saturating_sub(1))),
+                BinOp::Le => (None, Some(c)),
+                BinOp::Gt => (Some(c.saturating_add(1)), None),
+                BinOp::Ge => (Some(c), None),
+                BinOp::Eq => (Some(c), Some(c)),
+                _ => (None, None),
+            };
+            if let Some(min) = min {
+                range.0 = Some(range.0.map_or(min, |m: i64| m.max(min)));
             }
-            // Recurse for compound conditions like `X > 0 AND X < 10`
-            is_var_constrained(var_name, left) || is_var_constrained(var_name, right)
+            if let Some(max) = max {
+                range.1 = Some(range.1.map_or(max, |m: i64| m.min(max)));
+            }
+        }
+    }
+}
+
+/// Check the call itself, then its arguments. `assigned_to` is the target
+/// when the call is the right-hand side of an assignment.
+fn scan_call(
+    sc: Scope,
+    name: &str,
+    args: &[(&str, &Expression)],
+    assigned_to: Option<&Expression>,
+    line: usize,
+    guards: &[Guard],
+    out: &mut Vec<Violation>,
+) {
+    match string_copy(name, args, assigned_to) {
+        Some(copy) => check_string_copy(sc, name, &copy, line, out),
+        None => check_unsafe_call(name, line, out),
+    }
+    for (_, arg) in args {
+        find_violations_in_expr(sc, arg, line, guards, out);
+    }
+}
+
+fn check_unsafe_call(name: &str, line: usize, out: &mut Vec<Violation>) {
+    let name_up = name.to_ascii_uppercase();
+    if UNSAFE_FUNCTIONS.iter().any(|&f| name_up.contains(f)) {
+        out.push(unsafe_call(name, line));
+    }
+}
+
+fn unsafe_call(name: &str, line: usize) -> Violation {
+    Violation::new(
+        &RULE9,
+        line,
+        format!("Call to potentially unsafe function '{}'", name),
+        "Ensure destination buffer size is checked before calling memory copy functions.",
+        Severity::Warning,
+    )
+}
+
+/// Where a string copy writes and what it reads.
+struct StringCopy<'a> {
+    dest: Option<&'a Expression>,
+    sources: Vec<&'a Expression>,
+    /// `S_MOVE`/`STRCPY` are flagged even when the destination is unknown;
+    /// `CONCAT` only when it provably may not fit.
+    blanket: bool,
+}
+
+/// `STRCPY(Dst, Src)`, `S_MOVE(IN := Src, OUT := Dst)` (or `Dst := S_MOVE(Src)`)
+/// and `Dst := CONCAT(a, b, ...)`. `None` for any other call.
+fn string_copy<'a>(
+    name: &str,
+    args: &[(&'a str, &'a Expression)],
+    assigned_to: Option<&'a Expression>,
+) -> Option<StringCopy<'a>> {
+    let up = name.to_ascii_uppercase();
+    let named = |param: &str| args.iter().find(|(p, _)| p.eq_ignore_ascii_case(param)).map(|(_, a)| *a);
+    let positional = |i: usize| args.get(i).filter(|(p, _)| p.is_empty()).map(|(_, a)| *a);
+    if up.contains("STRCPY") {
+        Some(StringCopy { dest: positional(0), sources: positional(1).into_iter().collect(), blanket: true })
+    } else if up.contains("S_MOVE") {
+        let source = named("IN").or_else(|| positional(0));
+        let dest = named("OUT").or_else(|| positional(1)).or(assigned_to);
+        Some(StringCopy { dest, sources: source.into_iter().collect(), blanket: true })
+    } else if up.ends_with("CONCAT") {
+        Some(StringCopy { dest: assigned_to, sources: args.iter().map(|(_, a)| *a).collect(), blanket: false })
+    } else {
+        None
+    }
+}
+
+fn check_string_copy(sc: Scope, name: &str, copy: &StringCopy, line: usize, out: &mut Vec<Violation>) {
+    let dest = copy.dest.and_then(|d| d.name());
+    let capacity = dest.as_deref().and_then(|d| sc.f.declaration(d)).and_then(|d| d.data_type.string_capacity());
+    let (Some(dest), Some(capacity)) = (dest, capacity) else {
+        if copy.blanket {
+            out.push(unsafe_call(name, line));
+        }
+        return;
+    };
+
+    let reason = if copy.sources.iter().any(|s| mentions_hmi(s, sc.policy)) {
+        format!("{} copies HMI-supplied text into '{}' (STRING[{}]) without a length check", name, dest, capacity)
+    } else {
+        match copy.sources.iter().map(|s| max_length(sc.f, s)).sum::<Option<usize>>() {
+            Some(len) if len <= capacity => return,
+            Some(len) => format!("{} copies up to {} characters into '{}', which holds {}", name, len, dest, capacity),
+            None => format!("{} copies text of unknown length into '{}', which holds {}", name, dest, capacity),
         }
-        Expression::UnaryOp { expr, .. } => is_var_constrained(var_name, expr),
+    };
+    out.push(Violation::new(
+        &RULE9,
+        line,
+        reason,
+        format!("Check LEN() of the source against {} before copying, or enlarge '{}'.", capacity, dest),
+        Severity::Warning,
+    ));
+}
+
+/// Longest string `e` can evaluate to, from literals and declared lengths.
+fn max_length(f: &Function, e: &Expression) -> Option<usize> {
+    match e {
+        Expression::StringLiteral(s, _) => Some(s.chars().count()),
+        Expression::FuncCall { name, args, .. } if name.to_ascii_uppercase().ends_with("CONCAT") => {
+            args.iter().map(|a| max_length(f, a)).sum()
+        }
+        _ => e.name().and_then(|n| f.declaration(&n)).and_then(|d| d.data_type.string_capacity()),
+    }
+}
+
+fn mentions_hmi(e: &Expression, policy: &Policy) -> bool {
+    match e {
+        Expression::Identifier(_) | Expression::Member { .. } => {
+            e.name().is_some_and(|n| is_identifier_class(&n, IdentifierClass::HmiInput, policy))
+        }
+        Expression::FuncCall { args, .. } => args.iter().any(|a| mentions_hmi(a, policy)),
+        Expression::BinaryOp { left, right, .. } => mentions_hmi(left, policy) || mentions_hmi(right, policy),
+        Expression::Index { base, .. } => mentions_hmi(base, policy),
         _ => false,
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::il::parse_il_from_str;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[test]
+    fn unsafe_calls_and_indexing_inside_call_arguments_are_found() {
+        let src = "FUNCTION FC_Copy\nBEGIN\n    Log(MEMCPY(Dst, Src), Buf[k]);\n    ok := \"Lib\".STRCPY(a, b);\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &Policy::default());
+        let found: Vec<(usize, &str)> = result.violations.iter().map(|v| (v.line, v.reason.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (3, "Call to potentially unsafe function 'MEMCPY'"),
+                (3, "Array indexed by variable 'k' without bounds check"),
+                (4, "Call to potentially unsafe function 'Lib.STRCPY'"),
+            ]
+        );
+    }
+
+    #[test]
+    fn early_return_guards_the_rest_of_the_block() {
+        let src = "FUNCTION FC_Read\nBEGIN\n    IF Idx < 0 OR Idx > 9 THEN\n        RETURN;\n    END_IF;\n    Value := Table[Idx];\nEND_FUNCTION\n";
+        assert!(check(&parse_scl_from_str(src).unwrap(), &Policy::default()).violations.is_empty());
+    }
+
+    #[test]
+    fn statements_after_return_are_unreachable() {
+        let src = "FUNCTION FC_Read\nBEGIN\n    RETURN;\n    Value := Table[Idx];\nEND_FUNCTION\n";
+        assert!(check(&parse_scl_from_str(src).unwrap(), &Policy::default()).violations.is_empty());
+
+        let il = "LD Done\nRETC\nRET\nST Unreached\n";
+        let program = parse_il_from_str(il).unwrap();
+        let stmts = &program.functions[0].statements;
+        assert_eq!(stmts.len(), 2);
+        assert!(stmts[0].early_exit_condition().is_some());
+        assert!(matches!(stmts[1], Statement::Return { line: 3 }));
+    }
+
+    #[test]
+    fn exit_guarded_by_if_inside_while_bounds_the_index() {
+        let src = "FUNCTION FC_Fill\nBEGIN\n    WHILE Run DO\n        IF Idx < 0 OR Idx > 9 THEN\n            EXIT;\n        END_IF;\n        \
+                   Table[Idx] := 0;\n        Other[Pos] := 0;\n    END_WHILE;\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &Policy::default());
+        let lines: Vec<usize> = result.violations.iter().map(|v| v.line).collect();
+        assert_eq!(lines, vec![8]);
+    }
+
+    #[test]
+    fn for_bounds_do_not_guard_the_counter() {
+        let src = "FUNCTION FC_Fill\nBEGIN\n    FOR i := 0 TO 9 DO\n        Table[i] := 0;\n    END_FOR;\n    \
+                   FOR j := Count TO 0 BY -1 DO\n        Table[j] := 0;\n    END_FOR;\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &Policy::default());
+        let lines: Vec<usize> = result.violations.iter().map(|v| v.line).collect();
+        assert_eq!(lines, vec![4, 7]);
+    }
+
+    #[test]
+    fn one_sided_guard_is_not_a_range_check() {
+        let src = "FUNCTION FC_Read\nBEGIN\n    IF Idx < 10 THEN\n        v := Table[Idx];\n    END_IF;\n    \
+                   IF Pos >= 0 AND Pos <= 9 THEN\n        v := Table[Pos];\n    END_IF;\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &Policy::default());
+        let found: Vec<(usize, &str)> = result.violations.iter().map(|v| (v.line, v.reason.as_str())).collect();
+        assert_eq!(found, vec![(4, "Index 'Idx' has no lower bound check")]);
+    }
+
+    #[test]
+    fn guards_are_checked_against_declared_array_bounds() {
+        let src = "FUNCTION FC_Read\nVAR_TEMP\n    Table : ARRAY[0..9] OF INT;\nEND_VAR\nBEGIN\n    \
+                   IF i <= 10 THEN\n        v := Table[i];\n    END_IF;\n    \
+                   IF i >= 0 AND i < 10 THEN\n        v := Table[i];\n    END_IF;\n    \
+                   IF i > 0 THEN\n        v := Table[i];\n    END_IF;\n    \
+                   IF -1 <= k AND 9 >= k THEN\n        v := Table[k];\n    END_IF;\n    \
+                   v := Table[j];\n    v := Undeclared[i];\n\
+                   IF NOT (n >= 0 AND n <= 9) THEN\n        RETURN;\n    END_IF;\n    v := Table[n];\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &Policy::default());
+        let found: Vec<(usize, &str)> = result.violations.iter().map(|v| (v.line, v.reason.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (7, "Guard allows index 'i' up to 10, but 'Table' is declared [0..9]"),
+                (13, "Index 'i' has no upper bound check for 'Table' [0..9]"),
+                (16, "Guard allows index 'k' down to -1, but 'Table' is declared [0..9]"),
+                (18, "Array indexed by variable 'j' without bounds check"),
+                (19, "Array indexed by variable 'i' without bounds check"),
+            ]
+        );
+    }
+
+    #[test]
+    fn string_copies_are_checked_against_declared_lengths() {
+        let src = "FUNCTION FC_Fmt\nVAR_TEMP\n    Name : STRING[16];\n    Short : STRING[8];\n    Long : STRING;\nEND_VAR\nBEGIN\n    \
+                   S_MOVE(IN := Short, OUT := Name);\n    Name := CONCAT(Short, '-A');\n    S_MOVE(IN := Long, OUT := Name);\n    \
+                   STRCPY(Name, HMI_Text);\n    Name := CONCAT(Short, Other);\n    STRCPY(Undeclared, Short);\nEND_FUNCTION\n";
+        let result = check(&parse_scl_from_str(src).unwrap(), &Policy::default());
+        let found: Vec<(usize, &str)> = result.violations.iter().map(|v| (v.line, v.reason.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (10, "S_MOVE copies up to 254 characters into 'Name', which holds 16"),
+                (11, "STRCPY copies HMI-supplied text into 'Name' (STRING[16]) without a length check"),
+                (12, "CONCAT copies text of unknown length into 'Name', which holds 16"),
+                (13, "Call to potentially unsafe function 'STRCPY'"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Inline suppression pragmas.
+//!
+//! A comment containing `plc-checker:ignore rule9` (several rules may be
+//! listed, separated by spaces or commas) or `plc-checker:ignore all` hides
+//! matching violations reported on the same line or the line below it. This
+//! works on raw source text so it applies to every frontend, whether or not
+//! the parser keeps comments in the AST.
+
+use std::collections::HashMap;
+
+use super::RuleResult;
+
+const PRAGMA: &str = "plc-checker:ignore";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Scope {
+    All,
+    Rules(Vec<u8>),
+}
+
+impl Scope {
+    fn covers(&self, rule_no: u8) -> bool {
+        match self {
+            Scope::All => true,
+            Scope::Rules(rules) => rules.contains(&rule_no),
+        }
+    }
+}
+
+/// Suppression pragmas found in a source file, keyed by 1-based line.
+#[derive(Debug, Clone, Default)]
+pub struct Suppressions {
+    by_line: HashMap<usize, Scope>,
+}
+
+impl Suppressions {
+    pub fn from_source(source: &str) -> Self {
+        let mut by_line = HashMap::new();
+        for (i, line) in source.lines().enumerate() {
+            let lower = line.to_ascii_lowercase();
+            if let Some(pos) = lower.find(PRAGMA) {
+                if let Some(scope) = parse_scope(&lower[pos + PRAGMA.len()..]) {
+                    by_line.insert(i + 1, scope);
+                }
+            }
+        }
+        Self { by_line }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_line.is_empty()
+    }
+
+    /// True if a pragma on `line` or the line above covers `rule_no`.
+    pub fn is_suppressed(&self, rule_no: u8, line: usize) -> bool {
+        if line == 0 {
+            return false;
+        }
+        [line, line - 1]
+            .iter()
+            .filter_map(|l| self.by_line.get(l))
+            .any(|scope| scope.covers(rule_no))
+    }
+
+    /// Drop suppressed violations; a rule left with none is reported as OK.
+    pub fn apply(&self, mut results: Vec<RuleResult>) -> Vec<RuleResult> {
+        results.iter_mut().for_each(|r| self.apply_to(r));
+        results
+    }
+
+    /// [`apply`](Self::apply) for a single rule's result.
+    pub fn apply_to(&self, result: &mut RuleResult) {
+        if self.is_empty() {
+            return;
+        }
+        result.violations.retain(|v| !self.is_suppressed(v.rule_no, v.line));
+        result.update_status();
+    }
+}
+
+/// Parse the text after the pragma keyword: `all` or a list of `ruleN`.
+fn parse_scope(rest: &str) -> Option<Scope> {
+    let mut rules = Vec::new();
+    for tok in rest.split(|c: char| c.is_whitespace() || c == ',') {
+        if tok.is_empty() {
+            continue;
+        }
+        if tok == "all" {
+            return Some(Scope::All);
+        }
+        match tok.strip_prefix("rule").and_then(|n| n.parse::<u8>().ok()) {
+            Some(n) => rules.push(n),
+            // Stop at the first word that isn't a rule (e.g. `*)` or prose).
+            None => break,
+        }
+    }
+    if rules.is_empty() {
+        None
+    } else {
+        Some(Scope::Rules(rules))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{Expression, Function, FunctionKind, Program, Statement};
+    use crate::rules::{run_rules, Policy};
+
+    fn indexed_assign(target: &str, index: &str, line: usize) -> Statement {
+        Statement::Assign {
+            target: Expression::Identifier(target.into()),
+            value: Expression::Index {
+                base: Box::new(Expression::Identifier("Arr".into())),
+                index: Box::new(Expression::Identifier(index.into())),
+                line,
+                end_line: line,
+            },
+            line,
+        }
+    }
+
+    #[test]
+    fn suppressed_rule9_finding_disappears() {
+        let src = "FUNCTION FC_Test\nBEGIN\n// plc-checker:ignore rule9\nx := Arr[i];\ny := Arr[j];\nEND_FUNCTION";
+        let program = Program {
+            functions: vec![Function {
+                name: "FC_Test".into(),
+                kind: FunctionKind::FC,
+                statements: vec![indexed_assign("x", "i", 4), indexed_assign("y", "j", 5)],
+                line: 1,
+                regions: vec![],
+                declarations: vec![],
+            }],
+        };
+        let results = run_rules(&program, &Policy::default());
+        let rule9 = |rs: &[RuleResult]| rs.iter().find(|r| r.rule_no == 9).unwrap().violations.len();
+        assert_eq!(rule9(&results), 2);
+
+        let filtered = Suppressions::from_source(src).apply(results);
+        let remaining = filtered.iter().find(|r| r.rule_no == 9).unwrap();
+        assert_eq!(remaining.violations.len(), 1);
+        assert_eq!(remaining.violations[0].line, 5);
+    }
+
+    #[test]
+    fn parses_all_and_rule_lists() {
+        let s = Suppressions::from_source("(* plc-checker:ignore rule4, rule11 *)\nx := 1; // plc-checker:ignore all");
+        assert!(s.is_suppressed(4, 1));
+        assert!(s.is_suppressed(11, 2));
+        assert!(!s.is_suppressed(9, 1));
+        assert!(s.is_suppressed(9, 3));
+    }
+}
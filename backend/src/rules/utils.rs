@@ -2,64 +2,365 @@
 
 //! Shared utility functions for security rules.
 
-use crate::ast::{Expression, BinOp, UnaryOp};
-use std::sync::Mutex;
-use once_cell::sync::Lazy;
-
-// --- Globals for Context ---
-static SOURCE_LINES: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
-
-/// Caches the source code lines for context-aware checks.
-pub fn set_source_lines(source: &str) {
-    let mut handle = SOURCE_LINES.lock().unwrap();
-    *handle = source.lines().map(String::from).collect();
-}
+use crate::analysis::{normalize_comparison, ConstValue};
+use crate::ast::{BinOp, Expression, Statement, UnaryOp};
+use super::{AnalysisContext, Policy};
 
 /// Converts an AST Expression back into a string representation.
+/// Parentheses are emitted where precedence requires them, so `(a + b) * c`
+/// and `a + b * c` render differently. String literals are quoted as in
+/// the source, so the text parses back to the same expression.
 pub fn expr_text(e: &Expression) -> String {
     match e {
         Expression::NumberLiteral(n, _) => n.to_string(),
         Expression::BoolLiteral(b, _) => b.to_string().to_ascii_uppercase(),
         Expression::Identifier(s) => s.clone(),
-        Expression::StringLiteral(s, _) => s.clone(), 
+        Expression::StringLiteral(s, _) => format!("'{}'", s),
         Expression::UnaryOp { op, expr, .. } => {
-            let op_str = match op { UnaryOp::Not => "NOT " };
-            format!("{}{}", op_str, expr_text(expr))
+            let op_str = match op { UnaryOp::Not => "NOT ", UnaryOp::Neg => "-" };
+            match **expr {
+                Expression::BinaryOp { .. } => format!("{}({})", op_str, expr_text(expr)),
+                _ => format!("{}{}", op_str, expr_text(expr)),
+            }
         }
         Expression::BinaryOp { op, left, right, .. } => {
-            let op_str = match op {
-                BinOp::Add => "+", BinOp::Sub => "-", BinOp::Mul => "*", BinOp::Div => "/",
-                BinOp::Eq => "=", BinOp::Neq => "<>", BinOp::Lt => "<", BinOp::Le => "<=",
-                BinOp::Gt => ">", BinOp::Ge => ">=", BinOp::And => "AND", BinOp::Or => "OR",
-                BinOp::Assign => ":=", // <-- CORRECTED: Added the missing match arm
+            // Operators are left-associative: a right operand of equal
+            // precedence needs parentheses, a left one does not.
+            let operand = |e: &Expression, needs_parens: bool| match e {
+                Expression::BinaryOp { .. } if needs_parens => format!("({})", expr_text(e)),
+                _ => expr_text(e),
             };
-            format!("{} {} {}", expr_text(left), op_str, expr_text(right))
+            let prec = |e: &Expression| match e {
+                Expression::BinaryOp { op, .. } => op.precedence(),
+                _ => u8::MAX,
+            };
+            let l = operand(left, prec(left) < op.precedence());
+            let r = operand(right, prec(right) <= op.precedence());
+            format!("{} {} {}", l, op.symbol(), r)
         }
         Expression::Index { base, index, .. } => {
             format!("{}[{}]", expr_text(base), expr_text(index))
         }
+        Expression::Member { base, field, .. } => format!("{}.{}", expr_text(base), field),
         Expression::FuncCall { name, args, .. } => {
-            let arg_str = args.iter().map(|a| expr_text(a)).collect::<Vec<_>>().join(", ");
+            let arg_str = args.iter().map(expr_text).collect::<Vec<_>>().join(", ");
             format!("{}({})", name, arg_str)
         }
     }
 }
 
-/// Checks if a variable name suggests it's from a sensitive source like HMI or a recipe.
-pub fn is_sensitive_variable(name: &str) -> bool {
-    let up = name.to_ascii_uppercase();
-    up.contains("HMI") || up.contains("RECIPE") || up.contains("PARAM") || up.contains("SETPOINT")
+/// Comparison key for an identifier. SCL names are case-insensitive and
+/// `#x` is the same local as `x`, so rules that match names against each
+/// other or against the policy compare these keys and keep the original
+/// spelling for messages.
+pub fn ident_key(name: &str) -> String {
+    name.trim_start_matches('#').to_ascii_uppercase()
+}
+
+/// What a name stands for, as far as the rules that care about actuators
+/// and operator input are concerned. A name can belong to several
+/// classes: `HMI_Speed` is both an HMI input and an actuator name, and
+/// `Motor_Setpoint` is a critical output, an actuator and a sensitive
+/// source. See [`classify_identifier`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentifierClass {
+    /// A process-image output (`%Q...`) or a name containing one of
+    /// [`CRITICAL_OUTPUT_KEYWORDS`] or ending in `_OUT`, such as
+    /// `Motor_Run`, `Safe_Valve` or `Valve_Out`. Rules 15, 33, 34 and 42.
+    CriticalOutput,
+    /// An actuator or setpoint named by one of [`ACTUATOR_KEYWORDS`], such
+    /// as `Speed_Cmd` or `Axis_Position`. Rules 11, 12, 31 and 37.
+    Actuator,
+    /// A value written from an operator panel: a name containing `HMI`.
+    /// Rules 6, 8, 9 and 37.
+    HmiInput,
+    /// A value an operator or recipe supplies: a name containing one of
+    /// [`SENSITIVE_SOURCE_KEYWORDS`], such as `HMI_Speed` or
+    /// `Recipe.Temp`. Rules 5, 11, 12 and 45 and the call graph.
+    SensitiveSource,
+    /// Any other absolute address: `%I0.1`, `%MW10`, `%DB1.DBX0.0`.
+    MemoryAddress,
+    /// None of the above.
+    Ordinary,
+}
+
+/// Name fragments of critical outputs, upper-cased. Names ending in
+/// `_OUT` and `%Q` addresses count as well.
+pub const CRITICAL_OUTPUT_KEYWORDS: &[&str] = &["CRITICAL", "SAFE", "MOTOR", "OUTPUT"];
+
+/// Name fragments of actuators and setpoints, upper-cased.
+pub const ACTUATOR_KEYWORDS: &[&str] = &["MOTOR", "SPEED", "SETPOINT", "POSITION", "CMD", "COMMAND", "OUTPUT"];
+
+/// Name fragments of operator-supplied sources, upper-cased.
+pub const SENSITIVE_SOURCE_KEYWORDS: &[&str] = &["HMI", "RECIPE", "PARAM", "SETPOINT"];
+
+/// Whether `name` belongs to `class`. The one place every rule that
+/// treats actuators or operator input specially asks, so they agree on
+/// what counts. The policy's `critical_output_keywords` extend both
+/// output classes ([`IdentifierClass::CriticalOutput`] and
+/// [`IdentifierClass::Actuator`]); its `sensitive_source_keywords` extend
+/// both input classes ([`IdentifierClass::HmiInput`] and
+/// [`IdentifierClass::SensitiveSource`]).
+pub fn is_identifier_class(name: &str, class: IdentifierClass, policy: &Policy) -> bool {
+    let key = ident_key(name);
+    let has_keyword = |defaults: &[&str], extra: &Option<Vec<String>>| {
+        defaults.iter().any(|k| key.contains(k)) || extra.iter().flatten().any(|k| key.contains(&k.trim().to_ascii_uppercase()))
+    };
+    match class {
+        IdentifierClass::CriticalOutput => {
+            is_process_output(&key) || key.ends_with("_OUT") || has_keyword(CRITICAL_OUTPUT_KEYWORDS, &policy.critical_output_keywords)
+        }
+        IdentifierClass::Actuator => has_keyword(ACTUATOR_KEYWORDS, &policy.critical_output_keywords),
+        IdentifierClass::HmiInput => has_keyword(&["HMI"], &policy.sensitive_source_keywords),
+        IdentifierClass::SensitiveSource => has_keyword(SENSITIVE_SOURCE_KEYWORDS, &policy.sensitive_source_keywords),
+        IdentifierClass::MemoryAddress => key.starts_with('%') && !is_process_output(&key),
+        IdentifierClass::Ordinary => CLASSES.iter().all(|c| !is_identifier_class(name, *c, policy)),
+    }
+}
+
+const CLASSES: &[IdentifierClass] = &[
+    IdentifierClass::CriticalOutput,
+    IdentifierClass::Actuator,
+    IdentifierClass::HmiInput,
+    IdentifierClass::SensitiveSource,
+    IdentifierClass::MemoryAddress,
+];
+
+/// Every class `name` belongs to, or just [`IdentifierClass::Ordinary`].
+pub fn classify_identifier(name: &str, policy: &Policy) -> Vec<IdentifierClass> {
+    let classes: Vec<_> = CLASSES.iter().copied().filter(|c| is_identifier_class(name, *c, policy)).collect();
+    if classes.is_empty() {
+        vec![IdentifierClass::Ordinary]
+    } else {
+        classes
+    }
+}
+
+/// `FirstScan`, `FirstScan = TRUE` or `FirstScan AND ...`; a negated flag
+/// (`NOT FirstScan`) guards the opposite case and does not count.
+pub fn is_first_scan_guard(e: &Expression) -> bool {
+    match e {
+        Expression::Identifier(_) | Expression::Member { .. } => {
+            let up = e.name().unwrap_or_default().to_ascii_uppercase().replace('_', "");
+            up.contains("FIRSTSCAN") || up.contains("FIRSTCYCLE") || up.contains("OB1SCAN1")
+        }
+        Expression::BinaryOp { op: BinOp::And, left, right, .. } => {
+            is_first_scan_guard(left) || is_first_scan_guard(right)
+        }
+        Expression::BinaryOp { op: BinOp::Eq, left, right, .. } => {
+            is_first_scan_guard(left) && matches!(**right, Expression::BoolLiteral(true, _))
+        }
+        _ => false,
+    }
+}
+
+/// A condition known to hold (`holds`) or known to be false where a
+/// statement runs, e.g. after `IF cond THEN RETURN; END_IF;`.
+#[derive(Clone, Copy)]
+pub(crate) struct Guard<'a> {
+    pub cond: &'a Expression,
+    pub holds: bool,
+}
+
+/// Checks if a guard expression `g` places a range constraint on `var_name`,
+/// i.e. contains a comparison of the variable with a numeric constant
+/// (in either order, see [`normalize_comparison`]) anywhere in a compound
+/// condition. Names are compared case-insensitively.
+pub fn is_var_constrained(var_name: &str, g: &Expression) -> bool {
+    match g {
+        Expression::BinaryOp { left, right, .. } => {
+            if let Some((var, _, value)) = normalize_comparison(g) {
+                if var.eq_ignore_ascii_case(var_name.trim()) && !matches!(value, ConstValue::Bool(_)) {
+                    return true;
+                }
+            }
+            // Recurse for compound conditions like `X > 0 AND X < 10`
+            is_var_constrained(var_name, left) || is_var_constrained(var_name, right)
+        }
+        Expression::UnaryOp { expr, .. } => is_var_constrained(var_name, expr),
+        _ => false,
+    }
+}
+
+/// Annotation tokens that mark a statement as plausibility-checked.
+pub const DEFAULT_ANNOTATIONS: &[&str] = &["@PLAUSIBILITYCHECK", "@VALIDATION"];
+
+/// Default number of lines above a statement searched for an annotation.
+pub const DEFAULT_ANNOTATION_DEPTH: usize = 3;
+
+/// Where and what to look for when deciding whether a statement carries a
+/// `@PlausibilityCheck`-style annotation. Built from the analysis context
+/// so the depth and accepted tokens are configurable and the source lines
+/// are those of the file being analysed.
+#[derive(Debug, Clone)]
+pub struct AnnotationSearch<'a> {
+    pub depth: usize,
+    /// Upper-cased tokens.
+    tokens: Vec<String>,
+    source_lines: &'a [&'a str],
+    file_starts: &'a [usize],
+}
+
+impl Default for AnnotationSearch<'_> {
+    fn default() -> Self {
+        Self {
+            depth: DEFAULT_ANNOTATION_DEPTH,
+            tokens: DEFAULT_ANNOTATIONS.iter().map(|t| t.to_string()).collect(),
+            source_lines: &[],
+            file_starts: &[],
+        }
+    }
+}
+
+impl<'a> AnnotationSearch<'a> {
+    pub fn from_context(cx: &'a AnalysisContext) -> Self {
+        let mut search = Self { source_lines: &cx.source_lines, file_starts: &cx.file_starts, ..Self::default() };
+        if let Some(p) = &cx.policy.plausibility {
+            if let Some(depth) = p.search_depth {
+                search.depth = depth;
+            }
+            search.tokens.extend(p.annotations.iter().flatten().map(|t| t.to_ascii_uppercase()));
+        }
+        search
+    }
+
+    /// True if an annotation sits within `depth` lines above `line`.
+    /// `before` holds the statements preceding it in the same block; any
+    /// `Statement::Comment` nodes directly before it are consulted first,
+    /// then the source lines for frontends that drop comments.
+    pub fn is_annotated(&self, before: &[Statement], line: usize) -> bool {
+        self.is_annotated_within(before, line, self.depth)
+    }
+
+    pub fn is_annotated_within(&self, before: &[Statement], line: usize, depth: usize) -> bool {
+        if line == 0 { return false; }
+        let in_reach = |l: usize| l < line && line - l <= depth;
+
+        let adjacent_comment = before
+            .iter()
+            .rev()
+            .map_while(|st| match st {
+                Statement::Comment { text, line } => Some((text, *line)),
+                _ => None,
+            })
+            .any(|(text, l)| in_reach(l) && self.matches(text));
+        if adjacent_comment { return true; }
+
+        // Lines above the first line of the statement's file belong to
+        // the file before it in a merged project.
+        let start = line.saturating_sub(depth).max(super::file_start(self.file_starts, line)).saturating_sub(1);
+        let end = line.saturating_sub(1);
+        self.source_lines.get(start..end).unwrap_or(&[]).iter().any(|l| self.matches(l))
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        let up = text.to_ascii_uppercase();
+        self.tokens.iter().any(|t| up.contains(t.as_str()))
+    }
+}
+
+/// Very simple parser for addresses like %MW100, %DB1.DBX10.0, %M100 etc.
+/// Returns the area prefix (with `%`) and the first numeric offset.
+pub fn parse_mem_address(s: &str) -> Option<(String, i64)> {
+    if !s.starts_with('%') || s.len() < 3 {  // Add length check
+        return None;
+    }
+    let mut area = String::new();
+    let mut num = String::new();
+    let mut seen_digit = false;
+    
+    for ch in s.chars().skip(1) {
+        if ch.is_ascii_alphabetic() && !seen_digit {
+            area.push(ch);
+        } else if ch.is_ascii_digit() {
+            num.push(ch);
+            seen_digit = true;
+        } else if ch == '.' && seen_digit {
+            break; // Stop at first dot after seeing digits
+        }
+    }
+    
+    if !area.is_empty() && !num.is_empty() {
+        if let Ok(n) = num.parse::<i64>() {
+            return Some((format!("%{}", area), n));
+        }
+    }
+    None
+}
+
+/// True for process-image output addresses: `%Q0.0`, `%QW4`, `%QX1.2`, ...
+pub fn is_process_output(name: &str) -> bool {
+    parse_mem_address(&name.to_ascii_uppercase()).is_some_and(|(area, _)| area.starts_with("%Q"))
 }
 
-/// Looks for a `@PlausibilityCheck` annotation in comments above a given line.
-pub fn has_plausibility_annotation_above(line: usize, search_depth: usize) -> bool {
-    if line == 0 { return false; }
-    let lines = SOURCE_LINES.lock().unwrap();
-    let start = line.saturating_sub(search_depth).saturating_sub(1);
-    let end = line.saturating_sub(1);
-
-    lines.get(start..end).unwrap_or(&[]).iter().any(|l| {
-        let up = l.to_ascii_uppercase();
-        up.contains("@PLAUSIBILITYCHECK") || up.contains("@VALIDATION")
-    })
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    fn value_of(expr: &str) -> Expression {
+        let src = format!("FUNCTION FC_A\nBEGIN\n    x := {};\nEND_FUNCTION\n", expr);
+        match parse_scl_from_str(&src).unwrap().functions.remove(0).statements.remove(0) {
+            Statement::Assign { value, .. } => value,
+            other => panic!("expected an assignment, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn indexed_targets_can_be_constrained() {
+        let guard = value_of("Data[i] > 0 AND 100 >= Data[i]");
+        assert!(is_var_constrained("Data[i]", &guard));
+        assert!(is_var_constrained("DATA[i]", &guard));
+        assert!(!is_var_constrained("Data[j]", &guard));
+        assert!(!is_var_constrained("i", &guard));
+    }
+
+    #[test]
+    fn expr_text_keeps_grouping() {
+        assert_eq!(expr_text(&value_of("(a+b)*c")), "(a + b) * c");
+        assert_eq!(expr_text(&value_of("a+b*c")), "a + b * c");
+        assert_eq!(expr_text(&value_of("a - (b - c)")), "a - (b - c)");
+        assert_eq!(expr_text(&value_of("(a - b) - c")), "a - b - c");
+        assert_eq!(expr_text(&value_of("NOT (Run OR Jog)")), "NOT (Run OR Jog)");
+        assert_eq!(expr_text(&value_of("Mode = 'AUTO'")), "Mode = 'AUTO'");
+        for src in ["(a + b) * c", "a - (b - c)", "x / (y * 2) <> 0 AND NOT (p OR q)", "Mode = 'AUTO' OR Mode = 'MAN'"] {
+            let reparsed = value_of(&expr_text(&value_of(src)));
+            assert_eq!(expr_text(&reparsed), expr_text(&value_of(src)));
+            assert_eq!(format!("{:?}", reparsed), format!("{:?}", value_of(src)));
+        }
+    }
+
+    #[test]
+    fn identifiers_are_classified_once_for_every_rule() {
+        use IdentifierClass::*;
+        let default = Policy::default();
+        let cases: &[(&str, &[IdentifierClass])] = &[
+            ("%Q0.0", &[CriticalOutput]),
+            ("%QW4", &[CriticalOutput]),
+            ("Motor_Run", &[CriticalOutput, Actuator]),
+            ("Safe_Valve", &[CriticalOutput]),
+            ("Valve_Out", &[CriticalOutput]),
+            ("Speed_Cmd", &[Actuator]),
+            ("Speed_Setpoint", &[Actuator, SensitiveSource]),
+            ("HMI_Speed", &[Actuator, HmiInput, SensitiveSource]),
+            ("#Recipe.Temp", &[SensitiveSource]),
+            ("%I0.1", &[MemoryAddress]),
+            ("%MW10", &[MemoryAddress]),
+            ("Count", &[Ordinary]),
+            ("Valve_1", &[Ordinary]),
+            ("Scada_Flow", &[Ordinary]),
+        ];
+        for (name, classes) in cases {
+            assert_eq!(classify_identifier(name, &default), classes.to_vec(), "{}", name);
+        }
+
+        let policy = Policy {
+            critical_output_keywords: Some(vec!["valve".into()]),
+            sensitive_source_keywords: Some(vec!["SCADA".into()]),
+            ..Default::default()
+        };
+        assert_eq!(classify_identifier("Valve_1", &policy), vec![CriticalOutput, Actuator]);
+        assert_eq!(classify_identifier("Scada_Flow", &policy), vec![HmiInput, SensitiveSource]);
+        assert_eq!(classify_identifier("Count", &policy), vec![Ordinary]);
+    }
+}
@@ -0,0 +1,166 @@
+//! Generic traversal of the AST.
+//!
+//! Rules implement [`Visitor`] and override only the hooks they care
+//! about; the `walk_*` functions do the recursion, so every rule sees
+//! every branch (THEN, ELSIF/ELSE, CASE arms and CASE ELSE) and loop
+//! body. Statements after an unconditional `RETURN`/`EXIT`/`CONTINUE` are
+//! unreachable and skipped. An override
+//! that still wants to descend calls the matching `walk_*` function.
+//! Everything is generic over `V`, so traversal is monomorphized per rule.
+
+use crate::ast::{reachable, Expression, Function, Program, Statement};
+
+pub trait Visitor {
+    fn visit_function(&mut self, f: &Function) {
+        walk_function(self, f);
+    }
+
+    fn visit_statement(&mut self, st: &Statement) {
+        walk_statement(self, st);
+    }
+
+    fn visit_expression(&mut self, e: &Expression) {
+        walk_expression(self, e);
+    }
+}
+
+/// Visits the executable blocks; data blocks are skipped (see [`Program::code`]).
+pub fn walk_program<V: Visitor + ?Sized>(v: &mut V, program: &Program) {
+    for f in program.code() {
+        v.visit_function(f);
+    }
+}
+
+pub fn walk_function<V: Visitor + ?Sized>(v: &mut V, f: &Function) {
+    walk_statements(v, &f.statements);
+}
+
+pub fn walk_statements<V: Visitor + ?Sized>(v: &mut V, stmts: &[Statement]) {
+    for st in reachable(stmts) {
+        v.visit_statement(st);
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(v: &mut V, st: &Statement) {
+    match st {
+        Statement::Assign { target, value, .. } => {
+            v.visit_expression(target);
+            v.visit_expression(value);
+        }
+        Statement::Call { args, .. } => {
+            for (_, a) in args {
+                v.visit_expression(a);
+            }
+        }
+        Statement::IfStmt { condition, then_branch, else_branch, .. } => {
+            v.visit_expression(condition);
+            walk_statements(v, then_branch);
+            walk_statements(v, else_branch);
+        }
+        Statement::CaseStmt { expression, cases, else_branch, .. } => {
+            v.visit_expression(expression);
+            for (labels, body) in cases {
+                for l in labels {
+                    v.visit_expression(l);
+                }
+                walk_statements(v, body);
+            }
+            walk_statements(v, else_branch);
+        }
+        Statement::WhileStmt { condition, body, .. } => {
+            v.visit_expression(condition);
+            walk_statements(v, body);
+        }
+        Statement::ForStmt { start, end, step, body, .. } => {
+            v.visit_expression(start);
+            v.visit_expression(end);
+            if let Some(step) = step {
+                v.visit_expression(step);
+            }
+            walk_statements(v, body);
+        }
+        Statement::Expr { expr, .. } => v.visit_expression(expr),
+        Statement::Comment { .. }
+        | Statement::Return { .. }
+        | Statement::Exit { .. }
+        | Statement::Continue { .. }
+        | Statement::Goto { .. }
+        | Statement::Label { .. }
+        | Statement::ElseMarker { .. } => {}
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(v: &mut V, e: &Expression) {
+    match e {
+        Expression::UnaryOp { expr, .. } => v.visit_expression(expr),
+        Expression::BinaryOp { left, right, .. } => {
+            v.visit_expression(left);
+            v.visit_expression(right);
+        }
+        Expression::Index { base, index, .. } => {
+            v.visit_expression(base);
+            v.visit_expression(index);
+        }
+        Expression::Member { base, .. } => v.visit_expression(base),
+        Expression::FuncCall { args, .. } => {
+            for a in args {
+                v.visit_expression(a);
+            }
+        }
+        Expression::NumberLiteral(..)
+        | Expression::BoolLiteral(..)
+        | Expression::Identifier(_)
+        | Expression::StringLiteral(..) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::scl::parse_scl_from_str;
+
+    #[derive(Default)]
+    struct Counter {
+        calls: usize,
+        idents: usize,
+    }
+
+    impl Visitor for Counter {
+        fn visit_statement(&mut self, st: &Statement) {
+            if matches!(st, Statement::Call { .. }) {
+                self.calls += 1;
+            }
+            walk_statement(self, st);
+        }
+
+        fn visit_expression(&mut self, e: &Expression) {
+            if matches!(e, Expression::Identifier(_)) {
+                self.idents += 1;
+            }
+            walk_expression(self, e);
+        }
+    }
+
+    #[test]
+    fn reaches_elsif_else_and_case_arms() {
+        let src = "ORGANIZATION_BLOCK OB1\nBEGIN\n\
+                   IF a THEN\n    f1();\nELSIF b THEN\n    f2();\nELSE\n    f3();\nEND_IF;\n\
+                   CASE s OF\n    1: f4();\nELSE\n    f5();\nEND_CASE;\n\
+                   END_ORGANIZATION_BLOCK\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let mut c = Counter::default();
+        walk_program(&mut c, &program);
+        assert_eq!(c.calls, 5);
+        assert_eq!(c.idents, 3);
+    }
+
+    #[test]
+    fn data_block_start_values_are_not_code() {
+        let src = "DATA_BLOCK DB_Cfg\nVAR\n    Limit : INT;\nEND_VAR\nBEGIN\n    Limit := Max;\nEND_DATA_BLOCK\n\
+                   FUNCTION FC_A\nBEGIN\n    f1(x := Limit);\nEND_FUNCTION\n";
+        let program = parse_scl_from_str(src).unwrap();
+        let mut c = Counter::default();
+        walk_program(&mut c, &program);
+        assert_eq!((c.calls, c.idents), (1, 1));
+    }
+}
@@ -0,0 +1,51 @@
+//! Parsed programs kept alive between WASM calls.
+//!
+//! `check_plc_code` parses and runs every rule in one synchronous call,
+//! which blocks the browser on large inputs. The handle-based entry points
+//! parse once, park the result here under an opaque handle, and let the
+//! caller run one rule per call so it can yield to the event loop in
+//! between.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::ast::Program;
+use crate::rules::{Policy, WasmRuleResult};
+
+/// Everything a rule needs, captured at parse time.
+pub struct Session {
+    pub source: String,
+    /// `None` when nothing could be parsed; rules then have nothing to check.
+    pub program: Option<Program>,
+    pub policy: Arc<Policy>,
+    /// Input, parse and policy errors, in the order `check_plc_code` reports them.
+    pub errors: Vec<WasmRuleResult>,
+}
+
+/// Open sessions by handle. Handles start at 1 and are never reused.
+#[derive(Default)]
+pub struct Sessions {
+    next: u32,
+    open: HashMap<u32, Session>,
+}
+
+impl Sessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, session: Session) -> u32 {
+        self.next += 1;
+        self.open.insert(self.next, session);
+        self.next
+    }
+
+    pub fn get(&self, handle: u32) -> Option<&Session> {
+        self.open.get(&handle)
+    }
+
+    /// Drop a session; false if the handle was not open.
+    pub fn remove(&mut self, handle: u32) -> bool {
+        self.open.remove(&handle).is_some()
+    }
+}
@@ -7,7 +7,7 @@ import InfoPage from './components/InfoPage';
 import ResultsChart from './components/ResultsChart';
 import {AnalysisResult} from './components/ViolationCard';
 import ViolationFrequencyChart from './components/ViolationFrequencyChart';
-// Extend Violation for analysis results; status may be "OK", "NOT FOLLOWED" or "ERROR"
+// Extend Violation for analysis results; status may be "OK", "NOT FOLLOWED", "NOT APPLICABLE" or "ERROR"
 
 const App: React.FC = () => {
   const [fileContent, setFileContent] = useState<string>('');
@@ -63,14 +63,35 @@ const App: React.FC = () => {
       if (typeof module.default === 'function') {
         await module.default();
       }
-      // Retrieve the exported check_plc_code function from the wasm module.
-      const checkPlcCode: (src: string, policy: string, fileName: string) => string =
-        module.check_plc_code || module.checkPlcCode || module.check_plc_code;
-      if (typeof checkPlcCode !== 'function') {
-        throw new Error('WASM function check_plc_code was not found. Make sure the Rust code is compiled with wasm-bindgen and placed correctly.');
+      let parsed: any[];
+      if (typeof module.parse_to_handle === 'function') {
+        // Run one rule per call and yield in between so large files do not
+        // freeze the page.
+        const handle: number = module.parse_to_handle(fileContent, policy || '', fileName || 'uploaded.scl');
+        try {
+          parsed = JSON.parse(module.handle_errors(handle));
+          const catalog: Array<{ no: number }> = JSON.parse(module.list_rules());
+          for (const rule of catalog) {
+            const entries: string =
+              typeof module.run_rule_explained === 'function'
+                ? module.run_rule_explained(handle, rule.no, true)
+                : module.run_rule(handle, rule.no);
+            parsed.push(...JSON.parse(entries));
+            await new Promise((resolve) => setTimeout(resolve, 0));
+          }
+        } finally {
+          module.release_handle(handle);
+        }
+      } else {
+        // Retrieve the exported check_plc_code function from the wasm module.
+        const checkPlcCode: (src: string, policy: string, fileName: string) => string =
+          module.check_plc_code || module.checkPlcCode || module.check_plc_code;
+        if (typeof checkPlcCode !== 'function') {
+          throw new Error('WASM function check_plc_code was not found. Make sure the Rust code is compiled with wasm-bindgen and placed correctly.');
+        }
+        const rawResult = checkPlcCode(fileContent, policy || '', fileName || 'uploaded.scl');
+        parsed = JSON.parse(rawResult);
       }
-      const rawResult = checkPlcCode(fileContent, policy || '', fileName || 'uploaded.scl');
-    const parsed: any[] = JSON.parse(rawResult);
 
     // 1. Flatten the nested data structure
     const flattenedResults: AnalysisResult[] = parsed.map((result) => {
@@ -1,4 +1,4 @@
-import React from 'react';
+import React, { useEffect, useState } from 'react';
 
 interface InfoPageProps {
   /** Whether the info page is open */
@@ -7,9 +7,16 @@ interface InfoPageProps {
   onClose: () => void;
 }
 
+interface RuleInfo {
+  no: number;
+  name: string;
+  text: string;
+}
+
 // Array of rule descriptions used in the About section. Each entry lists
-// the rule number, name and a short summary of what it checks for.
-const RULES: Array<{ no: number; name: string; text: string }> = [
+// the rule number, name and a short summary of what it checks for. Shown
+// until the wasm module's list_rules() catalog has loaded.
+const RULES: RuleInfo[] = [
   { no: 1,  name: 'Modularize PLC Code', text: 'Use FC/FB/OB separation; avoid monolithic logic.' },
   { no: 2,  name: 'Track operating modes', text: 'Gate risky actions on RUN/STOP/STARTUP states.' },
   { no: 3,  name: 'Validate and alert for paired I/O', text: 'Never drive conflicting outputs simultaneously.' },
@@ -21,7 +28,7 @@ const RULES: Array<{ no: number; name: string; text: string }> = [
   { no: 9,  name: 'Validate indirections', text: 'Bounds-check array and indirect memory access.' },
   { no: 10, name: 'Assign designated register blocks', text: 'Writes only to policy-allowed memory regions.' },
   { no: 11, name: 'Plausibility Checks', text: 'Document plausibility with inline comments before use.' },
-  { no: 12, name: 'Document critical assumptions', text: 'Attach context/assumptions before critical operations.' },
+  { no: 12, name: 'Plausibility-Gated Writes', text: 'Gate actuator and setpoint writes on a plausibility flag.' },
   { no: 13, name: 'Alarm escalation path', text: 'Escalate persistent alarms instead of silencing.' },
   { no: 14, name: 'Fail-safe defaults', text: 'Default outputs to safe states during uncertainty.' },
   { no: 15, name: 'Define a safe restart state', text: 'Use OB100 to reset to a secure, deterministic baseline.' },
@@ -30,6 +37,28 @@ const RULES: Array<{ no: number; name: string; text: string }> = [
   { no: 18, name: 'Log PLC hard stops', text: 'Use OB121/OB82/OB86 to capture faults and program errors.' },
   { no: 19, name: 'Monitor PLC memory usage', text: 'Track memory usage trends to avoid overflows.' },
   { no: 20, name: 'Trap false alerts', text: 'Debounce/validate alarms to reduce noise and flapping.' },
+  { no: 30, name: 'Avoid empty branches', text: 'Empty IF/ELSE branches often hide deleted or missing handling.' },
+  { no: 31, name: 'Avoid self-assignment', text: 'X := X does nothing and is usually a typo for a different source.' },
+  { no: 32, name: 'Protect variables shared with interrupts', text: 'Variables written by an interrupt OB and by other code can race.' },
+  { no: 33, name: 'Single point of control for outputs', text: 'Each critical output should be written in one place; competing writers race.' },
+  { no: 34, name: 'Avoid hardwired conditions', text: 'IF TRUE bypasses a check and IF FALSE disables a block; both are leftover overrides.' },
+  { no: 35, name: 'Avoid recursive calls', text: 'A block that calls itself, directly or through others, overflows the stack at runtime.' },
+  { no: 36, name: 'Follow naming conventions', text: 'Block and interface names must match the naming patterns configured in the policy.' },
+  { no: 37, name: 'Clamp HMI setpoints on both sides', text: 'HMI values reaching a setpoint must be checked against both a lower and an upper limit.' },
+  { no: 38, name: 'Limit nesting depth', text: 'IF/CASE/loop nesting deeper than the policy\'s max_nesting (default 6) is hard to verify.' },
+  { no: 39, name: 'Store the previous value for edge detection', text: 'A manual edge check against X_PREV/X_OLD needs X_PREV := X every scan.' },
+  { no: 40, name: 'Do not assign the FOR loop counter', text: 'Writing the control variable inside a FOR body is undefined behaviour.' },
+  { no: 41, name: 'Scale analog inputs before use', text: 'Raw analog counts (%IW, *_RAW, *_AI) need NORM_X/SCALE_X or a range check before arithmetic.' },
+  { no: 42, name: 'Resolve TODO/FIXME/HACK markers', text: 'Comments marked TODO, FIXME or HACK are open work; right before a critical output write they are a warning.' },
+  { no: 43, name: 'Call only defined blocks', text: 'Calls to blocks missing from the analysed files (and not standard instructions) point to a forgotten file or a typo.' },
+  { no: 44, name: 'Do not overwrite unread values', text: 'A value assigned again before anything reads it is a dead store, often leftover code or a mistyped target.' },
+  { no: 45, name: 'Ramp speed and position setpoints', text: 'Operator setpoints sent straight to a drive make it jump; pass them through a ramp or limit block.' },
+  { no: 46, name: 'Do not mix up = and :=', text: 'A comparison stored where a value was meant, or an assignment written as a condition, is usually a one-character typo.' },
+  { no: 47, name: 'Guard writes to retentive memory', text: 'Retentive data written on every scan wears out flash; write it only when the value changes.' },
+  { no: 48, name: "Assign values of the target's type", text: 'Mixing BOOL and numeric values in an assignment relies on silent coercion; convert explicitly.' },
+  { no: 49, name: 'Poll emergency stops on every scan', text: 'An e-stop read only inside a mode or state branch is ignored in every other mode; read it unconditionally in OB1.' },
+  { no: 50, name: 'Do not share a timer or counter instance across loop iterations', text: 'A timer called in a loop is restarted by every iteration; give each element its own instance from an array.' },
+  { no: 51, name: 'Detect stuck and out-of-range sensor signals', text: 'A failed sensor often freezes or pins at its limit; compare it against its range or previous value, and set a fault flag from that comparison.' },
 ];
 
 /**
@@ -40,6 +69,31 @@ const RULES: Array<{ no: number; name: string; text: string }> = [
  * the user clicks Close.
  */
 const InfoPage: React.FC<InfoPageProps> = ({ open, onClose }) => {
+  const [catalog, setCatalog] = useState<RuleInfo[]>(RULES);
+
+  useEffect(() => {
+    if (!open) return;
+    let cancelled = false;
+    (async () => {
+      try {
+        const module = await import(/* @vite-ignore */ '../../wasm/plc_secure_checker_lib.js');
+        if (typeof module.default === 'function') {
+          await module.default();
+        }
+        if (typeof module.list_rules !== 'function') return;
+        const rules: Array<{ no: number; name: string; description: string }> = JSON.parse(module.list_rules());
+        if (!cancelled) {
+          setCatalog(rules.map((r) => ({ no: r.no, name: r.name, text: r.description })));
+        }
+      } catch {
+        // Keep the built-in list if the wasm module is unavailable.
+      }
+    })();
+    return () => {
+      cancelled = true;
+    };
+  }, [open]);
+
   if (!open) return null;
   return (
     <div className="fixed inset-0 z-50 flex items-start justify-center bg-black/70">
@@ -65,7 +119,7 @@ const InfoPage: React.FC<InfoPageProps> = ({ open, onClose }) => {
         <section className="mt-6">
           <h3 className="text-lg font-semibold mb-2">Security Checks Performed</h3>
           <ul className="grid grid-cols-1 gap-2 md:grid-cols-2">
-            {RULES.map((r) => (
+            {catalog.map((r) => (
               <li key={r.no} className="rounded border border-gray-700 bg-gray-800 p-3">
                 <div className="font-medium">{r.no}. {r.name}</div>
                 <div className="text-sm text-gray-400">{r.text}</div>
@@ -1,4 +1,4 @@
-import React from 'react';
+import React, { useEffect, useState } from 'react';
 
 interface PolicyInputProps {
   /**
@@ -13,8 +13,33 @@ interface PolicyInputProps {
 
 /**
  * PolicyInput component exposes a textarea for optional JSON policy input.
+ * The policy is checked with the Wasm module's validate_policy as the user
+ * types, so mistakes show up before an analysis is run.
  */
 const PolicyInput: React.FC<PolicyInputProps> = ({ policy, onPolicyChange }) => {
+  const [problem, setProblem] = useState<string>('');
+
+  useEffect(() => {
+    let cancelled = false;
+    (async () => {
+      try {
+        const module = await import(/* @vite-ignore */ '../../wasm/plc_secure_checker_lib.js');
+        if (typeof module.default === 'function') {
+          await module.default();
+        }
+        if (typeof module.validate_policy !== 'function') return;
+        const check = JSON.parse(module.validate_policy(policy));
+        const where = check.line ? `Line ${check.line}, column ${check.column}: ` : '';
+        if (!cancelled) setProblem(check.ok ? '' : `${where}${check.error}`);
+      } catch {
+        // Without the Wasm module the policy is still checked on analysis.
+      }
+    })();
+    return () => {
+      cancelled = true;
+    };
+  }, [policy]);
+
   return (
     <div className="mb-4">
       <label className="block mb-1 font-medium">Policy JSON (optional)</label>
@@ -30,6 +55,7 @@ const PolicyInput: React.FC<PolicyInputProps> = ({ policy, onPolicyChange }) =>
         value={policy}
         onChange={(e) => onPolicyChange(e.target.value)}
       />
+      {problem && <p className="mt-1 text-sm text-yellow-400">{problem}</p>}
     </div>
   );
 };
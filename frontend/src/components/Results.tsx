@@ -1,7 +1,7 @@
 import React from 'react';
 import ViolationCard, { Violation } from './ViolationCard';
 
-// Extend Violation for analysis results; status may be "OK", "NOT FOLLOWED" or "ERROR"
+// Extend Violation for analysis results; status may be "OK", "NOT FOLLOWED", "NOT APPLICABLE" or "ERROR"
 export interface AnalysisResult extends Violation {}
 
 interface ResultsProps {
@@ -29,6 +29,8 @@ const Results: React.FC<ResultsProps> = ({ results, source }) => {
   const errors = results.filter((r) => r.status === 'ERROR');
   const followed = results.filter((r) => r.status === 'OK');
   const notFollowed = results.filter((r) => r.status === 'NOT FOLLOWED');
+  // Rules that checked nothing because the policy lacks their input
+  const notApplicable = results.filter((r) => r.status === 'NOT APPLICABLE');
   // Sort violations by line number then rule number for consistency
   const sortedViolations = [...notFollowed].sort((a, b) => {
     const la = a.line ?? Number.MAX_SAFE_INTEGER;
@@ -36,6 +38,15 @@ const Results: React.FC<ResultsProps> = ({ results, source }) => {
     if (la !== lb) return la - lb;
     return (a.rule_no ?? 0) - (b.rule_no ?? 0);
   });
+  // Findings per block, e.g. "3 in FB_Motor, 1 in OB1", most first
+  const perBlock = new Map<string, number>();
+  notFollowed.forEach((r) => {
+    if (r.function) perBlock.set(r.function, (perBlock.get(r.function) ?? 0) + 1);
+  });
+  const blockSummary = [...perBlock.entries()]
+    .sort((a, b) => b[1] - a[1])
+    .map(([name, n]) => `${n} in ${name}`)
+    .join(', ');
   return (
     <div className="mt-4">
       {errors.length > 0 && (
@@ -60,15 +71,29 @@ const Results: React.FC<ResultsProps> = ({ results, source }) => {
           </ul>
         </div>
       )}
+      {notApplicable.length > 0 && (
+        <div className="mb-4">
+          <h2 className="text-xl font-semibold mb-2">➖ Not Applicable</h2>
+          <p className="mb-1 text-sm text-gray-400">Skipped: the policy does not configure what these rules check.</p>
+          <ul className="list-disc list-inside space-y-1 text-gray-400">
+            {notApplicable.map((r, idx) => (
+              <li key={`${r.rule_no}-${idx}`}>
+                {r.rule_no}. {r.rule_name}
+              </li>
+            ))}
+          </ul>
+        </div>
+      )}
       {sortedViolations.length > 0 && (
         <div>
           <h2 className="text-xl font-semibold mb-2">❌ Practices Not Followed</h2>
+          {blockSummary && <p className="mb-2 text-sm text-gray-400">{blockSummary}</p>}
           <div className="space-y-4">
             {sortedViolations.map((r, idx) => (
               <ViolationCard
                 key={`${r.rule_no}-${r.line ?? 'noline'}-${idx}`}
                 violation={r}
-                code={codeFor(r.line)}
+                code={r.snippet ?? codeFor(r.line)}
               />
             ))}
           </div>
@@ -18,7 +18,7 @@ const ResultsChart: React.FC<ResultsChartProps> = ({ results }) => {
     }
     // Find all unique rule numbers that have at least one violation
     const failedRuleNumbers = new Set(
-      results.filter((r) => r.status !== 'OK').map((r) => r.rule_no)
+      results.filter((r) => r.status !== 'OK' && r.status !== 'NOT APPLICABLE').map((r) => r.rule_no)
     );
 
     const failedCount = failedRuleNumbers.size;
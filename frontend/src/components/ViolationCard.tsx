@@ -4,12 +4,34 @@ import React from 'react';
  * Interface describing the shape of a single analysis result.
  */
 export interface Violation {
-  status: 'OK' | 'NOT FOLLOWED' | 'ERROR';
+  status: 'OK' | 'NOT FOLLOWED' | 'NOT APPLICABLE' | 'ERROR';
   rule_no: number;
   rule_name: string;
   line?: number;
+  /** Last line of a finding spanning several lines. */
+  end_line?: number;
   reason?: string;
   suggestion?: string;
+  /** Offending source text as reported by the checker. */
+  snippet?: string;
+  /** Machine-applicable fix: replace the text from start_line:start_col to end_line:end_col (1-based, end column exclusive). */
+  fix?: TextEdit;
+  /** Block (FB/FC/OB) the finding is in, when it can be attributed. */
+  function?: string;
+  /** Source file, when a whole project was checked at once. */
+  file?: string;
+  /** Why the rule matters; only present when explanations were requested. */
+  rationale?: string;
+  /** Anchor of the rule's documentation section, e.g. "rule-11-plausibility-checks". */
+  doc_anchor?: string;
+}
+
+export interface TextEdit {
+  start_line: number;
+  start_col: number;
+  end_line: number;
+  end_col: number;
+  replacement: string;
 }
 
 export interface AnalysisResult extends Violation {}
@@ -26,7 +48,7 @@ interface ViolationCardProps {
 
 const ViolationCard: React.FC<ViolationCardProps> = (props: ViolationCardProps) => {
   const { violation, code } = props;
-  const { rule_no, rule_name, line, reason, suggestion, status } = violation;
+  const { rule_no, rule_name, line, end_line, reason, suggestion, status, fix, function: block, file, rationale, doc_anchor } = violation;
 
   // Choose border and title colours based on status
   const border =
@@ -41,7 +63,9 @@ const ViolationCard: React.FC<ViolationCardProps> = (props: ViolationCardProps)
         </h3>
         {typeof line === 'number' && line > 0 && (
           <span className="ml-4 inline-flex items-center text-xs px-2 py-1 rounded bg-gray-900 border border-gray-700">
-            Line {line}
+            {file ? `${file}: ` : ''}
+            {block ? `${block}, line` : 'Line'}
+            {end_line && end_line > line ? `s ${line}–${end_line}` : ` ${line}`}
           </span>
         )}
       </div>
@@ -60,6 +84,20 @@ const ViolationCard: React.FC<ViolationCardProps> = (props: ViolationCardProps)
           <span className="font-medium">Suggestion:</span> {suggestion}
         </p>
       )}
+      {rationale && (
+        <details className="mt-2 text-sm" id={doc_anchor}>
+          <summary className="cursor-pointer font-medium">Why this matters</summary>
+          <p className="mt-1 text-gray-300">{rationale}</p>
+        </details>
+      )}
+      {fix && (
+        <div className="mt-2">
+          <span className="text-sm font-medium">Quick fix:</span>
+          <pre className="mt-1 text-xs bg-gray-900 border border-green-700 rounded p-2 overflow-x-auto">
+{fix.replacement}
+          </pre>
+        </div>
+      )}
     </div>
   );
 };